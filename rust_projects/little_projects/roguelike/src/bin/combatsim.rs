@@ -0,0 +1,204 @@
+//! Headless combat simulator. Fights a player loadout against a mob, or against whatever a
+//! depth's spawn table turns up, many times over and reports how it went.
+//!
+//! Usage: combatsim (--mob NAME | --depth N[-M]) [--fights N] [--level N] [--might N]
+//!                  [--fitness N] [--quickness N] [--intelligence N] [--equip ITEM]...
+//!                  [--max-rounds N] [--seed S] [--format csv|json] [--out FILE]
+
+use roguelike::simulator::{simulate_depth, simulate_mob, Loadout, Report, Stats};
+use roguelike::State;
+use std::fs::File;
+use std::io::Write;
+
+#[derive(PartialEq)]
+enum OutputFormat {
+    Csv,
+    Json,
+}
+
+enum Opponents {
+    Mob(String),
+    Depths(i32, i32),
+}
+
+struct Options {
+    opponents: Opponents,
+    fights: usize,
+    loadout: Loadout,
+    max_rounds: i32,
+    seed: u64,
+    format: OutputFormat,
+    out: Option<String>,
+}
+
+fn usage() -> ! {
+    eprintln!("Usage: combatsim (--mob NAME | --depth N[-M]) [--fights N] [--level N] [--might N]");
+    eprintln!(
+        "                 [--fitness N] [--quickness N] [--intelligence N] [--equip ITEM]..."
+    );
+    eprintln!("                 [--max-rounds N] [--seed S] [--format csv|json] [--out FILE]");
+    eprintln!("Without --equip, the player has the starting gear.");
+    std::process::exit(1);
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: Option<String>) -> T {
+    match value.map(|v| v.parse::<T>()) {
+        Some(Ok(v)) => v,
+        _ => {
+            eprintln!("Missing or invalid value for {}", flag);
+            usage();
+        }
+    }
+}
+
+fn parse_depths(flag: &str, value: Option<String>) -> Opponents {
+    let value: String = parse_value(flag, value);
+    let (first, last) = match value.split_once('-') {
+        Some((first, last)) => (first.parse::<i32>(), last.parse::<i32>()),
+        None => (value.parse::<i32>(), value.parse::<i32>()),
+    };
+    match (first, last) {
+        (Ok(first), Ok(last)) if first <= last => Opponents::Depths(first, last),
+        _ => {
+            eprintln!("Invalid depth range {}", value);
+            usage();
+        }
+    }
+}
+
+fn parse_args() -> Options {
+    let mut opponents = None;
+    let mut options = Options {
+        opponents: Opponents::Depths(1, 1),
+        fights: 1000,
+        loadout: Loadout::default(),
+        max_rounds: 200,
+        seed: roguelike::rng::new_seed(),
+        format: OutputFormat::Csv,
+        out: None,
+    };
+    let mut equipped = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--mob" => opponents = Some(Opponents::Mob(parse_value(&arg, args.next()))),
+            "--depth" => opponents = Some(parse_depths(&arg, args.next())),
+            "--fights" => options.fights = parse_value(&arg, args.next()),
+            "--level" => options.loadout.level = parse_value(&arg, args.next()),
+            "--might" => options.loadout.might = parse_value(&arg, args.next()),
+            "--fitness" => options.loadout.fitness = parse_value(&arg, args.next()),
+            "--quickness" => options.loadout.quickness = parse_value(&arg, args.next()),
+            "--intelligence" => options.loadout.intelligence = parse_value(&arg, args.next()),
+            "--equip" => equipped.push(parse_value(&arg, args.next())),
+            "--max-rounds" => options.max_rounds = parse_value(&arg, args.next()),
+            "--seed" => options.seed = parse_value(&arg, args.next()),
+            "--format" => {
+                options.format = match parse_value::<String>(&arg, args.next()).as_str() {
+                    "csv" => OutputFormat::Csv,
+                    "json" => OutputFormat::Json,
+                    _ => usage(),
+                }
+            }
+            "--out" => options.out = Some(parse_value(&arg, args.next())),
+            _ => usage(),
+        }
+    }
+
+    options.opponents = opponents.unwrap_or_else(|| usage());
+    if !equipped.is_empty() {
+        options.loadout.equipped = equipped;
+    }
+    options
+}
+
+fn stats_header(name: &str) -> String {
+    ["count", "mean", "min", "median", "p90", "max"]
+        .iter()
+        .map(|column| format!("{}_{}", name, column))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn stats_columns(stats: &Stats) -> String {
+    format!(
+        "{},{:.2},{},{},{},{}",
+        stats.count, stats.mean, stats.min, stats.median, stats.p90, stats.max
+    )
+}
+
+fn to_csv(reports: &[Report]) -> String {
+    let mut text = format!(
+        "depth,opponent,fights,wins,win_rate,{},{},{}\n",
+        stats_header("turns_to_kill"),
+        stats_header("damage_per_hit"),
+        stats_header("damage_taken")
+    );
+    for report in reports.iter() {
+        text += &format!(
+            "{},\"{}\",{},{},{:.3},{},{},{}\n",
+            report.depth.map_or(String::new(), |d| d.to_string()),
+            report.opponent.replace('"', "\"\""),
+            report.fights,
+            report.wins,
+            report.win_rate,
+            stats_columns(&report.turns_to_kill),
+            stats_columns(&report.damage_per_hit),
+            stats_columns(&report.damage_taken)
+        );
+    }
+    text
+}
+
+fn main() {
+    let options = parse_args();
+    let mut gs = State::new();
+    roguelike::rng::reseed(options.seed);
+
+    let result = match &options.opponents {
+        Opponents::Mob(name) => simulate_mob(
+            &mut gs.ecs,
+            &options.loadout,
+            name,
+            options.fights,
+            options.max_rounds,
+        )
+        .map(|report| vec![report]),
+        Opponents::Depths(first, last) => {
+            let mut reports = Vec::new();
+            let mut depth = *first;
+            let mut result = Ok(());
+            while depth <= *last && result.is_ok() {
+                result = simulate_depth(
+                    &mut gs.ecs,
+                    &options.loadout,
+                    depth,
+                    options.fights,
+                    options.max_rounds,
+                )
+                .map(|mut batch| reports.append(&mut batch));
+                depth += 1;
+            }
+            result.map(|_| reports)
+        }
+    };
+    let reports = result.unwrap_or_else(|e| {
+        eprintln!("Simulation failed: {}", e);
+        std::process::exit(1);
+    });
+
+    let output = match options.format {
+        OutputFormat::Csv => to_csv(&reports),
+        OutputFormat::Json => {
+            serde_json::to_string_pretty(&reports).expect("Unable to serialize reports")
+        }
+    };
+    match &options.out {
+        None => print!("{}", output),
+        Some(path) => {
+            let mut file = File::create(path).expect("Unable to create output file");
+            file.write_all(output.as_bytes())
+                .expect("Unable to write output file");
+        }
+    }
+}
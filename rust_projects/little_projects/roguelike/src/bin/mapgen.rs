@@ -0,0 +1,201 @@
+//! Headless map generator. Runs a builder chain without opening a window and prints the result.
+//!
+//! Usage: mapgen [--depth N] [--width W] [--height H] [--seed S] [--builder NAME]
+//!               [--format ascii|json] [--history] [--out FILE]
+
+use roguelike::map_builders::{level_builder, named_builder};
+use roguelike::{tile_ascii, Map};
+use serde::Serialize;
+use std::fs::File;
+use std::io::Write;
+
+#[derive(PartialEq)]
+enum OutputFormat {
+    Ascii,
+    Json,
+}
+
+struct Options {
+    depth: i32,
+    width: i32,
+    height: i32,
+    seed: u64,
+    builder: Option<String>,
+    format: OutputFormat,
+    history: bool,
+    out: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Spawn<'a> {
+    x: i32,
+    y: i32,
+    name: &'a str,
+}
+
+#[derive(Serialize)]
+struct MapgenOutput<'a> {
+    seed: u64,
+    depth: i32,
+    builder: &'a str,
+    starting_position: Option<(i32, i32)>,
+    spawns: Vec<Spawn<'a>>,
+    map: &'a Map,
+    history: &'a [Map],
+}
+
+fn usage() -> ! {
+    eprintln!("Usage: mapgen [--depth N] [--width W] [--height H] [--seed S] [--builder NAME]");
+    eprintln!("              [--format ascii|json] [--history] [--out FILE]");
+    let level_names = roguelike::raws::get_level_names(&roguelike::raws::RAWS.lock().unwrap());
+    eprintln!("Builders: random, {}", level_names.join(", "));
+    std::process::exit(1);
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: Option<String>) -> T {
+    match value.map(|v| v.parse::<T>()) {
+        Some(Ok(v)) => v,
+        _ => {
+            eprintln!("Missing or invalid value for {}", flag);
+            usage();
+        }
+    }
+}
+
+fn parse_args() -> Options {
+    let mut options = Options {
+        depth: 1,
+        width: 80,
+        height: 50,
+        seed: roguelike::rng::new_seed(),
+        builder: None,
+        format: OutputFormat::Ascii,
+        history: false,
+        out: None,
+    };
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--depth" => options.depth = parse_value(&arg, args.next()),
+            "--width" => options.width = parse_value(&arg, args.next()),
+            "--height" => options.height = parse_value(&arg, args.next()),
+            "--seed" => options.seed = parse_value(&arg, args.next()),
+            "--builder" => options.builder = Some(parse_value(&arg, args.next())),
+            "--format" => {
+                options.format = match parse_value::<String>(&arg, args.next()).as_str() {
+                    "ascii" => OutputFormat::Ascii,
+                    "json" => OutputFormat::Json,
+                    _ => usage(),
+                }
+            }
+            "--history" => options.history = true,
+            "--out" => options.out = Some(parse_value(&arg, args.next())),
+            _ => usage(),
+        }
+    }
+
+    options
+}
+
+fn map_to_ascii(map: &Map, spawns: &[Spawn], start: Option<(i32, i32)>) -> String {
+    let mut rows: Vec<Vec<char>> = map
+        .tiles
+        .chunks(map.width as usize)
+        .map(|row| row.iter().map(|tt| tile_ascii(*tt)).collect())
+        .collect();
+    for spawn in spawns.iter() {
+        rows[spawn.y as usize][spawn.x as usize] = 'e';
+    }
+    if let Some((x, y)) = start {
+        rows[y as usize][x as usize] = '@';
+    }
+
+    let mut result = String::new();
+    for row in rows.iter() {
+        result.extend(row.iter());
+        result.push('\n');
+    }
+    result
+}
+
+fn main() {
+    roguelike::raws::load_raws();
+    let options = parse_args();
+
+    // Same seeding as the game, so a run seed and depth reproduce the level exactly
+    roguelike::rng::reseed(roguelike::rng::depth_seed(options.seed, options.depth));
+    let builder_name = options.builder.as_deref().unwrap_or("level");
+    let mut builder = match &options.builder {
+        None => level_builder(options.depth, options.width, options.height),
+        Some(name) => named_builder(name, options.depth, options.width, options.height)
+            .unwrap_or_else(|| {
+                eprintln!("Unknown builder: {}", name);
+                usage();
+            }),
+    };
+    if options.history {
+        builder.record_history();
+    }
+    builder.build_map();
+    builder.place_up_stairs();
+
+    let build_data = &builder.build_data;
+    let width = build_data.map.width as usize;
+    let start = build_data
+        .starting_position
+        .as_ref()
+        .map(|pos| (pos.x, pos.y));
+    let spawns: Vec<Spawn> = build_data
+        .spawn_list
+        .iter()
+        .map(|(idx, name)| Spawn {
+            x: (idx % width) as i32,
+            y: (idx / width) as i32,
+            name,
+        })
+        .collect();
+
+    let output = match options.format {
+        OutputFormat::Json => {
+            let report = MapgenOutput {
+                seed: options.seed,
+                depth: options.depth,
+                builder: builder_name,
+                starting_position: start,
+                spawns,
+                map: &build_data.map,
+                history: &build_data.history,
+            };
+            serde_json::to_string_pretty(&report).expect("Unable to serialize map")
+        }
+        OutputFormat::Ascii => {
+            let mut text = String::new();
+            for (i, snapshot) in build_data.history.iter().enumerate() {
+                text += &format!("-- Snapshot {} --\n", i);
+                text += &map_to_ascii(snapshot, &[], None);
+            }
+            text += &format!(
+                "-- {} (depth {}, builder {}, seed {}) --\n",
+                build_data.map.name, options.depth, builder_name, options.seed
+            );
+            text += &map_to_ascii(&build_data.map, &spawns, start);
+            if let Some((x, y)) = start {
+                text += &format!("Start: {},{}\n", x, y);
+            }
+            for spawn in spawns.iter() {
+                text += &format!("Spawn: {},{} {}\n", spawn.x, spawn.y, spawn.name);
+            }
+            text
+        }
+    };
+
+    match &options.out {
+        None => println!("{}", output),
+        Some(path) => {
+            let mut file = File::create(path).expect("Unable to create output file");
+            file.write_all(output.as_bytes())
+                .expect("Unable to write output file");
+        }
+    }
+}
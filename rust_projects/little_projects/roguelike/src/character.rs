@@ -0,0 +1,205 @@
+//! The rules for new characters, and building the player from the choices made on the character
+//! creation screen.
+
+use crate::components::*;
+use crate::raws::{get_background, skill_by_name, spawn_named_entity, SpawnType, RAWS};
+use crate::{attr_bonus, mana_at_level, player_hp_at_level};
+use specs::prelude::*;
+use std::collections::HashMap;
+
+pub const DEFAULT_NAME: &str = "Player";
+pub const MAX_NAME_LENGTH: usize = 20;
+/// Point-buy attributes can't go below or above these.
+pub const POINT_BUY_MIN: i32 = 8;
+pub const POINT_BUY_MAX: i32 = 15;
+/// Points to spend above the minimum, at one point per attribute point.
+pub const POINT_BUY_POINTS: i32 = 12;
+
+impl Default for Character {
+    /// A brawler with an even spread of attributes, as every run used to start with.
+    fn default() -> Self {
+        Character {
+            name: DEFAULT_NAME.to_string(),
+            background: crate::raws::DEFAULT_BACKGROUND.to_string(),
+            might: 11,
+            fitness: 11,
+            quickness: 11,
+            intelligence: 11,
+        }
+    }
+}
+
+impl Character {
+    /// Might, fitness, quickness and intelligence, in that order.
+    pub fn attributes(&self) -> [i32; 4] {
+        [self.might, self.fitness, self.quickness, self.intelligence]
+    }
+
+    fn attribute_mut(&mut self, index: usize) -> &mut i32 {
+        match index {
+            0 => &mut self.might,
+            1 => &mut self.fitness,
+            2 => &mut self.quickness,
+            _ => &mut self.intelligence,
+        }
+    }
+
+    /// Point-buy points left to spend; negative if the attributes cost more than point-buy allows.
+    pub fn points_left(&self) -> i32 {
+        POINT_BUY_POINTS
+            - self
+                .attributes()
+                .iter()
+                .map(|a| a - POINT_BUY_MIN)
+                .sum::<i32>()
+    }
+
+    /// Raises or lowers an attribute by a point, if point-buy allows it. Returns false if not.
+    pub fn buy(&mut self, index: usize, change: i32) -> bool {
+        let value = *self.attribute_mut(index) + change;
+        if !(POINT_BUY_MIN..=POINT_BUY_MAX).contains(&value) || self.points_left() < change {
+            return false;
+        }
+        *self.attribute_mut(index) = value;
+        true
+    }
+
+    /// Rolls 3d6 for each attribute. Takes its own generator, so rolling again and again doesn't
+    /// change the run.
+    pub fn roll(&mut self, rng: &mut rltk::RandomNumberGenerator) {
+        for index in 0..4 {
+            *self.attribute_mut(index) = rng.roll_dice(3, 6);
+        }
+    }
+}
+
+fn attribute(base: i32, modifiers: i32) -> Attribute {
+    Attribute {
+        base,
+        modifiers,
+        bonus: attr_bonus(base + modifiers),
+    }
+}
+
+/// Rebuilds the player as the character: their name, attributes and pools, and their
+/// background's skills, spells and kit. Anything the player was carrying is replaced.
+pub fn apply(ecs: &mut World, player: Entity, character: &Character) {
+    let mut skills = HashMap::new();
+    for skill in [Skill::Melee, Skill::Defense, Skill::Magic, Skill::Stealth] {
+        skills.insert(skill, 1);
+    }
+    let mut spells = Vec::new();
+    let mut equipped = Vec::new();
+    let mut carried = Vec::new();
+    {
+        let raws = RAWS.lock().unwrap();
+        match get_background(&raws, &character.background) {
+            None => crate::diagnostic(format!(
+                "WARNING - unknown background [{}]",
+                character.background
+            )),
+            Some(background) => {
+                for (name, level) in background.skills.iter().flatten() {
+                    if let Some(skill) = skill_by_name(name) {
+                        skills.insert(skill, *level);
+                    }
+                }
+                spells = background.spells.clone().unwrap_or_default();
+                equipped = background.equipped.clone().unwrap_or_default();
+                carried = background.carried.clone().unwrap_or_default();
+            }
+        }
+    }
+
+    let mut known = Vec::new();
+    for spell in spells.iter() {
+        let mana_cost = crate::raws::find_spell_entity(ecs, spell)
+            .and_then(|entity| ecs.read_storage::<SpellTemplate>().get(entity).cloned())
+            .map(|template| template.mana_cost);
+        match mana_cost {
+            Some(mana_cost) => known.push(KnownSpell {
+                display_name: spell.clone(),
+                mana_cost,
+                cooldown: 0,
+            }),
+            None => crate::diagnostic(format!("WARNING - unknown spell [{}]", spell)),
+        }
+    }
+
+    {
+        let mut names = ecs.write_storage::<Name>();
+        names
+            .insert(
+                player,
+                Name {
+                    name: character.name.clone(),
+                },
+            )
+            .expect("Insert failed");
+
+        let mut attributes = ecs.write_storage::<Attributes>();
+        if let Some(attr) = attributes.get_mut(player) {
+            attr.might = attribute(character.might, attr.might.modifiers);
+            attr.fitness = attribute(character.fitness, attr.fitness.modifiers);
+            attr.quickness = attribute(character.quickness, attr.quickness.modifiers);
+            attr.intelligence = attribute(character.intelligence, attr.intelligence.modifiers);
+        }
+
+        let mut pools = ecs.write_storage::<Pools>();
+        if let Some(pools) = pools.get_mut(player) {
+            let hp = player_hp_at_level(character.fitness, pools.level);
+            let mana = mana_at_level(character.intelligence, pools.level);
+            pools.hit_points = Pool {
+                current: hp,
+                max: hp,
+            };
+            pools.mana = Pool {
+                current: mana,
+                max: mana,
+            };
+        }
+
+        let mut storage = ecs.write_storage::<Skills>();
+        storage
+            .insert(player, Skills { skills })
+            .expect("Insert failed");
+        let mut storage = ecs.write_storage::<KnownSpells>();
+        storage
+            .insert(player, KnownSpells { spells: known })
+            .expect("Insert failed");
+        let mut storage = ecs.write_storage::<Character>();
+        storage
+            .insert(player, character.clone())
+            .expect("Insert failed");
+        let mut storage = ecs.write_storage::<EquipmentChanged>();
+        storage
+            .insert(player, EquipmentChanged {})
+            .expect("Insert failed");
+    }
+
+    // Swap the old kit for the background's
+    let mut old_kit = Vec::new();
+    for (entity, equipped) in (&ecs.entities(), &ecs.read_storage::<Equipped>()).join() {
+        if equipped.owner == player {
+            old_kit.push(entity);
+        }
+    }
+    for (entity, pack) in (&ecs.entities(), &ecs.read_storage::<InBackpack>()).join() {
+        if pack.owner == player {
+            old_kit.push(entity);
+        }
+    }
+    for entity in old_kit {
+        ecs.delete_entity(entity).expect("Delete failed");
+    }
+    for (items, spawn_type) in [
+        (&equipped, SpawnType::Equipped { by: player }),
+        (&carried, SpawnType::Carried { by: player }),
+    ] {
+        for item in items.iter() {
+            if spawn_named_entity(&RAWS.lock().unwrap(), ecs, item, spawn_type).is_none() {
+                crate::diagnostic(format!("WARNING - unknown item [{}]", item));
+            }
+        }
+    }
+}
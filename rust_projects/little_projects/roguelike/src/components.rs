@@ -40,6 +40,9 @@ pub struct Player {}
 pub struct KnownSpell {
     pub display_name: String,
     pub mana_cost: i32,
+    /// Turns until it can be cast again
+    #[serde(default)]
+    pub cooldown: i32,
 }
 
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
@@ -71,6 +74,31 @@ pub struct AlwaysTargetsSelf {}
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct SpellTemplate {
     pub mana_cost: i32,
+    #[serde(default)]
+    pub cooldown: i32,
+    /// The Magic skill needed to cast it
+    #[serde(default)]
+    pub min_magic: i32,
+}
+
+/// The tiles a spell hits, worked out from where it is cast and where it is aimed.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum TargetShape {
+    /// Stops at the first thing in its path
+    Bolt,
+    /// Spreads out from the caster towards the target
+    Cone { length: i32 },
+    /// Passes through everything until it hits a wall
+    Line { length: i32 },
+    /// Everything around the caster, but not the caster
+    Burst { radius: i32 },
+    /// Jumps from the target to whoever is nearest, this many times
+    Chain { jumps: i32 },
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct SpellShape {
+    pub shape: TargetShape,
 }
 
 #[derive(Component, ConvertSaveload, Clone)]
@@ -94,6 +122,9 @@ pub struct Initiative {
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct Vendor {
     pub categories: Vec<String>,
+    /// How many of each item the vendor has left to sell
+    #[serde(default)]
+    pub stock: HashMap<String, i32>,
 }
 
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
@@ -116,14 +147,41 @@ pub struct ApplyTeleport {
     pub dest_depth: i32,
 }
 
+/// The behavior from the raws that decides what a mob does on its turn, and where it started.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct AiBehavior {
+    pub name: String,
+    pub home_x: i32,
+    pub home_y: i32,
+}
+
+/// Members of its faction nearby rout when this mob dies.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct GroupLeader {}
+
+/// Flees from every enemy in sight, whatever its behavior, until the turns run out.
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
-pub struct WantsToApproach {
-    pub idx: i32,
+pub struct Routed {
+    pub turns: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, Eq, PartialEq)]
+pub enum AlertState {
+    Unaware,
+    Suspicious,
+    Hunting,
 }
 
+/// How much a mob knows about its enemies. Until it is hunting, it ignores enemies in view.
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
-pub struct WantsToFlee {
-    pub indices: Vec<usize>,
+pub struct Alertness {
+    pub state: AlertState,
+    /// Added to the loudness of any noise that reaches the mob
+    pub hearing: i32,
+    /// The tile the mob last heard or glimpsed something on, and will go and look at
+    pub heard: Option<usize>,
+    /// Turns before the mob calms down a step
+    pub turns: i32,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq, Hash)]
@@ -194,6 +252,7 @@ pub enum Skill {
     Melee,
     Defense,
     Magic,
+    Stealth,
 }
 
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
@@ -201,6 +260,36 @@ pub struct Skills {
     pub skills: HashMap<Skill, i32>,
 }
 
+/// How close each skill is to improving through use.
+#[derive(Component, Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SkillProgress {
+    pub practice: HashMap<Skill, i32>,
+}
+
+/// The perks someone has taken, and what they add up to.
+#[derive(Component, Serialize, Deserialize, Clone, Default)]
+pub struct Perks {
+    pub taken: Vec<String>,
+    /// Level-ups that haven't had a perk picked yet
+    pub pending: i32,
+    pub crit_chance: i32,
+    pub carry_weight: i32,
+    pub spell_slots: i32,
+    /// Made alongside every melee attack
+    pub extra_attacks: Vec<NaturalAttack>,
+}
+
+/// The choices the player made when creating their character.
+#[derive(Component, Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Character {
+    pub name: String,
+    pub background: String,
+    pub might: i32,
+    pub fitness: i32,
+    pub quickness: i32,
+    pub intelligence: i32,
+}
+
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct WantsToMelee {
     pub target: Entity,
@@ -266,14 +355,20 @@ pub struct ProvidesRemoveCurse {}
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct ProvidesIdentification {}
 
+/// Picking this up wins the game.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct WinsGame {}
+
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct Ranged {
     pub range: i32,
 }
 
-#[derive(Component, Debug, ConvertSaveload, Clone)]
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct InflictsDamage {
     pub damage: i32,
+    #[serde(default)]
+    pub damage_type: DamageType,
 }
 
 #[derive(Component, Debug, ConvertSaveload, Clone)]
@@ -292,6 +387,19 @@ pub struct Slow {
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct DamageOverTime {
     pub damage: i32,
+    #[serde(default)]
+    pub damage_type: DamageType,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct HealOverTime {
+    pub amount: i32,
+}
+
+/// Can see no further than this, whatever the viewshed's own range.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Blinded {
+    pub vision_range: i32,
 }
 
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
@@ -304,6 +412,39 @@ pub struct StatusEffect {
     pub target: Entity,
 }
 
+/// How many times a status effect that stacks has been applied.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct StatusStacks {
+    pub stacks: i32,
+}
+
+/// Applies the named status effects from the raws.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct AppliesStatus {
+    pub statuses: Vec<String>,
+}
+
+/// Percentage taken off the duration of each named status effect; 100 is immunity. Worn items
+/// add theirs to the wearer's.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct StatusResistances {
+    pub resistances: HashMap<String, i32>,
+}
+
+/// How much of one type of damage is shrugged off: a percentage, then a flat amount. Negative
+/// values are weaknesses.
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, Default, PartialEq)]
+pub struct Mitigation {
+    pub percent: i32,
+    pub flat: i32,
+}
+
+/// Worn items add theirs to the wearer's.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct DamageResistances {
+    pub resistances: HashMap<DamageType, Mitigation>,
+}
+
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct ProvidesHealing {
     pub heal_amount: i32,
@@ -360,7 +501,7 @@ pub struct WantsToRemoveItem {
     pub item: Entity,
 }
 
-#[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
+#[derive(PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum EquipmentSlot {
     Melee,
     Shield,
@@ -388,6 +529,39 @@ pub enum WeaponAttribute {
     Quickness,
 }
 
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone, Default, Serialize, Deserialize)]
+pub enum DamageType {
+    #[default]
+    Physical,
+    Fire,
+    Cold,
+    Poison,
+    Acid,
+    Arcane,
+}
+
+impl DamageType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DamageType::Physical => "physical",
+            DamageType::Fire => "fire",
+            DamageType::Cold => "cold",
+            DamageType::Poison => "poison",
+            DamageType::Acid => "acid",
+            DamageType::Arcane => "arcane",
+        }
+    }
+}
+
+/// Extra damage of its own type that lands with every hit.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DamageRider {
+    pub damage_type: DamageType,
+    pub n_dice: i32,
+    pub die_type: i32,
+    pub bonus: i32,
+}
+
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Weapon {
     pub range: Option<i32>,
@@ -398,6 +572,10 @@ pub struct Weapon {
     pub hit_bonus: i32,
     pub proc_chance: Option<f32>,
     pub proc_target: Option<String>,
+    #[serde(default)]
+    pub damage_type: DamageType,
+    #[serde(default)]
+    pub rider: Option<DamageRider>,
 }
 
 #[derive(Component, Serialize, Deserialize, Clone)]
@@ -413,6 +591,8 @@ pub struct NaturalAttack {
     pub damage_die_type: i32,
     pub damage_bonus: i32,
     pub hit_bonus: i32,
+    #[serde(default)]
+    pub damage_type: DamageType,
 }
 
 #[derive(Component, Serialize, Deserialize, Clone)]
@@ -17,6 +17,7 @@ pub fn delete_the_dead(ecs: &mut World) {
                         let victim_name = names.get(entity);
                         if let Some(victim_name) = victim_name {
                             crate::gamelog::Logger::new()
+                                .category(crate::gamelog::LogCategory::Combat)
                                 .color(rltk::RED)
                                 .append(&victim_name.name)
                                 .append("is dead!")
@@ -96,6 +97,57 @@ pub fn delete_the_dead(ecs: &mut World) {
         }
     }
 
+    // Followers of a fallen leader lose heart
+    {
+        use crate::components::{Faction, GroupLeader, Routed};
+        let entities = ecs.entities();
+        let leaders = ecs.read_storage::<GroupLeader>();
+        let factions = ecs.read_storage::<Faction>();
+        let positions = ecs.read_storage::<Position>();
+        let names = ecs.read_storage::<Name>();
+        let mut routed = ecs.write_storage::<Routed>();
+        let raws = crate::raws::RAWS.lock().unwrap();
+        for victim in dead.iter() {
+            if leaders.get(*victim).is_none() {
+                continue;
+            }
+            let (faction, pos) = match (factions.get(*victim), positions.get(*victim)) {
+                (Some(faction), Some(pos)) => (faction, pos),
+                _ => continue,
+            };
+            let (radius, turns) = crate::raws::faction_tactics(&raws, &faction.name);
+            if turns < 1 {
+                continue;
+            }
+            let here = rltk::Point::new(pos.x, pos.y);
+            let mut fled = false;
+            for (follower, follower_faction, follower_pos) in
+                (&entities, &factions, &positions).join()
+            {
+                if !dead.contains(&follower)
+                    && follower_faction.name == faction.name
+                    && rltk::DistanceAlg::Pythagoras
+                        .distance2d(here, rltk::Point::new(follower_pos.x, follower_pos.y))
+                        <= radius as f32
+                {
+                    routed
+                        .insert(follower, Routed { turns })
+                        .expect("Unable to insert");
+                    fled = true;
+                }
+            }
+            if fled {
+                if let Some(name) = names.get(*victim) {
+                    crate::gamelog::Logger::new()
+                        .category(crate::gamelog::LogCategory::Combat)
+                        .npc_name(&name.name)
+                        .append("has fallen, and their followers flee in panic!")
+                        .log();
+                }
+            }
+        }
+    }
+
     // Fire death events
     use crate::components::{AreaOfEffect, OnDeath};
     use crate::effects::*;
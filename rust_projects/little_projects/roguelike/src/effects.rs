@@ -1,3 +1,4 @@
+use crate::components::{DamageType, Skill};
 use crate::map::Map;
 use crate::Point;
 use specs::prelude::*;
@@ -8,9 +9,16 @@ mod targeting;
 pub use targeting::*;
 mod hunger;
 mod movement;
+mod noise;
+pub use noise::{
+    DOOR_NOISE, HUNTING_TURNS, MELEE_NOISE, OVERBURDENED_NOISE, SHOT_NOISE, SUSPICIOUS_TURNS,
+};
 mod particles;
+mod skills;
+mod status;
+pub use status::add_status;
+pub(crate) use status::status_expired;
 mod triggers;
-use crate::components::AttributeBonus;
 
 lazy_static! {
     pub static ref EFFECT_QUEUE: Mutex<VecDeque<EffectSpawner>> = Mutex::new(VecDeque::new());
@@ -20,6 +28,7 @@ lazy_static! {
 pub enum EffectType {
     Damage {
         amount: i32,
+        damage_type: DamageType,
     },
     Bloodstain,
     Particle {
@@ -42,9 +51,6 @@ pub enum EffectType {
     Mana {
         amount: i32,
     },
-    Confusion {
-        turns: i32,
-    },
     TriggerFire {
         trigger: Entity,
     },
@@ -54,16 +60,8 @@ pub enum EffectType {
         depth: i32,
         player_only: bool,
     },
-    AttributeEffect {
-        bonus: AttributeBonus,
+    Status {
         name: String,
-        duration: i32,
-    },
-    Slow {
-        initiative_penalty: f32,
-    },
-    DamageOverTime {
-        damage: i32,
     },
     ParticleProjectile {
         glyph: rltk::FontCharType,
@@ -73,6 +71,13 @@ pub enum EffectType {
         speed: f32,
         path: Vec<Point>,
     },
+    Noise {
+        volume: i32,
+    },
+    /// A use of a skill, counting towards improving it
+    Practice {
+        skill: Skill,
+    },
 }
 
 #[derive(Clone, Debug)]
@@ -138,11 +143,8 @@ fn tile_effect_hits_entities(effect: &EffectType) -> bool {
         EffectType::WellFed => true,
         EffectType::Healing { .. } => true,
         EffectType::Mana { .. } => true,
-        EffectType::Confusion { .. } => true,
         EffectType::TeleportTo { .. } => true,
-        EffectType::AttributeEffect { .. } => true,
-        EffectType::Slow { .. } => true,
-        EffectType::DamageOverTime { .. } => true,
+        EffectType::Status { .. } => true,
         _ => false,
     }
 }
@@ -159,6 +161,7 @@ fn affect_tile(ecs: &mut World, effect: &mut EffectSpawner, tile_idx: i32) {
         EffectType::Bloodstain => damage::bloodstain(ecs, tile_idx),
         EffectType::Particle { .. } => particles::particle_to_tile(ecs, tile_idx, &effect),
         EffectType::ParticleProjectile { .. } => particles::projectile(ecs, tile_idx, &effect),
+        EffectType::Noise { .. } => noise::make_noise(ecs, effect, tile_idx),
         _ => {}
     }
 }
@@ -181,14 +184,17 @@ fn affect_entity(ecs: &mut World, effect: &mut EffectSpawner, target: Entity) {
                 particles::particle_to_tile(ecs, pos, &effect)
             }
         }
+        EffectType::Noise { .. } => {
+            if let Some(pos) = entity_position(ecs, target) {
+                noise::make_noise(ecs, effect, pos)
+            }
+        }
         EffectType::WellFed => hunger::well_fed(ecs, effect, target),
         EffectType::Healing { .. } => damage::heal_damage(ecs, effect, target),
         EffectType::Mana { .. } => damage::restore_mana(ecs, effect, target),
-        EffectType::Confusion { .. } => damage::add_confusion(ecs, effect, target),
         EffectType::TeleportTo { .. } => movement::apply_teleport(ecs, effect, target),
-        EffectType::AttributeEffect { .. } => damage::attribute_effect(ecs, effect, target),
-        EffectType::Slow { .. } => damage::slow(ecs, effect, target),
-        EffectType::DamageOverTime { .. } => damage::damage_over_time(ecs, effect, target),
+        EffectType::Status { .. } => status::apply_status(ecs, effect, target),
+        EffectType::Practice { .. } => skills::practice(ecs, effect, target),
         _ => {}
     }
 }
@@ -1,11 +1,11 @@
 use super::*;
 use crate::components::{
-    Attributes, Confusion, DamageOverTime, Duration, EquipmentChanged, Name, Player, Pools,
-    SerializeMe, Skills, Slow, StatusEffect,
+    AlertState, Alertness, Attributes, DamageResistances, EquipmentChanged, Equipped, Perks,
+    Player, Pools,
 };
-use crate::gamesystem::{mana_at_level, player_hp_at_level};
+use crate::gamesystem::{mana_at_level, mitigate_damage, player_hp_at_level};
 use crate::map::Map;
-use specs::saveload::{MarkedBuilder, SimpleMarker};
+use crate::RunState;
 
 pub fn inflict_damage(ecs: &mut World, damage: &EffectSpawner, target: Entity) {
     let mut pools = ecs.write_storage::<Pools>();
@@ -17,7 +17,19 @@ pub fn inflict_damage(ecs: &mut World, damage: &EffectSpawner, target: Entity) {
                     return;
                 }
             }
-            if let EffectType::Damage { amount } = damage.effect_type {
+            if let EffectType::Damage {
+                amount,
+                damage_type,
+            } = damage.effect_type
+            {
+                let (amount, _) = mitigate_damage(
+                    amount,
+                    damage_type,
+                    target,
+                    &ecs.read_storage::<DamageResistances>(),
+                    &ecs.read_storage::<Equipped>(),
+                );
+                let was_alive = pool.hit_points.current > 0;
                 pool.hit_points.current -= amount;
                 add_effect(None, EffectType::Bloodstain, Targets::Single { target });
                 add_effect(
@@ -33,13 +45,23 @@ pub fn inflict_damage(ecs: &mut World, damage: &EffectSpawner, target: Entity) {
                 if target == *player_entity {
                     crate::gamelog::record_event("Damage Taken", amount);
                 }
+                if let Some(alert) = ecs.write_storage::<Alertness>().get_mut(target) {
+                    alert.state = AlertState::Hunting;
+                    alert.turns = HUNTING_TURNS;
+                }
                 if let Some(creator) = damage.creator {
                     if creator == *player_entity {
                         crate::gamelog::record_event("Damage Inflicted", amount);
                     }
                 }
 
-                if pool.hit_points.current < 1 {
+                if was_alive && pool.hit_points.current < 1 {
+                    if target == *player_entity {
+                        crate::morgue::record_death(
+                            ecs,
+                            crate::morgue::cause_of_death(ecs, damage.creator, damage_type),
+                        );
+                    }
                     add_effect(
                         damage.creator,
                         EffectType::EntityDeath,
@@ -83,6 +105,7 @@ pub fn death(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
                     // We've gone up a level!
                     player_stats.level += 1;
                     crate::gamelog::Logger::new()
+                        .category(crate::gamelog::LogCategory::Combat)
                         .color(rltk::MAGENTA)
                         .append("Congratulations, you are now level")
                         .append(format!("{}", player_stats.level))
@@ -94,6 +117,7 @@ pub fn death(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
                         1 => {
                             player_attributes.might.base += 1;
                             crate::gamelog::Logger::new()
+                                .category(crate::gamelog::LogCategory::Combat)
                                 .color(rltk::GREEN)
                                 .append("You feel stronger!")
                                 .log();
@@ -101,6 +125,7 @@ pub fn death(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
                         2 => {
                             player_attributes.fitness.base += 1;
                             crate::gamelog::Logger::new()
+                                .category(crate::gamelog::LogCategory::Combat)
                                 .color(rltk::GREEN)
                                 .append("You feel stronger!")
                                 .log();
@@ -108,6 +133,7 @@ pub fn death(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
                         3 => {
                             player_attributes.quickness.base += 1;
                             crate::gamelog::Logger::new()
+                                .category(crate::gamelog::LogCategory::Combat)
                                 .color(rltk::GREEN)
                                 .append("You feel quicker!")
                                 .log();
@@ -115,17 +141,17 @@ pub fn death(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
                         _ => {
                             player_attributes.intelligence.base += 1;
                             crate::gamelog::Logger::new()
+                                .category(crate::gamelog::LogCategory::Combat)
                                 .color(rltk::GREEN)
                                 .append("You feel quicker!")
                                 .log();
                         }
                     }
 
-                    // Improve all skills
-                    let mut skills = ecs.write_storage::<Skills>();
-                    let player_skills = skills.get_mut(*ecs.fetch::<Entity>()).unwrap();
-                    for sk in player_skills.skills.iter_mut() {
-                        *sk.1 += 1;
+                    // Skills improve with use; levels buy a perk instead
+                    if let Some(perks) = ecs.write_storage::<Perks>().get_mut(source) {
+                        perks.pending += 1;
+                        *ecs.write_resource::<RunState>() = RunState::ShowPerks { selection: 0 };
                     }
 
                     ecs.write_storage::<EquipmentChanged>()
@@ -206,73 +232,3 @@ pub fn restore_mana(ecs: &mut World, mana: &EffectSpawner, target: Entity) {
         }
     }
 }
-
-pub fn add_confusion(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
-    if let EffectType::Confusion { turns } = &effect.effect_type {
-        ecs.create_entity()
-            .with(StatusEffect { target })
-            .with(Confusion {})
-            .with(Duration { turns: *turns })
-            .with(Name {
-                name: "Confusion".to_string(),
-            })
-            .marked::<SimpleMarker<SerializeMe>>()
-            .build();
-    }
-}
-
-pub fn attribute_effect(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
-    if let EffectType::AttributeEffect {
-        bonus,
-        name,
-        duration,
-    } = &effect.effect_type
-    {
-        ecs.create_entity()
-            .with(StatusEffect { target })
-            .with(bonus.clone())
-            .with(Duration { turns: *duration })
-            .with(Name { name: name.clone() })
-            .marked::<SimpleMarker<SerializeMe>>()
-            .build();
-        ecs.write_storage::<EquipmentChanged>()
-            .insert(target, EquipmentChanged {})
-            .expect("Insert failed");
-    }
-}
-
-pub fn slow(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
-    if let EffectType::Slow { initiative_penalty } = &effect.effect_type {
-        ecs.create_entity()
-            .with(StatusEffect { target })
-            .with(Slow {
-                initiative_penalty: *initiative_penalty,
-            })
-            .with(Duration { turns: 5 })
-            .with(if *initiative_penalty > 0.0 {
-                Name {
-                    name: "Slowed".to_string(),
-                }
-            } else {
-                Name {
-                    name: "Hasted".to_string(),
-                }
-            })
-            .marked::<SimpleMarker<SerializeMe>>()
-            .build();
-    }
-}
-
-pub fn damage_over_time(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
-    if let EffectType::DamageOverTime { damage } = &effect.effect_type {
-        ecs.create_entity()
-            .with(StatusEffect { target })
-            .with(DamageOverTime { damage: *damage })
-            .with(Duration { turns: 5 })
-            .with(Name {
-                name: "Damage Over Time".to_string(),
-            })
-            .marked::<SimpleMarker<SerializeMe>>()
-            .build();
-    }
-}
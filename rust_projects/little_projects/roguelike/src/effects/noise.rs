@@ -0,0 +1,97 @@
+use super::*;
+use crate::components::{AlertState, Alertness, Position, Skill, Skills};
+use crate::map::tile_opaque;
+use crate::skill_bonus;
+
+/// How loud the things people do are, in tiles of open floor the sound carries.
+pub const MELEE_NOISE: i32 = 8;
+pub const SHOT_NOISE: i32 = 10;
+pub const DOOR_NOISE: i32 = 6;
+pub const OVERBURDENED_NOISE: i32 = 5;
+
+/// Sound loses this much extra going through a closed door.
+const DOOR_MUFFLING: i32 = 4;
+
+/// Turns a mob stays hunting, and then suspicious, after it last saw or heard anything.
+pub const HUNTING_TURNS: i32 = 10;
+pub const SUSPICIOUS_TURNS: i32 = 8;
+
+/// How loud the noise still is on every tile it reaches. Walls stop it and closed doors muffle it.
+fn propagate(map: &Map, start: usize, volume: i32) -> Vec<i32> {
+    let mut loudness = vec![0; map.tiles.len()];
+    loudness[start] = volume;
+    let mut open = VecDeque::new();
+    open.push_back(start);
+    while let Some(idx) = open.pop_front() {
+        let (x, y) = (idx as i32 % map.width, idx as i32 / map.width);
+        for (dx, dy) in [
+            (-1, -1),
+            (0, -1),
+            (1, -1),
+            (-1, 0),
+            (1, 0),
+            (-1, 1),
+            (0, 1),
+            (1, 1),
+        ] {
+            let (nx, ny) = (x + dx, y + dy);
+            if nx < 0 || nx >= map.width || ny < 0 || ny >= map.height {
+                continue;
+            }
+            let next = map.xy_idx(nx, ny);
+            if tile_opaque(map.tiles[next]) {
+                continue;
+            }
+            let cost = if map.view_blocked.contains(&next) {
+                1 + DOOR_MUFFLING
+            } else {
+                1
+            };
+            let heard = loudness[idx] - cost;
+            if heard > loudness[next] {
+                loudness[next] = heard;
+                open.push_back(next);
+            }
+        }
+    }
+    loudness
+}
+
+pub fn make_noise(ecs: &mut World, effect: &EffectSpawner, tile_idx: i32) {
+    if let EffectType::Noise { volume } = effect.effect_type {
+        // Stealthy people go about their business more quietly
+        let volume = match effect
+            .creator
+            .and_then(|c| ecs.read_storage::<Skills>().get(c).cloned())
+        {
+            Some(skills) => i32::max(1, volume - skill_bonus(Skill::Stealth, &skills)),
+            None => volume,
+        };
+
+        let map = ecs.fetch::<Map>();
+        let entities = ecs.entities();
+        let positions = ecs.read_storage::<Position>();
+        let mut alertness = ecs.write_storage::<Alertness>();
+        // Carry the sound as far as the keenest ears could pick it up
+        let keenest = alertness
+            .join()
+            .map(|a| a.hearing)
+            .max()
+            .unwrap_or(0)
+            .max(0);
+        let loudness = propagate(&map, tile_idx as usize, volume + keenest);
+        for (entity, pos, alert) in (&entities, &positions, &mut alertness).join() {
+            let reached = loudness[map.xy_idx(pos.x, pos.y)];
+            if Some(entity) == effect.creator || reached < 1 {
+                continue;
+            }
+            if reached - keenest + alert.hearing > 0 {
+                alert.heard = Some(tile_idx as usize);
+                if alert.state == AlertState::Unaware {
+                    alert.state = AlertState::Suspicious;
+                    alert.turns = SUSPICIOUS_TURNS;
+                }
+            }
+        }
+    }
+}
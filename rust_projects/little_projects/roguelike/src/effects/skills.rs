@@ -0,0 +1,26 @@
+use super::*;
+use crate::components::{SkillProgress, Skills};
+use crate::gamesystem::practice_to_advance;
+
+/// Counts a use of a skill towards its next level. Only those with `SkillProgress` learn by doing.
+pub fn practice(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
+    if let EffectType::Practice { skill } = &effect.effect_type {
+        let mut progress = ecs.write_storage::<SkillProgress>();
+        let mut skills = ecs.write_storage::<Skills>();
+        if let (Some(progress), Some(skills)) = (progress.get_mut(target), skills.get_mut(target))
+        {
+            let level = skills.skills.get(skill).copied().unwrap_or(0);
+            let practice = progress.practice.entry(skill.clone()).or_insert(0);
+            *practice += 1;
+            if *practice >= practice_to_advance(level) {
+                *practice = 0;
+                skills.skills.insert(skill.clone(), level + 1);
+                crate::gamelog::Logger::new()
+                    .category(crate::gamelog::LogCategory::Combat)
+                    .color(rltk::GREEN)
+                    .append(format!("Your {:?} skill rises to {}!", skill, level + 1))
+                    .log();
+            }
+        }
+    }
+}
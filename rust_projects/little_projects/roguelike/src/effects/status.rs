@@ -0,0 +1,215 @@
+use super::*;
+use crate::components::{
+    AttributeBonus, Blinded, Confusion, DamageOverTime, DamageType, Duration, EquipmentChanged,
+    Equipped, HealOverTime, Name, Player, Routed, SerializeMe, Slow, StatusEffect,
+    StatusResistances, StatusStacks, Viewshed,
+};
+use crate::raws::{get_status_effect, string_to_damage_type, StatusEffectDef, RAWS};
+use specs::saveload::{MarkedBuilder, SimpleMarker};
+
+pub fn apply_status(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
+    if let EffectType::Status { name } = &effect.effect_type {
+        add_status(ecs, target, name);
+    }
+}
+
+/// The target's resistance to a status effect, counting what they wear, as a percentage.
+fn resistance(ecs: &World, target: Entity, name: &str) -> i32 {
+    let resistances = ecs.read_storage::<StatusResistances>();
+    let own = resistances
+        .get(target)
+        .and_then(|r| r.resistances.get(name))
+        .copied()
+        .unwrap_or(0);
+    let worn: i32 = (&ecs.read_storage::<Equipped>(), &resistances)
+        .join()
+        .filter(|(equipped, _)| equipped.owner == target)
+        .filter_map(|(_, r)| r.resistances.get(name))
+        .sum();
+    i32::min(100, own + worn)
+}
+
+/// Afflicts the target with a status effect from the raws, following its stacking rule.
+pub fn add_status(ecs: &mut World, target: Entity, name: &str) {
+    let raws = RAWS.lock().unwrap();
+    let def = match get_status_effect(&raws, name) {
+        Some(def) => def,
+        None => {
+            crate::diagnostic(format!("WARNING - unknown status effect [{}]", name));
+            return;
+        }
+    };
+
+    let turns = def.duration * (100 - resistance(ecs, target, name)) / 100;
+    if turns < 1 {
+        if ecs.read_storage::<Player>().get(target).is_some() {
+            crate::gamelog::Logger::new()
+                .category(crate::gamelog::LogCategory::Combat)
+                .append("You are unaffected by")
+                .color(rltk::CYAN)
+                .append(name)
+                .log();
+        }
+        return;
+    }
+
+    let existing = (
+        &ecs.entities(),
+        &ecs.read_storage::<StatusEffect>(),
+        &ecs.read_storage::<Name>(),
+    )
+        .join()
+        .find(|(_, status, status_name)| status.target == target && status_name.name == name)
+        .map(|(entity, _, _)| entity);
+
+    let (status, stacks) = match existing {
+        Some(status) => {
+            let stacks = ecs
+                .read_storage::<StatusStacks>()
+                .get(status)
+                .map_or(1, |s| s.stacks);
+            let stacks = match def.stacking.as_deref() {
+                Some("ignore") => return,
+                Some("stack") => i32::min(stacks + 1, def.max_stacks.unwrap_or(i32::MAX)),
+                _ => stacks,
+            };
+            if let Some(duration) = ecs.write_storage::<Duration>().get_mut(status) {
+                duration.turns = i32::max(duration.turns, turns);
+            }
+            (status, stacks)
+        }
+        None => {
+            let status = ecs
+                .create_entity()
+                .with(StatusEffect { target })
+                .with(Duration { turns })
+                .with(Name {
+                    name: name.to_string(),
+                })
+                .marked::<SimpleMarker<SerializeMe>>()
+                .build();
+            (status, 1)
+        }
+    };
+    apply_stacks(ecs, status, def, stacks);
+
+    if def.flee.unwrap_or(false) && ecs.read_storage::<Player>().get(target).is_none() {
+        let mut routed = ecs.write_storage::<Routed>();
+        let turns = routed
+            .get(target)
+            .map_or(turns, |r| i32::max(r.turns, turns));
+        routed
+            .insert(target, Routed { turns })
+            .expect("Insert failed");
+    }
+    if let Some(viewshed) = ecs.write_storage::<Viewshed>().get_mut(target) {
+        viewshed.dirty = true;
+    }
+    ecs.write_storage::<EquipmentChanged>()
+        .insert(target, EquipmentChanged {})
+        .expect("Insert failed");
+}
+
+/// Gives a status effect's entity the components that do its work, scaled by the stacks.
+fn apply_stacks(ecs: &mut World, status: Entity, def: &StatusEffectDef, stacks: i32) {
+    ecs.write_storage::<StatusStacks>()
+        .insert(status, StatusStacks { stacks })
+        .expect("Insert failed");
+    if def.skip_turn.unwrap_or(false) {
+        ecs.write_storage::<Confusion>()
+            .insert(status, Confusion {})
+            .expect("Insert failed");
+    }
+    if let Some(penalty) = def.initiative_penalty {
+        ecs.write_storage::<Slow>()
+            .insert(
+                status,
+                Slow {
+                    initiative_penalty: penalty * stacks as f32,
+                },
+            )
+            .expect("Insert failed");
+    }
+    if let Some(vision_range) = def.vision_range {
+        ecs.write_storage::<Blinded>()
+            .insert(status, Blinded { vision_range })
+            .expect("Insert failed");
+    }
+    if let Some(attr) = &def.attributes {
+        let scale = |bonus: Option<i32>| bonus.map(|b| b * stacks);
+        ecs.write_storage::<AttributeBonus>()
+            .insert(
+                status,
+                AttributeBonus {
+                    might: scale(attr.might),
+                    fitness: scale(attr.fitness),
+                    quickness: scale(attr.quickness),
+                    intelligence: scale(attr.intelligence),
+                },
+            )
+            .expect("Insert failed");
+    }
+    if let Some(tick) = &def.on_tick {
+        if let Some(damage) = tick.damage {
+            ecs.write_storage::<DamageOverTime>()
+                .insert(
+                    status,
+                    DamageOverTime {
+                        damage: damage * stacks,
+                        damage_type: tick
+                            .damage_type
+                            .as_ref()
+                            .map_or(DamageType::Physical, |t| string_to_damage_type(t)),
+                    },
+                )
+                .expect("Insert failed");
+        }
+        if let Some(amount) = tick.healing {
+            ecs.write_storage::<HealOverTime>()
+                .insert(
+                    status,
+                    HealOverTime {
+                        amount: amount * stacks,
+                    },
+                )
+                .expect("Insert failed");
+        }
+    }
+}
+
+/// Queues whatever a status effect does when it runs out. Effects that aren't in the raws, or
+/// are no longer, do nothing.
+pub fn status_expired(name: &str, target: Entity) {
+    let raws = RAWS.lock().unwrap();
+    let expiry = match get_status_effect(&raws, name).and_then(|def| def.on_expire.as_ref()) {
+        Some(expiry) => expiry,
+        None => return,
+    };
+    if let Some(amount) = expiry.damage {
+        add_effect(
+            None,
+            EffectType::Damage {
+                amount,
+                damage_type: expiry
+                    .damage_type
+                    .as_ref()
+                    .map_or(DamageType::Physical, |t| string_to_damage_type(t)),
+            },
+            Targets::Single { target },
+        );
+    }
+    if let Some(amount) = expiry.healing {
+        add_effect(
+            None,
+            EffectType::Healing { amount },
+            Targets::Single { target },
+        );
+    }
+    if let Some(name) = &expiry.status {
+        add_effect(
+            None,
+            EffectType::Status { name: name.clone() },
+            Targets::Single { target },
+        );
+    }
+}
@@ -1,6 +1,13 @@
 use specs::prelude::*;
-use crate::components::{Position, InBackpack, Equipped};
+use crate::components::{Position, InBackpack, Equipped, Pools, TargetShape};
 use crate::map::Map;
+use rltk::BaseMap;
+use rltk::Point;
+
+/// How far either side of the aim a cone spreads, in degrees.
+const CONE_HALF_ANGLE: f32 = 45.0;
+/// How far a chain can jump between targets.
+const CHAIN_RANGE: i32 = 4;
 
 pub fn entity_position(ecs: &World, target: Entity) -> Option<i32> {
     if let Some(pos) = ecs.read_storage::<Position>().get(target) {
@@ -20,6 +27,88 @@ pub fn aoe_tiles(map: &Map, target: rltk::Point, radius: i32) -> Vec<i32> {
     result
 }
 
+fn in_bounds(map: &Map, p: Point) -> bool {
+    p.x > 0 && p.x < map.width-1 && p.y > 0 && p.y < map.height-1
+}
+
+/// Someone who could be hurt is standing on the tile.
+fn has_creature(ecs: &World, idx: usize) -> bool {
+    let pools = ecs.read_storage::<Pools>();
+    crate::spatial::get_tile_content_clone(idx).iter().any(|e| pools.get(*e).is_some())
+}
+
+/// The tiles on the way from `origin` to `end`, stopping before the first wall or closed door.
+fn path(map: &Map, origin: Point, end: Point) -> Vec<Point> {
+    rltk::line2d(rltk::LineAlg::Bresenham, origin, end).into_iter()
+        .filter(|p| *p != origin)
+        .take_while(|p| in_bounds(map, *p) && !map.is_opaque(map.xy_idx(p.x, p.y)))
+        .collect()
+}
+
+/// `length` tiles from `origin` towards `target`, and past it if it's closer than that.
+fn ray(map: &Map, origin: Point, target: Point, length: i32) -> Vec<Point> {
+    let distance = rltk::DistanceAlg::Pythagoras.distance2d(origin, target).max(1.0);
+    let scale = length as f32 / distance;
+    let end = Point::new(
+        origin.x + ((target.x - origin.x) as f32 * scale).round() as i32,
+        origin.y + ((target.y - origin.y) as f32 * scale).round() as i32
+    );
+    path(map, origin, end)
+}
+
+/// The tiles a spell of this shape hits when cast from `origin` at `target`.
+pub fn shape_tiles(ecs: &World, shape: TargetShape, origin: Point, target: Point) -> Vec<i32> {
+    let map = ecs.fetch::<Map>();
+    let idx = |p: &Point| map.xy_idx(p.x, p.y) as i32;
+    match shape {
+        TargetShape::Bolt => {
+            let path = path(&map, origin, target);
+            match path.iter().find(|p| has_creature(ecs, map.xy_idx(p.x, p.y))) {
+                Some(hit) => vec![idx(hit)],
+                None => path.last().map(|p| vec![idx(p)]).unwrap_or_default()
+            }
+        }
+        TargetShape::Line { length } => ray(&map, origin, target, length).iter().map(idx).collect(),
+        TargetShape::Cone { length } => {
+            if target == origin { return Vec::new(); }
+            let aim = ((target.x - origin.x) as f32, (target.y - origin.y) as f32);
+            let aim_length = f32::sqrt(aim.0 * aim.0 + aim.1 * aim.1);
+            let min_cos = CONE_HALF_ANGLE.to_radians().cos();
+            rltk::field_of_view(origin, length, &*map).iter()
+                .filter(|p| **p != origin && in_bounds(&map, **p))
+                .filter(|p| {
+                    let offset = ((p.x - origin.x) as f32, (p.y - origin.y) as f32);
+                    let offset_length = f32::sqrt(offset.0 * offset.0 + offset.1 * offset.1);
+                    (offset.0 * aim.0 + offset.1 * aim.1) / (offset_length * aim_length) >= min_cos
+                })
+                .map(idx)
+                .collect()
+        }
+        TargetShape::Burst { radius } => {
+            aoe_tiles(&map, origin, radius).into_iter().filter(|t| *t != idx(&origin)).collect()
+        }
+        TargetShape::Chain { jumps } => {
+            let mut hit = vec![idx(&target)];
+            let mut last = target;
+            for _ in 0..jumps {
+                let next = rltk::field_of_view(last, CHAIN_RANGE, &*map).into_iter()
+                    .filter(|p| *p != origin && in_bounds(&map, *p) && !hit.contains(&idx(p)))
+                    .filter(|p| has_creature(ecs, map.xy_idx(p.x, p.y)))
+                    .min_by(|a, b| {
+                        let da = rltk::DistanceAlg::Pythagoras.distance2d(last, *a);
+                        let db = rltk::DistanceAlg::Pythagoras.distance2d(last, *b);
+                        da.partial_cmp(&db).unwrap()
+                    });
+                match next {
+                    Some(p) => { hit.push(idx(&p)); last = p; }
+                    None => break
+                }
+            }
+            hit
+        }
+    }
+}
+
 pub fn find_item_position(ecs: &World, target: Entity, creator: Option<Entity>) -> Option<i32> {
     let positions = ecs.read_storage::<Position>();
     let map = ecs.fetch::<Map>();
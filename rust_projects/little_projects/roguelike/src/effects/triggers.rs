@@ -8,6 +8,7 @@ pub fn item_trigger(creator: Option<Entity>, item: Entity, targets: &Targets, ec
         if c.charges < 1 {
             // Cancel
             crate::gamelog::Logger::new()
+                .category(crate::gamelog::LogCategory::Loot)
                 .item_name(&ecs.read_storage::<Name>().get(item).unwrap().name)
                 .append("is out of charges!")
                 .log();
@@ -23,7 +24,7 @@ pub fn item_trigger(creator: Option<Entity>, item: Entity, targets: &Targets, ec
     // If it was a consumable, then it gets deleted
     if did_something {
         if let Some(c) = ecs.read_storage::<Consumable>().get(item) {
-            rltk::console::log(format!("{}", c.max_charges));
+            crate::diagnostic(format!("{}", c.max_charges));
             if c.max_charges < 2 {
                 ecs.entities().delete(item).expect("Delete Failed");
             }
@@ -35,14 +36,41 @@ pub fn spell_trigger(creator: Option<Entity>, spell: Entity, targets: &Targets,
     let mut targeting = targets.clone();
     let mut self_destruct = false;
     if let Some(template) = ecs.read_storage::<SpellTemplate>().get(spell) {
-        let mut pools = ecs.write_storage::<Pools>();
         if let Some(caster) = creator {
-            if let Some(pool) = pools.get_mut(caster) {
+            if let Some(pool) = ecs.write_storage::<Pools>().get_mut(caster) {
                 if template.mana_cost <= pool.mana.current {
                     pool.mana.current -= template.mana_cost;
                 }
             }
 
+            // Start the cooldown for anyone who knows the spell
+            if let Some(known) = ecs.write_storage::<KnownSpells>().get_mut(caster) {
+                let names = ecs.read_storage::<Name>();
+                let name = &names.get(spell).unwrap().name;
+                for known_spell in known.spells.iter_mut() {
+                    if known_spell.display_name == *name {
+                        known_spell.cooldown = template.cooldown;
+                    }
+                }
+            }
+
+            // Shaped spells work out their own tiles from the caster to where they aimed
+            if let Some(shape) = ecs.read_storage::<SpellShape>().get(spell) {
+                if let Some(pos) = ecs.read_storage::<Position>().get(caster) {
+                    let origin = rltk::Point::new(pos.x, pos.y);
+                    let aim = match &targeting {
+                        Targets::Tile { tile_idx } => {
+                            let map = ecs.fetch::<Map>();
+                            rltk::Point::new(tile_idx % map.width, tile_idx / map.width)
+                        }
+                        _ => origin,
+                    };
+                    targeting = Targets::Tiles {
+                        tiles: shape_tiles(ecs, shape.shape, origin, aim),
+                    };
+                }
+            }
+
             // Handle self-targeting override
             if ecs.read_storage::<AlwaysTargetsSelf>().get(spell).is_some() {
                 if let Some(pos) = ecs.read_storage::<Position>().get(caster) {
@@ -141,6 +169,7 @@ fn event_trigger(
         add_effect(creator, EffectType::WellFed, targets.clone());
         let names = ecs.read_storage::<Name>();
         crate::gamelog::Logger::new()
+            .category(crate::gamelog::LogCategory::Loot)
             .append("You eat the")
             .item_name(&names.get(entity).unwrap().name)
             .log();
@@ -151,6 +180,7 @@ fn event_trigger(
     if ecs.read_storage::<MagicMapper>().get(entity).is_some() {
         let mut runstate = ecs.fetch_mut::<RunState>();
         crate::gamelog::Logger::new()
+            .category(crate::gamelog::LogCategory::System)
             .append("The map is revealed to you!")
             .log();
         *runstate = RunState::MagicMapReveal { row: 0 };
@@ -184,10 +214,12 @@ fn event_trigger(
         let map = ecs.fetch::<Map>();
         if map.depth == 1 {
             crate::gamelog::Logger::new()
+                .category(crate::gamelog::LogCategory::System)
                 .append("You are already in town, so the scroll does nothing.")
                 .log();
         } else {
             crate::gamelog::Logger::new()
+                .category(crate::gamelog::LogCategory::System)
                 .append("You are telported back to town!")
                 .log();
             let mut runstate = ecs.fetch_mut::<RunState>();
@@ -226,26 +258,13 @@ fn event_trigger(
             creator,
             EffectType::Damage {
                 amount: damage.damage,
+                damage_type: damage.damage_type,
             },
             targets.clone(),
         );
         did_something = true;
     }
 
-    // Confusion
-    if let Some(_confusion) = ecs.read_storage::<Confusion>().get(entity) {
-        if let Some(duration) = ecs.read_storage::<Duration>().get(entity) {
-            add_effect(
-                creator,
-                EffectType::Confusion {
-                    turns: duration.turns,
-                },
-                targets.clone(),
-            );
-            did_something = true;
-        }
-    }
-
     // Teleport
     if let Some(teleport) = ecs.read_storage::<TeleportTo>().get(entity) {
         add_effect(
@@ -261,20 +280,6 @@ fn event_trigger(
         did_something = true;
     }
 
-    // Attribute Modifiers
-    if let Some(attr) = ecs.read_storage::<AttributeBonus>().get(entity) {
-        add_effect(
-            creator,
-            EffectType::AttributeEffect {
-                bonus: attr.clone(),
-                duration: 10,
-                name: ecs.read_storage::<Name>().get(entity).unwrap().name.clone(),
-            },
-            targets.clone(),
-        );
-        did_something = true;
-    }
-
     // Learn spells
     if let Some(spell) = ecs.read_storage::<TeachesSpell>().get(entity) {
         if let Some(known) = ecs.write_storage::<KnownSpells>().get_mut(creator.unwrap()) {
@@ -286,10 +291,18 @@ fn event_trigger(
                             already_known = true
                         }
                     });
-                    if !already_known {
+                    let slots =
+                        crate::spell_slots(ecs.read_storage::<Perks>().get(creator.unwrap()));
+                    if !already_known && known.spells.len() >= slots {
+                        crate::gamelog::Logger::new()
+                            .category(crate::gamelog::LogCategory::System)
+                            .append("You can't hold any more spells in your head.")
+                            .log();
+                    } else if !already_known {
                         known.spells.push(KnownSpell {
                             display_name: spell.spell.clone(),
                             mana_cost: spell_info.mana_cost,
+                            cooldown: 0,
                         });
                     }
                 }
@@ -298,27 +311,15 @@ fn event_trigger(
         did_something = true;
     }
 
-    // Slow
-    if let Some(slow) = ecs.read_storage::<Slow>().get(entity) {
-        add_effect(
-            creator,
-            EffectType::Slow {
-                initiative_penalty: slow.initiative_penalty,
-            },
-            targets.clone(),
-        );
-        did_something = true;
-    }
-
-    // Damage Over Time
-    if let Some(damage) = ecs.read_storage::<DamageOverTime>().get(entity) {
-        add_effect(
-            creator,
-            EffectType::DamageOverTime {
-                damage: damage.damage,
-            },
-            targets.clone(),
-        );
+    // Status effects
+    if let Some(applies) = ecs.read_storage::<AppliesStatus>().get(entity) {
+        for name in applies.statuses.iter() {
+            add_effect(
+                creator,
+                EffectType::Status { name: name.clone() },
+                targets.clone(),
+            );
+        }
         did_something = true;
     }
 
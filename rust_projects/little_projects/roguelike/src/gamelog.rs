@@ -1,15 +1,51 @@
 use rltk::RGB;
 mod logstore;
 use logstore::*;
-pub use logstore::{clear_log, clone_log, print_log, restore_log};
+pub use logstore::{
+    clear_log, clone_log, export_log, log_lines, print_log, recent_lines, restore_log, LogLine,
+};
 mod builder;
 pub use builder::*;
 use serde::{Deserialize, Serialize};
 mod events;
 pub use events::*;
 
+/// What a log entry is about, so the log screen can show one kind at a time.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub enum LogCategory {
+    Combat,
+    Loot,
+    Dialogue,
+    #[default]
+    System,
+}
+
+impl LogCategory {
+    pub const ALL: [LogCategory; 4] = [
+        LogCategory::Combat,
+        LogCategory::Loot,
+        LogCategory::Dialogue,
+        LogCategory::System,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LogCategory::Combat => "Combat",
+            LogCategory::Loot => "Loot",
+            LogCategory::Dialogue => "Dialogue",
+            LogCategory::System => "System",
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct LogFragment {
     pub color: RGB,
     pub text: String,
+    /// Missing from saves made before the log was categorised
+    #[serde(default)]
+    pub category: LogCategory,
+    /// The turn the entry was logged on
+    #[serde(default)]
+    pub turn: i32,
 }
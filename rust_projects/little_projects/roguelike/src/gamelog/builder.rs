@@ -1,15 +1,24 @@
-use super::{append_entry, LogFragment};
+use super::{append_entry, get_event_count, LogCategory, LogFragment};
+use crate::components::DamageType;
 use rltk::prelude::*;
 
 pub struct Logger {
     current_color: RGB,
+    category: LogCategory,
     fragments: Vec<LogFragment>,
 }
 
+impl Default for Logger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Logger {
     pub fn new() -> Self {
         Logger {
             current_color: RGB::named(rltk::WHITE),
+            category: LogCategory::System,
             fragments: Vec::new(),
         }
     }
@@ -19,39 +28,62 @@ impl Logger {
         self
     }
 
-    pub fn append<T: ToString>(mut self, text: T) -> Self {
+    /// Files the entry under this category; entries are system messages unless told otherwise.
+    pub fn category(mut self, category: LogCategory) -> Self {
+        self.category = category;
+        self
+    }
+
+    fn push(&mut self, color: RGB, text: String) {
         self.fragments.push(LogFragment {
-            color: self.current_color,
-            text: text.to_string(),
+            color,
+            text,
+            category: LogCategory::System,
+            turn: 0,
         });
+    }
+
+    pub fn append<T: ToString>(mut self, text: T) -> Self {
+        self.push(self.current_color, text.to_string());
         self
     }
 
-    pub fn log(self) {
+    pub fn log(mut self) {
+        let turn = get_event_count("Turn");
+        for fragment in self.fragments.iter_mut() {
+            fragment.category = self.category;
+            fragment.turn = turn;
+        }
         append_entry(self.fragments)
     }
 
     pub fn npc_name<T: ToString>(mut self, text: T) -> Self {
-        self.fragments.push(LogFragment {
-            color: RGB::named(rltk::YELLOW),
-            text: text.to_string(),
-        });
+        self.push(RGB::named(rltk::YELLOW), text.to_string());
         self
     }
 
     pub fn item_name<T: ToString>(mut self, text: T) -> Self {
-        self.fragments.push(LogFragment {
-            color: RGB::named(rltk::CYAN),
-            text: text.to_string(),
-        });
+        self.push(RGB::named(rltk::CYAN), text.to_string());
         self
     }
 
     pub fn damage(mut self, damage: i32) -> Self {
-        self.fragments.push(LogFragment {
-            color: RGB::named(rltk::RED),
-            text: format!("{}", damage).to_string(),
-        });
+        self.push(RGB::named(rltk::RED), format!("{}", damage));
         self
     }
+
+    /// Damage dealt, its type and how much of it was resisted, ending the sentence.
+    pub fn typed_damage(self, damage: i32, damage_type: DamageType, resisted: i32) -> Self {
+        let kind = if damage_type == DamageType::Physical {
+            "hp".to_string()
+        } else {
+            format!("{} damage", damage_type.name())
+        };
+        let text = match resisted {
+            0 => format!("{}.", kind),
+            r if r > 0 => format!("{} ({} resisted).", kind, r),
+            r => format!("{} ({} from weakness).", kind, -r),
+        };
+        self.damage(damage).append(text)
+    }
 }
@@ -1,5 +1,6 @@
-use super::LogFragment;
+use super::{LogCategory, LogFragment};
 use rltk::prelude::*;
+use std::path::Path;
 use std::sync::Mutex;
 
 lazy_static! {
@@ -18,11 +19,94 @@ pub fn clear_log() {
     LOG.lock().unwrap().clear();
 }
 
+/// A log entry, with identical entries in a row folded into it.
+pub struct LogLine {
+    pub turn: i32,
+    pub category: LogCategory,
+    pub fragments: Vec<LogFragment>,
+    /// How many times in a row it was logged
+    pub count: usize,
+}
+
+impl LogLine {
+    pub fn text(&self) -> String {
+        let text = self
+            .fragments
+            .iter()
+            .map(|frag| frag.text.as_str())
+            .collect::<Vec<&str>>()
+            .join(" ");
+        if self.count > 1 {
+            format!("{} x{}", text, self.count)
+        } else {
+            text
+        }
+    }
+}
+
+fn entry_text(entry: &[LogFragment]) -> Vec<&str> {
+    entry.iter().map(|frag| frag.text.as_str()).collect()
+}
+
+/// The log from oldest to newest, optionally only one category of it, with repeats collapsed.
+pub fn log_lines(filter: Option<LogCategory>) -> Vec<LogLine> {
+    let log = LOG.lock().unwrap();
+    let mut lines: Vec<LogLine> = Vec::new();
+    for entry in log.iter() {
+        let category = entry
+            .first()
+            .map_or(LogCategory::System, |frag| frag.category);
+        if entry.is_empty() || filter.is_some_and(|filter| filter != category) {
+            continue;
+        }
+        if let Some(last) = lines.last_mut() {
+            if entry_text(&last.fragments) == entry_text(entry) {
+                last.count += 1;
+                last.turn = entry[0].turn;
+                continue;
+            }
+        }
+        lines.push(LogLine {
+            turn: entry[0].turn,
+            category,
+            fragments: entry.clone(),
+            count: 1,
+        });
+    }
+    lines
+}
+
+/// The last few lines of the whole log, oldest first, collapsed as by [`log_lines`]. Only the end
+/// of the log is read, so it is cheap enough to call every frame.
+pub fn recent_lines(count: usize) -> Vec<LogLine> {
+    let log = LOG.lock().unwrap();
+    let mut lines: Vec<LogLine> = Vec::new();
+    for entry in log.iter().rev().filter(|entry| !entry.is_empty()) {
+        if let Some(last) = lines.last_mut() {
+            if entry_text(&last.fragments) == entry_text(entry) {
+                last.count += 1;
+                continue;
+            }
+        }
+        if lines.len() == count {
+            break;
+        }
+        lines.push(LogLine {
+            turn: entry[0].turn,
+            category: entry[0].category,
+            fragments: entry.clone(),
+            count: 1,
+        });
+    }
+    lines.reverse();
+    lines
+}
+
 pub fn print_log(console: &mut Box<dyn Console>, pos: Point) {
     let mut y = pos.y;
     let mut x = pos.x;
-    LOG.lock().unwrap().iter().rev().take(6).for_each(|log| {
-        log.iter().for_each(|frag| {
+    recent_lines(6).iter().rev().for_each(|line| {
+        line.fragments.iter().for_each(|frag| {
             console.print_color(
                 x,
                 y,
@@ -33,11 +117,39 @@ pub fn print_log(console: &mut Box<dyn Console>, pos: Point) {
             x += frag.text.len() as i32;
             x += 1;
         });
+        if line.count > 1 {
+            console.print_color(
+                x,
+                y,
+                RGBA::named(rltk::GRAY),
+                RGBA::named(rltk::BLACK),
+                &format!("x{}", line.count),
+            );
+        }
         y += 1;
         x = pos.x;
     });
 }
 
+/// Writes the whole log to a text file, one entry per line with the turn it happened on.
+pub fn export_log(path: &Path) -> Result<(), String> {
+    let text: String = log_lines(None)
+        .iter()
+        .map(|line| {
+            format!(
+                "[turn {:>5}] [{}] {}\n",
+                line.turn,
+                line.category.name(),
+                line.text()
+            )
+        })
+        .collect();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(path, text).map_err(|e| e.to_string())
+}
+
 pub fn clone_log() -> Vec<Vec<crate::gamelog::LogFragment>> {
     LOG.lock().unwrap().clone()
 }
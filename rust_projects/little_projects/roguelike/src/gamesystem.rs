@@ -1,4 +1,5 @@
-use super::{Skill, Skills};
+use super::{Attributes, DamageResistances, DamageType, Equipped, Perks, Skill, Skills};
+use specs::prelude::*;
 
 pub fn attr_bonus(value: i32) -> i32 {
     (value-10)/2 // See: https://roll20.net/compendium/dnd5e/Ability%20Scores#content
@@ -28,6 +29,21 @@ pub fn mana_at_level(intelligence: i32, level: i32) -> i32 {
     mana_per_level(intelligence) * level
 }
 
+/// Anyone carrying more than this is overburdened.
+pub fn carry_capacity_lbs(attributes: &Attributes, perks: Option<&Perks>) -> i32 {
+    (attributes.might.base + attributes.might.modifiers) * 15 + perks.map_or(0, |p| p.carry_weight)
+}
+
+/// How many spells can be known at once.
+pub fn spell_slots(perks: Option<&Perks>) -> usize {
+    (3 + perks.map_or(0, |p| p.spell_slots)).max(0) as usize
+}
+
+/// Uses of a skill it takes to raise it from this level to the next.
+pub fn practice_to_advance(level: i32) -> i32 {
+    25 * i32::max(1, level)
+}
+
 pub fn skill_bonus(skill : Skill, skills: &Skills) -> i32 {
     if skills.skills.contains_key(&skill) {
         skills.skills[&skill]
@@ -35,3 +51,28 @@ pub fn skill_bonus(skill : Skill, skills: &Skills) -> i32 {
         -4
     }
 }
+
+/// Applies the target's resistances, and those of whatever they wear, to some damage. Returns
+/// the damage dealt and how much was resisted; weaknesses resist a negative amount.
+pub fn mitigate_damage(
+    amount: i32,
+    damage_type: DamageType,
+    target: Entity,
+    resistances: &ReadStorage<DamageResistances>,
+    equipped: &ReadStorage<Equipped>,
+) -> (i32, i32) {
+    let mut total = resistances
+        .get(target)
+        .and_then(|r| r.resistances.get(&damage_type))
+        .copied()
+        .unwrap_or_default();
+    for (_, worn) in (equipped, resistances).join().filter(|(e, _)| e.owner == target) {
+        if let Some(mitigation) = worn.resistances.get(&damage_type) {
+            total.percent += mitigation.percent;
+            total.flat += mitigation.flat;
+        }
+    }
+    let percent = i32::min(100, total.percent);
+    let dealt = i32::max(0, amount * (100 - percent) / 100 - total.flat);
+    (dealt, amount - dealt)
+}
@@ -29,9 +29,29 @@ mod ranged_target;
 pub use ranged_target::*;
 mod main_menu;
 pub use main_menu::*;
+mod seed_menu;
+pub use seed_menu::*;
+mod character_menu;
+pub use character_menu::*;
+mod perk_menu;
+pub use perk_menu::*;
+mod spellbook_menu;
+pub use spellbook_menu::*;
+mod key_bindings_menu;
+pub use key_bindings_menu::*;
+mod log_menu;
+pub use log_menu::*;
+mod high_scores_menu;
+pub use high_scores_menu::*;
+mod load_game_menu;
+pub use load_game_menu::*;
+mod save_game_menu;
+pub use save_game_menu::*;
 mod game_over_menu;
 pub use game_over_menu::*;
 mod cheat_menu;
 pub use cheat_menu::*;
 mod vendor_menu;
 pub use vendor_menu::*;
+mod context_menu;
+pub use context_menu::*;
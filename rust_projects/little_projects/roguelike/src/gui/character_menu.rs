@@ -0,0 +1,323 @@
+use crate::character::{DEFAULT_NAME, MAX_NAME_LENGTH, POINT_BUY_MAX, POINT_BUY_MIN};
+use crate::input::{key_hint, menu_action, Action};
+use crate::raws::{background_names, get_background, RAWS};
+use crate::{Character, State};
+use rltk::prelude::*;
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum CreationStep {
+    Background,
+    Attributes { rolled: bool },
+    Name { rolled: bool },
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum CharacterMenuResult {
+    NoResponse {
+        step: CreationStep,
+        selection: usize,
+    },
+    Cancel,
+    Done,
+}
+
+const ATTRIBUTE_NAMES: [&str; 4] = ["Might", "Fitness", "Quickness", "Intelligence"];
+
+fn white() -> ColorPair {
+    ColorPair::new(RGB::named(rltk::WHITE), RGB::named(rltk::BLACK))
+}
+
+fn gray() -> ColorPair {
+    ColorPair::new(RGB::named(rltk::GRAY), RGB::named(rltk::BLACK))
+}
+
+fn highlight(highlighted: bool) -> ColorPair {
+    if highlighted {
+        ColorPair::new(RGB::named(rltk::MAGENTA), RGB::named(rltk::BLACK))
+    } else {
+        white()
+    }
+}
+
+fn join_or_none(list: &Option<Vec<String>>) -> String {
+    match list {
+        Some(list) if !list.is_empty() => list.join(", "),
+        _ => "nothing".to_string(),
+    }
+}
+
+/// The background's description and what it starts with, one line each.
+fn background_lines(name: &str) -> Vec<String> {
+    let raws = RAWS.lock().unwrap();
+    let background = match get_background(&raws, name) {
+        Some(background) => background,
+        None => return Vec::new(),
+    };
+    let mut skills: Vec<String> = background
+        .skills
+        .iter()
+        .flatten()
+        .map(|(skill, level)| format!("{} {}", skill, level))
+        .collect();
+    skills.sort();
+    vec![
+        background.description.clone(),
+        format!(
+            "Skills: {}",
+            if skills.is_empty() {
+                "nothing special".to_string()
+            } else {
+                skills.join(", ")
+            }
+        ),
+        format!("Spells: {}", join_or_none(&background.spells)),
+        format!("Gear: {}", join_or_none(&background.equipped)),
+        format!("Pack: {}", join_or_none(&background.carried)),
+    ]
+}
+
+/// The letter or punctuation a key types into a name, if any.
+fn name_char(key: VirtualKeyCode, upper: bool) -> Option<char> {
+    let letter = rltk::letter_to_option(key);
+    if letter >= 0 {
+        let c = (b'a' + letter as u8) as char;
+        return Some(if upper { c.to_ascii_uppercase() } else { c });
+    }
+    match key {
+        VirtualKeyCode::Space => Some(' '),
+        VirtualKeyCode::Minus => Some('-'),
+        VirtualKeyCode::Apostrophe => Some('\''),
+        _ => None,
+    }
+}
+
+fn background_step(
+    gs: &mut State,
+    ctx: &Rltk,
+    draw_batch: &mut DrawBatch,
+    selection: usize,
+) -> CharacterMenuResult {
+    let names = background_names(&RAWS.lock().unwrap());
+    if names.is_empty() {
+        return CharacterMenuResult::Cancel;
+    }
+    let selection = selection.min(names.len() - 1);
+
+    draw_batch.print_color(Point::new(8, 16), "Choose a background:", white());
+    for (i, name) in names.iter().enumerate() {
+        draw_batch.print_color(
+            Point::new(10, 18 + i as i32),
+            name,
+            highlight(i == selection),
+        );
+    }
+    let y = 19 + names.len() as i32;
+    for (i, line) in background_lines(&names[selection]).iter().enumerate() {
+        draw_batch.print_color(Point::new(8, y + i as i32), line, gray());
+    }
+    draw_batch.print_color_centered(
+        38,
+        format!(
+            "{}/{} to choose, {} to pick, {} to cancel",
+            key_hint(Action::MenuUp),
+            key_hint(Action::MenuDown),
+            key_hint(Action::Confirm),
+            key_hint(Action::Cancel)
+        ),
+        gray(),
+    );
+
+    let step = CreationStep::Background;
+    match menu_action(ctx) {
+        Some(Action::Cancel) => CharacterMenuResult::Cancel,
+        Some(Action::MenuUp) => CharacterMenuResult::NoResponse {
+            step,
+            selection: (selection + names.len() - 1) % names.len(),
+        },
+        Some(Action::MenuDown) => CharacterMenuResult::NoResponse {
+            step,
+            selection: (selection + 1) % names.len(),
+        },
+        Some(Action::Confirm) => {
+            gs.new_character.background = names[selection].clone();
+            CharacterMenuResult::NoResponse {
+                step: CreationStep::Attributes { rolled: false },
+                selection: 0,
+            }
+        }
+        _ => CharacterMenuResult::NoResponse { step, selection },
+    }
+}
+
+fn attributes_step(
+    gs: &mut State,
+    ctx: &Rltk,
+    draw_batch: &mut DrawBatch,
+    rolled: bool,
+    selection: usize,
+) -> CharacterMenuResult {
+    let character = &mut gs.new_character;
+    draw_batch.print_color(
+        Point::new(8, 16),
+        format!("Background: {}", character.background),
+        white(),
+    );
+    for (i, (name, value)) in ATTRIBUTE_NAMES
+        .iter()
+        .zip(character.attributes().iter())
+        .enumerate()
+    {
+        draw_batch.print_color(
+            Point::new(10, 18 + i as i32),
+            format!("{:<14}{:>2}", name, value),
+            highlight(i == selection),
+        );
+    }
+    let method = if rolled {
+        format!(
+            "Rolled on 3d6 each. {} to roll again, {} for point-buy.",
+            key_hint(Action::RollAttributes),
+            key_hint(Action::PointBuy)
+        )
+    } else {
+        format!(
+            "Point-buy ({} to {}): {} points left. {} to roll instead.",
+            POINT_BUY_MIN,
+            POINT_BUY_MAX,
+            character.points_left(),
+            key_hint(Action::RollAttributes)
+        )
+    };
+    draw_batch.print_color(Point::new(8, 23), method, gray());
+    draw_batch.print_color_centered(
+        38,
+        format!(
+            "{}/{} to choose, {}/{} to adjust, {} to accept",
+            key_hint(Action::MenuUp),
+            key_hint(Action::MenuDown),
+            key_hint(Action::MenuLeft),
+            key_hint(Action::MenuRight),
+            key_hint(Action::Confirm)
+        ),
+        gray(),
+    );
+
+    let mut step = CreationStep::Attributes { rolled };
+    let mut selection = selection;
+    match menu_action(ctx) {
+        Some(Action::Cancel) => {
+            let names = background_names(&RAWS.lock().unwrap());
+            return CharacterMenuResult::NoResponse {
+                step: CreationStep::Background,
+                selection: names
+                    .iter()
+                    .position(|name| *name == character.background)
+                    .unwrap_or(0),
+            };
+        }
+        Some(Action::Confirm) => step = CreationStep::Name { rolled },
+        Some(Action::MenuUp) => selection = (selection + 3) % 4,
+        Some(Action::MenuDown) => selection = (selection + 1) % 4,
+        Some(Action::MenuLeft) if !rolled => {
+            character.buy(selection, -1);
+        }
+        Some(Action::MenuRight) if !rolled => {
+            character.buy(selection, 1);
+        }
+        Some(Action::RollAttributes) => {
+            character.roll(&mut rltk::RandomNumberGenerator::new());
+            step = CreationStep::Attributes { rolled: true };
+        }
+        Some(Action::PointBuy) if rolled => {
+            let default = Character::default();
+            character.might = default.might;
+            character.fitness = default.fitness;
+            character.quickness = default.quickness;
+            character.intelligence = default.intelligence;
+            step = CreationStep::Attributes { rolled: false };
+        }
+        _ => {}
+    }
+    CharacterMenuResult::NoResponse { step, selection }
+}
+
+fn name_step(
+    gs: &mut State,
+    ctx: &Rltk,
+    draw_batch: &mut DrawBatch,
+    rolled: bool,
+) -> CharacterMenuResult {
+    let name = &mut gs.new_character.name;
+    draw_batch.print_color(Point::new(8, 16), "What is your name?", white());
+    draw_batch.print_color(Point::new(10, 18), format!("{}_", name), highlight(true));
+    draw_batch.print_color_centered(
+        38,
+        format!(
+            "Type a name, {} to begin, {} to go back",
+            key_hint(Action::Confirm),
+            key_hint(Action::Cancel)
+        ),
+        gray(),
+    );
+
+    let step = CreationStep::Name { rolled };
+    match menu_action(ctx) {
+        Some(Action::Cancel) => {
+            return CharacterMenuResult::NoResponse {
+                step: CreationStep::Attributes { rolled },
+                selection: 0,
+            }
+        }
+        Some(Action::Confirm) => {
+            *name = name.trim().to_string();
+            if name.is_empty() {
+                *name = DEFAULT_NAME.to_string();
+            }
+            return CharacterMenuResult::Done;
+        }
+        Some(Action::Erase) => {
+            name.pop();
+        }
+        _ => {
+            if let Some(key) = ctx.key {
+                let upper = ctx.shift || name.is_empty() || name.ends_with(' ');
+                if let Some(c) = name_char(key, upper) {
+                    if name.len() < MAX_NAME_LENGTH && !(c == ' ' && name.is_empty()) {
+                        name.push(c);
+                    }
+                }
+            }
+        }
+    }
+    CharacterMenuResult::NoResponse { step, selection: 0 }
+}
+
+/// Walks the player through picking a background, their attributes and a name, building up
+/// `State::new_character` as they go.
+pub fn character_menu(
+    gs: &mut State,
+    ctx: &mut Rltk,
+    step: CreationStep,
+    selection: usize,
+) -> CharacterMenuResult {
+    let mut draw_batch = DrawBatch::new();
+    draw_batch.draw_double_box(
+        Rect::with_size(6, 12, 67, 28),
+        ColorPair::new(RGB::named(rltk::WHEAT), RGB::named(rltk::BLACK)),
+    );
+    draw_batch.print_color_centered(
+        14,
+        "Create your character",
+        ColorPair::new(RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK)),
+    );
+
+    let result = match step {
+        CreationStep::Background => background_step(gs, ctx, &mut draw_batch, selection),
+        CreationStep::Attributes { rolled } => {
+            attributes_step(gs, ctx, &mut draw_batch, rolled, selection)
+        }
+        CreationStep::Name { rolled } => name_step(gs, ctx, &mut draw_batch, rolled),
+    };
+    let _ = draw_batch.submit(6000);
+    result
+}
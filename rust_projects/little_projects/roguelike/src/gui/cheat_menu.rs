@@ -1,43 +1,44 @@
 use rltk::prelude::*;
 
 use crate::State;
-use super::{menu_option, menu_box};
+use crate::input::{action, key_hint, menu_action, Action, ActionGroup};
+use super::{action_option, menu_box};
 
 #[derive(PartialEq, Copy, Clone)]
-pub enum CheatMenuResult { NoResponse, Cancel, TeleportToExit, Heal, Reveal, GodMode }
+pub enum CheatMenuResult { NoResponse, Cancel, TeleportToExit, Heal, Reveal, GodMode, ReloadRaws }
 
 pub fn show_cheat_mode(_gs : &mut State, ctx : &mut Rltk) -> CheatMenuResult {
     let mut draw_batch = DrawBatch::new();
-    let count = 4;
+    let count = 5;
     let mut y = 25 - (count / 2);
     menu_box(&mut draw_batch, 15, y, count+3, "Cheating!");
     draw_batch.print_color(
         Point::new(18, y+count+1),
-        "ESCAPE to cancel",
+        format!("{} to cancel", key_hint(Action::Cancel)),
         ColorPair::new(RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK))
     );
 
-    menu_option(&mut draw_batch, 17, y, rltk::to_cp437('T'), "Teleport to next level");
+    action_option(&mut draw_batch, 17, y, Action::CheatTeleport, "Teleport to next level");
     y += 1;
-    menu_option(&mut draw_batch, 17, y, rltk::to_cp437('H'), "Heal all wounds");
+    action_option(&mut draw_batch, 17, y, Action::CheatHeal, "Heal all wounds");
     y += 1;
-    menu_option(&mut draw_batch, 17, y, rltk::to_cp437('R'), "Reveal the map");
+    action_option(&mut draw_batch, 17, y, Action::CheatReveal, "Reveal the map");
     y += 1;
-    menu_option(&mut draw_batch, 17, y, rltk::to_cp437('G'), "God Mode (No Death)");
+    action_option(&mut draw_batch, 17, y, Action::CheatGodMode, "God Mode (No Death)");
+    y += 1;
+    action_option(&mut draw_batch, 17, y, Action::CheatReloadRaws, "Reload raws from disk");
 
     let _ = draw_batch.submit(6000);
 
-    match ctx.key {
-        None => CheatMenuResult::NoResponse,
-        Some(key) => {
-            match key {
-                VirtualKeyCode::T => CheatMenuResult::TeleportToExit,
-                VirtualKeyCode::H => CheatMenuResult::Heal,
-                VirtualKeyCode::R => CheatMenuResult::Reveal,
-                VirtualKeyCode::G => CheatMenuResult::GodMode,
-                VirtualKeyCode::Escape => CheatMenuResult::Cancel,
-                _ => CheatMenuResult::NoResponse
-            }
-        }
+    if menu_action(ctx) == Some(Action::Cancel) {
+        return CheatMenuResult::Cancel;
+    }
+    match action(ctx, ActionGroup::CheatMenu) {
+        Some(Action::CheatTeleport) => CheatMenuResult::TeleportToExit,
+        Some(Action::CheatHeal) => CheatMenuResult::Heal,
+        Some(Action::CheatReveal) => CheatMenuResult::Reveal,
+        Some(Action::CheatGodMode) => CheatMenuResult::GodMode,
+        Some(Action::CheatReloadRaws) => CheatMenuResult::ReloadRaws,
+        _ => CheatMenuResult::NoResponse
     }
 }
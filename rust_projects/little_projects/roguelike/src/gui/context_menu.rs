@@ -0,0 +1,87 @@
+use super::menu_option;
+use crate::input::{menu_action, right_click, Action};
+use crate::{camera, ContextAction, State};
+use rltk::prelude::*;
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum ContextMenuResult {
+    NoResponse,
+    Cancel,
+    Selected(ContextAction),
+}
+
+/// The actions for a right-clicked tile, in a small menu beside it. An action is picked by its
+/// letter or by clicking it; clicking anywhere else closes the menu.
+pub fn show_context_menu(
+    gs: &mut State,
+    ctx: &mut Rltk,
+    tile: Point,
+    actions: &[(ContextAction, String)],
+) -> ContextMenuResult {
+    let screen = match camera::map_to_screen(&gs.ecs, ctx, tile) {
+        Some(screen) if !actions.is_empty() => screen,
+        _ => return ContextMenuResult::Cancel,
+    };
+    let mouse = gs.replay.mouse_pos(ctx);
+
+    let count = actions.len() as i32;
+    let width = actions
+        .iter()
+        .map(|(_, label)| label.len() as i32)
+        .max()
+        .unwrap_or(0)
+        + 6;
+    // Beside the tile, flipped to the left near the edge of the map and kept above the log
+    let x = if screen.x + width + 2 < 49 {
+        screen.x + 1
+    } else {
+        screen.x - width - 2
+    };
+    let y = i32::min(screen.y, 44 - count - 1);
+
+    let mut draw_batch = DrawBatch::new();
+    draw_batch.set_bg(screen, RGB::named(rltk::CYAN));
+    draw_batch.draw_box(
+        Rect::with_size(x, y, width + 1, count + 1),
+        ColorPair::new(RGB::named(rltk::WHITE), RGB::named(rltk::BLACK)),
+    );
+    for (j, (_, label)) in actions.iter().enumerate() {
+        menu_option(
+            &mut draw_batch,
+            x + 1,
+            y + 1 + j as i32,
+            97 + j as rltk::FontCharType,
+            label,
+        );
+    }
+    let hovered = mouse.1 - (y + 1);
+    let on_menu = mouse.0 > x && mouse.0 <= x + width && hovered >= 0 && hovered < count;
+    if on_menu {
+        for cell_x in x + 1..=x + width {
+            draw_batch.set_bg(Point::new(cell_x, mouse.1), RGB::named(rltk::DARKSLATEGRAY));
+        }
+    }
+    let _ = draw_batch.submit(6000);
+
+    if ctx.left_click {
+        return if on_menu {
+            ContextMenuResult::Selected(actions[hovered as usize].0)
+        } else {
+            ContextMenuResult::Cancel
+        };
+    }
+    if right_click() || menu_action(ctx) == Some(Action::Cancel) {
+        return ContextMenuResult::Cancel;
+    }
+    match ctx.key {
+        None => ContextMenuResult::NoResponse,
+        Some(key) => {
+            let selection = rltk::letter_to_option(key);
+            if selection > -1 && selection < count {
+                ContextMenuResult::Selected(actions[selection as usize].0)
+            } else {
+                ContextMenuResult::NoResponse
+            }
+        }
+    }
+}
@@ -21,7 +21,8 @@ pub fn drop_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
         "Drop which item?",
         items.len(),
         &items,
-        ctx.key,
+        ctx,
+        gs.replay.mouse_pos(ctx),
     );
     let _ = draw_batch.submit(6000);
     result
@@ -1,24 +1,22 @@
 use rltk::prelude::*;
-
+use crate::input::{key_hint, menu_action, Action};
+use crate::morgue::RunEnd;
 
 #[derive(PartialEq, Copy, Clone)]
-pub enum GameOverResult { NoSelection, QuitToMenu }
+pub enum GameOverResult { NoSelection, ExportLog, QuitToMenu }
 
-pub fn game_over(ctx : &mut Rltk) -> GameOverResult {
+/// `notes` say what became of the morgue file, the high score and the message log.
+pub fn game_over(ctx : &mut Rltk, outcome : &RunEnd, notes : &[String]) -> GameOverResult {
     let mut draw_batch = DrawBatch::new();
+    let title = if *outcome == RunEnd::Won { "You have won!" } else { "Your journey has ended!" };
     draw_batch.print_color_centered(
         15, 
-        "Your journey has ended!",
+        title,
         ColorPair::new(RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK))
     );
     draw_batch.print_color_centered(
         17, 
-        "One day, we'll tell you all about how you did.",
-        ColorPair::new(RGB::named(rltk::WHITE), RGB::named(rltk::BLACK))
-    );
-    draw_batch.print_color_centered(
-        18, 
-        "That day, sadly, is not in this chapter..",
+        format!("You {}.", outcome.describe()),
         ColorPair::new(RGB::named(rltk::WHITE), RGB::named(rltk::BLACK))
     );
 
@@ -39,14 +37,22 @@ pub fn game_over(ctx : &mut Rltk) -> GameOverResult {
 
     draw_batch.print_color_centered(
         23,
-        "Press any key to return to the menu.",
+        format!("Press {} to save the message log, or any other key to return to the menu.", key_hint(Action::ExportLog)),
         ColorPair::new(RGB::named(rltk::MAGENTA), RGB::named(rltk::BLACK))
     );
+    for (i, note) in notes.iter().enumerate() {
+        draw_batch.print_color_centered(
+            25 + i as i32,
+            note,
+            ColorPair::new(RGB::named(rltk::GRAY), RGB::named(rltk::BLACK))
+        );
+    }
 
     let _ = draw_batch.submit(6000);
 
     match ctx.key {
         None => GameOverResult::NoSelection,
+        Some(_) if menu_action(ctx) == Some(Action::ExportLog) => GameOverResult::ExportLog,
         Some(_) => GameOverResult::QuitToMenu
     }
 }
@@ -0,0 +1,67 @@
+use crate::input::{key_hint, menu_action, Action};
+use crate::morgue::HighScores;
+use rltk::prelude::*;
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum HighScoresResult {
+    NoResponse,
+    Done,
+}
+
+/// The local high-score table, best run first.
+pub fn high_scores_menu(ctx: &mut Rltk, high_scores: &HighScores) -> HighScoresResult {
+    let mut draw_batch = DrawBatch::new();
+    let white = ColorPair::new(RGB::named(rltk::WHITE), RGB::named(rltk::BLACK));
+    let gray = ColorPair::new(RGB::named(rltk::GRAY), RGB::named(rltk::BLACK));
+
+    draw_batch.draw_double_box(
+        Rect::with_size(1, 1, 77, 54),
+        ColorPair::new(RGB::named(rltk::WHEAT), RGB::named(rltk::BLACK)),
+    );
+    draw_batch.print_color_centered(
+        2,
+        "High Scores",
+        ColorPair::new(RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK)),
+    );
+
+    if high_scores.entries.is_empty() {
+        draw_batch.print_color_centered(25, "No runs have ended yet.", gray);
+    } else {
+        draw_batch.print_color(
+            Point::new(3, 4),
+            format!(
+                "{:>3} {:>7}  {:<28}{:>5}{:>7}",
+                "#", "Score", "Character", "Depth", "Turns"
+            ),
+            gray,
+        );
+    }
+    for (i, entry) in high_scores.entries.iter().enumerate() {
+        let y = 6 + i as i32 * 2;
+        let who: String = format!("{}, level {} {}", entry.name, entry.level, entry.background)
+            .chars()
+            .take(27)
+            .collect();
+        draw_batch.print_color(
+            Point::new(3, y),
+            format!(
+                "{:>3} {:>7}  {:<28}{:>5}{:>7}",
+                i + 1,
+                entry.score,
+                who,
+                entry.depth,
+                entry.turns
+            ),
+            white,
+        );
+        draw_batch.print_color(Point::new(15, y + 1), &entry.outcome, gray);
+    }
+
+    draw_batch.print_color_centered(52, format!("{} to return", key_hint(Action::Cancel)), gray);
+    let _ = draw_batch.submit(6000);
+
+    match menu_action(ctx) {
+        Some(Action::Cancel) | Some(Action::Confirm) => HighScoresResult::Done,
+        _ => HighScoresResult::NoResponse,
+    }
+}
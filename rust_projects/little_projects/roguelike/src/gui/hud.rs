@@ -1,7 +1,8 @@
 use super::{draw_tooltips, get_item_color, get_item_display_name};
+use crate::raws::{get_status_effect, RAWS};
 use crate::{
     gamelog, Attribute, Attributes, Consumable, Duration, Equipped, HungerClock, HungerState,
-    InBackpack, KnownSpells, Map, Name, Pools, StatusEffect, Weapon,
+    InBackpack, KnownSpells, Map, Name, Pools, RunState, StatusEffect, StatusStacks, Weapon,
 };
 use rltk::prelude::*;
 use specs::prelude::*;
@@ -282,17 +283,78 @@ fn spells(ecs: &World, draw_batch: &mut DrawBatch, player_entity: &Entity, mut y
             format!("^{}", index),
             ColorPair::new(blue, black),
         );
-        draw_batch.print_color(
-            Point::new(53, y),
-            format!("{} ({})", &spell.display_name, spell.mana_cost),
-            ColorPair::new(blue, black),
-        );
+        if spell.cooldown > 0 {
+            draw_batch.print_color(
+                Point::new(53, y),
+                format!("{} [{}]", &spell.display_name, spell.cooldown),
+                ColorPair::new(RGB::named(rltk::GRAY), black),
+            );
+        } else {
+            draw_batch.print_color(
+                Point::new(53, y),
+                format!("{} ({})", &spell.display_name, spell.mana_cost),
+                ColorPair::new(blue, black),
+            );
+        }
         index += 1;
         y += 1;
     }
     y
 }
 
+/// Something on the side panel that does something when clicked.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum HudClick {
+    /// A consumable, by hotkey slot (0-8)
+    Consumable(usize),
+    /// A known spell, by its place in the player's spell list
+    Spell(usize),
+}
+
+/// What's drawn at this console cell on the side panel, laid out as in `draw_ui`.
+pub fn hud_click(ecs: &World, mouse: (i32, i32)) -> Option<HudClick> {
+    if mouse.0 < 50 || mouse.0 > 78 {
+        return None;
+    }
+    let player_entity = ecs.fetch::<Entity>();
+    let equipped_count = ecs
+        .read_storage::<Equipped>()
+        .join()
+        .filter(|equipped| equipped.owner == *player_entity)
+        .count();
+    let consumable_count = (
+        &ecs.read_storage::<InBackpack>(),
+        &ecs.read_storage::<Consumable>(),
+    )
+        .join()
+        .filter(|(carried_by, _)| carried_by.owner == *player_entity)
+        .count()
+        .min(9);
+    let spell_count = ecs
+        .read_storage::<KnownSpells>()
+        .get(*player_entity)
+        .map_or(0, |known| known.spells.len());
+
+    let consumables_top = 13 + equipped_count as i32 + 1;
+    let spells_top = consumables_top + consumable_count as i32 + 1;
+    let row = mouse.1;
+    if row >= consumables_top && row < consumables_top + consumable_count as i32 {
+        Some(HudClick::Consumable((row - consumables_top) as usize))
+    } else if row >= spells_top && row < spells_top + spell_count as i32 {
+        Some(HudClick::Spell((row - spells_top) as usize))
+    } else {
+        None
+    }
+}
+
+/// A status effect's name, how many times it has stacked, and the turns it has left.
+pub fn status_label(name: &str, stacks: Option<&StatusStacks>, turns: i32) -> String {
+    match stacks {
+        Some(stacks) if stacks.stacks > 1 => format!("{} x{} ({})", name, stacks.stacks, turns),
+        _ => format!("{} ({})", name, turns),
+    }
+}
+
 fn status(ecs: &World, draw_batch: &mut DrawBatch, player_entity: &Entity) {
     let mut y = 44;
     let hunger = ecs.read_storage::<HungerClock>();
@@ -327,12 +389,19 @@ fn status(ecs: &World, draw_batch: &mut DrawBatch, player_entity: &Entity) {
     let statuses = ecs.read_storage::<StatusEffect>();
     let durations = ecs.read_storage::<Duration>();
     let names = ecs.read_storage::<Name>();
-    for (status, duration, name) in (&statuses, &durations, &names).join() {
+    let stacks = ecs.read_storage::<StatusStacks>();
+    let raws = RAWS.lock().unwrap();
+    for (entity, status, duration, name) in (&ecs.entities(), &statuses, &durations, &names).join()
+    {
         if status.target == *player_entity {
+            let color = get_status_effect(&raws, &name.name)
+                .and_then(|def| def.color.as_ref())
+                .and_then(|color| RGB::from_hex(color).ok())
+                .unwrap_or_else(|| RGB::named(rltk::RED));
             draw_batch.print_color(
                 Point::new(50, y),
-                format!("{} ({})", name.name, duration.turns),
-                ColorPair::new(RGB::named(rltk::RED), RGB::named(rltk::BLACK)),
+                status_label(&name.name, stacks.get(entity), duration.turns),
+                ColorPair::new(color, RGB::named(rltk::BLACK)),
             );
             y -= 1;
         }
@@ -348,15 +417,18 @@ pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
     draw_stats(ecs, &mut draw_batch, &player_entity);
     draw_attributes(ecs, &mut draw_batch, &player_entity);
     initiative_weight(ecs, &mut draw_batch, &player_entity);
-    let mut y = equipped(ecs, &mut draw_batch, &player_entity);
-    y += consumables(ecs, &mut draw_batch, &player_entity, y);
+    let y = equipped(ecs, &mut draw_batch, &player_entity);
+    let y = consumables(ecs, &mut draw_batch, &player_entity, y);
     spells(ecs, &mut draw_batch, &player_entity, y);
     status(ecs, &mut draw_batch, &player_entity);
     gamelog::print_log(
         &mut rltk::BACKEND_INTERNAL.lock().consoles[1].console,
         Point::new(1, 23),
     );
-    draw_tooltips(ecs, ctx);
+    // The context menu sits over the map, where tooltips would cover it
+    if !matches!(*ecs.fetch::<RunState>(), RunState::ShowContextMenu { .. }) {
+        draw_tooltips(ecs, ctx);
+    }
 
     let _ = draw_batch.submit(5000);
 }
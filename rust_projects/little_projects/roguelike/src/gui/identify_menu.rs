@@ -50,7 +50,8 @@ pub fn identify_menu(gs : &mut State, ctx : &mut Rltk) -> (ItemMenuResult, Optio
         "Inventory",
         items.len(),
         &items,
-        ctx.key
+        ctx,
+        gs.replay.mouse_pos(ctx)
     );
     let _ = draw_batch.submit(6000);
     result
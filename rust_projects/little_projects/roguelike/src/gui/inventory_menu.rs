@@ -26,7 +26,8 @@ pub fn show_inventory(gs : &mut State, ctx : &mut Rltk) -> (ItemMenuResult, Opti
         "Inventory",
         items.len(),
         &items,
-        ctx.key
+        ctx,
+        gs.replay.mouse_pos(ctx)
     );
     let _ = draw_batch.submit(6000);
     result
@@ -0,0 +1,111 @@
+use crate::input::{key_name, Action, ActionGroup, KeyCombo, BINDINGS};
+use rltk::prelude::*;
+
+/// How many actions fit on screen at once.
+const ROWS: usize = 44;
+/// Actions this screen can't be used without, so they always keep at least one key.
+const REQUIRED: [Action; 4] = [
+    Action::MenuUp,
+    Action::MenuDown,
+    Action::Confirm,
+    Action::Cancel,
+];
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum KeyBindingsResult {
+    NoResponse { selection: usize, capturing: bool },
+    Done,
+}
+
+/// Lists every action with its keys. The player can add a key to an action, clear it or put its
+/// defaults back; while `capturing`, the next key pressed is bound to the selected action.
+pub fn key_bindings_menu(ctx: &mut Rltk, selection: usize, capturing: bool) -> KeyBindingsResult {
+    let mut bindings = BINDINGS.lock().unwrap();
+    let mut draw_batch = DrawBatch::new();
+    let white = ColorPair::new(RGB::named(rltk::WHITE), RGB::named(rltk::BLACK));
+    let gray = ColorPair::new(RGB::named(rltk::GRAY), RGB::named(rltk::BLACK));
+    let highlight = ColorPair::new(RGB::named(rltk::MAGENTA), RGB::named(rltk::BLACK));
+
+    draw_batch.draw_double_box(
+        Rect::with_size(1, 1, 77, 54),
+        ColorPair::new(RGB::named(rltk::WHEAT), RGB::named(rltk::BLACK)),
+    );
+    draw_batch.print_color_centered(
+        2,
+        "Key Bindings",
+        ColorPair::new(RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK)),
+    );
+
+    let first = selection
+        .saturating_sub(ROWS / 2)
+        .min(Action::ALL.len() - ROWS);
+    for (i, action) in Action::ALL.iter().enumerate().skip(first).take(ROWS) {
+        let y = 4 + (i - first) as i32;
+        let color = if i == selection { highlight } else { white };
+        draw_batch.print_color(Point::new(4, y), action.description(), color);
+        let keys = if i == selection && capturing {
+            "press a key...".to_string()
+        } else {
+            bindings.describe(*action)
+        };
+        draw_batch.print_color(Point::new(28, y), keys, color);
+    }
+
+    let hint = if capturing {
+        format!(
+            "Press the new key for {}",
+            Action::ALL[selection].description()
+        )
+    } else {
+        format!(
+            "{}/{} choose, {} add key, {} clear, {} defaults, {} save",
+            bindings.describe(Action::MenuUp),
+            bindings.describe(Action::MenuDown),
+            bindings.describe(Action::Confirm),
+            bindings.describe(Action::Erase),
+            bindings.describe(Action::SwitchMode),
+            bindings.describe(Action::Cancel)
+        )
+    };
+    draw_batch.print_color_centered(50, hint, gray);
+    draw_batch.print_color_centered(
+        52,
+        "A key can do one thing in the game, one in menus and one in replays",
+        gray,
+    );
+    let _ = draw_batch.submit(6000);
+
+    let action = Action::ALL[selection];
+    if capturing {
+        // Modifier keys on their own aren't bindable, so keep waiting for the real key
+        return match KeyCombo::pressed(ctx) {
+            Some(pressed) if key_name(pressed.key).is_some() => {
+                bindings.bind(action, pressed);
+                KeyBindingsResult::NoResponse {
+                    selection,
+                    capturing: false,
+                }
+            }
+            _ => KeyBindingsResult::NoResponse {
+                selection,
+                capturing: true,
+            },
+        };
+    }
+
+    let mut capturing = false;
+    let mut selection = selection;
+    match KeyCombo::pressed(ctx).and_then(|pressed| bindings.action(ActionGroup::Menu, pressed)) {
+        Some(Action::Cancel) => return KeyBindingsResult::Done,
+        Some(Action::MenuUp) => selection = (selection + Action::ALL.len() - 1) % Action::ALL.len(),
+        Some(Action::MenuDown) => selection = (selection + 1) % Action::ALL.len(),
+        Some(Action::Confirm) => capturing = true,
+        Some(Action::Erase) if !REQUIRED.contains(&action) => bindings.clear(action),
+        Some(Action::SwitchMode) => bindings.reset(action),
+        _ => {}
+    }
+    KeyBindingsResult::NoResponse {
+        selection,
+        capturing,
+    }
+}
@@ -0,0 +1,64 @@
+use rltk::prelude::*;
+
+use crate::State;
+use crate::input::{key_hint, menu_action, Action};
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum LoadGameMenuResult { NoResponse{ selection : usize }, Cancel, Selected{ selection : usize } }
+
+fn describe_age(timestamp : u64) -> String {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let minutes = now.saturating_sub(timestamp) / 60;
+    if minutes < 60 { format!("{}m ago", minutes) }
+    else if minutes < 60 * 24 { format!("{}h ago", minutes / 60) }
+    else { format!("{}d ago", minutes / (60 * 24)) }
+}
+
+pub fn load_game_menu(gs : &mut State, ctx : &mut Rltk, selection : usize) -> LoadGameMenuResult {
+    let mut draw_batch = DrawBatch::new();
+    let slots = &gs.save_slots;
+    let height = i32::max(slots.len() as i32, 1) + 5;
+
+    draw_batch.draw_double_box(Rect::with_size(9, 18, 61, height), ColorPair::new(RGB::named(rltk::WHEAT), RGB::named(rltk::BLACK)));
+    draw_batch.print_color_centered(19, "Load Game", ColorPair::new(RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK)));
+
+    let mut y = 21;
+    for (i, slot) in slots.iter().enumerate() {
+        let bg = if i == selection { RGB::named(rltk::DARK_GRAY) } else { RGB::named(rltk::BLACK) };
+        draw_batch.print_color(Point::new(11, y), &slot.name, ColorPair::new(RGB::named(rltk::WHITE), bg));
+        match &slot.header {
+            Ok(header) => {
+                let summary = if header.format_version == 1 {
+                    "old save format".to_string()
+                } else {
+                    format!("Depth {}, {}", header.depth, describe_age(header.timestamp))
+                };
+                draw_batch.print_color(Point::new(40, y), summary, ColorPair::new(RGB::named(rltk::GRAY), bg));
+            }
+            Err(error) => {
+                draw_batch.print_color(Point::new(40, y), error, ColorPair::new(RGB::named(rltk::RED), bg));
+            }
+        }
+        y += 1;
+    }
+    if slots.is_empty() {
+        draw_batch.print_color_centered(y, "No saved games", ColorPair::new(RGB::named(rltk::GRAY), RGB::named(rltk::BLACK)));
+    }
+
+    draw_batch.print_color_centered(18 + height - 2, format!("{} to load, {} to cancel", key_hint(Action::Confirm), key_hint(Action::Cancel)), ColorPair::new(RGB::named(rltk::GRAY), RGB::named(rltk::BLACK)));
+
+    let _ = draw_batch.submit(6000);
+
+    match menu_action(ctx) {
+        Some(Action::Cancel) => LoadGameMenuResult::Cancel,
+        Some(Action::MenuUp) => LoadGameMenuResult::NoResponse{ selection: selection.saturating_sub(1) },
+        Some(Action::MenuDown) => {
+            LoadGameMenuResult::NoResponse{ selection: usize::min(selection + 1, slots.len().saturating_sub(1)) }
+        }
+        Some(Action::Confirm) if selection < slots.len() => LoadGameMenuResult::Selected{ selection },
+        _ => LoadGameMenuResult::NoResponse{ selection }
+    }
+}
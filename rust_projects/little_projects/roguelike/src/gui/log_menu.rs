@@ -0,0 +1,106 @@
+use crate::gamelog::{log_lines, LogCategory};
+use crate::input::{key_hint, menu_action, Action};
+use rltk::prelude::*;
+
+/// How many log lines fit on screen at once.
+const ROWS: usize = 46;
+/// The log is cut off at the box's right edge.
+const RIGHT_EDGE: i32 = 77;
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum LogMenuResult {
+    NoResponse {
+        offset: usize,
+        filter: Option<LogCategory>,
+    },
+    Cancel,
+}
+
+/// The filter after this one, going through every category and back to showing everything.
+fn next_filter(filter: Option<LogCategory>, step: i32) -> Option<LogCategory> {
+    let mut filters = vec![None];
+    filters.extend(LogCategory::ALL.iter().map(|c| Some(*c)));
+    let current = filters.iter().position(|f| *f == filter).unwrap_or(0) as i32;
+    let count = filters.len() as i32;
+    filters[((current + step + count) % count) as usize]
+}
+
+/// The whole message log, newest at the bottom. `offset` is how many lines the view has been
+/// scrolled back from the newest.
+pub fn show_log(ctx: &mut Rltk, offset: usize, filter: Option<LogCategory>) -> LogMenuResult {
+    let lines = log_lines(filter);
+    let max_offset = lines.len().saturating_sub(ROWS);
+    let offset = offset.min(max_offset);
+
+    let mut draw_batch = DrawBatch::new();
+    let gray = ColorPair::new(RGB::named(rltk::GRAY), RGB::named(rltk::BLACK));
+    draw_batch.draw_double_box(
+        Rect::with_size(1, 1, 77, 54),
+        ColorPair::new(RGB::named(rltk::WHEAT), RGB::named(rltk::BLACK)),
+    );
+    draw_batch.print_color_centered(
+        2,
+        format!(
+            "Message Log: {}",
+            filter.map_or("Everything", |category| category.name())
+        ),
+        ColorPair::new(RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK)),
+    );
+    if lines.is_empty() {
+        draw_batch.print_color_centered(25, "Nothing to show.", gray);
+    }
+
+    let end = lines.len() - offset;
+    let start = end.saturating_sub(ROWS);
+    for (row, line) in lines[start..end].iter().enumerate() {
+        let y = 4 + row as i32;
+        draw_batch.print_color(Point::new(3, y), format!("{:>5}", line.turn), gray);
+        let mut x = 10;
+        for frag in line.fragments.iter() {
+            if x >= RIGHT_EDGE {
+                break;
+            }
+            let room = (RIGHT_EDGE - x) as usize;
+            let text: String = frag.text.chars().take(room).collect();
+            draw_batch.print_color(
+                Point::new(x, y),
+                &text,
+                ColorPair::new(frag.color, RGB::named(rltk::BLACK)),
+            );
+            x += text.len() as i32 + 1;
+        }
+        if line.count > 1 && x < RIGHT_EDGE {
+            draw_batch.print_color(Point::new(x, y), format!("x{}", line.count), gray);
+        }
+    }
+
+    draw_batch.print_color_centered(
+        51,
+        format!(
+            "{}/{} scroll, {}/{} page, {} filter, {} close",
+            key_hint(Action::MenuUp),
+            key_hint(Action::MenuDown),
+            key_hint(Action::PageUp),
+            key_hint(Action::PageDown),
+            key_hint(Action::SwitchMode),
+            key_hint(Action::Cancel)
+        ),
+        gray,
+    );
+    if offset > 0 {
+        draw_batch.print_color_centered(52, format!("{} newer lines below", offset), gray);
+    }
+    let _ = draw_batch.submit(6000);
+
+    let (offset, filter) = match menu_action(ctx) {
+        Some(Action::Cancel) => return LogMenuResult::Cancel,
+        Some(Action::MenuUp) => ((offset + 1).min(max_offset), filter),
+        Some(Action::MenuDown) => (offset.saturating_sub(1), filter),
+        Some(Action::PageUp) => ((offset + ROWS).min(max_offset), filter),
+        Some(Action::PageDown) => (offset.saturating_sub(ROWS), filter),
+        Some(Action::SwitchMode) | Some(Action::MenuRight) => (0, next_filter(filter, 1)),
+        Some(Action::MenuLeft) => (0, next_filter(filter, -1)),
+        _ => (offset, filter),
+    };
+    LogMenuResult::NoResponse { offset, filter }
+}
@@ -1,85 +1,95 @@
 use rltk::prelude::*;
 
 use crate::{State, RunState, rex_assets::RexAssets };
+use crate::input::{key_hint, menu_action, Action};
 
 #[derive(PartialEq, Copy, Clone)]
-pub enum MainMenuSelection { NewGame, LoadGame, Quit }
+pub enum MainMenuSelection { NewGame, NewSeededGame, LoadGame, HighScores, KeyBindings, Quit }
 
 #[derive(PartialEq, Copy, Clone)]
 pub enum MainMenuResult { NoSelection{ selected : MainMenuSelection }, Selected{ selected: MainMenuSelection } }
 
+fn menu_entry(draw_batch : &mut DrawBatch, y : i32, text : &str, highlighted : bool) {
+    let fg = if highlighted { RGB::named(rltk::MAGENTA) } else { RGB::named(rltk::WHITE) };
+    draw_batch.print_color_centered(y, text, ColorPair::new(fg, RGB::named(rltk::BLACK)));
+}
+
 pub fn main_menu(gs : &mut State, ctx : &mut Rltk) -> MainMenuResult {
     let mut draw_batch = DrawBatch::new();
     let save_exists = crate::saveload_system::does_save_exist();
     let runstate = gs.ecs.fetch::<RunState>();
     let assets = gs.ecs.fetch::<RexAssets>();
+    let seed = gs.ecs.fetch::<crate::map::MasterDungeonMap>().seed;
     ctx.render_xp_sprite(&assets.menu, 0, 0);
 
-    draw_batch.draw_double_box(Rect::with_size(24, 18, 31, 10), ColorPair::new(RGB::named(rltk::WHEAT), RGB::named(rltk::BLACK)));
+    draw_batch.draw_double_box(Rect::with_size(24, 18, 31, 14), ColorPair::new(RGB::named(rltk::WHEAT), RGB::named(rltk::BLACK)));
 
     draw_batch.print_color_centered(20, "Rust Roguelike Tutorial", ColorPair::new(RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK)));
     draw_batch.print_color_centered(21, "by Herbert Wolverson", ColorPair::new(RGB::named(rltk::CYAN), RGB::named(rltk::BLACK)));
-    draw_batch.print_color_centered(22, "Use Up/Down Arrows and Enter", ColorPair::new(RGB::named(rltk::GRAY), RGB::named(rltk::BLACK)));
+    draw_batch.print_color_centered(22, format!("Use {}/{} and {}", key_hint(Action::MenuUp), key_hint(Action::MenuDown), key_hint(Action::Confirm)), ColorPair::new(RGB::named(rltk::GRAY), RGB::named(rltk::BLACK)));
 
     let mut y = 24;
     if let RunState::MainMenu{ menu_selection : selection } = *runstate {
-        if selection == MainMenuSelection::NewGame {
-            draw_batch.print_color_centered(y, "Begin New Game", ColorPair::new(RGB::named(rltk::MAGENTA), RGB::named(rltk::BLACK)));
-        } else {
-            draw_batch.print_color_centered(y, "Begin New Game", ColorPair::new(RGB::named(rltk::WHITE), RGB::named(rltk::BLACK)));
-        }
+        menu_entry(&mut draw_batch, y, "Begin New Game", selection == MainMenuSelection::NewGame);
+        y += 1;
+
+        menu_entry(&mut draw_batch, y, "New Game with Seed", selection == MainMenuSelection::NewSeededGame);
         y += 1;
 
         if save_exists {
-            if selection == MainMenuSelection::LoadGame {
-                draw_batch.print_color_centered(y, "Load Game", ColorPair::new(RGB::named(rltk::MAGENTA), RGB::named(rltk::BLACK)));
-            } else {
-                draw_batch.print_color_centered(y, "Load Game", ColorPair::new(RGB::named(rltk::WHITE), RGB::named(rltk::BLACK)));
-            }
+            menu_entry(&mut draw_batch, y, "Load Game", selection == MainMenuSelection::LoadGame);
             y += 1;
         }
 
-        if selection == MainMenuSelection::Quit {
-            draw_batch.print_color_centered(y, "Quit", ColorPair::new(RGB::named(rltk::MAGENTA), RGB::named(rltk::BLACK)));
-        } else {
-            draw_batch.print_color_centered(y, "Quit", ColorPair::new(RGB::named(rltk::WHITE), RGB::named(rltk::BLACK)));
+        menu_entry(&mut draw_batch, y, "High Scores", selection == MainMenuSelection::HighScores);
+        y += 1;
+
+        menu_entry(&mut draw_batch, y, "Key Bindings", selection == MainMenuSelection::KeyBindings);
+        y += 1;
+
+        menu_entry(&mut draw_batch, y, "Quit", selection == MainMenuSelection::Quit);
+
+        draw_batch.print_color_centered(31, format!("Seed: {}", seed), ColorPair::new(RGB::named(rltk::GRAY), RGB::named(rltk::BLACK)));
+        if let Some(error) = &gs.save_error {
+            draw_batch.print_color_centered(33, format!("Unable to load: {}", error), ColorPair::new(RGB::named(rltk::RED), RGB::named(rltk::BLACK)));
         }
 
         let _ = draw_batch.submit(6000);
 
-        match ctx.key {
-            None => return MainMenuResult::NoSelection{ selected: selection },
-            Some(key) => {
-                match key {
-                    VirtualKeyCode::Escape => { return MainMenuResult::NoSelection{ selected: MainMenuSelection::Quit } }
-                    VirtualKeyCode::Up => {
-                        let mut newselection;
-                        match selection {
-                            MainMenuSelection::NewGame => newselection = MainMenuSelection::Quit,
-                            MainMenuSelection::LoadGame => newselection = MainMenuSelection::NewGame,
-                            MainMenuSelection::Quit => newselection = MainMenuSelection::LoadGame
-                        }
-                        if newselection == MainMenuSelection::LoadGame && !save_exists {
-                            newselection = MainMenuSelection::NewGame;
-                        }
-                        return MainMenuResult::NoSelection{ selected: newselection }
-                    }
-                    VirtualKeyCode::Down => {
-                        let mut newselection;
-                        match selection {
-                            MainMenuSelection::NewGame => newselection = MainMenuSelection::LoadGame,
-                            MainMenuSelection::LoadGame => newselection = MainMenuSelection::Quit,
-                            MainMenuSelection::Quit => newselection = MainMenuSelection::NewGame
-                        }
-                        if newselection == MainMenuSelection::LoadGame && !save_exists {
-                            newselection = MainMenuSelection::Quit;
-                        }
-                        return MainMenuResult::NoSelection{ selected: newselection }
-                    }
-                    VirtualKeyCode::Return => return MainMenuResult::Selected{ selected : selection },
-                    _ => return MainMenuResult::NoSelection{ selected: selection }
+        match menu_action(ctx) {
+            Some(Action::Cancel) => { return MainMenuResult::NoSelection{ selected: MainMenuSelection::Quit } }
+            Some(Action::MenuUp) => {
+                let mut newselection;
+                match selection {
+                    MainMenuSelection::NewGame => newselection = MainMenuSelection::Quit,
+                    MainMenuSelection::NewSeededGame => newselection = MainMenuSelection::NewGame,
+                    MainMenuSelection::LoadGame => newselection = MainMenuSelection::NewSeededGame,
+                    MainMenuSelection::HighScores => newselection = MainMenuSelection::LoadGame,
+                    MainMenuSelection::KeyBindings => newselection = MainMenuSelection::HighScores,
+                    MainMenuSelection::Quit => newselection = MainMenuSelection::KeyBindings
+                }
+                if newselection == MainMenuSelection::LoadGame && !save_exists {
+                    newselection = MainMenuSelection::NewSeededGame;
+                }
+                return MainMenuResult::NoSelection{ selected: newselection }
+            }
+            Some(Action::MenuDown) => {
+                let mut newselection;
+                match selection {
+                    MainMenuSelection::NewGame => newselection = MainMenuSelection::NewSeededGame,
+                    MainMenuSelection::NewSeededGame => newselection = MainMenuSelection::LoadGame,
+                    MainMenuSelection::LoadGame => newselection = MainMenuSelection::HighScores,
+                    MainMenuSelection::HighScores => newselection = MainMenuSelection::KeyBindings,
+                    MainMenuSelection::KeyBindings => newselection = MainMenuSelection::Quit,
+                    MainMenuSelection::Quit => newselection = MainMenuSelection::NewGame
+                }
+                if newselection == MainMenuSelection::LoadGame && !save_exists {
+                    newselection = MainMenuSelection::HighScores;
                 }
+                return MainMenuResult::NoSelection{ selected: newselection }
             }
+            Some(Action::Confirm) => return MainMenuResult::Selected{ selected : selection },
+            _ => return MainMenuResult::NoSelection{ selected: selection }
         }
     }
 
@@ -1,4 +1,5 @@
 use super::ItemMenuResult;
+use crate::input::{key_hint, menu_action, Action};
 use rltk::prelude::*;
 use specs::Entity;
 
@@ -43,14 +44,48 @@ pub fn menu_option<T: ToString>(
     );
 }
 
+/// A menu option picked by an action, labelled with the first key bound to it.
+pub fn action_option<T: ToString>(
+    draw_batch: &mut DrawBatch,
+    x: i32,
+    y: i32,
+    action: Action,
+    text: T,
+) {
+    let key = key_hint(action);
+    let width = key.len() as i32;
+    draw_batch.print_color(
+        Point::new(x, y),
+        "(",
+        ColorPair::new(RGB::named(rltk::WHITE), RGB::named(rltk::BLACK)),
+    );
+    draw_batch.print_color(
+        Point::new(x + 1, y),
+        key,
+        ColorPair::new(RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK)),
+    );
+    draw_batch.print_color(
+        Point::new(x + 1 + width, y),
+        ")",
+        ColorPair::new(RGB::named(rltk::WHITE), RGB::named(rltk::BLACK)),
+    );
+    draw_batch.print_color(
+        Point::new(x + 4 + width, y),
+        text.to_string(),
+        ColorPair::new(RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK)),
+    );
+}
+
 pub fn item_result_menu<S: ToString>(
     draw_batch: &mut DrawBatch,
     title: S,
     count: usize,
     items: &[(Entity, String)],
-    key: Option<VirtualKeyCode>,
+    ctx: &Rltk,
+    mouse: (i32, i32),
 ) -> (ItemMenuResult, Option<Entity>) {
     let mut y = (25 - (count / 2)) as i32;
+    let first_row = y;
     draw_batch.draw_box(
         Rect::with_size(15, y - 2, 31, (count + 3) as i32),
         ColorPair::new(RGB::named(rltk::WHITE), RGB::named(rltk::BLACK)),
@@ -62,7 +97,7 @@ pub fn item_result_menu<S: ToString>(
     );
     draw_batch.print_color(
         Point::new(18, y + count as i32 + 1),
-        "ESCAPE to cancel",
+        format!("{} to cancel", key_hint(Action::Cancel)),
         ColorPair::new(RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK)),
     );
 
@@ -73,20 +108,31 @@ pub fn item_result_menu<S: ToString>(
         y += 1;
     }
 
-    match key {
+    // The item under the mouse is lit up, and clicking it picks it
+    let hovered = mouse.1 - first_row;
+    if mouse.0 > 15 && mouse.0 < 46 && hovered >= 0 && hovered < count as i32 {
+        for x in 16..46 {
+            draw_batch.set_bg(Point::new(x, mouse.1), RGB::named(rltk::DARKSLATEGRAY));
+        }
+        if ctx.left_click {
+            return (ItemMenuResult::Selected, Some(item_list[hovered as usize]));
+        }
+    }
+
+    if menu_action(ctx) == Some(Action::Cancel) {
+        return (ItemMenuResult::Cancel, None);
+    }
+    match ctx.key {
         None => (ItemMenuResult::NoResponse, None),
-        Some(key) => match key {
-            VirtualKeyCode::Escape => (ItemMenuResult::Cancel, None),
-            _ => {
-                let selection = rltk::letter_to_option(key);
-                if selection > -1 && selection < count as i32 {
-                    return (
-                        ItemMenuResult::Selected,
-                        Some(item_list[selection as usize]),
-                    );
-                }
-                (ItemMenuResult::NoResponse, None)
+        Some(key) => {
+            let selection = rltk::letter_to_option(key);
+            if selection > -1 && selection < count as i32 {
+                return (
+                    ItemMenuResult::Selected,
+                    Some(item_list[selection as usize]),
+                );
             }
-        },
+            (ItemMenuResult::NoResponse, None)
+        }
     }
 }
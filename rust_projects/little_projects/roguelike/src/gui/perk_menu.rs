@@ -0,0 +1,77 @@
+use crate::input::{key_hint, menu_action, Action};
+use crate::raws::{get_perk, RAWS};
+use crate::State;
+use rltk::prelude::*;
+
+#[derive(PartialEq, Clone)]
+pub enum PerkMenuResult {
+    NoResponse { selection: usize },
+    Selected { perk: String },
+}
+
+/// Asks the player to pick one of the perks on offer after gaining a level.
+pub fn perk_menu(
+    _gs: &mut State,
+    ctx: &mut Rltk,
+    choices: &[String],
+    selection: usize,
+) -> PerkMenuResult {
+    let selection = selection.min(choices.len().saturating_sub(1));
+    let mut draw_batch = DrawBatch::new();
+    let white = ColorPair::new(RGB::named(rltk::WHITE), RGB::named(rltk::BLACK));
+    let gray = ColorPair::new(RGB::named(rltk::GRAY), RGB::named(rltk::BLACK));
+    let height = choices.len() as i32 + 6;
+    draw_batch.draw_box(
+        Rect::with_size(10, 10, 60, height),
+        ColorPair::new(RGB::named(rltk::WHEAT), RGB::named(rltk::BLACK)),
+    );
+    draw_batch.print_color_centered(
+        11,
+        "You gained a level! Choose a perk:",
+        ColorPair::new(RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK)),
+    );
+
+    let raws = RAWS.lock().unwrap();
+    for (i, name) in choices.iter().enumerate() {
+        let color = if i == selection {
+            ColorPair::new(RGB::named(rltk::MAGENTA), RGB::named(rltk::BLACK))
+        } else {
+            white
+        };
+        draw_batch.print_color(Point::new(12, 13 + i as i32), name, color);
+    }
+    if let Some(perk) = choices
+        .get(selection)
+        .and_then(|name| get_perk(&raws, name))
+    {
+        draw_batch.print_color(
+            Point::new(12, 14 + choices.len() as i32),
+            &perk.description,
+            gray,
+        );
+    }
+    draw_batch.print_color_centered(
+        9 + height,
+        format!(
+            "{}/{} to choose, {} to take",
+            key_hint(Action::MenuUp),
+            key_hint(Action::MenuDown),
+            key_hint(Action::Confirm)
+        ),
+        gray,
+    );
+    let _ = draw_batch.submit(6000);
+
+    match menu_action(ctx) {
+        Some(Action::MenuUp) if !choices.is_empty() => PerkMenuResult::NoResponse {
+            selection: (selection + choices.len() - 1) % choices.len(),
+        },
+        Some(Action::MenuDown) if !choices.is_empty() => PerkMenuResult::NoResponse {
+            selection: (selection + 1) % choices.len(),
+        },
+        Some(Action::Confirm) if !choices.is_empty() => PerkMenuResult::Selected {
+            perk: choices[selection].clone(),
+        },
+        _ => PerkMenuResult::NoResponse { selection },
+    }
+}
@@ -1,10 +1,23 @@
 use rltk::prelude::*;
 
 use specs::prelude::*;
-use crate::{State, camera, Viewshed };
+use crate::{State, camera, Viewshed, AreaOfEffect, SpellShape, Map };
+use crate::effects::{aoe_tiles, shape_tiles};
 use super::ItemMenuResult;
 
-pub fn ranged_target(gs : &mut State, ctx : &mut Rltk, range : i32) -> (ItemMenuResult, Option<Point>) {
+/// The tiles an item or spell would hit if aimed at `target`.
+fn affected_tiles(gs: &State, item: Entity, origin: Point, target: Point) -> Vec<i32> {
+    if let Some(shape) = gs.ecs.read_storage::<SpellShape>().get(item) {
+        return shape_tiles(&gs.ecs, shape.shape, origin, target);
+    }
+    let map = gs.ecs.fetch::<Map>();
+    if let Some(aoe) = gs.ecs.read_storage::<AreaOfEffect>().get(item) {
+        return aoe_tiles(&map, target, aoe.radius);
+    }
+    vec![map.xy_idx(target.x, target.y) as i32]
+}
+
+pub fn ranged_target(gs : &mut State, ctx : &mut Rltk, range : i32, item : Entity) -> (ItemMenuResult, Option<Point>) {
     let (min_x, max_x, min_y, max_y) = camera::get_screen_bounds(&gs.ecs, ctx);
     let player_entity = gs.ecs.fetch::<Entity>();
     let player_pos = gs.ecs.fetch::<Point>();
@@ -13,7 +26,7 @@ pub fn ranged_target(gs : &mut State, ctx : &mut Rltk, range : i32) -> (ItemMenu
     let mut draw_batch = DrawBatch::new();
 
     draw_batch.print_color(
-        Point::new(5, 0), 
+        Point::new(5, 0),
         "Select Target:",
         ColorPair::new(RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK))
     );
@@ -29,7 +42,7 @@ pub fn ranged_target(gs : &mut State, ctx : &mut Rltk, range : i32) -> (ItemMenu
                 let screen_x = idx.x - min_x;
                 let screen_y = idx.y - min_y;
                 if screen_x > 1 && screen_x < (max_x - min_x)-1 && screen_y > 1 && screen_y < (max_y - min_y)-1 {
-                    draw_batch.set_bg(Point::new(screen_x, screen_y), RGB::named(rltk::BLUE));
+                    draw_batch.set_bg(Point::new(screen_x + 1, screen_y + 1), RGB::named(rltk::BLUE));
                     available_cells.push(idx);
                 }
             }
@@ -39,16 +52,26 @@ pub fn ranged_target(gs : &mut State, ctx : &mut Rltk, range : i32) -> (ItemMenu
     }
 
     // Draw mouse cursor
-    let mouse_pos = ctx.mouse_pos();
+    let mouse_pos = gs.replay.mouse_pos(ctx);
     let mut mouse_map_pos = mouse_pos;
     mouse_map_pos.0 += min_x - 1;
     mouse_map_pos.1 += min_y - 1;
     let mut valid_target = false;
     for idx in available_cells.iter() { if idx.x == mouse_map_pos.0 && idx.y == mouse_map_pos.1 { valid_target = true; } }
     if valid_target {
+        // Show what it would hit
+        let target = Point::new(mouse_map_pos.0, mouse_map_pos.1);
+        let width = gs.ecs.fetch::<Map>().width;
+        for tile in affected_tiles(gs, item, *player_pos, target) {
+            let screen_x = tile % width - min_x + 1;
+            let screen_y = tile / width - min_y + 1;
+            if screen_x > 0 && screen_x < max_x - min_x && screen_y > 0 && screen_y < max_y - min_y {
+                draw_batch.set_bg(Point::new(screen_x, screen_y), RGB::named(rltk::ORANGE));
+            }
+        }
         draw_batch.set_bg(Point::new(mouse_pos.0, mouse_pos.1), RGB::named(rltk::CYAN));
         if ctx.left_click {
-            return (ItemMenuResult::Selected, Some(Point::new(mouse_map_pos.0, mouse_map_pos.1)));
+            return (ItemMenuResult::Selected, Some(target));
         }
     } else {
         draw_batch.set_bg(Point::new(mouse_pos.0, mouse_pos.1), RGB::named(rltk::RED));
@@ -61,4 +84,3 @@ pub fn ranged_target(gs : &mut State, ctx : &mut Rltk, range : i32) -> (ItemMenu
 
     (ItemMenuResult::NoResponse, None)
 }
-
@@ -45,7 +45,8 @@ pub fn remove_curse_menu(gs : &mut State, ctx : &mut Rltk) -> (ItemMenuResult, O
         "Inventory",
         items.len(),
         &items,
-        ctx.key
+        ctx,
+        gs.replay.mouse_pos(ctx)
     );
     let _ = draw_batch.submit(6000);
     result
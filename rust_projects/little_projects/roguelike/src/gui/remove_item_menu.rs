@@ -23,7 +23,8 @@ pub fn remove_item_menu(gs : &mut State, ctx : &mut Rltk) -> (ItemMenuResult, Op
         "Remove which item?",
         items.len(),
         &items,
-        ctx.key
+        ctx,
+        gs.replay.mouse_pos(ctx)
     );
     let _ = draw_batch.submit(6000);
     result
@@ -0,0 +1,37 @@
+use rltk::prelude::*;
+
+use crate::saveload_system::SaveFormat;
+use crate::input::{action, key_hint, menu_action, Action, ActionGroup};
+use super::{action_option, menu_box};
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum SaveGameMenuResult { NoResponse, Cancel, Selected{ format : SaveFormat } }
+
+const ACTIONS : [Action; 3] = [Action::SaveAsJson, Action::SaveAsBinary, Action::SaveAsCompressed];
+
+pub fn save_game_menu(ctx : &mut Rltk) -> SaveGameMenuResult {
+    let mut draw_batch = DrawBatch::new();
+    let count = SaveFormat::ALL.len() as i32;
+    let y = 25 - (count / 2);
+    menu_box(&mut draw_batch, 15, y, count+3, "Save and quit as");
+    draw_batch.print_color(
+        Point::new(18, y+count+1),
+        format!("{} to keep playing", key_hint(Action::Cancel)),
+        ColorPair::new(RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK))
+    );
+
+    for (i, (format, save_as)) in SaveFormat::ALL.iter().zip(ACTIONS.iter()).enumerate() {
+        action_option(&mut draw_batch, 17, y + i as i32, *save_as, format.name());
+    }
+
+    let _ = draw_batch.submit(6000);
+
+    if menu_action(ctx) == Some(Action::Cancel) {
+        return SaveGameMenuResult::Cancel;
+    }
+    let chosen = action(ctx, ActionGroup::SaveMenu);
+    match SaveFormat::ALL.iter().zip(ACTIONS.iter()).find(|(_, save_as)| Some(**save_as) == chosen) {
+        Some((format, _)) => SaveGameMenuResult::Selected{ format: *format },
+        None => SaveGameMenuResult::NoResponse
+    }
+}
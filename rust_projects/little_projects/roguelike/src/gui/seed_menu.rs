@@ -0,0 +1,50 @@
+use rltk::prelude::*;
+use crate::input::{key_hint, menu_action, Action};
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum SeedMenuResult { NoResponse{ seed : u64 }, Cancel, Selected{ seed : u64 } }
+
+fn key_to_digit(key : VirtualKeyCode) -> Option<u64> {
+    match key {
+        VirtualKeyCode::Key0 | VirtualKeyCode::Numpad0 => Some(0),
+        VirtualKeyCode::Key1 | VirtualKeyCode::Numpad1 => Some(1),
+        VirtualKeyCode::Key2 | VirtualKeyCode::Numpad2 => Some(2),
+        VirtualKeyCode::Key3 | VirtualKeyCode::Numpad3 => Some(3),
+        VirtualKeyCode::Key4 | VirtualKeyCode::Numpad4 => Some(4),
+        VirtualKeyCode::Key5 | VirtualKeyCode::Numpad5 => Some(5),
+        VirtualKeyCode::Key6 | VirtualKeyCode::Numpad6 => Some(6),
+        VirtualKeyCode::Key7 | VirtualKeyCode::Numpad7 => Some(7),
+        VirtualKeyCode::Key8 | VirtualKeyCode::Numpad8 => Some(8),
+        VirtualKeyCode::Key9 | VirtualKeyCode::Numpad9 => Some(9),
+        _ => None
+    }
+}
+
+pub fn seed_menu(ctx : &mut Rltk, seed : u64) -> SeedMenuResult {
+    let mut draw_batch = DrawBatch::new();
+
+    draw_batch.draw_double_box(Rect::with_size(19, 20, 41, 6), ColorPair::new(RGB::named(rltk::WHEAT), RGB::named(rltk::BLACK)));
+    draw_batch.print_color_centered(21, "Enter a seed for the new game", ColorPair::new(RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK)));
+    draw_batch.print_color_centered(23, format!("{}_", seed), ColorPair::new(RGB::named(rltk::WHITE), RGB::named(rltk::BLACK)));
+    draw_batch.print_color_centered(25, format!("{} to start, {} to cancel", key_hint(Action::Confirm), key_hint(Action::Cancel)), ColorPair::new(RGB::named(rltk::GRAY), RGB::named(rltk::BLACK)));
+
+    let _ = draw_batch.submit(6000);
+
+    match ctx.key {
+        None => SeedMenuResult::NoResponse{ seed },
+        Some(key) => {
+            match menu_action(ctx) {
+                Some(Action::Cancel) => SeedMenuResult::Cancel,
+                Some(Action::Confirm) => SeedMenuResult::Selected{ seed },
+                Some(Action::Erase) => SeedMenuResult::NoResponse{ seed: seed / 10 },
+                _ => {
+                    // Ignore digits that would overflow the seed
+                    let new_seed = key_to_digit(key)
+                        .and_then(|digit| seed.checked_mul(10).and_then(|s| s.checked_add(digit)))
+                        .unwrap_or(seed);
+                    SeedMenuResult::NoResponse{ seed: new_seed }
+                }
+            }
+        }
+    }
+}
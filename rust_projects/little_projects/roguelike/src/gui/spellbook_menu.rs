@@ -0,0 +1,94 @@
+use super::{menu_option, ItemMenuResult};
+use crate::input::{key_hint, menu_action, Action};
+use crate::raws::{get_spell, RAWS};
+use crate::{KnownSpells, Skill, Skills, State};
+use rltk::prelude::*;
+use specs::prelude::*;
+
+/// Lists the spells the player knows, and lets them pick one to cast.
+pub fn show_spellbook(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<usize>) {
+    let player_entity = gs.ecs.fetch::<Entity>();
+    let known_spells = gs.ecs.read_storage::<KnownSpells>();
+    let spells = &known_spells.get(*player_entity).unwrap().spells;
+    let magic = gs
+        .ecs
+        .read_storage::<Skills>()
+        .get(*player_entity)
+        .and_then(|skills| skills.skills.get(&Skill::Magic).copied())
+        .unwrap_or(0);
+    let raws = RAWS.lock().unwrap();
+
+    let mut draw_batch = DrawBatch::new();
+    let yellow = ColorPair::new(RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK));
+    let gray = ColorPair::new(RGB::named(rltk::GRAY), RGB::named(rltk::BLACK));
+    let red = ColorPair::new(RGB::named(rltk::RED), RGB::named(rltk::BLACK));
+    let height = spells.len() as i32 * 2 + 4;
+    let mut y = 25 - height / 2;
+    draw_batch.draw_box(
+        Rect::with_size(10, y - 2, 60, height),
+        ColorPair::new(RGB::named(rltk::WHITE), RGB::named(rltk::BLACK)),
+    );
+    draw_batch.print_color(Point::new(13, y - 2), "Spellbook", yellow);
+    draw_batch.print_color(
+        Point::new(13, y - 2 + height),
+        format!("{} to cancel", key_hint(Action::Cancel)),
+        yellow,
+    );
+    if spells.is_empty() {
+        draw_batch.print_color(Point::new(12, y), "You don't know any spells.", gray);
+    }
+
+    for (j, spell) in spells.iter().enumerate() {
+        let raw = get_spell(&raws, &spell.display_name);
+        menu_option(
+            &mut draw_batch,
+            12,
+            y,
+            97 + j as rltk::FontCharType,
+            &spell.display_name,
+        );
+        draw_batch.print_color(
+            Point::new(36, y),
+            format!("{} mana", spell.mana_cost),
+            yellow,
+        );
+        if let Some(school) = raw.and_then(|raw| raw.school.as_ref()) {
+            draw_batch.print_color(Point::new(45, y), school, gray);
+        }
+        let min_magic = raw.and_then(|raw| raw.min_magic).unwrap_or(0);
+        if magic < min_magic {
+            draw_batch.print_color(
+                Point::new(58, y),
+                format!("Magic {}", min_magic),
+                red,
+            );
+        } else if spell.cooldown > 0 {
+            draw_batch.print_color(
+                Point::new(58, y),
+                format!("{} turns", spell.cooldown),
+                red,
+            );
+        }
+        if let Some(description) = raw.and_then(|raw| raw.description.as_ref()) {
+            draw_batch.print_color(Point::new(17, y + 1), description, gray);
+        }
+        y += 2;
+    }
+
+    let _ = draw_batch.submit(6000);
+
+    if menu_action(ctx) == Some(Action::Cancel) {
+        return (ItemMenuResult::Cancel, None);
+    }
+    match ctx.key {
+        None => (ItemMenuResult::NoResponse, None),
+        Some(key) => {
+            let selection = rltk::letter_to_option(key);
+            if selection > -1 && selection < spells.len() as i32 {
+                (ItemMenuResult::Selected, Some(selection as usize))
+            } else {
+                (ItemMenuResult::NoResponse, None)
+            }
+        }
+    }
+}
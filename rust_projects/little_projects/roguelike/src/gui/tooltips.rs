@@ -1,5 +1,5 @@
-use super::get_item_display_name;
-use crate::{camera, Attributes, Duration, Hidden, Map, Name, Pools, StatusEffect};
+use super::{get_item_display_name, status_label};
+use crate::{camera, Attributes, Duration, Hidden, Map, Name, Pools, StatusEffect, StatusStacks};
 use rltk::prelude::*;
 use specs::prelude::*;
 
@@ -53,30 +53,24 @@ impl Tooltip {
 pub fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
     let mut draw_batch = DrawBatch::new();
 
-    let (min_x, _max_x, min_y, _max_y) = camera::get_screen_bounds(ecs, ctx);
     let map = ecs.fetch::<Map>();
     let hidden = ecs.read_storage::<Hidden>();
     let attributes = ecs.read_storage::<Attributes>();
     let pools = ecs.read_storage::<Pools>();
 
     let mouse_pos = ctx.mouse_pos();
-    let mut mouse_map_pos = mouse_pos;
-    mouse_map_pos.0 += min_x - 1;
-    mouse_map_pos.1 += min_y - 1;
-    if mouse_pos.0 < 1 || mouse_pos.0 > 49 || mouse_pos.1 < 1 || mouse_pos.1 > 40 {
-        return;
-    }
-    if mouse_map_pos.0 >= map.width - 1
-        || mouse_map_pos.1 >= map.height - 1
-        || mouse_map_pos.0 < 1
-        || mouse_map_pos.1 < 1
+    let mouse_map_pos = match camera::screen_to_map(ecs, ctx, mouse_pos) {
+        Some(tile) => tile,
+        None => return,
+    };
+    if mouse_map_pos.x >= map.width - 1
+        || mouse_map_pos.y >= map.height - 1
+        || mouse_map_pos.x < 1
+        || mouse_map_pos.y < 1
     {
         return;
     }
-    if !map.in_bounds(rltk::Point::new(mouse_map_pos.0, mouse_map_pos.1)) {
-        return;
-    }
-    let mouse_idx = map.xy_idx(mouse_map_pos.0, mouse_map_pos.1);
+    let mouse_idx = map.xy_idx(mouse_map_pos.x, mouse_map_pos.y);
     if !map.visible_tiles[mouse_idx] {
         return;
     }
@@ -133,9 +127,16 @@ pub fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
         let statuses = ecs.read_storage::<StatusEffect>();
         let durations = ecs.read_storage::<Duration>();
         let names = ecs.read_storage::<Name>();
-        for (status, duration, name) in (&statuses, &durations, &names).join() {
+        let stacks = ecs.read_storage::<StatusStacks>();
+        for (status_entity, status, duration, name) in
+            (&ecs.entities(), &statuses, &durations, &names).join()
+        {
             if status.target == entity {
-                tip.add(format!("{} ({})", name.name, duration.turns));
+                tip.add(status_label(
+                    &name.name,
+                    stacks.get(status_entity),
+                    duration.turns,
+                ));
             }
         }
 
@@ -3,6 +3,7 @@ use rltk::prelude::*;
 use specs::prelude::*;
 use crate::{Name, State, InBackpack, VendorMode, Vendor, Item };
 use super::{get_item_display_name, get_item_color, menu_box};
+use crate::input::{key_hint, menu_action, Action};
 
 #[derive(PartialEq, Copy, Clone)]
 pub enum VendorResult { NoResponse, Cancel, Sell, BuyMode, SellMode, Buy }
@@ -19,10 +20,10 @@ fn vendor_sell_menu(gs : &mut State, ctx : &mut Rltk, _vendor : Entity, _mode :
     let count = inventory.count();
 
     let mut y = (25 - (count / 2)) as i32;
-    menu_box(&mut draw_batch, 15, y, (count+3) as i32, "Sell Which Item? (space to switch to buy mode)");
+    menu_box(&mut draw_batch, 15, y, (count+3) as i32, format!("Sell Which Item? ({} to switch to buy mode)", key_hint(Action::SwitchMode)));
     draw_batch.print_color(
         Point::new(18, y+count as i32+1),
-        "ESCAPE to cancel",
+        format!("{} to cancel", key_hint(Action::Cancel)),
         ColorPair::new(RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK))
     );
 
@@ -47,9 +48,9 @@ fn vendor_sell_menu(gs : &mut State, ctx : &mut Rltk, _vendor : Entity, _mode :
     match ctx.key {
         None => (VendorResult::NoResponse, None, None, None),
         Some(key) => {
-            match key {
-                VirtualKeyCode::Space => { (VendorResult::BuyMode, None, None, None) }
-                VirtualKeyCode::Escape => { (VendorResult::Cancel, None, None, None) }
+            match menu_action(ctx) {
+                Some(Action::SwitchMode) => { (VendorResult::BuyMode, None, None, None) }
+                Some(Action::Cancel) => { (VendorResult::Cancel, None, None, None) }
                 _ => {
                     let selection = rltk::letter_to_option(key);
                     if selection > -1 && selection < count as i32 {
@@ -68,14 +69,18 @@ fn vendor_buy_menu(gs : &mut State, ctx : &mut Rltk, vendor : Entity, _mode : Ve
 
     let vendors = gs.ecs.read_storage::<Vendor>();
 
-    let inventory = crate::raws::get_vendor_items(&vendors.get(vendor).unwrap().categories, &RAWS.lock().unwrap());
+    let vendor_info = vendors.get(vendor).unwrap();
+    let inventory : Vec<(String, f32)> = crate::raws::get_vendor_items(&vendor_info.categories, &RAWS.lock().unwrap())
+        .into_iter()
+        .filter(|sale| vendor_info.stock.get(&sale.0).copied().unwrap_or(0) > 0)
+        .collect();
     let count = inventory.len();
 
     let mut y = (25 - (count / 2)) as i32;
-    menu_box(&mut draw_batch, 15, y, (count+3) as i32, "Buy Which Item? (space to switch to sell mode)");
+    menu_box(&mut draw_batch, 15, y, (count+3) as i32, format!("Buy Which Item? ({} to switch to sell mode)", key_hint(Action::SwitchMode)));
     draw_batch.print_color(
         Point::new(18, y+count as i32+1),
-        "ESCAPE to cancel",
+        format!("{} to cancel", key_hint(Action::Cancel)),
         ColorPair::new(RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK))
     );
 
@@ -85,6 +90,7 @@ fn vendor_buy_menu(gs : &mut State, ctx : &mut Rltk, vendor : Entity, _mode : Ve
         draw_batch.set(Point::new(19, y), ColorPair::new(RGB::named(rltk::WHITE), RGB::named(rltk::BLACK)), rltk::to_cp437(')'));
 
         draw_batch.print(Point::new(21, y), &sale.0);
+        draw_batch.print(Point::new(45, y), format!("x{}", vendor_info.stock[&sale.0]));
         draw_batch.print(Point::new(50, y), format!("{:.1} gp", sale.1 * 1.2));
         y += 1;
     }
@@ -94,9 +100,9 @@ fn vendor_buy_menu(gs : &mut State, ctx : &mut Rltk, vendor : Entity, _mode : Ve
     match ctx.key {
         None => (VendorResult::NoResponse, None, None, None),
         Some(key) => {
-            match key {
-                VirtualKeyCode::Space => { (VendorResult::SellMode, None, None, None) }
-                VirtualKeyCode::Escape => { (VendorResult::Cancel, None, None, None) }
+            match menu_action(ctx) {
+                Some(Action::SwitchMode) => { (VendorResult::SellMode, None, None, None) }
+                Some(Action::Cancel) => { (VendorResult::Cancel, None, None, None) }
                 _ => {
                     let selection = rltk::letter_to_option(key);
                     if selection > -1 && selection < count as i32 {
@@ -0,0 +1,596 @@
+//! Turns key presses into named actions. Every screen asks for actions rather than keys, so the
+//! keys themselves can be remapped in a config file or on the key bindings screen.
+
+use rltk::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+/// Where the player's key bindings are kept, next to the saves and replays.
+pub const BINDINGS_FILE: &str = "./keybindings.toml";
+
+lazy_static! {
+    pub static ref BINDINGS: Mutex<KeyBindings> = Mutex::new(KeyBindings::default());
+    static ref RIGHT_BUTTON: Mutex<RightButton> = Mutex::new(RightButton::default());
+}
+
+/// Rltk only reports left clicks, so right clicks are picked out of the held mouse buttons.
+#[derive(Default)]
+struct RightButton {
+    held: bool,
+    clicked: bool,
+}
+
+macro_rules! key_names {
+    ( $( $key:ident ),* ) => {
+        /// The name a key goes by in replays and the bindings file.
+        pub fn key_name(key: VirtualKeyCode) -> Option<&'static str> {
+            match key {
+                $( VirtualKeyCode::$key => Some(stringify!($key)), )*
+                _ => None,
+            }
+        }
+
+        pub fn key_from_name(name: &str) -> Option<VirtualKeyCode> {
+            match name {
+                $( stringify!($key) => Some(VirtualKeyCode::$key), )*
+                _ => None,
+            }
+        }
+    };
+}
+
+// Keys are stored by name, so files don't depend on the windowing library's numbering
+#[rustfmt::skip]
+key_names!(
+    Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9, Key0, A, B, C, D, E, F, G, H, I, J, K,
+    L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, Escape, F1, F2, F3, F4, F5, F6, F7, F8, F9, F10,
+    F11, F12, Insert, Home, Delete, End, PageDown, PageUp, Left, Up, Right, Down, Back, Return,
+    Space, Tab, Numpad0, Numpad1, Numpad2, Numpad3, Numpad4, Numpad5, Numpad6, Numpad7, Numpad8,
+    Numpad9, NumpadAdd, NumpadDecimal, NumpadDivide, NumpadEnter, NumpadMultiply,
+    NumpadSubtract, Apostrophe, Backslash, Comma, Equals, Grave, LBracket, Minus, Period,
+    RBracket, Semicolon, Slash
+);
+
+/// Actions only compete for keys with others in the same group, so a key can move the player
+/// in the dungeon and the cursor in a menu.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum ActionGroup {
+    Game,
+    Menu,
+    Replay,
+    SaveMenu,
+    CheatMenu,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Debug, Hash)]
+pub enum Action {
+    MoveN,
+    MoveS,
+    MoveE,
+    MoveW,
+    MoveNE,
+    MoveNW,
+    MoveSE,
+    MoveSW,
+    Wait,
+    Descend,
+    Ascend,
+    AutoExplore,
+    TravelToStairs,
+    CycleTarget,
+    Fire,
+    PickUp,
+    Inventory,
+    Drop,
+    RemoveItem,
+    Spellbook,
+    MessageLog,
+    SaveGame,
+    Cheats,
+    Hotkey1,
+    Hotkey2,
+    Hotkey3,
+    Hotkey4,
+    Hotkey5,
+    Hotkey6,
+    Hotkey7,
+    Hotkey8,
+    Hotkey9,
+    Cast1,
+    Cast2,
+    Cast3,
+    Cast4,
+    Cast5,
+    Cast6,
+    Cast7,
+    Cast8,
+    Cast9,
+    MenuUp,
+    MenuDown,
+    MenuLeft,
+    MenuRight,
+    Confirm,
+    Cancel,
+    PageUp,
+    PageDown,
+    Erase,
+    SwitchMode,
+    ExportLog,
+    RollAttributes,
+    PointBuy,
+    ReplayPause,
+    ReplayStep,
+    ReplayFast,
+    ReplayTakeOver,
+    SaveAsJson,
+    SaveAsBinary,
+    SaveAsCompressed,
+    CheatTeleport,
+    CheatHeal,
+    CheatReveal,
+    CheatGodMode,
+    CheatReloadRaws,
+}
+
+#[rustfmt::skip]
+const HOTKEYS: [Action; 9] = [
+    Action::Hotkey1, Action::Hotkey2, Action::Hotkey3, Action::Hotkey4, Action::Hotkey5,
+    Action::Hotkey6, Action::Hotkey7, Action::Hotkey8, Action::Hotkey9,
+];
+
+#[rustfmt::skip]
+const CASTS: [Action; 9] = [
+    Action::Cast1, Action::Cast2, Action::Cast3, Action::Cast4, Action::Cast5, Action::Cast6,
+    Action::Cast7, Action::Cast8, Action::Cast9,
+];
+
+impl Action {
+    /// Every action, in the order the key bindings screen lists them.
+    #[rustfmt::skip]
+    pub const ALL: [Action; 66] = [
+        Action::MoveN, Action::MoveS, Action::MoveE, Action::MoveW, Action::MoveNE, Action::MoveNW,
+        Action::MoveSE, Action::MoveSW, Action::Wait, Action::Descend, Action::Ascend,
+        Action::AutoExplore, Action::TravelToStairs, Action::CycleTarget, Action::Fire, Action::PickUp, Action::Inventory, Action::Drop,
+        Action::RemoveItem, Action::Spellbook, Action::MessageLog, Action::SaveGame, Action::Cheats,
+        Action::Hotkey1, Action::Hotkey2, Action::Hotkey3, Action::Hotkey4, Action::Hotkey5,
+        Action::Hotkey6, Action::Hotkey7, Action::Hotkey8, Action::Hotkey9,
+        Action::Cast1, Action::Cast2, Action::Cast3, Action::Cast4, Action::Cast5, Action::Cast6,
+        Action::Cast7, Action::Cast8, Action::Cast9,
+        Action::MenuUp, Action::MenuDown, Action::MenuLeft, Action::MenuRight, Action::Confirm,
+        Action::Cancel, Action::PageUp, Action::PageDown, Action::Erase, Action::SwitchMode,
+        Action::ExportLog, Action::RollAttributes, Action::PointBuy,
+        Action::ReplayPause, Action::ReplayStep, Action::ReplayFast, Action::ReplayTakeOver,
+        Action::SaveAsJson, Action::SaveAsBinary, Action::SaveAsCompressed,
+        Action::CheatTeleport, Action::CheatHeal, Action::CheatReveal, Action::CheatGodMode,
+        Action::CheatReloadRaws,
+    ];
+
+    pub fn group(self) -> ActionGroup {
+        match self {
+            Action::MenuUp
+            | Action::MenuDown
+            | Action::MenuLeft
+            | Action::MenuRight
+            | Action::Confirm
+            | Action::Cancel
+            | Action::PageUp
+            | Action::PageDown
+            | Action::Erase
+            | Action::SwitchMode
+            | Action::ExportLog
+            | Action::RollAttributes
+            | Action::PointBuy => ActionGroup::Menu,
+            Action::ReplayPause
+            | Action::ReplayStep
+            | Action::ReplayFast
+            | Action::ReplayTakeOver => ActionGroup::Replay,
+            Action::SaveAsJson | Action::SaveAsBinary | Action::SaveAsCompressed => {
+                ActionGroup::SaveMenu
+            }
+            Action::CheatTeleport
+            | Action::CheatHeal
+            | Action::CheatReveal
+            | Action::CheatGodMode
+            | Action::CheatReloadRaws => ActionGroup::CheatMenu,
+            _ => ActionGroup::Game,
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::MoveN => "Move north",
+            Action::MoveS => "Move south",
+            Action::MoveE => "Move east",
+            Action::MoveW => "Move west",
+            Action::MoveNE => "Move north-east",
+            Action::MoveNW => "Move north-west",
+            Action::MoveSE => "Move south-east",
+            Action::MoveSW => "Move south-west",
+            Action::Wait => "Wait a turn",
+            Action::Descend => "Go down stairs",
+            Action::Ascend => "Go up stairs",
+            Action::AutoExplore => "Explore",
+            Action::TravelToStairs => "Travel to stairs",
+            Action::CycleTarget => "Cycle target",
+            Action::Fire => "Fire at target",
+            Action::PickUp => "Pick up",
+            Action::Inventory => "Inventory",
+            Action::Drop => "Drop item",
+            Action::RemoveItem => "Remove equipment",
+            Action::Spellbook => "Spellbook",
+            Action::MessageLog => "Message log",
+            Action::SaveGame => "Save and quit",
+            Action::Cheats => "Cheat menu",
+            Action::Hotkey1 => "Use consumable 1",
+            Action::Hotkey2 => "Use consumable 2",
+            Action::Hotkey3 => "Use consumable 3",
+            Action::Hotkey4 => "Use consumable 4",
+            Action::Hotkey5 => "Use consumable 5",
+            Action::Hotkey6 => "Use consumable 6",
+            Action::Hotkey7 => "Use consumable 7",
+            Action::Hotkey8 => "Use consumable 8",
+            Action::Hotkey9 => "Use consumable 9",
+            Action::Cast1 => "Cast spell 1",
+            Action::Cast2 => "Cast spell 2",
+            Action::Cast3 => "Cast spell 3",
+            Action::Cast4 => "Cast spell 4",
+            Action::Cast5 => "Cast spell 5",
+            Action::Cast6 => "Cast spell 6",
+            Action::Cast7 => "Cast spell 7",
+            Action::Cast8 => "Cast spell 8",
+            Action::Cast9 => "Cast spell 9",
+            Action::MenuUp => "Menu up",
+            Action::MenuDown => "Menu down",
+            Action::MenuLeft => "Menu left",
+            Action::MenuRight => "Menu right",
+            Action::Confirm => "Confirm",
+            Action::Cancel => "Cancel",
+            Action::PageUp => "Page up",
+            Action::PageDown => "Page down",
+            Action::Erase => "Erase",
+            Action::SwitchMode => "Switch mode",
+            Action::ExportLog => "Export log",
+            Action::RollAttributes => "Roll attributes",
+            Action::PointBuy => "Point buy",
+            Action::ReplayPause => "Pause replay",
+            Action::ReplayStep => "Step replay",
+            Action::ReplayFast => "Fast replay",
+            Action::ReplayTakeOver => "Take over replay",
+            Action::SaveAsJson => "Save as JSON",
+            Action::SaveAsBinary => "Save as binary",
+            Action::SaveAsCompressed => "Save as compressed binary",
+            Action::CheatTeleport => "Cheat: next level",
+            Action::CheatHeal => "Cheat: heal",
+            Action::CheatReveal => "Cheat: reveal map",
+            Action::CheatGodMode => "Cheat: god mode",
+            Action::CheatReloadRaws => "Cheat: reload raws",
+        }
+    }
+
+    /// Which consumable hotkey (0-8) this action uses, if it is one.
+    pub fn hotkey_slot(self) -> Option<usize> {
+        HOTKEYS.iter().position(|a| *a == self)
+    }
+
+    /// Which spell (0-8) this action casts, if it is one.
+    pub fn spell_slot(self) -> Option<usize> {
+        CASTS.iter().position(|a| *a == self)
+    }
+}
+
+/// A key, along with the modifiers that have to be held for it.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Copy, Clone, Debug)]
+#[serde(into = "String", try_from = "String")]
+pub struct KeyCombo {
+    pub key: VirtualKeyCode,
+    pub shift: bool,
+    pub control: bool,
+}
+
+impl KeyCombo {
+    pub fn new(key: VirtualKeyCode) -> KeyCombo {
+        KeyCombo {
+            key,
+            shift: false,
+            control: false,
+        }
+    }
+
+    pub fn shift(key: VirtualKeyCode) -> KeyCombo {
+        KeyCombo {
+            shift: true,
+            ..KeyCombo::new(key)
+        }
+    }
+
+    pub fn control(key: VirtualKeyCode) -> KeyCombo {
+        KeyCombo {
+            control: true,
+            ..KeyCombo::new(key)
+        }
+    }
+
+    /// What was pressed this frame, if anything.
+    pub fn pressed(ctx: &Rltk) -> Option<KeyCombo> {
+        ctx.key.map(|key| KeyCombo {
+            key,
+            shift: ctx.shift,
+            control: ctx.control,
+        })
+    }
+}
+
+impl fmt::Display for KeyCombo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.control {
+            write!(f, "Ctrl+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        write!(f, "{}", key_name(self.key).unwrap_or("?"))
+    }
+}
+
+impl FromStr for KeyCombo {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<KeyCombo, String> {
+        let mut parts: Vec<&str> = s.split('+').map(str::trim).collect();
+        let name = parts.pop().unwrap_or("");
+        let mut combo =
+            KeyCombo::new(key_from_name(name).ok_or_else(|| format!("unknown key '{}'", name))?);
+        for modifier in parts {
+            match modifier {
+                "Shift" => combo.shift = true,
+                "Ctrl" => combo.control = true,
+                _ => return Err(format!("unknown modifier '{}' in '{}'", modifier, s)),
+            }
+        }
+        Ok(combo)
+    }
+}
+
+impl From<KeyCombo> for String {
+    fn from(combo: KeyCombo) -> String {
+        combo.to_string()
+    }
+}
+
+impl TryFrom<String> for KeyCombo {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<KeyCombo, String> {
+        s.parse()
+    }
+}
+
+/// The keys for every action.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct KeyBindings {
+    pub bindings: BTreeMap<Action, Vec<KeyCombo>>,
+}
+
+fn default_keys(action: Action) -> Vec<KeyCombo> {
+    use VirtualKeyCode::*;
+    let keys: &[VirtualKeyCode] = match action {
+        Action::MoveN => &[Up, Numpad8, K],
+        Action::MoveS => &[Down, Numpad2, J],
+        Action::MoveE => &[Right, Numpad6, L],
+        Action::MoveW => &[Left, Numpad4, H],
+        Action::MoveNE => &[Numpad9, U],
+        Action::MoveNW => &[Numpad7, Y],
+        Action::MoveSE => &[Numpad3, N],
+        Action::MoveSW => &[Numpad1, B],
+        Action::Wait => &[Numpad5, Space],
+        Action::Descend => &[Period],
+        Action::Ascend => &[Comma],
+        Action::AutoExplore => &[O],
+        Action::TravelToStairs => &[T],
+        Action::CycleTarget => &[V],
+        Action::Fire => &[F],
+        Action::PickUp => &[G],
+        Action::Inventory => &[I],
+        Action::Drop => &[D],
+        Action::RemoveItem => &[R],
+        Action::Spellbook => &[Z],
+        Action::MessageLog => &[M],
+        Action::SaveGame => &[Escape],
+        Action::Cheats => &[Backslash],
+        Action::MenuUp => &[Up],
+        Action::MenuDown => &[Down],
+        Action::MenuLeft => &[Left],
+        Action::MenuRight => &[Right],
+        Action::Confirm => &[Return],
+        Action::Cancel => &[Escape],
+        Action::PageUp => &[PageUp],
+        Action::PageDown => &[PageDown],
+        Action::Erase => &[Back],
+        Action::SwitchMode => &[Space],
+        Action::ExportLog => &[X],
+        Action::RollAttributes => &[R],
+        Action::PointBuy => &[P],
+        Action::ReplayPause => &[Space],
+        Action::ReplayStep => &[Period],
+        Action::ReplayFast => &[F],
+        Action::ReplayTakeOver => &[Escape],
+        Action::SaveAsJson => &[J],
+        Action::SaveAsBinary => &[B],
+        Action::SaveAsCompressed => &[C],
+        Action::CheatTeleport => &[T],
+        Action::CheatHeal => &[H],
+        Action::CheatReveal => &[R],
+        Action::CheatGodMode => &[G],
+        Action::CheatReloadRaws => &[L],
+        _ => &[],
+    };
+    const DIGITS: [VirtualKeyCode; 9] = [Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9];
+    if let Some(slot) = action.hotkey_slot() {
+        return vec![KeyCombo::shift(DIGITS[slot])];
+    }
+    if let Some(slot) = action.spell_slot() {
+        return vec![KeyCombo::control(DIGITS[slot])];
+    }
+    keys.iter().map(|key| KeyCombo::new(*key)).collect()
+}
+
+impl Default for KeyBindings {
+    fn default() -> KeyBindings {
+        KeyBindings {
+            bindings: Action::ALL
+                .iter()
+                .map(|action| (*action, default_keys(*action)))
+                .collect(),
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Reads a bindings file. Actions the file leaves out keep their default keys.
+    pub fn from_toml(data: &str) -> Result<KeyBindings, String> {
+        let loaded: KeyBindings = toml::from_str(data).map_err(|e| e.to_string())?;
+        Ok(loaded.with_defaults())
+    }
+
+    /// Gives the actions these bindings don't mention their default keys, such as actions added
+    /// since the bindings were saved.
+    pub fn with_defaults(self) -> KeyBindings {
+        let mut bindings = KeyBindings::default();
+        bindings.bindings.extend(self.bindings);
+        bindings
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("Unable to serialize key bindings")
+    }
+
+    pub fn keys(&self, action: Action) -> &[KeyCombo] {
+        self.bindings
+            .get(&action)
+            .map_or(&[], |keys| keys.as_slice())
+    }
+
+    /// The action in the group this key triggers. Modifiers have to match exactly, except that
+    /// a key bound without modifiers still works while they're held.
+    pub fn action(&self, group: ActionGroup, pressed: KeyCombo) -> Option<Action> {
+        let in_group = || {
+            Action::ALL
+                .iter()
+                .copied()
+                .filter(move |action| action.group() == group)
+        };
+        in_group()
+            .find(|action| self.keys(*action).contains(&pressed))
+            .or_else(|| {
+                in_group().find(|action| self.keys(*action).contains(&KeyCombo::new(pressed.key)))
+            })
+    }
+
+    /// Adds a key to an action, taking it away from anything else in the action's group.
+    pub fn bind(&mut self, action: Action, combo: KeyCombo) {
+        for (other, keys) in self.bindings.iter_mut() {
+            if other.group() == action.group() {
+                keys.retain(|key| *key != combo);
+            }
+        }
+        self.bindings.entry(action).or_default().push(combo);
+    }
+
+    pub fn clear(&mut self, action: Action) {
+        self.bindings.insert(action, Vec::new());
+    }
+
+    /// Puts an action's default keys back, taking them away from anything else in its group.
+    pub fn reset(&mut self, action: Action) {
+        self.clear(action);
+        for combo in default_keys(action) {
+            self.bind(action, combo);
+        }
+    }
+
+    /// The action's keys as the player would read them.
+    pub fn describe(&self, action: Action) -> String {
+        let keys = self.keys(action);
+        if keys.is_empty() {
+            return "unbound".to_string();
+        }
+        keys.iter()
+            .map(|key| key.to_string())
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+/// Replaces the bindings with those in the file, if there is one. A broken file is reported and
+/// the defaults are kept.
+pub fn load_bindings(path: &Path) {
+    let data = match std::fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(_) => return,
+    };
+    match KeyBindings::from_toml(&data) {
+        Ok(bindings) => *BINDINGS.lock().unwrap() = bindings,
+        Err(e) => crate::diagnostic(format!(
+            "WARNING - unable to load key bindings from {}: {}",
+            path.display(),
+            e
+        )),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_bindings(path: &Path) -> Result<(), String> {
+    let data = BINDINGS.lock().unwrap().to_toml();
+    std::fs::write(path, data).map_err(|e| e.to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn save_bindings(_path: &Path) -> Result<(), String> {
+    Ok(())
+}
+
+/// The action this frame's key press triggers in the group, if any.
+pub fn action(ctx: &Rltk, group: ActionGroup) -> Option<Action> {
+    KeyCombo::pressed(ctx).and_then(|pressed| BINDINGS.lock().unwrap().action(group, pressed))
+}
+
+pub fn game_action(ctx: &Rltk) -> Option<Action> {
+    action(ctx, ActionGroup::Game)
+}
+
+pub fn menu_action(ctx: &Rltk) -> Option<Action> {
+    action(ctx, ActionGroup::Menu)
+}
+
+/// The first key bound to an action, for on-screen hints.
+pub fn key_hint(action: Action) -> String {
+    BINDINGS
+        .lock()
+        .unwrap()
+        .keys(action)
+        .first()
+        .map_or("(unbound)".to_string(), |key| key.to_string())
+}
+
+/// Notes a right click if the button went down since the last frame. Called once a frame, before
+/// anything asks for [`right_click`].
+pub fn poll_right_click() {
+    let down = INPUT.lock().is_mouse_button_pressed(1);
+    let mut button = RIGHT_BUTTON.lock().unwrap();
+    button.clicked |= down && !button.held;
+    button.held = down;
+}
+
+/// True on the frame the right mouse button was clicked.
+pub fn right_click() -> bool {
+    RIGHT_BUTTON.lock().unwrap().clicked
+}
+
+/// Sets this frame's right click, for replays; it lasts until the end of the frame.
+pub fn set_right_click(clicked: bool) {
+    RIGHT_BUTTON.lock().unwrap().clicked = clicked;
+}
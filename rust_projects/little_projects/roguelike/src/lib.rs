@@ -0,0 +1,1192 @@
+extern crate serde;
+use rltk::{GameState, Point, Rltk};
+use specs::prelude::*;
+use specs::saveload::{SimpleMarker, SimpleMarkerAllocator};
+
+mod components;
+pub use components::*;
+mod map;
+mod systems;
+pub use map::*;
+use systems::*;
+mod damage_system;
+mod player;
+use player::*;
+mod character;
+mod rect;
+pub mod rng;
+pub use rect::Rect;
+pub mod gamelog;
+mod gamesystem;
+mod gui;
+pub mod map_builders;
+pub mod morgue;
+pub mod progression;
+pub mod input;
+pub mod random_table;
+pub mod raws;
+pub mod replay;
+pub mod rex_assets;
+pub mod saveload_system;
+pub mod simulator;
+pub mod travel;
+mod spawner;
+pub use gamesystem::*;
+pub mod effects;
+#[macro_use]
+extern crate lazy_static;
+pub mod spatial;
+
+/// Reports a warning or debugging note for whoever is running the game. Native builds send it
+/// to stderr, so tools that print their results to stdout (like `mapgen --format json`) stay
+/// parseable; the browser build logs it to the console.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn diagnostic<S: ToString>(message: S) {
+    eprintln!("{}", message.to_string());
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn diagnostic<S: ToString>(message: S) {
+    rltk::console::log(message);
+}
+
+const SHOW_MAPGEN_VISUALIZER: bool = false;
+const SHOW_FPS: bool = false;
+/// Message logs exported from the game over screen go here.
+const LOG_DIR: &str = "./logs";
+/// Autosave once this many turns have gone by since the run was last saved, as well as whenever a
+/// level is entered.
+const AUTOSAVE_TURNS: Option<i32> = Some(200);
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum VendorMode {
+    Buy,
+    Sell,
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum RunState {
+    AwaitingInput,
+    PreRun,
+    Ticking,
+    ShowInventory,
+    ShowSpellbook,
+    ShowDropItem,
+    ShowTargeting {
+        range: i32,
+        item: Entity,
+    },
+    MainMenu {
+        menu_selection: gui::MainMenuSelection,
+    },
+    EnterSeed {
+        seed: u64,
+    },
+    CharacterCreation {
+        step: gui::CreationStep,
+        selection: usize,
+    },
+    LoadGameMenu {
+        selection: usize,
+    },
+    KeyBindings {
+        selection: usize,
+        capturing: bool,
+    },
+    HighScores,
+    SaveGame,
+    NextLevel,
+    PreviousLevel,
+    TownPortal,
+    ShowRemoveItem,
+    GameOver,
+    MagicMapReveal {
+        row: i32,
+    },
+    MapGeneration,
+    ShowCheatMenu,
+    ShowVendor {
+        vendor: Entity,
+        mode: VendorMode,
+    },
+    TeleportingToOtherLevel {
+        x: i32,
+        y: i32,
+        depth: i32,
+    },
+    ShowRemoveCurse,
+    ShowIdentify,
+    ShowPerks {
+        selection: usize,
+    },
+    ShowLog {
+        offset: usize,
+        filter: Option<gamelog::LogCategory>,
+    },
+    Travelling,
+    ShowContextMenu {
+        x: i32,
+        y: i32,
+    },
+}
+
+pub struct State {
+    pub ecs: World,
+    mapgen_next_state: Option<RunState>,
+    mapgen_history: Vec<Map>,
+    mapgen_index: usize,
+    mapgen_timer: f32,
+    save_slots: Vec<saveload_system::SaveSlot>,
+    save_error: Option<String>,
+    /// What happened to the morgue file, high score and message log, for the game over screen
+    game_over_notes: Vec<String>,
+    /// Where morgue files and the high-score table are kept
+    record_dir: std::path::PathBuf,
+    /// The high-score table, as loaded for the main menu
+    high_scores: morgue::HighScores,
+    /// The character being put together on the creation screen
+    new_character: Character,
+    save_format: saveload_system::SaveFormat,
+    /// The turn the run was last saved on
+    last_save_turn: i32,
+    replay: replay::ReplayMode,
+    dispatcher: Box<dyn systems::UnifiedDispatcher + 'static>,
+}
+
+impl State {
+    fn run_systems(&mut self) {
+        self.dispatcher.run_now(&mut self.ecs);
+        self.ecs.maintain();
+    }
+}
+
+impl GameState for State {
+    #[allow(clippy::cognitive_complexity)]
+    fn tick(&mut self, ctx: &mut Rltk) {
+        let mut newrunstate;
+        {
+            let runstate = self.ecs.fetch::<RunState>();
+            newrunstate = *runstate;
+        }
+
+        ctx.set_active_console(1);
+        ctx.cls();
+        ctx.set_active_console(0);
+        ctx.cls();
+        particle_system::update_particles(&mut self.ecs, ctx);
+        input::poll_right_click();
+
+        match newrunstate {
+            RunState::MainMenu { .. } => {}
+            RunState::EnterSeed { .. } => {}
+            RunState::CharacterCreation { .. } => {}
+            RunState::LoadGameMenu { .. } => {}
+            RunState::KeyBindings { .. } => {}
+            RunState::HighScores => {}
+            RunState::GameOver { .. } => {}
+            _ => {
+                camera::render_camera(&self.ecs, ctx);
+                gui::draw_ui(&self.ecs, ctx);
+                self.replay.draw_status();
+            }
+        }
+
+        if replay::takes_input(newrunstate) {
+            self.replay.process_input(ctx);
+        }
+
+        match newrunstate {
+            RunState::MapGeneration => {
+                if !SHOW_MAPGEN_VISUALIZER {
+                    newrunstate = self.mapgen_next_state.unwrap();
+                } else {
+                    ctx.cls();
+                    if self.mapgen_index < self.mapgen_history.len()
+                        && self.mapgen_index < self.mapgen_history.len()
+                    {
+                        camera::render_debug_map(&self.mapgen_history[self.mapgen_index], ctx);
+                    }
+
+                    self.mapgen_timer += ctx.frame_time_ms;
+                    if self.mapgen_timer > 250.0 {
+                        self.mapgen_timer = 0.0;
+                        self.mapgen_index += 1;
+                        if self.mapgen_index >= self.mapgen_history.len() {
+                            //self.mapgen_index -= 1;
+                            newrunstate = self.mapgen_next_state.unwrap();
+                        }
+                    }
+                }
+            }
+            RunState::PreRun => {
+                self.run_systems();
+                self.ecs.maintain();
+                // Every new level starts here
+                self.autosave();
+                newrunstate = RunState::AwaitingInput;
+            }
+            RunState::AwaitingInput => {
+                newrunstate = player_input(self, ctx);
+                if newrunstate != RunState::AwaitingInput {
+                    crate::gamelog::record_event("Turn", 1);
+                }
+            }
+            RunState::Travelling => {
+                newrunstate = travel::step(&mut self.ecs);
+                if newrunstate != RunState::AwaitingInput {
+                    crate::gamelog::record_event("Turn", 1);
+                }
+            }
+            RunState::Ticking => {
+                particle_system::cull_particles(&mut self.ecs);
+                let mut should_change_target = false;
+                while newrunstate == RunState::Ticking {
+                    self.run_systems();
+                    self.ecs.maintain();
+                    match *self.ecs.fetch::<RunState>() {
+                        RunState::AwaitingInput => {
+                            newrunstate = RunState::AwaitingInput;
+                            should_change_target = true;
+                        }
+                        RunState::MagicMapReveal { .. } => {
+                            newrunstate = RunState::MagicMapReveal { row: 0 }
+                        }
+                        RunState::TownPortal => newrunstate = RunState::TownPortal,
+                        RunState::TeleportingToOtherLevel { x, y, depth } => {
+                            newrunstate = RunState::TeleportingToOtherLevel { x, y, depth }
+                        }
+                        RunState::ShowRemoveCurse => newrunstate = RunState::ShowRemoveCurse,
+                        RunState::ShowIdentify => newrunstate = RunState::ShowIdentify,
+                        RunState::ShowPerks { .. } => {
+                            newrunstate = RunState::ShowPerks { selection: 0 }
+                        }
+                        _ => newrunstate = RunState::Ticking,
+                    }
+                }
+                if should_change_target {
+                    // Only a turn that went all the way round counts, not opening a menu
+                    self.ecs.fetch_mut::<MasterDungeonMap>().turns += 1;
+                    player::end_turn_targeting(&mut self.ecs);
+                    if let Some(turns) = AUTOSAVE_TURNS {
+                        let now = self.ecs.fetch::<MasterDungeonMap>().turns;
+                        if now - self.last_save_turn >= turns {
+                            self.autosave();
+                        }
+                    }
+                }
+                // A journey carries on by itself, but anything out of the ordinary ends it
+                if newrunstate == RunState::AwaitingInput && travel::is_travelling(&self.ecs) {
+                    newrunstate = RunState::Travelling;
+                } else {
+                    travel::stop(&mut self.ecs);
+                }
+            }
+            RunState::ShowInventory => {
+                let result = gui::show_inventory(self, ctx);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let item_entity = result.1.unwrap();
+                        let is_ranged = self.ecs.read_storage::<Ranged>();
+                        let is_item_ranged = is_ranged.get(item_entity);
+                        if let Some(is_item_ranged) = is_item_ranged {
+                            newrunstate = RunState::ShowTargeting {
+                                range: is_item_ranged.range,
+                                item: item_entity,
+                            };
+                        } else {
+                            let mut intent = self.ecs.write_storage::<WantsToUseItem>();
+                            intent
+                                .insert(
+                                    *self.ecs.fetch::<Entity>(),
+                                    WantsToUseItem {
+                                        item: item_entity,
+                                        target: None,
+                                    },
+                                )
+                                .expect("Unable to insert intent");
+                            newrunstate = RunState::Ticking;
+                        }
+                    }
+                }
+            }
+            RunState::ShowSpellbook => {
+                let result = gui::show_spellbook(self, ctx);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        newrunstate = cast_known_spell(self, result.1.unwrap());
+                    }
+                }
+            }
+            RunState::ShowContextMenu { x, y } => {
+                let tile = Point::new(x, y);
+                let actions = context_actions(&mut self.ecs, tile);
+                match gui::show_context_menu(self, ctx, tile, &actions) {
+                    gui::ContextMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ContextMenuResult::NoResponse => {}
+                    gui::ContextMenuResult::Selected(action) => {
+                        newrunstate = perform_context_action(&mut self.ecs, tile, action);
+                    }
+                }
+            }
+            RunState::ShowLog { offset, filter } => match gui::show_log(ctx, offset, filter) {
+                gui::LogMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                gui::LogMenuResult::NoResponse { offset, filter } => {
+                    newrunstate = RunState::ShowLog { offset, filter }
+                }
+            },
+            RunState::ShowCheatMenu => {
+                let result = gui::show_cheat_mode(self, ctx);
+                match result {
+                    gui::CheatMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::CheatMenuResult::NoResponse => {}
+                    gui::CheatMenuResult::TeleportToExit => {
+                        self.goto_level(1);
+                        self.mapgen_next_state = Some(RunState::PreRun);
+                        newrunstate = RunState::MapGeneration;
+                    }
+                    gui::CheatMenuResult::Heal => {
+                        let player = self.ecs.fetch::<Entity>();
+                        let mut pools = self.ecs.write_storage::<Pools>();
+                        let player_pools = pools.get_mut(*player).unwrap();
+                        player_pools.hit_points.current = player_pools.hit_points.max;
+                        newrunstate = RunState::AwaitingInput;
+                    }
+                    gui::CheatMenuResult::Reveal => {
+                        let mut map = self.ecs.fetch_mut::<Map>();
+                        for v in map.revealed_tiles.iter_mut() {
+                            *v = true;
+                        }
+                        newrunstate = RunState::AwaitingInput;
+                    }
+                    gui::CheatMenuResult::GodMode => {
+                        let player = self.ecs.fetch::<Entity>();
+                        let mut pools = self.ecs.write_storage::<Pools>();
+                        let player_pools = pools.get_mut(*player).unwrap();
+                        player_pools.god_mode = true;
+                        newrunstate = RunState::AwaitingInput;
+                    }
+                    gui::CheatMenuResult::ReloadRaws => {
+                        match raws::reload_raws() {
+                            Ok(errors) => {
+                                for error in errors.iter() {
+                                    crate::diagnostic(format!("WARNING - {}", error));
+                                }
+                                gamelog::Logger::new()
+                                    .append(format!(
+                                        "Raws reloaded with {} problems.",
+                                        errors.len()
+                                    ))
+                                    .log();
+                            }
+                            Err(errors) => {
+                                for error in errors.iter() {
+                                    crate::diagnostic(format!("WARNING - {}", error));
+                                }
+                                gamelog::Logger::new()
+                                    .color(rltk::RED)
+                                    .append("Unable to reload raws, keeping the old ones.")
+                                    .log();
+                            }
+                        }
+                        newrunstate = RunState::AwaitingInput;
+                    }
+                }
+            }
+            RunState::ShowDropItem => {
+                let result = gui::drop_item_menu(self, ctx);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let item_entity = result.1.unwrap();
+                        let mut intent = self.ecs.write_storage::<WantsToDropItem>();
+                        intent
+                            .insert(
+                                *self.ecs.fetch::<Entity>(),
+                                WantsToDropItem { item: item_entity },
+                            )
+                            .expect("Unable to insert intent");
+                        newrunstate = RunState::Ticking;
+                    }
+                }
+            }
+            RunState::ShowRemoveItem => {
+                let result = gui::remove_item_menu(self, ctx);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let item_entity = result.1.unwrap();
+                        let mut intent = self.ecs.write_storage::<WantsToRemoveItem>();
+                        intent
+                            .insert(
+                                *self.ecs.fetch::<Entity>(),
+                                WantsToRemoveItem { item: item_entity },
+                            )
+                            .expect("Unable to insert intent");
+                        newrunstate = RunState::Ticking;
+                    }
+                }
+            }
+            RunState::ShowRemoveCurse => {
+                let result = gui::remove_curse_menu(self, ctx);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let item_entity = result.1.unwrap();
+                        self.ecs.write_storage::<CursedItem>().remove(item_entity);
+                        newrunstate = RunState::Ticking;
+                    }
+                }
+            }
+            RunState::ShowIdentify => {
+                let result = gui::identify_menu(self, ctx);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let item_entity = result.1.unwrap();
+                        if let Some(name) = self.ecs.read_storage::<Name>().get(item_entity) {
+                            let mut dm = self.ecs.fetch_mut::<MasterDungeonMap>();
+                            dm.identified_items.insert(name.name.clone());
+                        }
+                        newrunstate = RunState::Ticking;
+                    }
+                }
+            }
+            RunState::ShowPerks { selection } => {
+                let player = *self.ecs.fetch::<Entity>();
+                let choices = progression::perk_choices(&self.ecs, player);
+                if choices.is_empty() {
+                    // Nothing left to take; the level-up goes unspent
+                    if let Some(perks) = self.ecs.write_storage::<Perks>().get_mut(player) {
+                        perks.pending = 0;
+                    }
+                    newrunstate = RunState::Ticking;
+                } else {
+                    match gui::perk_menu(self, ctx, &choices, selection) {
+                        gui::PerkMenuResult::NoResponse { selection } => {
+                            newrunstate = RunState::ShowPerks { selection }
+                        }
+                        gui::PerkMenuResult::Selected { perk } => {
+                            progression::take_perk(&mut self.ecs, player, &perk);
+                            let pending = self
+                                .ecs
+                                .read_storage::<Perks>()
+                                .get(player)
+                                .map_or(0, |perks| perks.pending);
+                            newrunstate = if pending > 0 {
+                                RunState::ShowPerks { selection: 0 }
+                            } else {
+                                RunState::Ticking
+                            };
+                        }
+                    }
+                }
+            }
+            RunState::ShowTargeting { range, item } => {
+                let result = gui::ranged_target(self, ctx, range, item);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        if self.ecs.read_storage::<SpellTemplate>().get(item).is_some() {
+                            let mut intent = self.ecs.write_storage::<WantsToCastSpell>();
+                            intent
+                                .insert(
+                                    *self.ecs.fetch::<Entity>(),
+                                    WantsToCastSpell {
+                                        spell: item,
+                                        target: result.1,
+                                    },
+                                )
+                                .expect("Unable to insert intent");
+                            newrunstate = RunState::Ticking;
+                        } else {
+                            let mut intent = self.ecs.write_storage::<WantsToUseItem>();
+                            intent
+                                .insert(
+                                    *self.ecs.fetch::<Entity>(),
+                                    WantsToUseItem {
+                                        item,
+                                        target: result.1,
+                                    },
+                                )
+                                .expect("Unable to insert intent");
+                            newrunstate = RunState::Ticking;
+                        }
+                    }
+                }
+            }
+            RunState::ShowVendor { vendor, mode } => {
+                use crate::raws::*;
+                let result = gui::show_vendor_menu(self, ctx, vendor, mode);
+                match result.0 {
+                    gui::VendorResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::VendorResult::NoResponse => {}
+                    gui::VendorResult::Sell => {
+                        let price = self
+                            .ecs
+                            .read_storage::<Item>()
+                            .get(result.1.unwrap())
+                            .unwrap()
+                            .base_value
+                            * 0.8;
+                        self.ecs
+                            .write_storage::<Pools>()
+                            .get_mut(*self.ecs.fetch::<Entity>())
+                            .unwrap()
+                            .gold += price;
+                        self.ecs
+                            .delete_entity(result.1.unwrap())
+                            .expect("Unable to delete");
+                    }
+                    gui::VendorResult::Buy => {
+                        let tag = result.2.unwrap();
+                        let price = result.3.unwrap();
+                        let mut pools = self.ecs.write_storage::<Pools>();
+                        let player_entity = self.ecs.fetch::<Entity>();
+                        let mut identified = self.ecs.write_storage::<IdentifiedItem>();
+                        identified
+                            .insert(*player_entity, IdentifiedItem { name: tag.clone() })
+                            .expect("Unable to insert");
+                        std::mem::drop(identified);
+                        let player_pools = pools.get_mut(*player_entity).unwrap();
+                        std::mem::drop(player_entity);
+                        if player_pools.gold >= price {
+                            player_pools.gold -= price;
+                            std::mem::drop(pools);
+                            if let Some(stock) = self
+                                .ecs
+                                .write_storage::<Vendor>()
+                                .get_mut(vendor)
+                                .and_then(|v| v.stock.get_mut(&tag))
+                            {
+                                *stock -= 1;
+                            }
+                            let player_entity = *self.ecs.fetch::<Entity>();
+                            crate::raws::spawn_named_item(
+                                &RAWS.lock().unwrap(),
+                                &mut self.ecs,
+                                &tag,
+                                SpawnType::Carried { by: player_entity },
+                            );
+                        }
+                    }
+                    gui::VendorResult::BuyMode => {
+                        newrunstate = RunState::ShowVendor {
+                            vendor,
+                            mode: VendorMode::Buy,
+                        }
+                    }
+                    gui::VendorResult::SellMode => {
+                        newrunstate = RunState::ShowVendor {
+                            vendor,
+                            mode: VendorMode::Sell,
+                        }
+                    }
+                }
+            }
+            RunState::MainMenu { .. } => {
+                let result = gui::main_menu(self, ctx);
+                match result {
+                    gui::MainMenuResult::NoSelection { selected } => {
+                        newrunstate = RunState::MainMenu {
+                            menu_selection: selected,
+                        }
+                    }
+                    gui::MainMenuResult::Selected { selected } => match selected {
+                        gui::MainMenuSelection::NewGame => {
+                            newrunstate = self.start_character_creation();
+                        }
+                        gui::MainMenuSelection::NewSeededGame => {
+                            newrunstate = RunState::EnterSeed { seed: 0 }
+                        }
+                        gui::MainMenuSelection::LoadGame => {
+                            self.save_slots = saveload_system::list_saves();
+                            newrunstate = RunState::LoadGameMenu { selection: 0 };
+                        }
+                        gui::MainMenuSelection::KeyBindings => {
+                            newrunstate = RunState::KeyBindings {
+                                selection: 0,
+                                capturing: false,
+                            };
+                        }
+                        gui::MainMenuSelection::HighScores => {
+                            self.high_scores = morgue::HighScores::load(
+                                &self.record_dir.join(morgue::HIGH_SCORES_FILE),
+                            );
+                            newrunstate = RunState::HighScores;
+                        }
+                        gui::MainMenuSelection::Quit => {
+                            ::std::process::exit(0);
+                        }
+                    },
+                }
+            }
+            RunState::KeyBindings {
+                selection,
+                capturing,
+            } => match gui::key_bindings_menu(ctx, selection, capturing) {
+                gui::KeyBindingsResult::NoResponse {
+                    selection,
+                    capturing,
+                } => {
+                    newrunstate = RunState::KeyBindings {
+                        selection,
+                        capturing,
+                    }
+                }
+                gui::KeyBindingsResult::Done => {
+                    let path = std::path::Path::new(input::BINDINGS_FILE);
+                    if let Err(e) = input::save_bindings(path) {
+                        crate::diagnostic(format!(
+                            "WARNING - unable to save key bindings to {}: {}",
+                            path.display(),
+                            e
+                        ));
+                    }
+                    newrunstate = RunState::MainMenu {
+                        menu_selection: gui::MainMenuSelection::KeyBindings,
+                    };
+                }
+            },
+            RunState::HighScores => match gui::high_scores_menu(ctx, &self.high_scores) {
+                gui::HighScoresResult::NoResponse => {}
+                gui::HighScoresResult::Done => {
+                    newrunstate = RunState::MainMenu {
+                        menu_selection: gui::MainMenuSelection::HighScores,
+                    };
+                }
+            },
+            RunState::EnterSeed { seed } => {
+                let result = gui::seed_menu(ctx, seed);
+                match result {
+                    gui::SeedMenuResult::NoResponse { seed } => {
+                        newrunstate = RunState::EnterSeed { seed }
+                    }
+                    gui::SeedMenuResult::Cancel => {
+                        newrunstate = RunState::MainMenu {
+                            menu_selection: gui::MainMenuSelection::NewSeededGame,
+                        }
+                    }
+                    gui::SeedMenuResult::Selected { seed } => {
+                        self.new_run(seed);
+                        self.mapgen_next_state = Some(self.start_character_creation());
+                        newrunstate = RunState::MapGeneration;
+                    }
+                }
+            }
+            RunState::CharacterCreation { step, selection } => {
+                let result = gui::character_menu(self, ctx, step, selection);
+                match result {
+                    gui::CharacterMenuResult::NoResponse { step, selection } => {
+                        newrunstate = RunState::CharacterCreation { step, selection }
+                    }
+                    gui::CharacterMenuResult::Cancel => {
+                        newrunstate = RunState::MainMenu {
+                            menu_selection: gui::MainMenuSelection::NewGame,
+                        }
+                    }
+                    gui::CharacterMenuResult::Done => {
+                        // The world on show is a fresh run from its seed, so it can be recorded
+                        let character = self.new_character.clone();
+                        let seed = self.ecs.fetch::<map::MasterDungeonMap>().seed;
+                        self.create_character(&character);
+                        self.replay = replay::ReplayMode::record(
+                            &replay::replay_path(&saveload_system::run_slot(&self.ecs)),
+                            seed,
+                            &character,
+                        );
+                        newrunstate = RunState::PreRun;
+                    }
+                }
+            }
+            RunState::LoadGameMenu { selection } => {
+                let result = gui::load_game_menu(self, ctx, selection);
+                match result {
+                    gui::LoadGameMenuResult::NoResponse { selection } => {
+                        newrunstate = RunState::LoadGameMenu { selection }
+                    }
+                    gui::LoadGameMenuResult::Cancel => {
+                        newrunstate = RunState::MainMenu {
+                            menu_selection: gui::MainMenuSelection::LoadGame,
+                        }
+                    }
+                    gui::LoadGameMenuResult::Selected { selection } => {
+                        let path = self.save_slots[selection].path.clone();
+                        match saveload_system::load_game(&mut self.ecs, &path) {
+                            Ok(()) => {
+                                self.save_error = None;
+                                newrunstate = RunState::AwaitingInput;
+                                // Write it straight back into the run's slot, in the current
+                                // format, so the run stays on disk until it ends. The old file
+                                // only goes once the new one is safely written, and not at all
+                                // if it was the same file, which keeps its backup.
+                                match self.save_run() {
+                                    Ok(()) => {
+                                        let saved = saveload_system::slot_path(
+                                            &saveload_system::run_slot(&self.ecs),
+                                            self.save_format,
+                                        );
+                                        if saved != path {
+                                            saveload_system::delete_save(&path);
+                                        }
+                                    }
+                                    Err(error) => crate::diagnostic(format!(
+                                        "WARNING - unable to save the loaded run: {}",
+                                        error
+                                    )),
+                                }
+                            }
+                            Err(error) => {
+                                crate::diagnostic(format!(
+                                    "Unable to load {}: {}",
+                                    path.display(),
+                                    error
+                                ));
+                                self.save_error = Some(error);
+                                // The world may be partly replaced, so start over with a fresh run
+                                self.new_run(rng::new_seed());
+                                self.mapgen_next_state = Some(RunState::MainMenu {
+                                    menu_selection: gui::MainMenuSelection::LoadGame,
+                                });
+                                newrunstate = RunState::MapGeneration;
+                            }
+                        }
+                    }
+                }
+            }
+            RunState::GameOver => {
+                if self.game_over_notes.is_empty() {
+                    self.game_over_notes = self.record_run_end();
+                }
+                let outcome = (*self.ecs.fetch::<morgue::RunEnd>()).clone();
+                let result = gui::game_over(ctx, &outcome, &self.game_over_notes);
+                match result {
+                    gui::GameOverResult::NoSelection => {}
+                    gui::GameOverResult::ExportLog => {
+                        let path = std::path::Path::new(LOG_DIR)
+                            .join(format!("{}.txt", saveload_system::run_slot(&self.ecs)));
+                        self.game_over_notes.push(match gamelog::export_log(&path) {
+                            Ok(()) => format!("Message log saved to {}", path.display()),
+                            Err(e) => format!("Unable to save the message log: {}", e),
+                        });
+                    }
+                    gui::GameOverResult::QuitToMenu => {
+                        self.game_over_notes.clear();
+                        self.game_over_cleanup();
+                        self.replay = replay::ReplayMode::Off;
+                        newrunstate = RunState::MapGeneration;
+                        self.mapgen_next_state = Some(RunState::MainMenu {
+                            menu_selection: gui::MainMenuSelection::NewGame,
+                        });
+                    }
+                }
+            }
+            RunState::SaveGame => {
+                let result = gui::save_game_menu(ctx);
+                match result {
+                    gui::SaveGameMenuResult::NoResponse => {}
+                    gui::SaveGameMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::SaveGameMenuResult::Selected { format } => {
+                        self.save_format = format;
+                        match self.save_run() {
+                            Ok(()) => {
+                                // The run can only carry on from the save, and replays start
+                                // from the seed
+                                self.replay = replay::ReplayMode::Off;
+                                newrunstate = RunState::MainMenu {
+                                    menu_selection: gui::MainMenuSelection::LoadGame,
+                                };
+                            }
+                            Err(error) => {
+                                // Quitting now would lose the run, so carry on playing it
+                                gamelog::Logger::new()
+                                    .append(format!("The game could not be saved: {}", error))
+                                    .log();
+                                newrunstate = RunState::AwaitingInput;
+                            }
+                        }
+                    }
+                }
+            }
+            RunState::NextLevel => {
+                self.goto_level(1);
+                self.mapgen_next_state = Some(RunState::PreRun);
+                newrunstate = RunState::MapGeneration;
+            }
+            RunState::PreviousLevel => {
+                self.goto_level(-1);
+                self.mapgen_next_state = Some(RunState::PreRun);
+                newrunstate = RunState::MapGeneration;
+            }
+            RunState::TownPortal => {
+                // Spawn the portal
+                spawner::spawn_town_portal(&mut self.ecs);
+
+                // Transition
+                let map_depth = self.ecs.fetch::<Map>().depth;
+                let destination_offset = 0 - (map_depth - 1);
+                self.goto_level(destination_offset);
+                self.mapgen_next_state = Some(RunState::PreRun);
+                newrunstate = RunState::MapGeneration;
+            }
+            RunState::TeleportingToOtherLevel { x, y, depth } => {
+                self.goto_level(depth - 1);
+                let player_entity = self.ecs.fetch::<Entity>();
+                if let Some(pos) = self.ecs.write_storage::<Position>().get_mut(*player_entity) {
+                    pos.x = x;
+                    pos.y = y;
+                }
+                let mut ppos = self.ecs.fetch_mut::<rltk::Point>();
+                ppos.x = x;
+                ppos.y = y;
+                self.mapgen_next_state = Some(RunState::PreRun);
+                newrunstate = RunState::MapGeneration;
+            }
+            RunState::MagicMapReveal { row } => {
+                let mut map = self.ecs.fetch_mut::<Map>();
+                for x in 0..map.width {
+                    let idx = map.xy_idx(x, row);
+                    map.revealed_tiles[idx] = true;
+                }
+                if row == map.height - 1 {
+                    newrunstate = RunState::Ticking;
+                } else {
+                    newrunstate = RunState::MagicMapReveal { row: row + 1 };
+                }
+            }
+        }
+
+        {
+            let mut runwriter = self.ecs.write_resource::<RunState>();
+            *runwriter = newrunstate;
+        }
+        damage_system::delete_the_dead(&mut self.ecs);
+        if *self.ecs.fetch::<morgue::RunEnd>() == morgue::RunEnd::Won {
+            *self.ecs.write_resource::<RunState>() = RunState::GameOver;
+        }
+        let _ = rltk::render_draw_buffer(ctx);
+        input::set_right_click(false);
+        if SHOW_FPS {
+            ctx.print(1, 59, format!("FPS: {}", ctx.fps));
+        }
+    }
+}
+
+impl State {
+    fn goto_level(&mut self, offset: i32) {
+        map::catch_up_frozen_levels(&mut self.ecs);
+        freeze_level_entities(&mut self.ecs);
+
+        // Build a new map and place the player
+        let current_depth = self.ecs.fetch::<Map>().depth;
+        self.generate_world_map(current_depth + offset, offset);
+
+        // Notify the player
+        gamelog::Logger::new().append("You change level.").log();
+    }
+
+    /// Saves the run into its slot, in the last format the player picked. Replays leave the
+    /// slot alone, since it may hold the run being watched.
+    fn save_run(&mut self) -> Result<(), String> {
+        self.last_save_turn = self.ecs.fetch::<MasterDungeonMap>().turns;
+        if self.replay.is_playing() {
+            return Ok(());
+        }
+        let slot = saveload_system::run_slot(&self.ecs);
+        saveload_system::save_game(&mut self.ecs, &slot, self.save_format)
+    }
+
+    /// Saves the run as it goes. A failed autosave only costs the copy on disk, so it's reported
+    /// and play carries on.
+    fn autosave(&mut self) {
+        if let Err(error) = self.save_run() {
+            crate::diagnostic(format!("WARNING - unable to autosave: {}", error));
+        }
+    }
+
+    /// Writes the morgue file and puts the run on the high-score table. Watching a replay
+    /// doesn't count as playing the run again, so that leaves both alone.
+    fn record_run_end(&mut self) -> Vec<String> {
+        if self.replay.is_playing() {
+            return vec!["Replays don't go on the high-score table.".to_string()];
+        }
+        morgue::record_run_end(&self.ecs, &self.record_dir)
+    }
+
+    fn game_over_cleanup(&mut self) {
+        // The run is over, so its autosave can't be loaded any more
+        if !self.replay.is_playing() {
+            saveload_system::delete_slot(&saveload_system::run_slot(&self.ecs));
+        }
+        self.new_run(rng::new_seed());
+    }
+
+    fn new_run(&mut self, seed: u64) {
+        // Start from an empty world rather than deleting everything, so entity ids (and with them
+        // the order systems visit entities in) only depend on the seed
+        self.ecs = new_world();
+        self.ecs.insert(map::MasterDungeonMap::new(seed));
+        gamelog::clear_events();
+
+        // Spawn a new player
+        let player_entity = spawner::player(&mut self.ecs, 0, 0);
+        self.ecs.insert(player_entity);
+
+        // Build a new map and place the player
+        self.generate_world_map(1, 0);
+    }
+
+    /// Rebuilds the player from the character's choices.
+    fn create_character(&mut self, character: &Character) {
+        let player = *self.ecs.fetch::<Entity>();
+        character::apply(&mut self.ecs, player, character);
+    }
+
+    /// Clears the creation screen for a new character, and returns the state that opens it.
+    fn start_character_creation(&mut self) -> RunState {
+        self.new_character = Character {
+            name: String::new(),
+            ..Character::default()
+        };
+        let selection = raws::background_names(&raws::RAWS.lock().unwrap())
+            .iter()
+            .position(|name| *name == self.new_character.background)
+            .unwrap_or(0);
+        RunState::CharacterCreation {
+            step: gui::CreationStep::Background,
+            selection,
+        }
+    }
+
+    fn generate_world_map(&mut self, new_depth: i32, offset: i32) {
+        self.mapgen_index = 0;
+        self.mapgen_timer = 0.0;
+        self.mapgen_history.clear();
+        let map_building_info = map::level_transition(&mut self.ecs, new_depth, offset);
+        if let Some(history) = map_building_info {
+            self.mapgen_history = history;
+        } else {
+            map::thaw_level_entities(&mut self.ecs);
+        }
+        gamelog::clear_log();
+        gamelog::Logger::new()
+            .append("Welcome to")
+            .color(rltk::CYAN)
+            .append("Rusty Roguelike")
+            .log();
+    }
+}
+
+impl State {
+    /// A fresh world with every component registered and a new run generated. Doesn't need a
+    /// window, so tests and tools can drive the game too.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> State {
+        let mut gs = State {
+            ecs: World::new(),
+            mapgen_next_state: Some(RunState::MainMenu {
+                menu_selection: gui::MainMenuSelection::NewGame,
+            }),
+            mapgen_index: 0,
+            mapgen_history: Vec::new(),
+            mapgen_timer: 0.0,
+            save_slots: Vec::new(),
+            save_error: None,
+            game_over_notes: Vec::new(),
+            record_dir: std::path::PathBuf::from("."),
+            high_scores: morgue::HighScores::default(),
+            new_character: Character::default(),
+            save_format: saveload_system::SaveFormat::CompressedBinary,
+            last_save_turn: 0,
+            replay: replay::ReplayMode::Off,
+            dispatcher: systems::build(),
+        };
+        raws::load_raws();
+        gs.new_run(rng::new_seed());
+        gs
+    }
+
+    /// Starts a new run from the seed, recording the player's input into a replay file.
+    pub fn record_run(&mut self, seed: u64, path: &std::path::Path) {
+        self.record_run_as(seed, path, &Character::default());
+    }
+
+    /// Starts a new run from the seed with the player built from the character, recording the
+    /// player's input into a replay file.
+    pub fn record_run_as(&mut self, seed: u64, path: &std::path::Path, character: &Character) {
+        self.new_run(seed);
+        self.create_character(character);
+        self.replay = replay::ReplayMode::record(path, seed, character);
+        self.mapgen_next_state = Some(RunState::PreRun);
+        *self.ecs.write_resource::<RunState>() = RunState::MapGeneration;
+    }
+
+    /// Starts the replay's run from the beginning, with the player's input coming from the replay.
+    pub fn play_replay(&mut self, replay: replay::Replay) {
+        self.new_run(replay.seed);
+        if let Some(character) = &replay.character {
+            self.create_character(character);
+        }
+        self.replay = replay::ReplayMode::play(replay);
+        self.mapgen_next_state = Some(RunState::PreRun);
+        *self.ecs.write_resource::<RunState>() = RunState::MapGeneration;
+    }
+
+    /// Keeps morgue files and the high-score table under this directory instead of the current
+    /// one.
+    pub fn keep_records_in(&mut self, dir: &std::path::Path) {
+        self.record_dir = dir.to_path_buf();
+    }
+
+    /// True until the replay being watched runs out or the player takes over.
+    pub fn is_replaying(&self) -> bool {
+        self.replay.is_playing()
+    }
+}
+
+/// A world with every component registered and the run-independent resources in place.
+fn new_world() -> World {
+    let mut ecs = World::new();
+    ecs.register::<Position>();
+    ecs.register::<Renderable>();
+    ecs.register::<Player>();
+    ecs.register::<Viewshed>();
+    ecs.register::<Name>();
+    ecs.register::<BlocksTile>();
+    ecs.register::<WantsToMelee>();
+    ecs.register::<Item>();
+    ecs.register::<ProvidesHealing>();
+    ecs.register::<InflictsDamage>();
+    ecs.register::<AreaOfEffect>();
+    ecs.register::<Consumable>();
+    ecs.register::<Ranged>();
+    ecs.register::<InBackpack>();
+    ecs.register::<WantsToPickupItem>();
+    ecs.register::<WantsToUseItem>();
+    ecs.register::<WantsToDropItem>();
+    ecs.register::<Confusion>();
+    ecs.register::<SimpleMarker<SerializeMe>>();
+    ecs.register::<SerializationHelper>();
+    ecs.register::<DMSerializationHelper>();
+    ecs.register::<Equippable>();
+    ecs.register::<Equipped>();
+    ecs.register::<Weapon>();
+    ecs.register::<Wearable>();
+    ecs.register::<WantsToRemoveItem>();
+    ecs.register::<ParticleLifetime>();
+    ecs.register::<HungerClock>();
+    ecs.register::<ProvidesFood>();
+    ecs.register::<MagicMapper>();
+    ecs.register::<Hidden>();
+    ecs.register::<EntryTrigger>();
+    ecs.register::<EntityMoved>();
+    ecs.register::<SingleActivation>();
+    ecs.register::<BlocksVisibility>();
+    ecs.register::<Door>();
+    ecs.register::<Quips>();
+    ecs.register::<Attributes>();
+    ecs.register::<Skills>();
+    ecs.register::<Pools>();
+    ecs.register::<NaturalAttackDefense>();
+    ecs.register::<LootTable>();
+    ecs.register::<OtherLevelPosition>();
+    ecs.register::<LightSource>();
+    ecs.register::<Initiative>();
+    ecs.register::<MyTurn>();
+    ecs.register::<Faction>();
+    ecs.register::<AiBehavior>();
+    ecs.register::<GroupLeader>();
+    ecs.register::<Routed>();
+    ecs.register::<Alertness>();
+    ecs.register::<MoveMode>();
+    ecs.register::<Chasing>();
+    ecs.register::<EquipmentChanged>();
+    ecs.register::<Vendor>();
+    ecs.register::<TownPortal>();
+    ecs.register::<TeleportTo>();
+    ecs.register::<ApplyMove>();
+    ecs.register::<ApplyTeleport>();
+    ecs.register::<MagicItem>();
+    ecs.register::<ObfuscatedName>();
+    ecs.register::<IdentifiedItem>();
+    ecs.register::<SpawnParticleBurst>();
+    ecs.register::<SpawnParticleLine>();
+    ecs.register::<CursedItem>();
+    ecs.register::<ProvidesRemoveCurse>();
+    ecs.register::<ProvidesIdentification>();
+    ecs.register::<AttributeBonus>();
+    ecs.register::<Duration>();
+    ecs.register::<StatusEffect>();
+    ecs.register::<KnownSpells>();
+    ecs.register::<SpellTemplate>();
+    ecs.register::<SpellShape>();
+    ecs.register::<WantsToCastSpell>();
+    ecs.register::<TeachesSpell>();
+    ecs.register::<ProvidesMana>();
+    ecs.register::<Slow>();
+    ecs.register::<DamageOverTime>();
+    ecs.register::<HealOverTime>();
+    ecs.register::<Blinded>();
+    ecs.register::<StatusStacks>();
+    ecs.register::<AppliesStatus>();
+    ecs.register::<StatusResistances>();
+    ecs.register::<DamageResistances>();
+    ecs.register::<Character>();
+    ecs.register::<SkillProgress>();
+    ecs.register::<Perks>();
+    ecs.register::<SpecialAbilities>();
+    ecs.register::<TileSize>();
+    ecs.register::<OnDeath>();
+    ecs.register::<AlwaysTargetsSelf>();
+    ecs.register::<Target>();
+    ecs.register::<WantsToShoot>();
+    ecs.register::<WinsGame>();
+    ecs.insert(SimpleMarkerAllocator::<SerializeMe>::new());
+
+    ecs.insert(Map::new(1, 64, 64, "New Map"));
+    ecs.insert(Point::new(0, 0));
+    ecs.insert(RunState::MapGeneration {});
+    ecs.insert(morgue::RunEnd::Ongoing);
+    ecs.insert(travel::Travel::default());
+    ecs.insert(particle_system::ParticleBuilder::new());
+    ecs.insert(rex_assets::RexAssets::new());
+    ecs
+}
+
+fn build_context() -> rltk::BResult<Rltk> {
+    use rltk::RltkBuilder;
+    let mut context = RltkBuilder::simple(80, 60)
+        .unwrap()
+        .with_title("Roguelike Tutorial")
+        .with_font("vga8x16.png", 8, 16)
+        .with_sparse_console(80, 30, "vga8x16.png")
+        .build()?;
+    context.with_post_scanlines(true);
+    Ok(context)
+}
+
+pub fn run() -> rltk::BError {
+    input::load_bindings(std::path::Path::new(input::BINDINGS_FILE));
+    let context = build_context()?;
+    let gs = State::new();
+    rltk::main_loop(context, gs)
+}
+
+/// Opens the game straight into playback of a recorded run.
+pub fn watch_replay(path: &std::path::Path) -> rltk::BError {
+    let replay = replay::Replay::load(path)
+        .map_err(|e| format!("Unable to load {}: {}", path.display(), e))?;
+    input::load_bindings(std::path::Path::new(input::BINDINGS_FILE));
+    let context = build_context()?;
+    let mut gs = State::new();
+    gs.play_replay(replay);
+    rltk::main_loop(context, gs)
+}
@@ -2,11 +2,13 @@ use rltk::{Algorithm2D, BaseMap, Point};
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 mod tiletype;
-pub use tiletype::{tile_cost, tile_opaque, tile_walkable, TileType};
+pub use tiletype::{tile_ascii, tile_cost, tile_name, tile_opaque, tile_walkable, TileType};
 mod themes;
 pub use themes::*;
 mod dungeon;
 pub use dungeon::{freeze_level_entities, level_transition, thaw_level_entities, MasterDungeonMap};
+mod catch_up;
+pub use catch_up::catch_up_frozen_levels;
 pub mod camera;
 
 #[derive(Default, Serialize, Deserialize, Clone)]
@@ -22,6 +24,7 @@ pub struct Map {
     pub name: String,
     pub outdoors: bool,
     pub light: Vec<rltk::RGB>,
+    pub theme: MapTheme,
 }
 
 impl Map {
@@ -86,6 +89,7 @@ impl Map {
             name: name.to_string(),
             outdoors: true,
             light: vec![rltk::RGB::from_f32(0.0, 0.0, 0.0); map_tile_count],
+            theme: MapTheme::Default,
         }
     }
 }
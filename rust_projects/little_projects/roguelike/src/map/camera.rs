@@ -19,6 +19,24 @@ pub fn get_screen_bounds(ecs: &World, _ctx: &mut Rltk) -> (i32, i32, i32, i32) {
     (min_x, max_x, min_y, max_y)
 }
 
+/// The map tile drawn in this console cell, if the cell is part of the map view.
+pub fn screen_to_map(ecs: &World, ctx: &mut Rltk, screen: (i32, i32)) -> Option<Point> {
+    let (min_x, max_x, min_y, max_y) = get_screen_bounds(ecs, ctx);
+    if screen.0 < 1 || screen.0 > max_x - min_x || screen.1 < 1 || screen.1 > max_y - min_y {
+        return None;
+    }
+    Some(Point::new(screen.0 + min_x - 1, screen.1 + min_y - 1))
+}
+
+/// The console cell a map tile is drawn in, if it's in view.
+pub fn map_to_screen(ecs: &World, ctx: &mut Rltk, tile: Point) -> Option<Point> {
+    let (min_x, max_x, min_y, max_y) = get_screen_bounds(ecs, ctx);
+    if tile.x < min_x || tile.x >= max_x || tile.y < min_y || tile.y >= max_y {
+        return None;
+    }
+    Some(Point::new(tile.x - min_x + 1, tile.y - min_y + 1))
+}
+
 const SHOW_BOUNDARIES: bool = false;
 
 pub fn render_camera(ecs: &World, ctx: &mut Rltk) {
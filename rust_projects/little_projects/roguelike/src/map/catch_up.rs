@@ -0,0 +1,258 @@
+//! Nothing runs on the levels the player isn't on. When they change level, everyone left behind
+//! on the others catches up on the turns that went by, with a few cheap updates in place of the
+//! full set of systems.
+
+use super::{tile_walkable, Map, MasterDungeonMap, TileType};
+use crate::components::{
+    BlocksTile, Chasing, DamageOverTime, DamageResistances, Duration, EquipmentChanged, Equipped,
+    Faction, HealOverTime, HungerClock, HungerState, MoveMode, Movement, Name, OtherLevelPosition,
+    Pools, Position, StatusEffect, Vendor,
+};
+use crate::gamesystem::mitigate_damage;
+use crate::raws::{faction_reaction, get_vendor_items, Reaction, RAWS, VENDOR_STOCK};
+use specs::prelude::*;
+use std::collections::HashSet;
+
+/// Turns a mob takes to get back one hit point, and one point of mana.
+const REGEN_TURNS: i32 = 10;
+/// Turns a vendor takes to get one more of each of their wares in.
+const RESTOCK_TURNS: i32 = 50;
+/// Every this many turns, a wandering monster may take the stairs.
+const MIGRATION_TURNS: i32 = 100;
+/// How long each hunger state lasts, as in the hunger system.
+const HUNGER_TURNS: i32 = 200;
+
+/// Brings every frozen level up to the current turn. Call it before freezing the level the player
+/// is leaving, since that one was live until now.
+pub fn catch_up_frozen_levels(ecs: &mut World) {
+    let (from, to) = {
+        let mut dm = ecs.write_resource::<MasterDungeonMap>();
+        let from = dm.caught_up_to;
+        dm.caught_up_to = dm.turns;
+        (from, dm.turns)
+    };
+    if to <= from {
+        return;
+    }
+
+    run_status_effects(ecs, to - from);
+    regenerate(ecs, periods(from, to, REGEN_TURNS));
+    grow_hungry(ecs, to - from);
+    restock_vendors(ecs, periods(from, to, RESTOCK_TURNS));
+    migrate(ecs, periods(from, to, MIGRATION_TURNS));
+}
+
+/// How many times something that happens every `period` turns happened between the two turns.
+/// Counting from the start of the run, the turns short of a whole period count next time.
+fn periods(from: i32, to: i32, period: i32) -> i32 {
+    to / period - from / period
+}
+
+/// Status effects on the frozen levels tick down as they would have, damaging and healing
+/// as they go, though nobody dies of them there.
+fn run_status_effects(ecs: &mut World, elapsed: i32) {
+    let mut expired: Vec<(Entity, Entity)> = Vec::new();
+    {
+        let entities = ecs.entities();
+        let frozen = ecs.read_storage::<OtherLevelPosition>();
+        let statuses = ecs.read_storage::<StatusEffect>();
+        let mut durations = ecs.write_storage::<Duration>();
+        let dots = ecs.read_storage::<DamageOverTime>();
+        let hots = ecs.read_storage::<HealOverTime>();
+        let resistances = ecs.read_storage::<DamageResistances>();
+        let equipped = ecs.read_storage::<Equipped>();
+        let mut pools = ecs.write_storage::<Pools>();
+        for (effect_entity, duration, status) in (&entities, &mut durations, &statuses).join() {
+            if !entities.is_alive(status.target) || frozen.get(status.target).is_none() {
+                continue;
+            }
+            let turns = i32::min(elapsed, duration.turns);
+            duration.turns -= elapsed;
+            if let Some(pool) = pools.get_mut(status.target) {
+                if let Some(dot) = dots.get(effect_entity) {
+                    let (damage, _) = mitigate_damage(
+                        dot.damage,
+                        dot.damage_type,
+                        status.target,
+                        &resistances,
+                        &equipped,
+                    );
+                    pool.hit_points.current = i32::max(1, pool.hit_points.current - damage * turns);
+                }
+                if let Some(hot) = hots.get(effect_entity) {
+                    pool.hit_points.current = i32::min(
+                        pool.hit_points.max,
+                        pool.hit_points.current + hot.amount * turns,
+                    );
+                }
+            }
+            if duration.turns < 1 {
+                expired.push((effect_entity, status.target));
+            }
+        }
+    }
+
+    for (effect_entity, target) in expired {
+        ecs.write_storage::<EquipmentChanged>()
+            .insert(target, EquipmentChanged {})
+            .expect("Unable to insert");
+        if let Some(name) = ecs.read_storage::<Name>().get(effect_entity) {
+            crate::effects::status_expired(&name.name, target);
+        }
+        ecs.delete_entity(effect_entity).expect("Unable to delete");
+    }
+}
+
+fn regenerate(ecs: &mut World, regen: i32) {
+    let frozen = ecs.read_storage::<OtherLevelPosition>();
+    let mut pools = ecs.write_storage::<Pools>();
+    for (pool, _frozen) in (&mut pools, &frozen).join() {
+        if pool.hit_points.current > 0 {
+            pool.hit_points.current =
+                i32::min(pool.hit_points.max, pool.hit_points.current + regen);
+            pool.mana.current = i32::min(pool.mana.max, pool.mana.current + regen);
+        }
+    }
+}
+
+/// Hunger runs its course off-screen too, though nobody starves to death there.
+fn grow_hungry(ecs: &mut World, elapsed: i32) {
+    let frozen = ecs.read_storage::<OtherLevelPosition>();
+    let mut clocks = ecs.write_storage::<HungerClock>();
+    for (clock, _frozen) in (&mut clocks, &frozen).join() {
+        let mut turns = elapsed;
+        while turns > 0 && clock.state != HungerState::Starving {
+            if turns < clock.duration {
+                clock.duration -= turns;
+                break;
+            }
+            turns -= clock.duration;
+            clock.duration = HUNGER_TURNS;
+            clock.state = match clock.state {
+                HungerState::WellFed => HungerState::Normal,
+                HungerState::Normal => HungerState::Hungry,
+                _ => HungerState::Starving,
+            };
+        }
+    }
+}
+
+fn restock_vendors(ecs: &mut World, restocks: i32) {
+    let raws = RAWS.lock().unwrap();
+    let frozen = ecs.read_storage::<OtherLevelPosition>();
+    let mut vendors = ecs.write_storage::<Vendor>();
+    for (vendor, _frozen) in (&mut vendors, &frozen).join() {
+        for (name, _price) in get_vendor_items(&vendor.categories, &raws) {
+            let count = vendor.stock.entry(name).or_insert(0);
+            if *count < VENDOR_STOCK {
+                *count = i32::min(VENDOR_STOCK, *count + restocks);
+            }
+        }
+    }
+}
+
+/// Wandering monsters sometimes take the stairs to a neighbouring level the player has been to,
+/// arriving beside the stairs at the other end. The town keeps its monsters out.
+fn migrate(ecs: &mut World, chances: i32) {
+    let mut moves: Vec<(Entity, i32, i32)> = Vec::new();
+    {
+        let raws = RAWS.lock().unwrap();
+        let dm = ecs.fetch::<MasterDungeonMap>();
+        let entities = ecs.entities();
+        let frozen = ecs.read_storage::<OtherLevelPosition>();
+        let move_modes = ecs.read_storage::<MoveMode>();
+        let factions = ecs.read_storage::<Faction>();
+        let vendors = ecs.read_storage::<Vendor>();
+        for (entity, pos, mode, faction) in (&entities, &frozen, &move_modes, &factions).join() {
+            if matches!(mode.mode, Movement::Static)
+                || vendors.get(entity).is_some()
+                || faction_reaction(&faction.name, "Player", &raws) != Reaction::Attack
+            {
+                continue;
+            }
+            let mut depth = pos.depth;
+            for _ in 0..chances {
+                if crate::rng::roll_dice(1, 4) == 1 {
+                    let step = if crate::rng::roll_dice(1, 2) == 1 {
+                        -1
+                    } else {
+                        1
+                    };
+                    let next = depth + step;
+                    if next > 1 && dm.has_map(next) {
+                        depth = next;
+                    }
+                }
+            }
+            if depth != pos.depth {
+                moves.push((entity, pos.depth, depth));
+            }
+        }
+    }
+
+    for (entity, from, to) in moves {
+        let map = ecs.fetch::<MasterDungeonMap>().get_map(to).unwrap();
+        let stairs = if to > from {
+            TileType::UpStairs
+        } else {
+            TileType::DownStairs
+        };
+        let occupied = occupied_tiles(ecs, &map);
+        if let Some(idx) = arrival_tile(&map, stairs, &occupied) {
+            ecs.write_storage::<OtherLevelPosition>()
+                .insert(
+                    entity,
+                    OtherLevelPosition {
+                        x: idx as i32 % map.width,
+                        y: idx as i32 / map.width,
+                        depth: to,
+                    },
+                )
+                .expect("Insert fail");
+            ecs.write_storage::<Chasing>().remove(entity);
+        }
+    }
+}
+
+/// Tiles on this level that something stands in the way on.
+fn occupied_tiles(ecs: &World, map: &Map) -> HashSet<usize> {
+    let blockers = ecs.read_storage::<BlocksTile>();
+    let mut occupied = HashSet::new();
+    for (pos, _blocks) in (&ecs.read_storage::<OtherLevelPosition>(), &blockers).join() {
+        if pos.depth == map.depth {
+            occupied.insert(map.xy_idx(pos.x, pos.y));
+        }
+    }
+    if ecs.fetch::<Map>().depth == map.depth {
+        for (pos, _blocks) in (&ecs.read_storage::<Position>(), &blockers).join() {
+            occupied.insert(map.xy_idx(pos.x, pos.y));
+        }
+    }
+    occupied
+}
+
+/// The nearest free floor to the stairs, leaving the stairs themselves clear for the player.
+fn arrival_tile(map: &Map, stairs: TileType, occupied: &HashSet<usize>) -> Option<usize> {
+    let stairs_idx = map.tiles.iter().position(|tt| *tt == stairs)? as i32;
+    let (sx, sy) = (stairs_idx % map.width, stairs_idx / map.width);
+    for radius in 1..10 {
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                let (x, y) = (sx + dx, sy + dy);
+                if (dx.abs() != radius && dy.abs() != radius)
+                    || x < 1
+                    || x > map.width - 2
+                    || y < 1
+                    || y > map.height - 2
+                {
+                    continue;
+                }
+                let idx = map.xy_idx(x, y);
+                if tile_walkable(map.tiles[idx]) && !occupied.contains(&idx) {
+                    return Some(idx);
+                }
+            }
+        }
+    }
+    None
+}
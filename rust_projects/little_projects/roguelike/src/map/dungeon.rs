@@ -12,17 +12,35 @@ pub struct MasterDungeonMap {
     pub identified_items: HashSet<String>,
     pub scroll_mappings: HashMap<String, String>,
     pub potion_mappings: HashMap<String, String>,
+    pub seed: u64,
+    /// Turns the player has taken this run
+    #[serde(default)]
+    pub turns: i32,
+    /// The turn the frozen levels were last caught up to
+    #[serde(default)]
+    pub caught_up_to: i32,
+    /// Names the run's save slot, morgue file and replay. Saves from before runs had their own
+    /// slot leave it empty; see `saveload_system::run_slot`.
+    #[serde(default)]
+    pub slot: String,
 }
 
 impl MasterDungeonMap {
-    pub fn new() -> MasterDungeonMap {
+    pub fn new(seed: u64) -> MasterDungeonMap {
         let mut dm = MasterDungeonMap {
             maps: HashMap::new(),
             identified_items: HashSet::new(),
             scroll_mappings: HashMap::new(),
             potion_mappings: HashMap::new(),
+            seed,
+            turns: 0,
+            caught_up_to: 0,
+            slot: crate::saveload_system::new_slot_name(seed),
         };
 
+        // Item names come from their own stream, so they don't depend on which levels were visited
+        crate::rng::reseed(crate::rng::depth_seed(seed, 0));
+
         for scroll_tag in crate::raws::get_scroll_tags().iter() {
             let masked_name = make_scroll_name();
             dm.scroll_mappings
@@ -43,6 +61,15 @@ impl MasterDungeonMap {
         self.maps.insert(map.depth, map.clone());
     }
 
+    /// The deepest level generated so far.
+    pub fn deepest_level(&self) -> i32 {
+        self.maps.keys().copied().max().unwrap_or(1)
+    }
+
+    pub fn has_map(&self, depth: i32) -> bool {
+        self.maps.contains_key(&depth)
+    }
+
     pub fn get_map(&self, depth: i32) -> Option<Map> {
         if self.maps.contains_key(&depth) {
             let result = self.maps[&depth].clone();
@@ -126,14 +153,13 @@ fn make_potion_name(used_names: &mut HashSet<String>) -> String {
 }
 
 fn transition_to_new_map(ecs: &mut World, new_depth: i32) -> Vec<Map> {
+    // Every depth gets its own sub-seed, so a run seed always yields the same levels
+    let seed = ecs.fetch::<MasterDungeonMap>().seed;
+    crate::rng::reseed(crate::rng::depth_seed(seed, new_depth));
+
     let mut builder = level_builder(new_depth, 80, 50);
     builder.build_map();
-    if new_depth > 1 {
-        if let Some(pos) = &builder.build_data.starting_position {
-            let up_idx = builder.build_data.map.xy_idx(pos.x, pos.y);
-            builder.build_data.map.tiles[up_idx] = TileType::UpStairs;
-        }
-    }
+    builder.place_up_stairs();
     let mapgen_history = builder.build_data.history.clone();
     let player_start;
     {
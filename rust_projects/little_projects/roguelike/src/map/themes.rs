@@ -1,11 +1,22 @@
 use super::{Map, TileType};
 use rltk::RGB;
+use serde::{Deserialize, Serialize};
+
+#[derive(PartialEq, Copy, Clone, Default, Serialize, Deserialize, Debug)]
+pub enum MapTheme {
+    #[default]
+    Default,
+    Forest,
+    Limestone,
+    LimestoneTransition,
+    Mushroom,
+    MushroomEntrance,
+}
 
 pub fn tile_glyph(idx: usize, map : &Map) -> (rltk::FontCharType, RGB, RGB) {
-    let (glyph, mut fg, mut bg) = match map.depth {
-        9 => get_mushroom_glyph(idx, map),
-        8 => get_mushroom_glyph(idx, map),
-        7 => {
+    let (glyph, mut fg, mut bg) = match map.theme {
+        MapTheme::Mushroom => get_mushroom_glyph(idx, map),
+        MapTheme::MushroomEntrance => {
             let x = idx as i32 % map.width;
             if x > map.width-16 {
                 get_tile_glyph_default(idx, map)
@@ -13,7 +24,7 @@ pub fn tile_glyph(idx: usize, map : &Map) -> (rltk::FontCharType, RGB, RGB) {
                 get_mushroom_glyph(idx, map)
             }
         }
-        5 => {
+        MapTheme::LimestoneTransition => {
             let x = idx as i32 % map.width;
             if x < map.width/2 {
                 get_limestone_cavern_glyph(idx, map)
@@ -21,10 +32,9 @@ pub fn tile_glyph(idx: usize, map : &Map) -> (rltk::FontCharType, RGB, RGB) {
                 get_tile_glyph_default(idx, map)
             }
         }
-        4 => get_limestone_cavern_glyph(idx, map),
-        3 => get_limestone_cavern_glyph(idx, map),
-        2 => get_forest_glyph(idx, map),
-        _ => get_tile_glyph_default(idx, map)
+        MapTheme::Limestone => get_limestone_cavern_glyph(idx, map),
+        MapTheme::Forest => get_forest_glyph(idx, map),
+        MapTheme::Default => get_tile_glyph_default(idx, map)
     };
 
     if map.bloodstains.contains(&idx) { bg = RGB::from_f32(0.75, 0., 0.); }
@@ -1,6 +1,6 @@
 use serde::{Serialize, Deserialize};
 
-#[derive(PartialEq, Eq, Hash, Copy, Clone, Serialize, Deserialize)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Serialize, Deserialize)]
 pub enum TileType {
     Wall,
     Stalactite,
@@ -42,3 +42,41 @@ pub fn tile_cost(tt : TileType) -> f32 {
         _ => 1.0
     }
 }
+
+/// What a tile is called when the player examines it
+pub fn tile_name(tt : TileType) -> &'static str {
+    match tt {
+        TileType::Wall => "a wall",
+        TileType::Stalactite => "a stalactite",
+        TileType::Stalagmite => "a stalagmite",
+        TileType::Floor => "the floor",
+        TileType::DownStairs => "stairs leading down",
+        TileType::Road => "a road",
+        TileType::Grass => "grass",
+        TileType::ShallowWater => "shallow water",
+        TileType::DeepWater => "deep water",
+        TileType::WoodFloor => "a wooden floor",
+        TileType::Bridge => "a bridge",
+        TileType::Gravel => "gravel",
+        TileType::UpStairs => "stairs leading up"
+    }
+}
+
+/// Plain ASCII stand-in for a tile, for text dumps outside the game window
+pub fn tile_ascii(tt : TileType) -> char {
+    match tt {
+        TileType::Wall => '#',
+        TileType::Stalactite => '|',
+        TileType::Stalagmite => '!',
+        TileType::Floor => '.',
+        TileType::DownStairs => '>',
+        TileType::UpStairs => '<',
+        TileType::Road => '=',
+        TileType::Grass => '"',
+        TileType::ShallowWater => '~',
+        TileType::DeepWater => 'W',
+        TileType::WoodFloor => '_',
+        TileType::Bridge => '+',
+        TileType::Gravel => ';'
+    }
+}
@@ -1,4 +1,4 @@
-use super::{spawner, Map, Position, Rect, TileType, SHOW_MAPGEN_VISUALIZER};
+use super::{spawner, Map, MapTheme, Position, Rect, TileType, SHOW_MAPGEN_VISUALIZER};
 use specs::prelude::*;
 mod abyss;
 mod area_ending_point;
@@ -11,7 +11,6 @@ mod cull_unreachable;
 mod dark_elves;
 mod distant_exit;
 mod dla;
-use abyss::*;
 mod door_placement;
 mod drunkard;
 mod dwarf_fort_builder;
@@ -25,8 +24,8 @@ mod room_based_starting_position;
 mod room_corner_rounding;
 mod room_corridor_spawner;
 mod room_draw;
+mod registry;
 mod room_exploder;
-use dark_elves::*;
 mod room_sorter;
 mod rooms_corridors_bsp;
 mod rooms_corridors_dogleg;
@@ -48,9 +47,6 @@ use distant_exit::DistantExit;
 use dla::DLABuilder;
 use door_placement::DoorPlacement;
 use drunkard::DrunkardsWalkBuilder;
-use dwarf_fort_builder::*;
-use forest::forest_builder;
-use limestone_cavern::*;
 use maze::MazeBuilder;
 use prefab_builder::PrefabBuilder;
 use room_based_spawner::RoomBasedSpawner;
@@ -66,12 +62,10 @@ use rooms_corridors_dogleg::DoglegCorridors;
 use rooms_corridors_lines::StraightLineCorridors;
 use rooms_corridors_nearest::NearestCorridors;
 use simple_map::SimpleMapBuilder;
-use town::town_builder;
 use voronoi::VoronoiCellBuilder;
 use voronoi_spawning::VoronoiSpawning;
 use waveform_collapse::WaveformCollapseBuilder;
-mod mushroom_forest;
-use mushroom_forest::*;
+pub use registry::{initial_builder_by_name, meta_builder_by_name};
 
 pub struct BuilderMap {
     pub spawn_list: Vec<(usize, String)>,
@@ -82,11 +76,12 @@ pub struct BuilderMap {
     pub history: Vec<Map>,
     pub width: i32,
     pub height: i32,
+    pub record_history: bool,
 }
 
 impl BuilderMap {
     fn take_snapshot(&mut self) {
-        if SHOW_MAPGEN_VISUALIZER {
+        if self.record_history {
             let mut snapshot = self.map.clone();
             for v in snapshot.revealed_tiles.iter_mut() {
                 *v = true;
@@ -99,6 +94,7 @@ impl BuilderMap {
 pub struct BuilderChain {
     starter: Option<Box<dyn InitialMapBuilder>>,
     builders: Vec<Box<dyn MetaMapBuilder>>,
+    theme: MapTheme,
     pub build_data: BuilderMap,
 }
 
@@ -107,6 +103,7 @@ impl BuilderChain {
         BuilderChain {
             starter: None,
             builders: Vec::new(),
+            theme: MapTheme::Default,
             build_data: BuilderMap {
                 spawn_list: Vec::new(),
                 map: Map::new(new_depth, width, height, name),
@@ -116,6 +113,7 @@ impl BuilderChain {
                 history: Vec::new(),
                 width,
                 height,
+                record_history: SHOW_MAPGEN_VISUALIZER,
             },
         }
     }
@@ -131,6 +129,16 @@ impl BuilderChain {
         self.builders.push(metabuilder);
     }
 
+    /// Sets the theme the finished map is drawn with. Applied last, since some builders replace the map.
+    pub fn set_theme(&mut self, theme: MapTheme) {
+        self.theme = theme;
+    }
+
+    /// Keeps a snapshot of every build step, even when the visualizer is turned off.
+    pub fn record_history(&mut self) {
+        self.build_data.record_history = true;
+    }
+
     pub fn build_map(&mut self) {
         match &mut self.starter {
             None => panic!("Cannot run a map builder chain without a starting build system"),
@@ -144,6 +152,18 @@ impl BuilderChain {
         for metabuilder in self.builders.iter_mut() {
             metabuilder.build_map(&mut self.build_data);
         }
+
+        self.build_data.map.theme = self.theme;
+    }
+
+    /// Marks the starting position as the way back up, on every level below the town.
+    pub fn place_up_stairs(&mut self) {
+        if self.build_data.map.depth > 1 {
+            if let Some(pos) = &self.build_data.starting_position {
+                let up_idx = self.build_data.map.xy_idx(pos.x, pos.y);
+                self.build_data.map.tiles[up_idx] = TileType::UpStairs;
+            }
+        }
     }
 
     pub fn spawn_entities(&mut self, ecs: &mut World) {
@@ -291,6 +311,10 @@ pub fn random_builder(new_depth: i32, width: i32, height: i32) -> BuilderChain {
     if crate::rng::roll_dice(1, 3) == 1 {
         builder.with(WaveformCollapseBuilder::new());
 
+        // The collapse can leave isolated pockets, so set the start to the center and cull
+        builder.with(AreaStartingPosition::new(XStart::CENTER, YStart::CENTER));
+        builder.with(CullUnreachable::new());
+
         // Now set the start to a random starting area
         let (start_x, start_y) = random_start_position();
         builder.with(AreaStartingPosition::new(start_x, start_y));
@@ -312,21 +336,23 @@ pub fn random_builder(new_depth: i32, width: i32, height: i32) -> BuilderChain {
     builder
 }
 
+/// Builds a level by its name in the raws, or "random" for a random chain.
+pub fn named_builder(name: &str, new_depth: i32, width: i32, height: i32) -> Option<BuilderChain> {
+    if name == "random" {
+        return Some(random_builder(new_depth, width, height));
+    }
+    let raws = crate::raws::RAWS.lock().unwrap();
+    crate::raws::get_named_level_builder(&raws, name, new_depth, width, height)
+}
+
 pub fn level_builder(new_depth: i32, width: i32, height: i32) -> BuilderChain {
-    rltk::console::log(format!("Depth: {}", new_depth));
-    match new_depth {
-        1 => town_builder(new_depth, width, height),
-        2 => forest_builder(new_depth, width, height),
-        3 => limestone_cavern_builder(new_depth, width, height),
-        4 => limestone_deep_cavern_builder(new_depth, width, height),
-        5 => limestone_transition_builder(new_depth, width, height),
-        6 => dwarf_fort_builder(new_depth, width, height),
-        7 => mushroom_entrance(new_depth, width, height),
-        8 => mushroom_builder(new_depth, width, height),
-        9 => mushroom_exit(new_depth, width, height),
-        10 => dark_elf_city(new_depth, width, height),
-        11 => dark_elf_plaza(new_depth, width, height),
-        12 => abyss(new_depth, width, height),
-        _ => random_builder(new_depth, width, height),
+    crate::diagnostic(format!("Depth: {}", new_depth));
+    let raw_level = {
+        let raws = crate::raws::RAWS.lock().unwrap();
+        crate::raws::get_level_builder(&raws, new_depth, width, height)
+    };
+    match raw_level {
+        Some(builder) => builder,
+        None => random_builder(new_depth, width, height),
     }
 }
@@ -1,15 +1,5 @@
 use super::*;
 
-pub fn abyss(new_depth: i32, width: i32, height: i32) -> BuilderChain {
-    println!("Abyss builder");
-    let mut chain = BuilderChain::new(new_depth, width, height, "Abyss");
-    chain.start_with(AbyssMapBuilder::new());
-    chain.with(AreaStartingPosition::new(XStart::LEFT, YStart::CENTER));
-    chain.with(CullUnreachable::new());
-    chain.with(VoronoiSpawning::new());
-    chain
-}
-
 pub struct AbyssMapBuilder {}
 
 impl InitialMapBuilder for AbyssMapBuilder {
@@ -32,6 +22,7 @@ impl AbyssMapBuilder {
             .tiles
             .iter_mut()
             .for_each(|t| *t = TileType::Floor);
+        wall_edges(&mut build_data.map);
     }
 
     fn spawn_hell(&mut self, build_data: &mut BuilderMap) {
@@ -115,3 +115,19 @@ fn apply_paint(map: &mut Map, brush_size: i32, x: i32, y: i32) {
         }
     }
 }
+
+/// Turns the outermost ring of tiles into walls, for builders that paint right up to the edge.
+pub fn wall_edges(map: &mut Map) {
+    for x in 0..map.width {
+        let top = map.xy_idx(x, 0);
+        let bottom = map.xy_idx(x, map.height - 1);
+        map.tiles[top] = TileType::Wall;
+        map.tiles[bottom] = TileType::Wall;
+    }
+    for y in 0..map.height {
+        let left = map.xy_idx(0, y);
+        let right = map.xy_idx(map.width - 1, y);
+        map.tiles[left] = TileType::Wall;
+        map.tiles[right] = TileType::Wall;
+    }
+}
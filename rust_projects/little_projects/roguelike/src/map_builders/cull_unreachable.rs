@@ -35,5 +35,11 @@ impl CullUnreachable {
                 }
             }
         }
+
+        // Drop anything that was spawned in the culled areas
+        let tiles = &build_data.map.tiles;
+        build_data
+            .spawn_list
+            .retain(|(idx, _)| tiles[*idx] != TileType::Wall);
     }
 }
@@ -1,28 +1,4 @@
-use super::{
-    AreaEndingPosition, AreaStartingPosition, BspInteriorBuilder, BuilderChain, BuilderMap,
-    CullUnreachable, InitialMapBuilder, TileType, VoronoiSpawning, XEnd, XStart, YEnd, YStart,
-};
-
-pub fn dark_elf_city(new_depth: i32, width: i32, height: i32) -> BuilderChain {
-    println!("Dark elf builder");
-    let mut chain = BuilderChain::new(new_depth, width, height, "Dark Elven City");
-    chain.start_with(BspInteriorBuilder::new());
-    chain.with(AreaStartingPosition::new(XStart::CENTER, YStart::CENTER));
-    chain.with(CullUnreachable::new());
-    chain.with(AreaStartingPosition::new(XStart::RIGHT, YStart::CENTER));
-    chain.with(AreaEndingPosition::new(XEnd::LEFT, YEnd::CENTER));
-    chain.with(VoronoiSpawning::new());
-    chain
-}
-
-pub fn dark_elf_plaza(new_depth: i32, width: i32, height: i32) -> BuilderChain {
-    println!("Dark elf plaza builder");
-    let mut chain = BuilderChain::new(new_depth, width, height, "Dark Elven Plaza");
-    chain.start_with(PlazaMapBuilder::new());
-    chain.with(AreaStartingPosition::new(XStart::LEFT, YStart::CENTER));
-    chain.with(CullUnreachable::new());
-    chain
-}
+use super::{wall_edges, BuilderMap, InitialMapBuilder, TileType};
 
 // Plaza Builder
 
@@ -54,8 +30,9 @@ impl PlazaMapBuilder {
         let mut voronoi_seeds: Vec<(usize, rltk::Point)> = Vec::new();
 
         while voronoi_seeds.len() < 32 {
-            let vx = crate::rng::roll_dice(1, build_data.map.width - 1);
-            let vy = crate::rng::roll_dice(1, build_data.map.height - 1);
+            // Kept off the edge, which is walled once the zones are painted
+            let vx = crate::rng::roll_dice(1, build_data.map.width - 2);
+            let vy = crate::rng::roll_dice(1, build_data.map.height - 2);
             let vidx = build_data.map.xy_idx(vx, vy);
             let candidate = (vidx, rltk::Point::new(vx, vy));
             if !voronoi_seeds.contains(&candidate) {
@@ -124,6 +101,14 @@ impl PlazaMapBuilder {
 
         // Clear the path
         self.make_roads(build_data, &voronoi_membership);
+
+        // Zones run right up to the map edge, so close it off and drop anything placed on it
+        wall_edges(&mut build_data.map);
+        let map = &build_data.map;
+        build_data.spawn_list.retain(|(idx, _)| {
+            let (x, y) = (*idx as i32 % map.width, *idx as i32 / map.width);
+            x > 0 && y > 0 && x < map.width - 1 && y < map.height - 1
+        });
     }
 
     fn portal_park(
@@ -223,6 +208,10 @@ impl PlazaMapBuilder {
         let center = seeds[zone as usize].1;
         for y in center.y - 2..=center.y + 2 {
             for x in center.x - 2..=center.x + 2 {
+                // Seeds can sit right against the map edge
+                if x < 0 || y < 0 || x >= build_data.map.width || y >= build_data.map.height {
+                    continue;
+                }
                 let idx = build_data.map.xy_idx(x, y);
                 build_data.map.tiles[idx] = TileType::Road;
                 if crate::rng::roll_dice(1, 6) > 2 {
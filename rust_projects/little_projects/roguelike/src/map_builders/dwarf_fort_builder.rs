@@ -1,26 +1,4 @@
-use super::{
-    AreaEndingPosition, AreaStartingPosition, BspCorridors, BspDungeonBuilder, BuilderChain,
-    BuilderMap, CorridorSpawner, CullUnreachable, DLABuilder, DistantExit, MetaMapBuilder,
-    RoomDrawer, RoomSort, RoomSorter, TileType, VoronoiSpawning, XEnd, XStart, YEnd, YStart,
-};
-
-pub fn dwarf_fort_builder(new_depth: i32, width: i32, height: i32) -> BuilderChain {
-    let mut chain = BuilderChain::new(new_depth, width, height, "Dwarven Fortress");
-    chain.start_with(BspDungeonBuilder::new());
-    chain.with(RoomSorter::new(RoomSort::CENTRAL));
-    chain.with(RoomDrawer::new());
-    chain.with(BspCorridors::new());
-    chain.with(CorridorSpawner::new());
-    chain.with(DragonsLair::new());
-
-    chain.with(AreaStartingPosition::new(XStart::LEFT, YStart::TOP));
-    chain.with(CullUnreachable::new());
-    chain.with(AreaEndingPosition::new(XEnd::RIGHT, YEnd::BOTTOM));
-    chain.with(VoronoiSpawning::new());
-    chain.with(DistantExit::new());
-    chain.with(DragonSpawner::new());
-    chain
-}
+use super::{BuilderChain, BuilderMap, DLABuilder, MetaMapBuilder, TileType};
 
 pub struct DragonsLair {}
 
@@ -37,10 +15,14 @@ impl DragonsLair {
     }
 
     fn build(&mut self, build_data: &mut BuilderMap) {
-        build_data.map.depth = 7;
         build_data.take_snapshot();
 
-        let mut builder = BuilderChain::new(6, build_data.width, build_data.height, "New Map");
+        let mut builder = BuilderChain::new(
+            build_data.map.depth,
+            build_data.width,
+            build_data.height,
+            "New Map",
+        );
         builder.start_with(DLABuilder::insectoid());
         builder.build_map();
 
@@ -1,50 +1,8 @@
 use super::{
-    AreaEndingPosition, AreaStartingPosition, BspDungeonBuilder, BuilderChain, BuilderMap,
-    CellularAutomataBuilder, CullUnreachable, DLABuilder, DistantExit, DrunkardsWalkBuilder,
-    MetaMapBuilder, NearestCorridors, PrefabBuilder, RoomBasedSpawner, RoomDrawer, RoomExploder,
-    RoomSort, RoomSorter, TileType, VoronoiSpawning, XEnd, XStart, YEnd, YStart,
+    BspDungeonBuilder, BuilderChain, BuilderMap, MetaMapBuilder, NearestCorridors,
+    RoomBasedSpawner, RoomDrawer, RoomExploder, RoomSort, RoomSorter, TileType,
 };
 
-pub fn limestone_cavern_builder(new_depth: i32, width: i32, height: i32) -> BuilderChain {
-    let mut chain = BuilderChain::new(new_depth, width, height, "Limestone Caverns");
-    chain.start_with(DrunkardsWalkBuilder::winding_passages());
-    chain.with(AreaStartingPosition::new(XStart::CENTER, YStart::CENTER));
-    chain.with(CullUnreachable::new());
-    chain.with(AreaStartingPosition::new(XStart::LEFT, YStart::CENTER));
-    chain.with(VoronoiSpawning::new());
-    chain.with(DistantExit::new());
-    chain.with(CaveDecorator::new());
-    chain
-}
-
-pub fn limestone_deep_cavern_builder(new_depth: i32, width: i32, height: i32) -> BuilderChain {
-    let mut chain = BuilderChain::new(new_depth, width, height, "Deep Limestone Caverns");
-    chain.start_with(DLABuilder::central_attractor());
-    chain.with(AreaStartingPosition::new(XStart::LEFT, YStart::TOP));
-    chain.with(VoronoiSpawning::new());
-    chain.with(DistantExit::new());
-    chain.with(CaveDecorator::new());
-    chain.with(PrefabBuilder::sectional(
-        super::prefab_builder::prefab_sections::ORC_CAMP,
-    ));
-    chain
-}
-
-pub fn limestone_transition_builder(new_depth: i32, width: i32, height: i32) -> BuilderChain {
-    let mut chain = BuilderChain::new(new_depth, width, height, "Dwarf Fort - Upper Reaches");
-    chain.start_with(CellularAutomataBuilder::new());
-    chain.with(AreaStartingPosition::new(XStart::CENTER, YStart::CENTER));
-    chain.with(CullUnreachable::new());
-    chain.with(AreaStartingPosition::new(XStart::LEFT, YStart::CENTER));
-    chain.with(VoronoiSpawning::new());
-    chain.with(CaveDecorator::new());
-    chain.with(CaveTransition::new());
-    chain.with(AreaStartingPosition::new(XStart::LEFT, YStart::CENTER));
-    chain.with(CullUnreachable::new());
-    chain.with(AreaEndingPosition::new(XEnd::RIGHT, YEnd::CENTER));
-    chain
-}
-
 pub struct CaveDecorator {}
 
 impl MetaMapBuilder for CaveDecorator {
@@ -119,11 +77,15 @@ impl CaveTransition {
     }
 
     fn build(&mut self, build_data: &mut BuilderMap) {
-        build_data.map.depth = 5;
         build_data.take_snapshot();
 
         // Build a BSP-based dungeon
-        let mut builder = BuilderChain::new(5, build_data.width, build_data.height, "New Map");
+        let mut builder = BuilderChain::new(
+            build_data.map.depth,
+            build_data.width,
+            build_data.height,
+            "New Map",
+        );
         builder.start_with(BspDungeonBuilder::new());
         builder.with(RoomDrawer::new());
         builder.with(RoomSorter::new(RoomSort::RIGHTMOST));
@@ -150,13 +112,13 @@ impl CaveTransition {
         // Keep Voronoi spawn data from the left half of the map
         let w = build_data.map.width;
         build_data.spawn_list.retain(|s| {
-            let x = s.0 as i32 / w;
+            let x = s.0 as i32 % w;
             x < w / 2
         });
 
         // Keep room spawn data from the right half of the map
         for s in builder.build_data.spawn_list.iter() {
-            let x = s.0 as i32 / w;
+            let x = s.0 as i32 % w;
             if x > w / 2 {
                 build_data.spawn_list.push(s.clone());
             }
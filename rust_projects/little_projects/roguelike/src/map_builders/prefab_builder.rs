@@ -137,7 +137,7 @@ impl PrefabBuilder {
                 build_data.spawn_list.push((idx, "Watch Fire".to_string()));
             }
             _ => {
-                rltk::console::log(format!("Unknown glyph loading map: {}", (ch as u8) as char));
+                crate::diagnostic(format!("Unknown glyph loading map: {}", (ch as u8) as char));
             }
         }
     }
@@ -265,6 +265,57 @@ impl PrefabBuilder {
             }
         }
         build_data.take_snapshot();
+
+        let center_x = chunk_x + section.width as i32 / 2;
+        let center_y = chunk_y + section.height as i32 / 2;
+        PrefabBuilder::connect_to_start(center_x, center_y, build_data);
+    }
+
+    /// Sections can land in a part of the map that was already culled, so dig a tunnel from the
+    /// section to the nearest reachable floor if the player couldn't otherwise get in.
+    fn connect_to_start(x: i32, y: i32, build_data: &mut BuilderMap) {
+        let start = match &build_data.starting_position {
+            None => return,
+            Some(start) => build_data.map.xy_idx(start.x, start.y),
+        };
+        build_data.map.populate_blocked();
+        let dijkstra_map = rltk::DijkstraMap::new(
+            build_data.map.width as usize,
+            build_data.map.height as usize,
+            &[start],
+            &build_data.map,
+            1000.0,
+        );
+        let section_idx = build_data.map.xy_idx(x, y);
+        if dijkstra_map.map[section_idx] < f32::MAX {
+            return;
+        }
+
+        let width = build_data.map.width;
+        let target = dijkstra_map
+            .map
+            .iter()
+            .enumerate()
+            .filter(|(_, distance)| **distance < f32::MAX)
+            .map(|(idx, _)| idx as i32)
+            .min_by_key(|idx| i32::abs(idx % width - x) + i32::abs(idx / width - y));
+        if let Some(target) = target {
+            let (target_x, target_y) = (target % width, target / width);
+            let (mut tx, mut ty) = (x, y);
+            while tx != target_x || ty != target_y {
+                if tx != target_x {
+                    tx += i32::signum(target_x - tx);
+                } else {
+                    ty += i32::signum(target_y - ty);
+                }
+                // Only dig through walls, so stairs and water stay put
+                let idx = build_data.map.xy_idx(tx, ty);
+                if build_data.map.tiles[idx] == TileType::Wall {
+                    build_data.map.tiles[idx] = TileType::Floor;
+                }
+            }
+            build_data.take_snapshot();
+        }
     }
 
     fn apply_room_vaults(&mut self, build_data: &mut BuilderMap) {
@@ -355,12 +406,12 @@ impl PrefabBuilder {
                 let chunk_x = pos.x;
                 let chunk_y = pos.y;
 
-                let width = build_data.map.width; // The borrow checker really doesn't like it
-                let height = build_data.map.height; // when we access `self` inside the `retain`
+                // The borrow checker really doesn't like it when we access `self` inside the `retain`
+                let width = build_data.map.width;
                 build_data.spawn_list.retain(|e| {
                     let idx = e.0 as i32;
                     let x = idx % width;
-                    let y = idx / height;
+                    let y = idx / width;
                     x < chunk_x
                         || x > chunk_x + vault.width as i32
                         || y < chunk_y
@@ -0,0 +1,160 @@
+use super::abyss::AbyssMapBuilder;
+use super::dark_elves::PlazaMapBuilder;
+use super::dwarf_fort_builder::{DragonSpawner, DragonsLair};
+use super::forest::YellowBrickRoad;
+use super::limestone_cavern::{CaveDecorator, CaveTransition};
+use super::prefab_builder::{prefab_levels, prefab_sections};
+use super::town::TownBuilder;
+use super::*;
+
+// Builder names used by the "levels" section of the raws. Parameters are passed as strings, in
+// the same order as the builder's constructor arguments. Unknown names and bad parameters are
+// reported as an error message rather than falling back to a default.
+
+fn param(params: &[String], n: usize) -> Result<&str, String> {
+    params
+        .get(n)
+        .map(|p| p.as_str())
+        .ok_or_else(|| format!("missing parameter {}", n + 1))
+}
+
+fn x_start(name: &str) -> Result<XStart, String> {
+    match name {
+        "left" => Ok(XStart::LEFT),
+        "center" => Ok(XStart::CENTER),
+        "right" => Ok(XStart::RIGHT),
+        _ => Err(format!("unknown x position [{}]", name)),
+    }
+}
+
+fn y_start(name: &str) -> Result<YStart, String> {
+    match name {
+        "top" => Ok(YStart::TOP),
+        "center" => Ok(YStart::CENTER),
+        "bottom" => Ok(YStart::BOTTOM),
+        _ => Err(format!("unknown y position [{}]", name)),
+    }
+}
+
+fn x_end(name: &str) -> Result<XEnd, String> {
+    match name {
+        "left" => Ok(XEnd::LEFT),
+        "center" => Ok(XEnd::CENTER),
+        "right" => Ok(XEnd::RIGHT),
+        _ => Err(format!("unknown x position [{}]", name)),
+    }
+}
+
+fn y_end(name: &str) -> Result<YEnd, String> {
+    match name {
+        "top" => Ok(YEnd::TOP),
+        "center" => Ok(YEnd::CENTER),
+        "bottom" => Ok(YEnd::BOTTOM),
+        _ => Err(format!("unknown y position [{}]", name)),
+    }
+}
+
+fn drunkard(variant: &str) -> Result<Box<DrunkardsWalkBuilder>, String> {
+    match variant {
+        "open_area" => Ok(DrunkardsWalkBuilder::open_area()),
+        "open_halls" => Ok(DrunkardsWalkBuilder::open_halls()),
+        "winding_passages" => Ok(DrunkardsWalkBuilder::winding_passages()),
+        "fat_passages" => Ok(DrunkardsWalkBuilder::fat_passages()),
+        "fearful_symmetry" => Ok(DrunkardsWalkBuilder::fearful_symmetry()),
+        _ => Err(format!("unknown drunkard's walk [{}]", variant)),
+    }
+}
+
+fn dla(variant: &str) -> Result<Box<DLABuilder>, String> {
+    match variant {
+        "walk_inwards" => Ok(DLABuilder::walk_inwards()),
+        "walk_outwards" => Ok(DLABuilder::walk_outwards()),
+        "central_attractor" => Ok(DLABuilder::central_attractor()),
+        "insectoid" => Ok(DLABuilder::insectoid()),
+        "heavy_erosion" => Ok(DLABuilder::heavy_erosion()),
+        _ => Err(format!("unknown diffusion-limited aggregation [{}]", variant)),
+    }
+}
+
+pub fn initial_builder_by_name(
+    name: &str,
+    params: &[String],
+) -> Result<Box<dyn InitialMapBuilder>, String> {
+    Ok(match name {
+        "simple_map" => SimpleMapBuilder::new(),
+        "bsp_dungeon" => BspDungeonBuilder::new(),
+        "bsp_interior" => BspInteriorBuilder::new(),
+        "cellular_automata" => CellularAutomataBuilder::new(),
+        "drunkards_walk" => drunkard(param(params, 0)?)?,
+        "maze" => MazeBuilder::new(),
+        "dla" => dla(param(params, 0)?)?,
+        "voronoi" => match param(params, 0)? {
+            "pythagoras" => VoronoiCellBuilder::pythagoras(),
+            "manhattan" => VoronoiCellBuilder::manhattan(),
+            other => return Err(format!("unknown voronoi distance [{}]", other)),
+        },
+        "prefab_level" => match param(params, 0)? {
+            "wfc_populated" => PrefabBuilder::constant(prefab_levels::WFC_POPULATED),
+            other => return Err(format!("unknown prefab level [{}]", other)),
+        },
+        "town" => TownBuilder::new(),
+        "plaza" => PlazaMapBuilder::new(),
+        "abyss" => AbyssMapBuilder::new(),
+        _ => return Err(format!("unknown starting builder [{}]", name)),
+    })
+}
+
+pub fn meta_builder_by_name(
+    name: &str,
+    params: &[String],
+) -> Result<Box<dyn MetaMapBuilder>, String> {
+    Ok(match name {
+        "area_starting_position" => AreaStartingPosition::new(
+            x_start(param(params, 0)?)?,
+            y_start(param(params, 1)?)?,
+        ),
+        "area_ending_position" => {
+            AreaEndingPosition::new(x_end(param(params, 0)?)?, y_end(param(params, 1)?)?)
+        }
+        "room_based_starting_position" => RoomBasedStartingPosition::new(),
+        "cull_unreachable" => CullUnreachable::new(),
+        "distant_exit" => DistantExit::new(),
+        "room_based_stairs" => RoomBasedStairs::new(),
+        "voronoi_spawning" => VoronoiSpawning::new(),
+        "room_based_spawner" => RoomBasedSpawner::new(),
+        "corridor_spawner" => CorridorSpawner::new(),
+        "room_sorter" => match param(params, 0)? {
+            "leftmost" => RoomSorter::new(RoomSort::LEFTMOST),
+            "rightmost" => RoomSorter::new(RoomSort::RIGHTMOST),
+            "topmost" => RoomSorter::new(RoomSort::TOPMOST),
+            "bottommost" => RoomSorter::new(RoomSort::BOTTOMMOST),
+            "central" => RoomSorter::new(RoomSort::CENTRAL),
+            other => return Err(format!("unknown room sort [{}]", other)),
+        },
+        "room_drawer" => RoomDrawer::new(),
+        "room_exploder" => RoomExploder::new(),
+        "room_corner_rounder" => RoomCornerRounder::new(),
+        "bsp_corridors" => BspCorridors::new(),
+        "dogleg_corridors" => DoglegCorridors::new(),
+        "nearest_corridors" => NearestCorridors::new(),
+        "straight_line_corridors" => StraightLineCorridors::new(),
+        "door_placement" => DoorPlacement::new(),
+        "cellular_automata" => CellularAutomataBuilder::new(),
+        "drunkards_walk" => drunkard(param(params, 0)?)?,
+        "dla" => dla(param(params, 0)?)?,
+        "waveform_collapse" => WaveformCollapseBuilder::new(),
+        "prefab_section" => match param(params, 0)? {
+            "underground_fort" => PrefabBuilder::sectional(prefab_sections::UNDERGROUND_FORT),
+            "orc_camp" => PrefabBuilder::sectional(prefab_sections::ORC_CAMP),
+            "drow_entry" => PrefabBuilder::sectional(prefab_sections::DROW_ENTRY),
+            other => return Err(format!("unknown prefab section [{}]", other)),
+        },
+        "prefab_vaults" => PrefabBuilder::vaults(),
+        "yellow_brick_road" => YellowBrickRoad::new(),
+        "cave_decorator" => CaveDecorator::new(),
+        "cave_transition" => CaveTransition::new(),
+        "dragons_lair" => DragonsLair::new(),
+        "dragon_spawner" => DragonSpawner::new(),
+        _ => return Err(format!("unknown builder [{}]", name)),
+    })
+}
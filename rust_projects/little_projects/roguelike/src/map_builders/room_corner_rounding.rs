@@ -32,7 +32,27 @@ impl RoomCornerRounder {
             neighbor_walls += 1;
         }
 
-        if neighbor_walls == 2 {
+        // A corner only opens onto the three room tiles in one quadrant; anything else is a
+        // corridor passing through
+        let (mut open_neighbors, mut sum_x, mut sum_y) = (0, 0, 0);
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let (nx, ny) = (x + dx, y + dy);
+                if (dx != 0 || dy != 0)
+                    && nx >= 0
+                    && nx < w
+                    && ny >= 0
+                    && ny < h
+                    && build_data.map.tiles[build_data.map.xy_idx(nx, ny)] != TileType::Wall
+                {
+                    open_neighbors += 1;
+                    sum_x += dx;
+                    sum_y += dy;
+                }
+            }
+        }
+
+        if neighbor_walls == 2 && open_neighbors == 3 && sum_x.abs() == 2 && sum_y.abs() == 2 {
             build_data.map.tiles[idx] = TileType::Wall;
         }
     }
@@ -31,8 +31,9 @@ impl RoomDrawer {
         let radius = i32::min(room.x2 - room.x1, room.y2 - room.y1) as f32 / 2.0;
         let center = room.center();
         let center_pt = rltk::Point::new(center.0, center.1);
-        for y in room.y1..=room.y2 {
-            for x in room.x1..=room.x2 {
+        // Same bounds as a rectangular room, so the room edge stays solid
+        for y in room.y1 + 1..=room.y2 {
+            for x in room.x1 + 1..=room.x2 {
                 let idx = build_data.map.xy_idx(x, y);
                 let distance =
                     rltk::DistanceAlg::Pythagoras.distance2d(center_pt, rltk::Point::new(x, y));
@@ -1,4 +1,4 @@
-use super::{draw_corridor, BuilderMap, MetaMapBuilder, Rect};
+use super::{draw_corridor, BuilderMap, MetaMapBuilder, Rect, TileType};
 
 pub struct BspCorridors {}
 
@@ -15,6 +15,18 @@ impl BspCorridors {
         Box::new(BspCorridors {})
     }
 
+    /// A random floor tile in the room. Rooms are drawn from x1 + 1 to x2, and circular rooms
+    /// don't fill their corners, so fall back to the center if the roll misses.
+    fn point_in_room(room: &Rect, build_data: &BuilderMap) -> (i32, i32) {
+        let x = room.x1 + crate::rng::roll_dice(1, i32::abs(room.x1 - room.x2));
+        let y = room.y1 + crate::rng::roll_dice(1, i32::abs(room.y1 - room.y2));
+        if build_data.map.tiles[build_data.map.xy_idx(x, y)] == TileType::Floor {
+            (x, y)
+        } else {
+            room.center()
+        }
+    }
+
     fn corridors(&mut self, build_data: &mut BuilderMap) {
         let rooms: Vec<Rect>;
         if let Some(rooms_builder) = &build_data.rooms {
@@ -27,12 +39,8 @@ impl BspCorridors {
         for i in 0..rooms.len() - 1 {
             let room = rooms[i];
             let next_room = rooms[i + 1];
-            let start_x = room.x1 + (crate::rng::roll_dice(1, i32::abs(room.x1 - room.x2)) - 1);
-            let start_y = room.y1 + (crate::rng::roll_dice(1, i32::abs(room.y1 - room.y2)) - 1);
-            let end_x = next_room.x1
-                + (crate::rng::roll_dice(1, i32::abs(next_room.x1 - next_room.x2)) - 1);
-            let end_y = next_room.y1
-                + (crate::rng::roll_dice(1, i32::abs(next_room.y1 - next_room.y2)) - 1);
+            let (start_x, start_y) = BspCorridors::point_in_room(&room, build_data);
+            let (end_x, end_y) = BspCorridors::point_in_room(&next_room, build_data);
             let corridor = draw_corridor(&mut build_data.map, start_x, start_y, end_x, end_y);
             corridors.push(corridor);
             build_data.take_snapshot();
@@ -1,12 +1,8 @@
-use super::{BuilderChain, BuilderMap, InitialMapBuilder, Position, TileType};
-use std::collections::HashSet;
-
-pub fn town_builder(new_depth: i32, width: i32, height: i32) -> BuilderChain {
-    let mut chain = BuilderChain::new(new_depth, width, height, "The Town of Bracketon");
-    chain.start_with(TownBuilder::new());
-    chain
-}
+use super::{BuilderMap, InitialMapBuilder, Position, TileType};
+use std::collections::BTreeSet;
 
+/// Bracketon and the countryside around it. The town is the one level without a walled edge: the
+/// grass, shoreline and piers run out to the edge of the map, which the player can't step past.
 pub struct TownBuilder {}
 
 impl InitialMapBuilder for TownBuilder {
@@ -96,8 +92,8 @@ impl TownBuilder {
         build_data.take_snapshot();
     }
 
-    fn town_walls(&mut self, build_data: &mut BuilderMap) -> (HashSet<usize>, i32) {
-        let mut available_building_tiles: HashSet<usize> = HashSet::new();
+    fn town_walls(&mut self, build_data: &mut BuilderMap) -> (BTreeSet<usize>, i32) {
+        let mut available_building_tiles: BTreeSet<usize> = BTreeSet::new();
         let wall_gap_y = crate::rng::roll_dice(1, build_data.height - 9) + 5;
         for y in 1..build_data.height - 2 {
             if !(y > wall_gap_y - 4 && y < wall_gap_y + 4) {
@@ -136,7 +132,7 @@ impl TownBuilder {
     fn buildings(
         &mut self,
         build_data: &mut BuilderMap,
-        available_building_tiles: &mut HashSet<usize>,
+        available_building_tiles: &mut BTreeSet<usize>,
     ) -> Vec<(i32, i32, i32, i32)> {
         let mut buildings: Vec<(i32, i32, i32, i32)> = Vec::new();
         let mut n_buildings = 0;
@@ -458,7 +454,7 @@ impl TownBuilder {
     fn spawn_townsfolk(
         &mut self,
         build_data: &mut BuilderMap,
-        available_building_tiles: &mut HashSet<usize>,
+        available_building_tiles: &mut BTreeSet<usize>,
     ) {
         for idx in available_building_tiles.iter() {
             if crate::rng::roll_dice(1, 10) == 1 {
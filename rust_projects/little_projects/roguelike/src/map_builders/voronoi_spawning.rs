@@ -1,5 +1,5 @@
 use super::{spawner, BuilderMap, MetaMapBuilder, TileType};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 pub struct VoronoiSpawning {}
 
@@ -17,7 +17,7 @@ impl VoronoiSpawning {
 
     #[allow(clippy::map_entry)]
     fn build(&mut self, build_data: &mut BuilderMap) {
-        let mut noise_areas: HashMap<i32, Vec<usize>> = HashMap::new();
+        let mut noise_areas: BTreeMap<i32, Vec<usize>> = BTreeMap::new();
         let mut noise = rltk::FastNoise::seeded(crate::rng::roll_dice(1, 65536) as u64);
         noise.set_noise_type(rltk::NoiseType::Cellular);
         noise.set_frequency(0.08);
@@ -26,12 +26,13 @@ impl WaveformCollapseBuilder {
         const CHUNK_SIZE: i32 = 8;
         build_data.take_snapshot();
 
+        // Keep the source map before the tile gallery replaces it, in case the solver fails
+        let old_map = build_data.map.clone();
+
         let patterns = build_patterns(&build_data.map, CHUNK_SIZE, true, true);
         let constraints = patterns_to_constraints(patterns, CHUNK_SIZE);
         self.render_tile_gallery(&constraints, CHUNK_SIZE, build_data);
 
-        let old_map = build_data.map.clone();
-
         build_data.map = Map::new(
             build_data.map.depth,
             build_data.width,
@@ -61,6 +62,29 @@ impl WaveformCollapseBuilder {
             // Restore the old one
             build_data.map = old_map;
         }
+
+        // Stairs copied from the source map would lead nowhere; later builders place the real ones
+        for tile in build_data.map.tiles.iter_mut() {
+            if *tile == TileType::DownStairs {
+                *tile = TileType::Floor;
+            }
+        }
+
+        // Chunks are laid out right up to the map edge, so close it off
+        let (width, height) = (build_data.map.width, build_data.map.height);
+        for x in 0..width {
+            for y in [0, height - 1] {
+                let idx = build_data.map.xy_idx(x, y);
+                build_data.map.tiles[idx] = TileType::Wall;
+            }
+        }
+        for y in 0..height {
+            for x in [0, width - 1] {
+                let idx = build_data.map.xy_idx(x, y);
+                build_data.map.tiles[idx] = TileType::Wall;
+            }
+        }
+        build_data.take_snapshot();
     }
 
     fn render_tile_gallery(
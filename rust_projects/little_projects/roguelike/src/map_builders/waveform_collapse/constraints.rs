@@ -1,5 +1,5 @@
 use super::{TileType, Map, MapChunk, tile_idx_in_chunk};
-use std::collections::HashSet;
+use std::collections::BTreeSet;
 
 pub fn build_patterns(map : &Map, chunk_size: i32, include_flipping: bool, dedupe: bool) -> Vec<Vec<TileType>> {
     let chunks_x = map.width / chunk_size;
@@ -59,10 +59,10 @@ pub fn build_patterns(map : &Map, chunk_size: i32, include_flipping: bool, dedup
 
     // Dedupe
     if dedupe {
-        rltk::console::log(format!("Pre de-duplication, there are {} patterns", patterns.len()));
-        let set: HashSet<Vec<TileType>> = patterns.drain(..).collect(); // dedup
+        crate::diagnostic(format!("Pre de-duplication, there are {} patterns", patterns.len()));
+        let set: BTreeSet<Vec<TileType>> = patterns.drain(..).collect(); // dedup, in a stable order
         patterns.extend(set.into_iter());
-        rltk::console::log(format!("There are {} patterns", patterns.len()));
+        crate::diagnostic(format!("There are {} patterns", patterns.len()));
     }
 
     patterns
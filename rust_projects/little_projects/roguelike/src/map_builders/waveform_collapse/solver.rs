@@ -1,5 +1,5 @@
 use super::{Map, MapChunk};
-use std::collections::HashSet;
+use std::collections::BTreeSet;
 
 pub struct Solver {
     constraints: Vec<MapChunk>,
@@ -181,7 +181,7 @@ impl Solver {
             }
         } else {
             // There are neighbors, so we try to be compatible with them
-            let mut options_to_check: HashSet<usize> = HashSet::new();
+            let mut options_to_check: BTreeSet<usize> = BTreeSet::new();
             for o in options.iter() {
                 for i in o.iter() {
                     options_to_check.insert(*i);
@@ -202,7 +202,7 @@ impl Solver {
             }
 
             if possible_options.is_empty() {
-                rltk::console::log("Oh no! It's not possible!");
+                crate::diagnostic("Oh no! It's not possible!");
                 self.possible = false;
                 return true;
             } else {
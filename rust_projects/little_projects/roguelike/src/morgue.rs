@@ -0,0 +1,403 @@
+//! What's left when a run ends: a morgue file describing the character and how the run went, and
+//! a place on the local high-score table.
+
+use crate::components::*;
+use crate::gamelog;
+use crate::map::{tile_ascii, Map, MasterDungeonMap};
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// Morgue files are written into this directory when a run ends.
+pub const MORGUE_DIR: &str = "morgue";
+/// The local high-score table.
+pub const HIGH_SCORES_FILE: &str = "highscores.json";
+/// How many runs the high-score table remembers.
+pub const MAX_HIGH_SCORES: usize = 20;
+/// How many of the last log lines go into the morgue file.
+const MORGUE_LOG_LINES: usize = 20;
+/// Points for coming back with the amulet, on top of everything else.
+const VICTORY_BONUS: i32 = 5000;
+
+/// How the current run ended, if it has.
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum RunEnd {
+    #[default]
+    Ongoing,
+    Died {
+        cause: String,
+    },
+    Won,
+}
+
+impl RunEnd {
+    /// A sentence fragment, such as "killed by a Bandit".
+    pub fn describe(&self) -> String {
+        match self {
+            RunEnd::Ongoing => "still exploring".to_string(),
+            RunEnd::Died { cause } => cause.clone(),
+            RunEnd::Won => "escaped with the Amulet of Yala".to_string(),
+        }
+    }
+}
+
+fn with_article(name: &str) -> String {
+    match name.chars().next() {
+        Some(c) if "AEIOUaeiou".contains(c) => format!("an {}", name),
+        _ => format!("a {}", name),
+    }
+}
+
+/// Why the player died to this damage: whoever dealt it if they have a name, otherwise what
+/// kind of damage it was.
+pub fn cause_of_death(ecs: &World, creator: Option<Entity>, damage_type: DamageType) -> String {
+    if let Some(name) = creator.and_then(|creator| ecs.read_storage::<Name>().get(creator).cloned())
+    {
+        return format!("killed by {}", with_article(&name.name));
+    }
+    let player = *ecs.fetch::<Entity>();
+    if let Some(clock) = ecs.read_storage::<HungerClock>().get(player) {
+        if clock.state == HungerState::Starving {
+            return "starved to death".to_string();
+        }
+    }
+    format!("succumbed to {} damage", damage_type.name())
+}
+
+/// Marks the run as lost, unless it already ended another way.
+pub fn record_death(ecs: &World, cause: String) {
+    let mut run_end = ecs.write_resource::<RunEnd>();
+    if *run_end == RunEnd::Ongoing {
+        *run_end = RunEnd::Died { cause };
+    }
+}
+
+/// The deepest level the player has been to this run.
+pub fn deepest_level(ecs: &World) -> i32 {
+    let current = ecs.fetch::<Map>().depth;
+    i32::max(current, ecs.fetch::<MasterDungeonMap>().deepest_level())
+}
+
+/// Experience and gold, a hundred points for every level reached and a bonus for winning.
+pub fn score(ecs: &World) -> i32 {
+    let player = *ecs.fetch::<Entity>();
+    let (xp, gold) = ecs
+        .read_storage::<Pools>()
+        .get(player)
+        .map_or((0, 0), |pools| (pools.xp, pools.gold as i32));
+    let bonus = if *ecs.fetch::<RunEnd>() == RunEnd::Won {
+        VICTORY_BONUS
+    } else {
+        0
+    };
+    xp + gold + deepest_level(ecs) * 100 + bonus
+}
+
+fn character_name(ecs: &World, player: Entity) -> (String, String) {
+    match ecs.read_storage::<Character>().get(player) {
+        Some(character) => (character.name.clone(), character.background.clone()),
+        None => ("Nameless".to_string(), "Wanderer".to_string()),
+    }
+}
+
+/// The revealed part of the current level as text, with the player drawn on it.
+pub fn map_snapshot(ecs: &World) -> Vec<String> {
+    let map = ecs.fetch::<Map>();
+    let player_pos = *ecs.fetch::<rltk::Point>();
+    let mut rows = Vec::new();
+    for y in 0..map.height {
+        let row: String = (0..map.width)
+            .map(|x| {
+                let idx = map.xy_idx(x, y);
+                if x == player_pos.x && y == player_pos.y {
+                    '@'
+                } else if map.revealed_tiles[idx] {
+                    tile_ascii(map.tiles[idx])
+                } else {
+                    ' '
+                }
+            })
+            .collect();
+        rows.push(row.trim_end().to_string());
+    }
+    // Only keep the rows between the first and last with something on them
+    let first = rows.iter().position(|row| !row.is_empty()).unwrap_or(0);
+    let last = rows
+        .iter()
+        .rposition(|row| !row.is_empty())
+        .map_or(0, |i| i + 1);
+    rows.drain(first..last.max(first)).collect()
+}
+
+/// Everything worth knowing about the run, as the text of a morgue file.
+pub fn morgue_text(ecs: &World) -> String {
+    let player = *ecs.fetch::<Entity>();
+    let (name, background) = character_name(ecs, player);
+    let run_end = (*ecs.fetch::<RunEnd>()).clone();
+    let seed = ecs.fetch::<MasterDungeonMap>().seed;
+    let depth = ecs.fetch::<Map>().depth;
+    let mut text = String::new();
+
+    let _ = writeln!(text, "{} the {}", name, background);
+    if let Some(pools) = ecs.read_storage::<Pools>().get(player) {
+        let _ = writeln!(
+            text,
+            "Level {}, {} xp. Hit points {}/{}, mana {}/{}, {:.0} gold.",
+            pools.level,
+            pools.xp,
+            pools.hit_points.current,
+            pools.hit_points.max,
+            pools.mana.current,
+            pools.mana.max,
+            pools.gold
+        );
+    }
+    let _ = writeln!(
+        text,
+        "{} on level {}, having been as deep as level {}.",
+        capitalize(&run_end.describe()),
+        depth,
+        deepest_level(ecs)
+    );
+    let _ = writeln!(
+        text,
+        "Score {} after {} turns. Seed {}.",
+        score(ecs),
+        ecs.fetch::<MasterDungeonMap>().turns,
+        seed
+    );
+
+    text.push_str("\nAttributes\n");
+    if let Some(attributes) = ecs.read_storage::<Attributes>().get(player) {
+        for (label, attribute) in [
+            ("Might", &attributes.might),
+            ("Fitness", &attributes.fitness),
+            ("Quickness", &attributes.quickness),
+            ("Intelligence", &attributes.intelligence),
+        ] {
+            let _ = writeln!(
+                text,
+                "  {:<14}{:>3} ({:+})",
+                label,
+                attribute.base + attribute.modifiers,
+                attribute.bonus
+            );
+        }
+    }
+
+    text.push_str("\nSkills\n");
+    if let Some(skills) = ecs.read_storage::<Skills>().get(player) {
+        for skill in [Skill::Melee, Skill::Defense, Skill::Magic, Skill::Stealth] {
+            let level = skills.skills.get(&skill).copied().unwrap_or(0);
+            let _ = writeln!(text, "  {:<14}{:>3}", format!("{:?}", skill), level);
+        }
+    }
+    if let Some(perks) = ecs.read_storage::<Perks>().get(player) {
+        if !perks.taken.is_empty() {
+            let _ = writeln!(text, "\nPerks\n  {}", perks.taken.join(", "));
+        }
+    }
+
+    text.push_str("\nEquipment\n");
+    let mut equipment: Vec<(String, String)> = (&ecs.entities(), &ecs.read_storage::<Equipped>())
+        .join()
+        .filter(|(_, equipped)| equipped.owner == player)
+        .map(|(item, equipped)| {
+            (
+                format!("{:?}", equipped.slot),
+                crate::gui::get_item_display_name(ecs, item),
+            )
+        })
+        .collect();
+    equipment.sort();
+    push_list(
+        &mut text,
+        equipment
+            .iter()
+            .map(|(slot, item)| format!("{:<10}{}", slot, item)),
+    );
+
+    text.push_str("\nInventory\n");
+    let mut carried: Vec<String> = (&ecs.entities(), &ecs.read_storage::<InBackpack>())
+        .join()
+        .filter(|(_, pack)| pack.owner == player)
+        .map(|(item, _)| crate::gui::get_item_display_name(ecs, item))
+        .collect();
+    carried.sort();
+    push_list(&mut text, carried.into_iter());
+
+    text.push_str("\nSpells\n");
+    let spells: Vec<String> = ecs
+        .read_storage::<KnownSpells>()
+        .get(player)
+        .map(|known| {
+            known
+                .spells
+                .iter()
+                .map(|spell| format!("{} ({} mana)", spell.display_name, spell.mana_cost))
+                .collect()
+        })
+        .unwrap_or_default();
+    push_list(&mut text, spells.into_iter());
+
+    text.push_str("\nEvents\n");
+    let mut events: Vec<(String, i32)> = gamelog::clone_events().into_iter().collect();
+    events.sort();
+    push_list(
+        &mut text,
+        events
+            .iter()
+            .map(|(event, count)| format!("{:<24}{:>7}", event, count)),
+    );
+
+    let _ = writeln!(text, "\nLevel {}", depth);
+    for row in map_snapshot(ecs) {
+        let _ = writeln!(text, "  {}", row);
+    }
+
+    text.push_str("\nLast messages\n");
+    let lines = gamelog::log_lines(None);
+    let start = lines.len().saturating_sub(MORGUE_LOG_LINES);
+    push_list(
+        &mut text,
+        lines[start..]
+            .iter()
+            .map(|line| format!("[turn {:>5}] {}", line.turn, line.text())),
+    );
+    text
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// One indented line per entry, or "(none)" if there aren't any.
+fn push_list(text: &mut String, entries: impl Iterator<Item = String>) {
+    let mut empty = true;
+    for entry in entries {
+        let _ = writeln!(text, "  {}", entry);
+        empty = false;
+    }
+    if empty {
+        text.push_str("  (none)\n");
+    }
+}
+
+/// Writes the morgue file into the directory, named after the run's slot.
+pub fn write_morgue(ecs: &World, dir: &Path) -> Result<PathBuf, String> {
+    let path = dir.join(format!("{}.txt", crate::saveload_system::run_slot(ecs)));
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    std::fs::write(&path, morgue_text(ecs)).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+/// One finished run on the high-score table.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct HighScore {
+    pub name: String,
+    pub background: String,
+    pub level: i32,
+    pub score: i32,
+    pub depth: i32,
+    pub turns: i32,
+    /// How the run ended, such as "killed by a Bandit"
+    pub outcome: String,
+    pub seed: u64,
+}
+
+impl HighScore {
+    /// The entry for the run in this world.
+    pub fn from_run(ecs: &World) -> HighScore {
+        let player = *ecs.fetch::<Entity>();
+        let (name, background) = character_name(ecs, player);
+        HighScore {
+            name,
+            background,
+            level: ecs
+                .read_storage::<Pools>()
+                .get(player)
+                .map_or(1, |pools| pools.level),
+            score: score(ecs),
+            depth: deepest_level(ecs),
+            turns: ecs.fetch::<MasterDungeonMap>().turns,
+            outcome: ecs.fetch::<RunEnd>().describe(),
+            seed: ecs.fetch::<MasterDungeonMap>().seed,
+        }
+    }
+}
+
+/// The best runs played on this machine, best first.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct HighScores {
+    pub entries: Vec<HighScore>,
+}
+
+impl HighScores {
+    /// The table in the file, or an empty one if there isn't a readable file yet.
+    pub fn load(path: &Path) -> HighScores {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let text = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, text).map_err(|e| e.to_string())
+    }
+
+    /// Adds the run to the table, and returns its place (counting from 1) if it was good enough
+    /// to stay on it. Ties go to the run that got there first.
+    pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+        let place = self
+            .entries
+            .iter()
+            .position(|existing| existing.score < entry.score)
+            .unwrap_or(self.entries.len());
+        self.entries.insert(place, entry);
+        self.entries.truncate(MAX_HIGH_SCORES);
+        if place < MAX_HIGH_SCORES {
+            Some(place + 1)
+        } else {
+            None
+        }
+    }
+}
+
+/// Writes the morgue file and puts the run on the high-score table, both kept under `dir`.
+/// Returns what happened as lines for the game over screen.
+pub fn record_run_end(ecs: &World, dir: &Path) -> Vec<String> {
+    let high_scores = &dir.join(HIGH_SCORES_FILE);
+    let mut notes = Vec::new();
+    notes.push(match write_morgue(ecs, &dir.join(MORGUE_DIR)) {
+        Ok(path) => format!("Morgue file written to {}", path.display()),
+        Err(e) => format!("Unable to write the morgue file: {}", e),
+    });
+    let mut table = HighScores::load(high_scores);
+    let entry = HighScore::from_run(ecs);
+    let score = entry.score;
+    match table.insert(entry) {
+        Some(place) => {
+            notes.push(format!(
+                "Your score of {} is number {} on the high-score table.",
+                score, place
+            ));
+            if let Err(e) = table.save(high_scores) {
+                notes.push(format!("Unable to save the high scores: {}", e));
+            }
+        }
+        None => notes.push(format!(
+            "Your score of {} didn't make the high-score table.",
+            score
+        )),
+    }
+    notes
+}
@@ -1,10 +1,13 @@
 use super::{
-    raws::Reaction, Attributes, BlocksTile, BlocksVisibility, Door, EntityMoved, Equipped, Faction,
-    HungerClock, HungerState, Item, Map, Name, Player, Pools, Position, Renderable, RunState,
-    State, Target, TileType, Vendor, VendorMode, Viewshed, WantsToCastSpell, WantsToMelee,
-    WantsToPickupItem, WantsToShoot, Weapon,
+    carry_capacity_lbs, effects::*, input::Action, raws::Reaction, Attributes, BlocksTile,
+    BlocksVisibility, Door, EntityMoved, Equipped, Faction, HungerClock, HungerState, Item, Map,
+    Name, Perks, Player, Pools, Position, Quips, Renderable, RunState, State, Target, TileType,
+    Vendor, VendorMode, Viewshed, WantsToCastSpell, WantsToMelee, WantsToPickupItem, WantsToShoot,
+    Weapon,
 };
-use rltk::{Point, Rltk, VirtualKeyCode};
+use crate::gui::HudClick;
+use crate::travel::{self, Destination};
+use rltk::{Point, Rltk};
 use specs::prelude::*;
 use std::cmp::{max, min};
 
@@ -24,6 +27,8 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) -> RunState
     let factions = ecs.read_storage::<Faction>();
     let mut result = RunState::AwaitingInput;
     let vendors = ecs.read_storage::<Vendor>();
+    let pools = ecs.read_storage::<Pools>();
+    let perks = ecs.read_storage::<Perks>();
 
     let mut swap_entities: Vec<(Entity, i32, i32)> = Vec::new();
 
@@ -99,6 +104,13 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) -> RunState
                     let glyph = renderables.get_mut(potential_target).unwrap();
                     glyph.glyph = rltk::to_cp437('/');
                     viewshed.dirty = true;
+                    add_effect(
+                        Some(entity),
+                        EffectType::Noise { volume: DOOR_NOISE },
+                        Targets::Tile {
+                            tile_idx: destination_idx as i32,
+                        },
+                    );
                     return Some(RunState::Ticking);
                 }
                 None
@@ -114,6 +126,23 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) -> RunState
                 .insert(entity, EntityMoved {})
                 .expect("Unable to insert marker");
             crate::spatial::move_entity(entity, old_idx, new_idx);
+            let overburdened = match (pools.get(entity), combat_stats.get(entity)) {
+                (Some(pool), Some(attr)) => {
+                    pool.total_weight as i32 > carry_capacity_lbs(attr, perks.get(entity))
+                }
+                _ => false,
+            };
+            if overburdened {
+                add_effect(
+                    Some(entity),
+                    EffectType::Noise {
+                        volume: OVERBURDENED_NOISE,
+                    },
+                    Targets::Tile {
+                        tile_idx: new_idx as i32,
+                    },
+                );
+            }
 
             viewshed.dirty = true;
             let mut ppos = ecs.write_resource::<Point>();
@@ -171,9 +200,22 @@ pub fn try_previous_level(ecs: &mut World) -> bool {
     }
 }
 
+/// True if this entity's faction attacks the player on sight.
+pub fn is_hostile(ecs: &World, entity: Entity) -> bool {
+    match ecs.read_storage::<Faction>().get(entity) {
+        Some(faction) => {
+            crate::raws::faction_reaction(
+                &faction.name,
+                "Player",
+                &crate::raws::RAWS.lock().unwrap(),
+            ) == Reaction::Attack
+        }
+        None => false,
+    }
+}
+
 fn get_item(ecs: &mut World) {
     let player_pos = ecs.fetch::<Point>();
-    let player_entity = ecs.fetch::<Entity>();
     let entities = ecs.entities();
     let items = ecs.read_storage::<Item>();
     let positions = ecs.read_storage::<Position>();
@@ -187,23 +229,27 @@ fn get_item(ecs: &mut World) {
 
     match target_item {
         None => crate::gamelog::Logger::new()
+            .category(crate::gamelog::LogCategory::Loot)
             .append("There is nothing here to pick up.")
             .log(),
-        Some(item) => {
-            let mut pickup = ecs.write_storage::<WantsToPickupItem>();
-            pickup
-                .insert(
-                    *player_entity,
-                    WantsToPickupItem {
-                        collected_by: *player_entity,
-                        item,
-                    },
-                )
-                .expect("Unable to insert want to pickup");
-        }
+        Some(item) => pick_up(ecs, item),
     }
 }
 
+pub(crate) fn pick_up(ecs: &World, item: Entity) {
+    let player_entity = ecs.fetch::<Entity>();
+    let mut pickup = ecs.write_storage::<WantsToPickupItem>();
+    pickup
+        .insert(
+            *player_entity,
+            WantsToPickupItem {
+                collected_by: *player_entity,
+                item,
+            },
+        )
+        .expect("Unable to insert want to pickup");
+}
+
 fn skip_turn(ecs: &mut World) -> RunState {
     let player_entity = ecs.fetch::<Entity>();
     let viewshed_components = ecs.read_storage::<Viewshed>();
@@ -296,21 +342,44 @@ fn use_consumable_hotkey(gs: &mut State, key: i32) -> RunState {
     RunState::Ticking
 }
 
-fn use_spell_hotkey(gs: &mut State, key: i32) -> RunState {
+/// Casts one of the player's known spells, asking for a target first if it needs one.
+pub fn cast_known_spell(gs: &mut State, index: usize) -> RunState {
     use super::raws::find_spell_entity;
-    use super::KnownSpells;
+    use super::{KnownSpells, Skill, Skills, SpellTemplate};
 
     let player_entity = gs.ecs.fetch::<Entity>();
     let known_spells_storage = gs.ecs.read_storage::<KnownSpells>();
     let known_spells = &known_spells_storage.get(*player_entity).unwrap().spells;
 
-    if (key as usize) < known_spells.len() {
+    if let Some(known) = known_spells.get(index) {
         let pools = gs.ecs.read_storage::<Pools>();
         let player_pools = pools.get(*player_entity).unwrap();
-        if player_pools.mana.current >= known_spells[key as usize].mana_cost {
-            if let Some(spell_entity) =
-                find_spell_entity(&gs.ecs, &known_spells[key as usize].display_name)
-            {
+        let spell_entity = find_spell_entity(&gs.ecs, &known.display_name);
+        let min_magic = spell_entity
+            .and_then(|spell| gs.ecs.read_storage::<SpellTemplate>().get(spell).cloned())
+            .map_or(0, |template| template.min_magic);
+        let magic = gs
+            .ecs
+            .read_storage::<Skills>()
+            .get(*player_entity)
+            .and_then(|skills| skills.skills.get(&Skill::Magic).copied())
+            .unwrap_or(0);
+        if known.cooldown > 0 {
+            crate::gamelog::Logger::new()
+                .append(format!(
+                    "{} won't be ready for another {} turns.",
+                    known.display_name, known.cooldown
+                ))
+                .log();
+        } else if magic < min_magic {
+            crate::gamelog::Logger::new()
+                .append(format!(
+                    "You need {} Magic to cast {}.",
+                    min_magic, known.display_name
+                ))
+                .log();
+        } else if player_pools.mana.current >= known.mana_cost {
+            if let Some(spell_entity) = spell_entity {
                 use crate::components::Ranged;
                 if let Some(ranged) = gs.ecs.read_storage::<Ranged>().get(spell_entity) {
                     return RunState::ShowTargeting {
@@ -341,115 +410,113 @@ fn use_spell_hotkey(gs: &mut State, key: i32) -> RunState {
 }
 
 pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
-    // Hotkeys
-    if ctx.shift && ctx.key.is_some() {
-        let key: Option<i32> = match ctx.key.unwrap() {
-            VirtualKeyCode::Key1 => Some(1),
-            VirtualKeyCode::Key2 => Some(2),
-            VirtualKeyCode::Key3 => Some(3),
-            VirtualKeyCode::Key4 => Some(4),
-            VirtualKeyCode::Key5 => Some(5),
-            VirtualKeyCode::Key6 => Some(6),
-            VirtualKeyCode::Key7 => Some(7),
-            VirtualKeyCode::Key8 => Some(8),
-            VirtualKeyCode::Key9 => Some(9),
-            _ => None,
-        };
-        if let Some(key) = key {
-            return use_consumable_hotkey(gs, key - 1);
+    let mouse = gs.replay.mouse_pos(ctx);
+    if ctx.left_click {
+        // Clicking a consumable or spell on the side panel uses it, like its hotkey
+        match crate::gui::hud_click(&gs.ecs, mouse) {
+            Some(HudClick::Consumable(slot)) => return use_consumable_hotkey(gs, slot as i32),
+            Some(HudClick::Spell(slot)) => return cast_known_spell(gs, slot),
+            None => {}
+        }
+        // Clicking next to the player steps or attacks there; clicking further away travels
+        if let Some(tile) = crate::camera::screen_to_map(&gs.ecs, ctx, mouse) {
+            let player_pos = *gs.ecs.fetch::<Point>();
+            let (dx, dy) = (tile.x - player_pos.x, tile.y - player_pos.y);
+            if dx.abs() <= 1 && dy.abs() <= 1 && (dx, dy) != (0, 0) {
+                return try_move_player(dx, dy, &mut gs.ecs);
+            }
+            return travel::start(
+                &mut gs.ecs,
+                Destination::Tile {
+                    x: tile.x,
+                    y: tile.y,
+                },
+            );
         }
     }
-    if ctx.control && ctx.key.is_some() {
-        let key: Option<i32> = match ctx.key.unwrap() {
-            VirtualKeyCode::Key1 => Some(1),
-            VirtualKeyCode::Key2 => Some(2),
-            VirtualKeyCode::Key3 => Some(3),
-            VirtualKeyCode::Key4 => Some(4),
-            VirtualKeyCode::Key5 => Some(5),
-            VirtualKeyCode::Key6 => Some(6),
-            VirtualKeyCode::Key7 => Some(7),
-            VirtualKeyCode::Key8 => Some(8),
-            VirtualKeyCode::Key9 => Some(9),
-            _ => None,
-        };
-        if let Some(key) = key {
-            return use_spell_hotkey(gs, key - 1);
+    if crate::input::right_click() {
+        if let Some(tile) = crate::camera::screen_to_map(&gs.ecs, ctx, mouse) {
+            if !context_actions(&mut gs.ecs, tile).is_empty() {
+                return RunState::ShowContextMenu {
+                    x: tile.x,
+                    y: tile.y,
+                };
+            }
         }
     }
 
-    // Player movement
-    match ctx.key {
+    let action = match crate::input::game_action(ctx) {
         None => return RunState::AwaitingInput, // Nothing happened
-        Some(key) => match key {
-            VirtualKeyCode::Left | VirtualKeyCode::Numpad4 | VirtualKeyCode::H => {
-                return try_move_player(-1, 0, &mut gs.ecs)
-            }
-
-            VirtualKeyCode::Right | VirtualKeyCode::Numpad6 | VirtualKeyCode::L => {
-                return try_move_player(1, 0, &mut gs.ecs)
-            }
-
-            VirtualKeyCode::Up | VirtualKeyCode::Numpad8 | VirtualKeyCode::K => {
-                return try_move_player(0, -1, &mut gs.ecs)
-            }
-
-            VirtualKeyCode::Down | VirtualKeyCode::Numpad2 | VirtualKeyCode::J => {
-                return try_move_player(0, 1, &mut gs.ecs)
-            }
-
-            // Diagonals
-            VirtualKeyCode::Numpad9 | VirtualKeyCode::U => {
-                return try_move_player(1, -1, &mut gs.ecs)
-            }
+        Some(action) => action,
+    };
 
-            VirtualKeyCode::Numpad7 | VirtualKeyCode::Y => {
-                return try_move_player(-1, -1, &mut gs.ecs)
-            }
+    // Hotkeys
+    if let Some(slot) = action.hotkey_slot() {
+        return use_consumable_hotkey(gs, slot as i32);
+    }
+    if let Some(slot) = action.spell_slot() {
+        return cast_known_spell(gs, slot);
+    }
 
-            VirtualKeyCode::Numpad3 | VirtualKeyCode::N => {
-                return try_move_player(1, 1, &mut gs.ecs)
+    match action {
+        // Player movement
+        Action::MoveW => return try_move_player(-1, 0, &mut gs.ecs),
+        Action::MoveE => return try_move_player(1, 0, &mut gs.ecs),
+        Action::MoveN => return try_move_player(0, -1, &mut gs.ecs),
+        Action::MoveS => return try_move_player(0, 1, &mut gs.ecs),
+
+        // Diagonals
+        Action::MoveNE => return try_move_player(1, -1, &mut gs.ecs),
+        Action::MoveNW => return try_move_player(-1, -1, &mut gs.ecs),
+        Action::MoveSE => return try_move_player(1, 1, &mut gs.ecs),
+        Action::MoveSW => return try_move_player(-1, 1, &mut gs.ecs),
+
+        // Skip Turn
+        Action::Wait => return skip_turn(&mut gs.ecs),
+
+        // Level changes
+        Action::Descend => {
+            if try_next_level(&mut gs.ecs) {
+                return RunState::NextLevel;
             }
-
-            VirtualKeyCode::Numpad1 | VirtualKeyCode::B => {
-                return try_move_player(-1, 1, &mut gs.ecs)
+        }
+        Action::Ascend => {
+            if try_previous_level(&mut gs.ecs) {
+                return RunState::PreviousLevel;
             }
+        }
 
-            // Skip Turn
-            VirtualKeyCode::Numpad5 | VirtualKeyCode::Space => return skip_turn(&mut gs.ecs),
-
-            // Level changes
-            VirtualKeyCode::Period => {
-                if try_next_level(&mut gs.ecs) {
-                    return RunState::NextLevel;
-                }
-            }
-            VirtualKeyCode::Comma => {
-                if try_previous_level(&mut gs.ecs) {
-                    return RunState::PreviousLevel;
-                }
-            }
+        // Travel
+        Action::AutoExplore => return travel::start(&mut gs.ecs, Destination::Unexplored),
+        Action::TravelToStairs => return travel::start(&mut gs.ecs, Destination::DownStairs),
 
-            // Ranged
-            VirtualKeyCode::V => {
-                cycle_target(&mut gs.ecs);
-                return RunState::AwaitingInput;
+        // Ranged
+        Action::CycleTarget => {
+            cycle_target(&mut gs.ecs);
+            return RunState::AwaitingInput;
+        }
+        Action::Fire => return fire_on_target(&mut gs.ecs),
+
+        // Picking up items
+        Action::PickUp => get_item(&mut gs.ecs),
+        Action::Inventory => return RunState::ShowInventory,
+        Action::Drop => return RunState::ShowDropItem,
+        Action::RemoveItem => return RunState::ShowRemoveItem,
+        Action::Spellbook => return RunState::ShowSpellbook,
+        Action::MessageLog => {
+            return RunState::ShowLog {
+                offset: 0,
+                filter: None,
             }
-            VirtualKeyCode::F => return fire_on_target(&mut gs.ecs),
-
-            // Picking up items
-            VirtualKeyCode::G => get_item(&mut gs.ecs),
-            VirtualKeyCode::I => return RunState::ShowInventory,
-            VirtualKeyCode::D => return RunState::ShowDropItem,
-            VirtualKeyCode::R => return RunState::ShowRemoveItem,
+        }
 
-            // Save and Quit
-            VirtualKeyCode::Escape => return RunState::SaveGame,
+        // Save and Quit
+        Action::SaveGame => return RunState::SaveGame,
 
-            // Cheating!
-            VirtualKeyCode::Backslash => return RunState::ShowCheatMenu,
+        // Cheating!
+        Action::Cheats => return RunState::ShowCheatMenu,
 
-            _ => return RunState::AwaitingInput,
-        },
+        _ => return RunState::AwaitingInput,
     }
     RunState::Ticking
 }
@@ -547,6 +614,7 @@ fn fire_on_target(ecs: &mut World) -> RunState {
         let names = ecs.read_storage::<Name>();
         if let Some(name) = names.get(target) {
             crate::gamelog::Logger::new()
+                .category(crate::gamelog::LogCategory::Combat)
                 .append("You fire at")
                 .npc_name(&name.name)
                 .log();
@@ -558,8 +626,186 @@ fn fire_on_target(ecs: &mut World) -> RunState {
         RunState::Ticking
     } else {
         crate::gamelog::Logger::new()
+            .category(crate::gamelog::LogCategory::Combat)
             .append("You don't have a target selected!")
             .log();
         RunState::AwaitingInput
     }
 }
+
+/// Something the player can do from the menu that opens on a right-clicked tile.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum ContextAction {
+    Examine,
+    Attack(Entity),
+    Fire(Entity),
+    PickUp(Entity),
+    Talk(Entity),
+    Trade(Entity),
+    Walk,
+}
+
+/// What the player can do with a tile, with the menu label for each. Empty for tiles the player
+/// has never seen.
+pub fn context_actions(ecs: &mut World, tile: Point) -> Vec<(ContextAction, String)> {
+    let fire_targets: Vec<Entity> = get_player_target_list(ecs)
+        .iter()
+        .map(|(_, entity)| *entity)
+        .collect();
+    let map = ecs.fetch::<Map>();
+    if tile.x < 0 || tile.x >= map.width || tile.y < 0 || tile.y >= map.height {
+        return Vec::new();
+    }
+    let idx = map.xy_idx(tile.x, tile.y);
+    if !map.revealed_tiles[idx] {
+        return Vec::new();
+    }
+
+    let player_entity = *ecs.fetch::<Entity>();
+    let player_pos = *ecs.fetch::<Point>();
+    let (dx, dy) = (tile.x - player_pos.x, tile.y - player_pos.y);
+    let adjacent = dx.abs() <= 1 && dy.abs() <= 1 && (dx, dy) != (0, 0);
+    let mut actions = vec![(ContextAction::Examine, "Examine".to_string())];
+    if map.visible_tiles[idx] {
+        let hidden = ecs.read_storage::<crate::Hidden>();
+        let vendors = ecs.read_storage::<Vendor>();
+        let quips = ecs.read_storage::<Quips>();
+        let items = ecs.read_storage::<Item>();
+        let attributes = ecs.read_storage::<Attributes>();
+        let names = ecs.read_storage::<Name>();
+        crate::spatial::for_each_tile_content(idx, |entity| {
+            if entity == player_entity
+                || hidden.get(entity).is_some()
+                || names.get(entity).is_none()
+            {
+                return;
+            }
+            let name = crate::gui::get_item_display_name(ecs, entity);
+            if vendors.get(entity).is_some() {
+                if adjacent {
+                    actions.push((ContextAction::Talk(entity), format!("Talk to {}", name)));
+                    actions.push((ContextAction::Trade(entity), format!("Trade with {}", name)));
+                }
+            } else if quips.get(entity).is_some() && !is_hostile(ecs, entity) {
+                if adjacent {
+                    actions.push((ContextAction::Talk(entity), format!("Talk to {}", name)));
+                }
+            } else if attributes.get(entity).is_some() && is_hostile(ecs, entity) {
+                if adjacent {
+                    actions.push((ContextAction::Attack(entity), format!("Attack {}", name)));
+                }
+                if fire_targets.contains(&entity) {
+                    actions.push((ContextAction::Fire(entity), format!("Fire at {}", name)));
+                }
+            } else if items.get(entity).is_some() {
+                actions.push((ContextAction::PickUp(entity), format!("Pick up {}", name)));
+            }
+        });
+    }
+    if tile != player_pos && crate::tile_walkable(map.tiles[idx]) {
+        actions.push((ContextAction::Walk, "Walk here".to_string()));
+    }
+    actions
+}
+
+pub fn perform_context_action(ecs: &mut World, tile: Point, action: ContextAction) -> RunState {
+    let player_pos = *ecs.fetch::<Point>();
+    match action {
+        ContextAction::Examine => {
+            examine(ecs, tile);
+            RunState::AwaitingInput
+        }
+        ContextAction::Attack(_) => {
+            try_move_player(tile.x - player_pos.x, tile.y - player_pos.y, ecs)
+        }
+        ContextAction::Fire(target) => {
+            {
+                let mut targets = ecs.write_storage::<Target>();
+                targets.clear();
+                targets.insert(target, Target {}).expect("Insert fail");
+            }
+            fire_on_target(ecs)
+        }
+        ContextAction::PickUp(item) if tile == player_pos => {
+            pick_up(ecs, item);
+            RunState::Ticking
+        }
+        ContextAction::PickUp(item) => travel::start(ecs, Destination::Item(item)),
+        ContextAction::Talk(npc) => {
+            talk(ecs, npc);
+            RunState::AwaitingInput
+        }
+        ContextAction::Trade(vendor) => RunState::ShowVendor {
+            vendor,
+            mode: VendorMode::Sell,
+        },
+        ContextAction::Walk => travel::start(
+            ecs,
+            Destination::Tile {
+                x: tile.x,
+                y: tile.y,
+            },
+        ),
+    }
+}
+
+/// Has someone say one of their lines, or for a vendor without any, what they sell.
+fn talk(ecs: &World, npc: Entity) {
+    let names = ecs.read_storage::<Name>();
+    let name = match names.get(npc) {
+        Some(name) => &name.name,
+        None => return,
+    };
+    let line = match (
+        ecs.read_storage::<Quips>().get(npc),
+        ecs.read_storage::<Vendor>().get(npc),
+    ) {
+        (Some(quips), _) if !quips.available.is_empty() => {
+            let idx = (crate::rng::roll_dice(1, quips.available.len() as i32) - 1) as usize;
+            quips.available[idx].clone()
+        }
+        (_, Some(vendor)) => format!("Looking for {}?", vendor.categories.join(" or ")),
+        _ => {
+            crate::gamelog::Logger::new()
+                .category(crate::gamelog::LogCategory::Dialogue)
+                .npc_name(name)
+                .append("has nothing to say.")
+                .log();
+            return;
+        }
+    };
+    crate::gamelog::Logger::new()
+        .category(crate::gamelog::LogCategory::Dialogue)
+        .npc_name(name)
+        .append("says")
+        .item_name(line)
+        .log();
+}
+
+/// Logs what's on a tile, or what the player remembers of it if it's out of sight.
+fn examine(ecs: &World, tile: Point) {
+    let map = ecs.fetch::<Map>();
+    let idx = map.xy_idx(tile.x, tile.y);
+    let ground = crate::tile_name(map.tiles[idx]);
+    if !map.visible_tiles[idx] {
+        crate::gamelog::Logger::new()
+            .append(format!("You remember {} there.", ground))
+            .log();
+        return;
+    }
+    let player_entity = *ecs.fetch::<Entity>();
+    let hidden = ecs.read_storage::<crate::Hidden>();
+    let names = ecs.read_storage::<Name>();
+    let mut seen = Vec::new();
+    crate::spatial::for_each_tile_content(idx, |entity| {
+        if entity != player_entity && hidden.get(entity).is_none() && names.get(entity).is_some() {
+            seen.push(crate::gui::get_item_display_name(ecs, entity));
+        }
+    });
+    let message = if seen.is_empty() {
+        format!("You see {}.", ground)
+    } else {
+        format!("You see {} on {}.", seen.join(", "), ground)
+    };
+    crate::gamelog::Logger::new().append(message).log();
+}
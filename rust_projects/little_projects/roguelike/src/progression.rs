@@ -0,0 +1,59 @@
+//! What the player gets for gaining levels: a perk of their choice from the raws each time.
+
+use crate::components::*;
+use crate::raws::{available_perks, get_perk, natural_attack, skill_by_name, RAWS};
+use specs::prelude::*;
+
+/// The perks this entity can pick from right now.
+pub fn perk_choices(ecs: &World, entity: Entity) -> Vec<String> {
+    let level = ecs
+        .read_storage::<Pools>()
+        .get(entity)
+        .map_or(1, |pools| pools.level);
+    let perks = ecs.read_storage::<Perks>();
+    let taken = perks.get(entity).map_or(&[][..], |perks| &perks.taken[..]);
+    available_perks(&RAWS.lock().unwrap(), level, taken)
+}
+
+/// Gives the entity a perk, using up one of its pending picks. Returns false if the perk is
+/// unknown or the entity can't take perks.
+pub fn take_perk(ecs: &mut World, entity: Entity, name: &str) -> bool {
+    let raws = RAWS.lock().unwrap();
+    let perk = match get_perk(&raws, name) {
+        Some(perk) => perk,
+        None => {
+            crate::diagnostic(format!("WARNING - unknown perk [{}]", name));
+            return false;
+        }
+    };
+    let mut perks = ecs.write_storage::<Perks>();
+    let perks = match perks.get_mut(entity) {
+        Some(perks) => perks,
+        None => return false,
+    };
+    perks.taken.push(perk.name.clone());
+    perks.pending = i32::max(0, perks.pending - 1);
+    perks.crit_chance += perk.crit_chance.unwrap_or(0);
+    perks.carry_weight += perk.carry_weight.unwrap_or(0);
+    perks.spell_slots += perk.spell_slots.unwrap_or(0);
+    if let Some(attack) = &perk.natural_attack {
+        perks.extra_attacks.push(natural_attack(attack));
+    }
+    if let Some(skills) = ecs.write_storage::<Skills>().get_mut(entity) {
+        for (skill, bonus) in perk.skills.iter().flatten() {
+            if let Some(skill) = skill_by_name(skill) {
+                *skills.skills.entry(skill).or_insert(0) += bonus;
+            }
+        }
+    }
+    // Carry weight changes who's overburdened
+    ecs.write_storage::<EquipmentChanged>()
+        .insert(entity, EquipmentChanged {})
+        .expect("Insert failed");
+
+    crate::gamelog::Logger::new()
+        .color(rltk::GREEN)
+        .append(format!("You gain the {} perk.", perk.name))
+        .log();
+    true
+}
@@ -47,6 +47,11 @@ impl MasterTable {
             _ => "None".to_string(),
         }
     }
+
+    /// Rolls on the mobs alone.
+    pub fn roll_mob(&self) -> String {
+        self.mobs.roll()
+    }
 }
 
 #[derive(Default)]
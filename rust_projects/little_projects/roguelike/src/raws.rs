@@ -1,3 +1,5 @@
+mod background_structs;
+pub use background_structs::Background;
 mod item_structs;
 use item_structs::*;
 mod mob_structs;
@@ -10,17 +12,42 @@ mod loot_structs;
 use loot_structs::*;
 mod faction_structs;
 pub use faction_structs::*;
+mod perk_structs;
+pub use perk_structs::Perk;
 mod spell_structs;
 pub use spell_structs::Spell;
+use spell_structs::SpellShape;
 mod weapon_traits;
 pub use weapon_traits::*;
+mod level_structs;
+use level_structs::*;
+mod behavior_structs;
+pub use behavior_structs::*;
+mod status_structs;
+pub use status_structs::*;
+mod validate;
+pub use validate::RawError;
+mod merge;
+use merge::RawMerger;
 
 mod rawmaster;
 pub use rawmaster::*;
 use serde::Deserialize;
 use std::sync::Mutex;
 
-rltk::embedded_resource!(RAW_FILE, "../raws/spawns.json");
+rltk::embedded_resource!(BACKGROUNDS_FILE, "../raws/backgrounds.json");
+rltk::embedded_resource!(BEHAVIORS_FILE, "../raws/behaviors.json");
+rltk::embedded_resource!(FACTIONS_FILE, "../raws/factions.json");
+rltk::embedded_resource!(ITEMS_FILE, "../raws/items.json");
+rltk::embedded_resource!(LEVELS_FILE, "../raws/levels.json");
+rltk::embedded_resource!(LOOT_TABLES_FILE, "../raws/loot_tables.json");
+rltk::embedded_resource!(MOBS_FILE, "../raws/mobs.json");
+rltk::embedded_resource!(PERKS_FILE, "../raws/perks.json");
+rltk::embedded_resource!(PROPS_FILE, "../raws/props.json");
+rltk::embedded_resource!(SPAWN_TABLE_FILE, "../raws/spawn_table.json");
+rltk::embedded_resource!(SPELLS_FILE, "../raws/spells.json");
+rltk::embedded_resource!(STATUS_EFFECTS_FILE, "../raws/status_effects.json");
+rltk::embedded_resource!(WEAPON_TRAITS_FILE, "../raws/weapon_traits.json");
 
 lazy_static! {
     pub static ref RAWS: Mutex<RawMaster> = Mutex::new(RawMaster::empty());
@@ -36,19 +63,172 @@ pub struct Raws {
     pub faction_table: Vec<FactionInfo>,
     pub spells: Vec<Spell>,
     pub weapon_traits: Vec<WeaponTrait>,
+    pub levels: Vec<Level>,
+    pub behaviors: Vec<Behavior>,
+    pub status_effects: Vec<StatusEffectDef>,
+    pub backgrounds: Vec<Background>,
+    pub perks: Vec<Perk>,
+}
+
+/// Raw files in this directory take priority over the copies embedded in the executable.
+pub const RAW_DIR: &str = "./raws";
+/// Every directory in here is a mod, merged over the base raws in name order.
+pub const MOD_DIR: &str = "./mods";
+
+/// A list of (source, contents) pairs for every raw file in one layer.
+pub type RawFiles = Vec<(String, String)>;
+
+fn embedded_raw_files() -> RawFiles {
+    rltk::link_resource!(BACKGROUNDS_FILE, "../../raws/backgrounds.json");
+    rltk::link_resource!(BEHAVIORS_FILE, "../../raws/behaviors.json");
+    rltk::link_resource!(FACTIONS_FILE, "../../raws/factions.json");
+    rltk::link_resource!(ITEMS_FILE, "../../raws/items.json");
+    rltk::link_resource!(LEVELS_FILE, "../../raws/levels.json");
+    rltk::link_resource!(LOOT_TABLES_FILE, "../../raws/loot_tables.json");
+    rltk::link_resource!(MOBS_FILE, "../../raws/mobs.json");
+    rltk::link_resource!(PERKS_FILE, "../../raws/perks.json");
+    rltk::link_resource!(PROPS_FILE, "../../raws/props.json");
+    rltk::link_resource!(SPAWN_TABLE_FILE, "../../raws/spawn_table.json");
+    rltk::link_resource!(SPELLS_FILE, "../../raws/spells.json");
+    rltk::link_resource!(STATUS_EFFECTS_FILE, "../../raws/status_effects.json");
+    rltk::link_resource!(WEAPON_TRAITS_FILE, "../../raws/weapon_traits.json");
+
+    let names = [
+        "backgrounds",
+        "behaviors",
+        "factions",
+        "items",
+        "levels",
+        "loot_tables",
+        "mobs",
+        "perks",
+        "props",
+        "spawn_table",
+        "spells",
+        "status_effects",
+        "weapon_traits",
+    ];
+    names
+        .iter()
+        .map(|name| {
+            // Retrieve the raw data as an array of u8 (8-bit unsigned chars)
+            let raw_data = rltk::embedding::EMBED
+                .lock()
+                .get_resource(format!("../../raws/{}.json", name))
+                .unwrap();
+            let raw_string =
+                std::str::from_utf8(raw_data).expect("Unable to convert to a valid UTF-8 string.");
+            (format!("embedded {}.json", name), raw_string.to_string())
+        })
+        .collect()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn json_files(_dir: &std::path::Path) -> Option<RawFiles> {
+    None
+}
+
+/// Reads every .json file in a directory, in name order. None if the directory doesn't exist.
+#[cfg(not(target_arch = "wasm32"))]
+fn json_files(dir: &std::path::Path) -> Option<RawFiles> {
+    let mut paths: Vec<_> = std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+    Some(
+        paths
+            .iter()
+            .filter_map(|path| {
+                let source = path.display().to_string();
+                match std::fs::read_to_string(path) {
+                    Ok(contents) => Some((source, contents)),
+                    Err(e) => {
+                        crate::diagnostic(format!("WARNING - unable to read {}: {}", source, e));
+                        None
+                    }
+                }
+            })
+            .collect(),
+    )
+}
+
+#[cfg(target_arch = "wasm32")]
+fn mod_dirs() -> Vec<std::path::PathBuf> {
+    Vec::new()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn mod_dirs() -> Vec<std::path::PathBuf> {
+    let mut dirs: Vec<_> = match std::fs::read_dir(MOD_DIR) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.is_dir())
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    dirs.sort();
+    dirs
+}
+
+/// The base raws (from disk if present, otherwise embedded) followed by each mod.
+fn raw_layers() -> Vec<RawFiles> {
+    let base = json_files(std::path::Path::new(RAW_DIR)).unwrap_or_else(embedded_raw_files);
+    let mut layers = vec![base];
+    for dir in mod_dirs() {
+        layers.push(json_files(&dir).unwrap_or_default());
+    }
+    layers
+}
+
+/// Merges the layers in order: the base raws first, then each mod. Returns the raws, if they could
+/// be read, and every problem found, each naming the file it is in.
+pub fn merge_raws(layers: &[RawFiles]) -> (Option<RawMaster>, Vec<RawError>) {
+    let mut merger = RawMerger::new();
+    for (layer, files) in layers.iter().enumerate() {
+        for (source, contents) in files.iter() {
+            merger.add_file(layer, source, contents);
+        }
+    }
+
+    let raws = merger.finish();
+    let entry_paths = merger.entry_paths();
+    let mut errors = merger.errors;
+    let master = raws.map(|raws| {
+        let mut master = RawMaster::empty();
+        master.load(raws);
+        master.entry_paths = entry_paths;
+        errors.append(&mut master.validate());
+        master
+    });
+    (master, errors)
 }
 
 pub fn load_raws() {
-    rltk::link_resource!(RAW_FILE, "../../raws/spawns.json");
-
-    // Retrieve the raw data as an array of u8 (8-bit unsigned chars)
-    let raw_data = rltk::embedding::EMBED
-        .lock()
-        .get_resource("../../raws/spawns.json".to_string())
-        .unwrap();
-    let raw_string =
-        std::str::from_utf8(raw_data).expect("Unable to convert to a valid UTF-8 string.");
-    let decoder: Raws = serde_json::from_str(raw_string).expect("Unable to parse JSON");
-
-    RAWS.lock().unwrap().load(decoder);
+    let (master, errors) = merge_raws(&raw_layers());
+    for error in errors.iter() {
+        crate::diagnostic(format!("WARNING - {}", error));
+    }
+    let master = master.unwrap_or_else(|| {
+        crate::diagnostic("WARNING - falling back to the embedded raws".to_string());
+        merge_raws(&[embedded_raw_files()])
+            .0
+            .expect("Unable to parse embedded raws")
+    });
+
+    *RAWS.lock().unwrap() = master;
+}
+
+/// Re-reads the raws and mods from disk, returning every problem found. The current raws are
+/// kept if the new ones can't be read. Only affects things spawned after the reload.
+pub fn reload_raws() -> Result<Vec<RawError>, Vec<RawError>> {
+    let (master, errors) = merge_raws(&raw_layers());
+    match master {
+        Some(master) => {
+            *RAWS.lock().unwrap() = master;
+            Ok(errors)
+        }
+        None => Err(errors),
+    }
 }
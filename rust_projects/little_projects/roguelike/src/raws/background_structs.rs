@@ -0,0 +1,14 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Where a new character comes from: it decides their skills, spells and starting kit.
+#[derive(Deserialize, Debug)]
+pub struct Background {
+    pub name: String,
+    pub description: String,
+    /// Skill levels; any skill not listed starts at 1
+    pub skills: Option<HashMap<String, i32>>,
+    pub spells: Option<Vec<String>>,
+    pub equipped: Option<Vec<String>>,
+    pub carried: Option<Vec<String>>,
+}
@@ -0,0 +1,26 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+pub struct Behavior {
+    pub name: String,
+    pub actions: Vec<BehaviorAction>,
+}
+
+/// One thing a mob may do on its turn. The highest scoring action whose conditions hold, and that
+/// finds something to do, takes the turn.
+#[derive(Deserialize, Debug)]
+pub struct BehaviorAction {
+    pub action: String,
+    pub score: f32,
+    /// Only considered while the mob's hit points are below this fraction of its maximum
+    pub hp_below: Option<f32>,
+    /// Only considered while the mob's hit points are above this fraction of its maximum
+    pub hp_above: Option<f32>,
+    /// Chance (0 to 1) of considering the action at all on a given turn
+    pub chance: Option<f32>,
+    /// Action specific distance, such as how far a guard strays from home
+    pub range: Option<i32>,
+    pub spell: Option<String>,
+    /// The faction reactions this action responds to
+    pub reactions: Option<Vec<String>>,
+}
@@ -4,7 +4,11 @@ use std::collections::HashMap;
 #[derive(Deserialize, Debug)]
 pub struct FactionInfo {
     pub name : String,
-    pub responses : HashMap<String, String>
+    pub responses : HashMap<String, String>,
+    /// Members who see an enemy alert every other member within this many tiles
+    pub alert_radius : Option<i32>,
+    /// How many turns members within the alert radius flee for when a leader dies
+    pub rout_turns : Option<i32>
 }
 
 #[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
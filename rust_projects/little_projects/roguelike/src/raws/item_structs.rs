@@ -14,7 +14,12 @@ pub struct Item {
     pub vendor_category : Option<String>,
     pub magic : Option<MagicItem>,
     pub attributes : Option<ItemAttributeBonus>,
-    pub template_magic : Option<ItemMagicTemplate>
+    pub template_magic : Option<ItemMagicTemplate>,
+    /// Percentages, for when the item is worn
+    pub status_resistances : Option<HashMap<String, i32>>,
+    pub status_immunities : Option<Vec<String>>,
+    /// Picking it up ends the run in victory
+    pub wins_game : Option<bool>
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -41,13 +46,25 @@ pub struct Weapon {
     pub hit_bonus: i32,
     pub proc_chance : Option<f32>,
     pub proc_target : Option<String>,
-    pub proc_effects : Option<HashMap<String, String>>
+    pub proc_effects : Option<HashMap<String, String>>,
+    /// "physical" if missing
+    pub damage_type : Option<String>,
+    pub rider : Option<WeaponRider>
+}
+
+/// Extra typed damage dealt with every hit
+#[derive(Deserialize, Debug, Clone)]
+pub struct WeaponRider {
+    pub damage_type : String,
+    pub damage : String
 }
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct Wearable {
     pub armor_class: f32,
     pub slot : String,
+    /// Damage type to "50%" or a flat "2"; negative values are weaknesses
+    pub damage_resistances : Option<HashMap<String, String>>
 }
 
 #[derive(Deserialize, Debug, Clone)]
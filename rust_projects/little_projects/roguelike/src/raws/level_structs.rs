@@ -0,0 +1,17 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+pub struct Level {
+    pub name: String,
+    pub depth: i32,
+    pub map_name: String,
+    pub theme: Option<String>,
+    pub starter: LevelBuilder,
+    pub builders: Vec<LevelBuilder>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct LevelBuilder {
+    pub name: String,
+    pub params: Option<Vec<String>>,
+}
@@ -0,0 +1,286 @@
+use super::{RawError, Raws};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// The sections of `Raws`. Every entry in a section is identified by its "name", except in the
+/// spawn table, where an entry is one depth band of a name (see `entry_key`).
+const SECTIONS: &[&str] = &[
+    "items",
+    "mobs",
+    "props",
+    "spawn_table",
+    "loot_tables",
+    "faction_table",
+    "spells",
+    "weapon_traits",
+    "levels",
+    "behaviors",
+    "status_effects",
+    "backgrounds",
+    "perks",
+];
+
+struct Entry {
+    name: String,
+    key: String,
+    value: Value,
+    source: String,
+    // Where the entry was defined, such as "./raws/items.json:$.items[3]"
+    path: String,
+    layer: usize,
+}
+
+/// Merges raw files into a single `Raws`. Files are added in layers: layer 0 is the base game and
+/// each mod is a further layer. An entry replaces any earlier entry with the same key, and mods
+/// can also "remove" entries or "patch" some of their fields. Removes and patches go by name, so
+/// in the spawn table they apply to every depth band of that name.
+pub struct RawMerger {
+    sections: Vec<Vec<Entry>>,
+    pub errors: Vec<RawError>,
+}
+
+fn section_index(section: &str) -> Option<usize> {
+    SECTIONS.iter().position(|s| *s == section)
+}
+
+/// What an entry replaces. A name can appear in the spawn table once per depth range, so that an
+/// item can be common on some levels and rare on others.
+fn entry_key(section: usize, name: &str, value: &Value) -> String {
+    if SECTIONS[section] == "spawn_table" {
+        let depth = |field: &str| value.get(field).and_then(|d| d.as_i64()).unwrap_or(0);
+        format!(
+            "{} (depths {}-{})",
+            name,
+            depth("min_depth"),
+            depth("max_depth")
+        )
+    } else {
+        name.to_string()
+    }
+}
+
+/// JSON merge patch: objects are merged recursively, a null removes the field, anything else
+/// replaces it.
+fn apply_patch(target: &mut Value, patch: &Value) {
+    match (target, patch) {
+        (Value::Object(target), Value::Object(patch)) => {
+            for (key, value) in patch.iter() {
+                if value.is_null() {
+                    target.remove(key);
+                } else {
+                    apply_patch(target.entry(key.clone()).or_insert(Value::Null), value);
+                }
+            }
+        }
+        (target, patch) => *target = patch.clone(),
+    }
+}
+
+impl RawMerger {
+    pub fn new() -> RawMerger {
+        RawMerger {
+            sections: SECTIONS.iter().map(|_| Vec::new()).collect(),
+            errors: Vec::new(),
+        }
+    }
+
+    fn error(&mut self, path: String, message: String) {
+        self.errors.push(RawError { path, message });
+    }
+
+    pub fn add_file(&mut self, layer: usize, source: &str, json: &str) {
+        let root: Value = match serde_json::from_str(json) {
+            Ok(root) => root,
+            Err(e) => {
+                self.error(
+                    format!("{}:$", source),
+                    format!("unable to parse JSON: {}", e),
+                );
+                return;
+            }
+        };
+        let root = match root {
+            Value::Object(root) => root,
+            _ => {
+                self.error(format!("{}:$", source), "expected an object".to_string());
+                return;
+            }
+        };
+
+        for (key, value) in root.iter() {
+            match key.as_str() {
+                "remove" => {
+                    self.for_each_section(source, key, value, |merger, section, path, v| {
+                        merger.remove_entry(section, path, v)
+                    })
+                }
+                "patch" => self.for_each_section(source, key, value, |merger, section, path, v| {
+                    merger.patch_entry(section, path, v)
+                }),
+                _ => match section_index(key) {
+                    Some(section) => self.for_each_value(
+                        &format!("{}:$.{}", source, key),
+                        value,
+                        |merger, path, v| merger.add_entry(section, layer, source, path, v),
+                    ),
+                    None => self.error(
+                        format!("{}:$.{}", source, key),
+                        format!("unknown section [{}]", key),
+                    ),
+                },
+            }
+        }
+    }
+
+    fn for_each_section<F>(&mut self, source: &str, key: &str, value: &Value, mut f: F)
+    where
+        F: FnMut(&mut RawMerger, usize, String, &Value),
+    {
+        let path = format!("{}:$.{}", source, key);
+        let sections = match value {
+            Value::Object(sections) => sections,
+            _ => {
+                self.error(path, "expected an object of sections".to_string());
+                return;
+            }
+        };
+        for (section_name, entries) in sections.iter() {
+            let path = format!("{}.{}", path, section_name);
+            match section_index(section_name) {
+                Some(section) => self.for_each_value(&path, entries, |merger, path, v| {
+                    f(merger, section, path, v)
+                }),
+                None => self.error(path, format!("unknown section [{}]", section_name)),
+            }
+        }
+    }
+
+    fn for_each_value<F>(&mut self, path: &str, value: &Value, mut f: F)
+    where
+        F: FnMut(&mut RawMerger, String, &Value),
+    {
+        match value {
+            Value::Array(values) => {
+                for (i, v) in values.iter().enumerate() {
+                    f(self, format!("{}[{}]", path, i), v);
+                }
+            }
+            _ => self.error(path.to_string(), "expected an array".to_string()),
+        }
+    }
+
+    fn entry_name(&mut self, path: &str, value: &Value) -> Option<String> {
+        match value.get("name").and_then(|n| n.as_str()) {
+            Some(name) => Some(name.to_string()),
+            None => {
+                self.error(path.to_string(), "entry has no name".to_string());
+                None
+            }
+        }
+    }
+
+    fn add_entry(
+        &mut self,
+        section: usize,
+        layer: usize,
+        source: &str,
+        path: String,
+        value: &Value,
+    ) {
+        let name = match self.entry_name(&path, value) {
+            Some(name) => name,
+            None => return,
+        };
+        let entry = Entry {
+            key: entry_key(section, &name, value),
+            name,
+            value: value.clone(),
+            source: source.to_string(),
+            path: path.clone(),
+            layer,
+        };
+
+        match self.sections[section]
+            .iter()
+            .position(|e| e.key == entry.key)
+        {
+            None => self.sections[section].push(entry),
+            Some(i) => {
+                let existing = &self.sections[section][i];
+                // Mods are expected to override the base game, but not each other
+                if existing.layer == layer || existing.layer != 0 {
+                    let message = format!(
+                        "[{}] is also defined in {}; using this definition",
+                        entry.key, existing.source
+                    );
+                    self.error(path, message);
+                }
+                self.sections[section][i] = entry;
+            }
+        }
+    }
+
+    fn remove_entry(&mut self, section: usize, path: String, value: &Value) {
+        let name = match value.as_str() {
+            Some(name) => name,
+            None => {
+                self.error(path, "expected the name of an entry to remove".to_string());
+                return;
+            }
+        };
+        let before = self.sections[section].len();
+        self.sections[section].retain(|e| e.name != name);
+        if self.sections[section].len() == before {
+            self.error(path, format!("can't remove unknown entry [{}]", name));
+        }
+    }
+
+    fn patch_entry(&mut self, section: usize, path: String, value: &Value) {
+        let name = match self.entry_name(&path, value) {
+            Some(name) => name,
+            None => return,
+        };
+        let mut patched = false;
+        for entry in self.sections[section].iter_mut().filter(|e| e.name == name) {
+            apply_patch(&mut entry.value, value);
+            entry.key = entry_key(section, &entry.name, &entry.value);
+            patched = true;
+        }
+        if !patched {
+            self.error(path, format!("can't patch unknown entry [{}]", name));
+        }
+    }
+
+    /// Where each merged entry was defined, by section and in the order `finish` puts them, so
+    /// that problems found later can name the file they came from.
+    pub fn entry_paths(&self) -> HashMap<String, Vec<String>> {
+        SECTIONS
+            .iter()
+            .zip(self.sections.iter())
+            .map(|(name, entries)| {
+                let paths = entries.iter().map(|e| e.path.clone()).collect();
+                (name.to_string(), paths)
+            })
+            .collect()
+    }
+
+    /// Builds the merged raws. Returns None if the result doesn't deserialize; the reason is
+    /// added to the errors.
+    pub fn finish(&mut self) -> Option<Raws> {
+        let mut root = Map::new();
+        for (name, entries) in SECTIONS.iter().zip(self.sections.iter()) {
+            let values = entries.iter().map(|e| e.value.clone()).collect();
+            root.insert(name.to_string(), Value::Array(values));
+        }
+        match serde_json::from_value(Value::Object(root)) {
+            Ok(raws) => Some(raws),
+            Err(e) => {
+                self.error(
+                    "$".to_string(),
+                    format!("unable to read merged raws: {}", e),
+                );
+                None
+            }
+        }
+    }
+}
@@ -9,6 +9,7 @@ pub struct Mob {
     pub blocks_tile: bool,
     pub vision_range: i32,
     pub movement: String,
+    pub behavior: Option<String>,
     pub quips: Option<Vec<String>>,
     pub attributes: MobAttributes,
     pub skills: Option<HashMap<String, i32>>,
@@ -24,6 +25,13 @@ pub struct Mob {
     pub vendor: Option<Vec<String>>,
     pub abilities: Option<Vec<MobAbility>>,
     pub on_death: Option<Vec<MobAbility>>,
+    pub leader: Option<bool>,
+    pub hearing: Option<i32>,
+    /// Percentages taken off the duration of status effects
+    pub status_resistances: Option<HashMap<String, i32>>,
+    pub status_immunities: Option<Vec<String>>,
+    /// Damage type to "50%" or a flat "2"; negative values are weaknesses
+    pub damage_resistances: Option<HashMap<String, String>>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -45,6 +53,7 @@ pub struct NaturalAttack {
     pub name: String,
     pub hit_bonus: i32,
     pub damage: String,
+    pub damage_type: Option<String>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -0,0 +1,24 @@
+use super::NaturalAttack;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Something the player can pick when they gain a level. Each perk can only be taken once.
+#[derive(Deserialize, Debug)]
+pub struct Perk {
+    pub name: String,
+    pub description: String,
+    /// The lowest character level it can be taken at
+    pub min_level: Option<i32>,
+    /// Perks that have to be taken first
+    pub requires: Option<Vec<String>>,
+    /// An extra attack made alongside every melee attack
+    pub natural_attack: Option<NaturalAttack>,
+    /// Percent chance of a hit doing double dice
+    pub crit_chance: Option<i32>,
+    /// Extra pounds that can be carried before being overburdened
+    pub carry_weight: Option<i32>,
+    /// Extra spells that can be known at once
+    pub spell_slots: Option<i32>,
+    /// Skill levels to add
+    pub skills: Option<HashMap<String, i32>>,
+}
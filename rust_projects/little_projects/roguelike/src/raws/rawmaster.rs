@@ -1,5 +1,7 @@
 use super::{faction_structs::Reaction, Raws};
 use crate::components::*;
+use crate::map::MapTheme;
+use crate::map_builders::{initial_builder_by_name, meta_builder_by_name, BuilderChain};
 use crate::random_table::{MasterTable, RandomTable};
 use crate::{attr_bonus, mana_at_level, npc_hp};
 use regex::Regex;
@@ -28,6 +30,14 @@ pub fn parse_dice_string(dice: &str) -> (i32, i32, i32) {
     (n_dice, die_type, die_bonus)
 }
 
+/// True if the whole string is a dice expression, such as "1d6" or "2d8+2".
+pub fn is_dice_string(dice: &str) -> bool {
+    lazy_static! {
+        static ref FULL_DICE_RE: Regex = Regex::new(r"^\d+d\d+([\+\-]\d+)?$").unwrap();
+    }
+    FULL_DICE_RE.is_match(dice)
+}
+
 #[derive(PartialEq, Eq, Hash, Copy, Clone)]
 pub enum SpawnType {
     AtPosition { x: i32, y: i32 },
@@ -36,13 +46,23 @@ pub enum SpawnType {
 }
 
 pub struct RawMaster {
-    raws: Raws,
-    item_index: HashMap<String, usize>,
-    mob_index: HashMap<String, usize>,
-    prop_index: HashMap<String, usize>,
-    loot_index: HashMap<String, usize>,
-    faction_index: HashMap<String, HashMap<String, Reaction>>,
-    spell_index: HashMap<String, usize>,
+    pub(super) raws: Raws,
+    pub(super) item_index: HashMap<String, usize>,
+    pub(super) mob_index: HashMap<String, usize>,
+    pub(super) prop_index: HashMap<String, usize>,
+    pub(super) loot_index: HashMap<String, usize>,
+    pub(super) faction_index: HashMap<String, HashMap<String, Reaction>>,
+    pub(super) spell_index: HashMap<String, usize>,
+    pub(super) level_index: HashMap<i32, usize>,
+    pub(super) behavior_index: HashMap<String, usize>,
+    pub(super) status_index: HashMap<String, usize>,
+    pub(super) background_index: HashMap<String, usize>,
+    pub(super) perk_index: HashMap<String, usize>,
+    // Entries past these counts are magic items generated by `load`, not read from the file
+    pub(super) raw_item_count: usize,
+    pub(super) raw_spawn_count: usize,
+    // Where each entry read from the files came from, by section; see `RawMerger::entry_paths`
+    pub(super) entry_paths: HashMap<String, Vec<String>>,
 }
 
 struct NewMagicItem {
@@ -62,6 +82,11 @@ impl RawMaster {
                 faction_table: Vec::new(),
                 spells: Vec::new(),
                 weapon_traits: Vec::new(),
+                levels: Vec::new(),
+                behaviors: Vec::new(),
+                status_effects: Vec::new(),
+                backgrounds: Vec::new(),
+                perks: Vec::new(),
             },
             item_index: HashMap::new(),
             mob_index: HashMap::new(),
@@ -69,6 +94,14 @@ impl RawMaster {
             loot_index: HashMap::new(),
             faction_index: HashMap::new(),
             spell_index: HashMap::new(),
+            level_index: HashMap::new(),
+            behavior_index: HashMap::new(),
+            status_index: HashMap::new(),
+            background_index: HashMap::new(),
+            perk_index: HashMap::new(),
+            raw_item_count: 0,
+            raw_spawn_count: 0,
+            entry_paths: HashMap::new(),
         }
     }
 
@@ -88,7 +121,7 @@ impl RawMaster {
                     });
                 }
             } else {
-                rltk::console::log(format!(
+                crate::diagnostic(format!(
                     "{} is marked as templated, but isn't a weapon or armor.",
                     item.name
                 ));
@@ -177,8 +210,13 @@ impl RawMaster {
                         if let Some(base_value) = base_item_copy.base_value.as_mut() {
                             *base_value *= 2.0;
                         }
-                        weapon.proc_chance = Some(0.25);
-                        weapon.proc_effects = Some(wt.effects.clone());
+                        if !wt.effects.is_empty() {
+                            weapon.proc_chance = Some(0.25);
+                            weapon.proc_effects = Some(wt.effects.clone());
+                        }
+                        if wt.rider.is_some() {
+                            weapon.rider = wt.rider.clone();
+                        }
 
                         let real_name = base_item_copy.name.clone();
                         self.raws.items.push(base_item_copy);
@@ -205,7 +243,7 @@ impl RawMaster {
 
         for (i, item) in self.raws.items.iter().enumerate() {
             if used_names.contains(&item.name) {
-                rltk::console::log(format!(
+                crate::diagnostic(format!(
                     "WARNING -  duplicate item name in raws [{}]",
                     item.name
                 ));
@@ -217,7 +255,7 @@ impl RawMaster {
         }
         for (i, mob) in self.raws.mobs.iter().enumerate() {
             if used_names.contains(&mob.name) {
-                rltk::console::log(format!(
+                crate::diagnostic(format!(
                     "WARNING -  duplicate mob name in raws [{}]",
                     mob.name
                 ));
@@ -227,7 +265,7 @@ impl RawMaster {
         }
         for (i, prop) in self.raws.props.iter().enumerate() {
             if used_names.contains(&prop.name) {
-                rltk::console::log(format!(
+                crate::diagnostic(format!(
                     "WARNING -  duplicate prop name in raws [{}]",
                     prop.name
                 ));
@@ -238,7 +276,7 @@ impl RawMaster {
 
         for spawn in self.raws.spawn_table.iter() {
             if !used_names.contains(&spawn.name) {
-                rltk::console::log(format!(
+                crate::diagnostic(format!(
                     "WARNING - Spawn tables references unspecified entity {}",
                     spawn.name
                 ));
@@ -268,11 +306,117 @@ impl RawMaster {
             self.spell_index.insert(spell.name.clone(), i);
         }
 
+        for (i, level) in self.raws.levels.iter().enumerate() {
+            if self.level_index.contains_key(&level.depth) {
+                crate::diagnostic(format!(
+                    "WARNING -  more than one level for depth {} in raws [{}]",
+                    level.depth, level.name
+                ));
+            }
+            self.level_index.insert(level.depth, i);
+        }
+
+        for (i, behavior) in self.raws.behaviors.iter().enumerate() {
+            self.behavior_index.insert(behavior.name.clone(), i);
+        }
+
+        for (i, status) in self.raws.status_effects.iter().enumerate() {
+            self.status_index.insert(status.name.clone(), i);
+        }
+
+        for (i, background) in self.raws.backgrounds.iter().enumerate() {
+            self.background_index.insert(background.name.clone(), i);
+        }
+
+        for (i, perk) in self.raws.perks.iter().enumerate() {
+            self.perk_index.insert(perk.name.clone(), i);
+        }
+
+        self.raw_item_count = self.raws.items.len();
+        self.raw_spawn_count = self.raws.spawn_table.len();
         self.build_magic_weapon_or_armor(&items_to_build);
         self.build_traited_weapons(&items_to_build);
     }
 }
 
+/// Mobs that don't name a behavior in the raws use this one.
+pub const DEFAULT_BEHAVIOR: &str = "Default";
+
+pub fn get_behavior<'a>(raws: &'a RawMaster, name: &str) -> Option<&'a super::Behavior> {
+    raws.behavior_index
+        .get(name)
+        .map(|idx| &raws.raws.behaviors[*idx])
+}
+
+/// Whether a mob with this behavior (the default if it has none) ever takes the action.
+pub fn behavior_has_action(raws: &RawMaster, behavior: Option<&str>, action: &str) -> bool {
+    get_behavior(raws, behavior.unwrap_or(DEFAULT_BEHAVIOR))
+        .is_some_and(|b| b.actions.iter().any(|a| a.action == action))
+}
+
+pub fn get_status_effect<'a>(
+    raws: &'a RawMaster,
+    name: &str,
+) -> Option<&'a super::StatusEffectDef> {
+    raws.status_index
+        .get(name)
+        .map(|idx| &raws.raws.status_effects[*idx])
+}
+
+/// New characters start with this background unless the player picks another.
+pub const DEFAULT_BACKGROUND: &str = "Tavern Brawler";
+
+pub fn get_background<'a>(raws: &'a RawMaster, name: &str) -> Option<&'a super::Background> {
+    raws.background_index
+        .get(name)
+        .map(|idx| &raws.raws.backgrounds[*idx])
+}
+
+/// Background names, in the order the raws list them.
+pub fn background_names(raws: &RawMaster) -> Vec<String> {
+    raws.raws
+        .backgrounds
+        .iter()
+        .map(|b| b.name.clone())
+        .collect()
+}
+
+pub fn get_perk<'a>(raws: &'a RawMaster, name: &str) -> Option<&'a super::Perk> {
+    raws.perk_index.get(name).map(|idx| &raws.raws.perks[*idx])
+}
+
+pub fn get_spell<'a>(raws: &'a RawMaster, name: &str) -> Option<&'a super::Spell> {
+    raws.spell_index
+        .get(name)
+        .map(|idx| &raws.raws.spells[*idx])
+}
+
+/// The perks that can be taken at this level by someone who already has these, in the order the
+/// raws list them.
+pub fn available_perks(raws: &RawMaster, level: i32, taken: &[String]) -> Vec<String> {
+    raws.raws
+        .perks
+        .iter()
+        .filter(|perk| {
+            !taken.contains(&perk.name)
+                && perk.min_level.unwrap_or(0) <= level
+                && perk.requires.iter().flatten().all(|r| taken.contains(r))
+        })
+        .map(|perk| perk.name.clone())
+        .collect()
+}
+
+/// How far a faction's alerts carry and how long its members rout for; zero if it has neither.
+pub fn faction_tactics(raws: &RawMaster, faction: &str) -> (i32, i32) {
+    raws.raws
+        .faction_table
+        .iter()
+        .find(|f| f.name == faction)
+        .map_or((0, 0), |f| {
+            (f.alert_radius.unwrap_or(0), f.rout_turns.unwrap_or(0))
+        })
+}
+
 #[inline(always)]
 pub fn faction_reaction(my_faction: &str, their_faction: &str, raws: &RawMaster) -> Reaction {
     //println!("Looking for reaction to [{}] by [{}]", my_faction, their_faction);
@@ -307,6 +451,9 @@ fn find_slot_for_equippable_item(tag: &str, raws: &RawMaster) -> EquipmentSlot {
     panic!("Trying to equip {}, but it has no slot tag.", tag);
 }
 
+/// How many of each item a vendor holds when fully stocked.
+pub const VENDOR_STOCK: i32 = 3;
+
 pub fn get_vendor_items(categories: &[String], raws: &RawMaster) -> Vec<(String, f32)> {
     let mut result: Vec<(String, f32)> = Vec::new();
 
@@ -401,12 +548,120 @@ pub fn string_to_slot(slot: &str) -> EquipmentSlot {
         "Hands" => EquipmentSlot::Hands,
         "Melee" => EquipmentSlot::Melee,
         _ => {
-            rltk::console::log(format!("Warning: unknown equipment slot type [{}])", slot));
+            crate::diagnostic(format!("Warning: unknown equipment slot type [{}])", slot));
             EquipmentSlot::Melee
         }
     }
 }
 
+pub fn natural_attack(raw: &super::NaturalAttack) -> NaturalAttack {
+    let (n, d, b) = parse_dice_string(&raw.damage);
+    NaturalAttack {
+        name: raw.name.clone(),
+        hit_bonus: raw.hit_bonus,
+        damage_n_dice: n,
+        damage_die_type: d,
+        damage_bonus: b,
+        damage_type: raw
+            .damage_type
+            .as_ref()
+            .map_or(DamageType::Physical, |t| string_to_damage_type(t)),
+    }
+}
+
+/// Reads a spell's shape, filling in the usual size for its kind if the raws leave it out.
+pub fn target_shape(raw: &super::SpellShape) -> Option<TargetShape> {
+    match raw.kind.as_str() {
+        "bolt" => Some(TargetShape::Bolt),
+        "cone" => Some(TargetShape::Cone {
+            length: raw.size.unwrap_or(4),
+        }),
+        "line" => Some(TargetShape::Line {
+            length: raw.size.unwrap_or(8),
+        }),
+        "burst" => Some(TargetShape::Burst {
+            radius: raw.size.unwrap_or(2),
+        }),
+        "chain" => Some(TargetShape::Chain {
+            jumps: raw.size.unwrap_or(3),
+        }),
+        _ => None,
+    }
+}
+
+pub fn skill_by_name(name: &str) -> Option<Skill> {
+    match name {
+        "Melee" => Some(Skill::Melee),
+        "Defense" => Some(Skill::Defense),
+        "Magic" => Some(Skill::Magic),
+        "Stealth" => Some(Skill::Stealth),
+        _ => None,
+    }
+}
+
+pub fn damage_type_by_name(name: &str) -> Option<DamageType> {
+    match name {
+        "physical" => Some(DamageType::Physical),
+        "fire" => Some(DamageType::Fire),
+        "cold" => Some(DamageType::Cold),
+        "poison" => Some(DamageType::Poison),
+        "acid" => Some(DamageType::Acid),
+        "arcane" => Some(DamageType::Arcane),
+        _ => None,
+    }
+}
+
+pub fn string_to_damage_type(name: &str) -> DamageType {
+    damage_type_by_name(name).unwrap_or_else(|| {
+        crate::diagnostic(format!("Warning: unknown damage type [{}]", name));
+        DamageType::Physical
+    })
+}
+
+/// Reads a resistance such as "50%" (a percentage) or "3" (a flat amount); negatives are
+/// weaknesses.
+pub fn parse_mitigation(value: &str) -> Option<Mitigation> {
+    match value.strip_suffix('%') {
+        Some(percent) => percent
+            .trim()
+            .parse::<i32>()
+            .ok()
+            .map(|percent| Mitigation { percent, flat: 0 }),
+        None => value
+            .trim()
+            .parse::<i32>()
+            .ok()
+            .map(|flat| Mitigation { percent: 0, flat }),
+    }
+}
+
+fn damage_resistances(resistances: &Option<HashMap<String, String>>) -> Option<DamageResistances> {
+    let resistances = resistances.as_ref()?;
+    let mut all = HashMap::new();
+    for (name, value) in resistances.iter() {
+        match parse_mitigation(value) {
+            Some(mitigation) => {
+                all.insert(string_to_damage_type(name), mitigation);
+            }
+            None => crate::diagnostic(format!(
+                "Warning: bad damage resistance [{}] for [{}]",
+                value, name
+            )),
+        }
+    }
+    Some(DamageResistances { resistances: all })
+}
+
+fn parse_dice_rider(rider: &super::WeaponRider) -> DamageRider {
+    let (n_dice, die_type, bonus) = parse_dice_string(&rider.damage);
+    DamageRider {
+        damage_type: string_to_damage_type(&rider.damage_type),
+        n_dice,
+        die_type,
+        bonus,
+    }
+}
+
 fn parse_particle_line(n: &str) -> SpawnParticleLine {
     let tokens: Vec<_> = n.split(';').collect();
     SpawnParticleLine {
@@ -453,19 +708,18 @@ macro_rules! apply_effects {
                 "damage" => {
                     $eb = $eb.with(InflictsDamage {
                         damage: effect.1.parse::<i32>().unwrap(),
+                        damage_type: $effects
+                            .get("damage_type")
+                            .map_or(DamageType::Physical, |t| string_to_damage_type(t)),
                     })
                 }
+                // Read along with "damage"
+                "damage_type" => {}
                 "area_of_effect" => {
                     $eb = $eb.with(AreaOfEffect {
                         radius: effect.1.parse::<i32>().unwrap(),
                     })
                 }
-                "confusion" => {
-                    $eb = $eb.with(Confusion {});
-                    $eb = $eb.with(Duration {
-                        turns: effect.1.parse::<i32>().unwrap(),
-                    });
-                }
                 "magic_mapping" => $eb = $eb.with(MagicMapper {}),
                 "town_portal" => $eb = $eb.with(TownPortal {}),
                 "food" => $eb = $eb.with(ProvidesFood {}),
@@ -474,18 +728,13 @@ macro_rules! apply_effects {
                 "particle" => $eb = $eb.with(parse_particle(&effect.1)),
                 "remove_curse" => $eb = $eb.with(ProvidesRemoveCurse {}),
                 "identify" => $eb = $eb.with(ProvidesIdentification {}),
-                "slow" => {
-                    $eb = $eb.with(Slow {
-                        initiative_penalty: effect.1.parse::<f32>().unwrap(),
-                    })
-                }
-                "damage_over_time" => {
-                    $eb = $eb.with(DamageOverTime {
-                        damage: effect.1.parse::<i32>().unwrap(),
+                "status" => {
+                    $eb = $eb.with(AppliesStatus {
+                        statuses: effect.1.split(',').map(|s| s.trim().to_string()).collect(),
                     })
                 }
                 "target_self" => $eb = $eb.with(AlwaysTargetsSelf {}),
-                _ => rltk::console::log(format!(
+                _ => crate::diagnostic(format!(
                     "Warning: consumable effect {} not implemented.",
                     effect_name
                 )),
@@ -561,6 +810,11 @@ pub fn spawn_named_item(
                 hit_bonus: weapon.hit_bonus,
                 proc_chance: weapon.proc_chance,
                 proc_target: weapon.proc_target.clone(),
+                damage_type: weapon
+                    .damage_type
+                    .as_ref()
+                    .map_or(DamageType::Physical, |t| string_to_damage_type(t)),
+                rider: weapon.rider.as_ref().map(parse_dice_rider),
             };
             match weapon.attribute.as_str() {
                 "Quickness" => wpn.attribute = WeaponAttribute::Quickness,
@@ -579,6 +833,9 @@ pub fn spawn_named_item(
                 slot,
                 armor_class: wearable.armor_class,
             });
+            if let Some(resistances) = damage_resistances(&wearable.damage_resistances) {
+                eb = eb.with(resistances);
+            }
         }
 
         if let Some(magic) = &item_template.magic {
@@ -625,11 +882,37 @@ pub fn spawn_named_item(
             });
         }
 
+        if let Some(resistances) = status_resistances(
+            &item_template.status_resistances,
+            &item_template.status_immunities,
+        ) {
+            eb = eb.with(resistances);
+        }
+
+        if item_template.wins_game == Some(true) {
+            eb = eb.with(WinsGame {});
+        }
+
         return Some(eb.build());
     }
     None
 }
 
+/// Immunities count as complete resistance.
+fn status_resistances(
+    resistances: &Option<HashMap<String, i32>>,
+    immunities: &Option<Vec<String>>,
+) -> Option<StatusResistances> {
+    if resistances.is_none() && immunities.is_none() {
+        return None;
+    }
+    let mut all = resistances.clone().unwrap_or_default();
+    for name in immunities.iter().flatten() {
+        all.insert(name.clone(), 100);
+    }
+    Some(StatusResistances { resistances: all })
+}
+
 #[allow(clippy::cognitive_complexity)]
 pub fn spawn_named_mob(
     raws: &RawMaster,
@@ -681,6 +964,34 @@ pub fn spawn_named_mob(
             }
         }
 
+        let (home_x, home_y) = match pos {
+            SpawnType::AtPosition { x, y } => (x, y),
+            _ => (0, 0),
+        };
+        eb = eb.with(AiBehavior {
+            name: mob_template
+                .behavior
+                .clone()
+                .unwrap_or_else(|| DEFAULT_BEHAVIOR.to_string()),
+            home_x,
+            home_y,
+        });
+        eb = eb.with(Alertness {
+            state: AlertState::Unaware,
+            hearing: mob_template.hearing.unwrap_or(0),
+            heard: None,
+            turns: 0,
+        });
+        if let Some(resistances) = status_resistances(
+            &mob_template.status_resistances,
+            &mob_template.status_immunities,
+        ) {
+            eb = eb.with(resistances);
+        }
+        if let Some(resistances) = damage_resistances(&mob_template.damage_resistances) {
+            eb = eb.with(resistances);
+        }
+
         if let Some(quips) = &mob_template.quips {
             eb = eb.with(Quips {
                 available: quips.clone(),
@@ -785,6 +1096,7 @@ pub fn spawn_named_mob(
         skills.skills.insert(Skill::Melee, 1);
         skills.skills.insert(Skill::Defense, 1);
         skills.skills.insert(Skill::Magic, 1);
+        skills.skills.insert(Skill::Stealth, 1);
         if let Some(mobskills) = &mob_template.skills {
             for sk in mobskills.iter() {
                 match sk.0.as_str() {
@@ -797,8 +1109,11 @@ pub fn spawn_named_mob(
                     "Magic" => {
                         skills.skills.insert(Skill::Magic, *sk.1);
                     }
+                    "Stealth" => {
+                        skills.skills.insert(Skill::Stealth, *sk.1);
+                    }
                     _ => {
-                        rltk::console::log(format!("Unknown skill referenced: [{}]", sk.0));
+                        crate::diagnostic(format!("Unknown skill referenced: [{}]", sk.0));
                     }
                 }
             }
@@ -818,15 +1133,7 @@ pub fn spawn_named_mob(
             };
             if let Some(attacks) = &na.attacks {
                 for nattack in attacks.iter() {
-                    let (n, d, b) = parse_dice_string(&nattack.damage);
-                    let attack = NaturalAttack {
-                        name: nattack.name.clone(),
-                        hit_bonus: nattack.hit_bonus,
-                        damage_n_dice: n,
-                        damage_die_type: d,
-                        damage_bonus: b,
-                    };
-                    nature.attacks.push(attack);
+                    nature.attacks.push(natural_attack(nattack));
                 }
             }
             eb = eb.with(nature);
@@ -855,9 +1162,17 @@ pub fn spawn_named_mob(
             })
         }
 
+        if mob_template.leader == Some(true) {
+            eb = eb.with(GroupLeader {});
+        }
+
         if let Some(vendor) = &mob_template.vendor {
             eb = eb.with(Vendor {
                 categories: vendor.clone(),
+                stock: get_vendor_items(vendor, raws)
+                    .into_iter()
+                    .map(|(name, _)| (name, VENDOR_STOCK))
+                    .collect(),
             });
         }
 
@@ -980,10 +1295,15 @@ pub fn spawn_named_spell(raws: &RawMaster, ecs: &mut World, key: &str) -> Option
         let mut eb = ecs.create_entity().marked::<SimpleMarker<SerializeMe>>();
         eb = eb.with(SpellTemplate {
             mana_cost: spell_template.mana_cost,
+            cooldown: spell_template.cooldown.unwrap_or(0),
+            min_magic: spell_template.min_magic.unwrap_or(0),
         });
         eb = eb.with(Name {
             name: spell_template.name.clone(),
         });
+        if let Some(shape) = spell_template.shape.as_ref().and_then(target_shape) {
+            eb = eb.with(SpellShape { shape });
+        }
         apply_effects!(spell_template.effects, eb);
 
         return Some(eb.build());
@@ -1093,3 +1413,94 @@ pub fn get_item_drop(raws: &RawMaster, table: &str) -> Option<String> {
 
     None
 }
+
+pub fn theme_by_name(theme: &str) -> Option<MapTheme> {
+    match theme {
+        "forest" => Some(MapTheme::Forest),
+        "limestone" => Some(MapTheme::Limestone),
+        "limestone_transition" => Some(MapTheme::LimestoneTransition),
+        "mushroom" => Some(MapTheme::Mushroom),
+        "mushroom_entrance" => Some(MapTheme::MushroomEntrance),
+        "default" => Some(MapTheme::Default),
+        _ => None,
+    }
+}
+
+pub fn string_to_theme(theme: &str) -> MapTheme {
+    theme_by_name(theme).unwrap_or_else(|| {
+        crate::diagnostic(format!("WARNING - unknown map theme [{}]", theme));
+        MapTheme::Default
+    })
+}
+
+fn build_level_chain(
+    level: &super::Level,
+    new_depth: i32,
+    width: i32,
+    height: i32,
+) -> Option<BuilderChain> {
+    let no_params = Vec::new();
+    let mut chain = BuilderChain::new(new_depth, width, height, &level.map_name);
+
+    let starter = &level.starter;
+    match initial_builder_by_name(&starter.name, starter.params.as_ref().unwrap_or(&no_params)) {
+        Ok(builder) => chain.start_with(builder),
+        Err(problem) => {
+            crate::diagnostic(format!(
+                "WARNING - Level {} starting builder [{}]: {}",
+                level.name, starter.name, problem
+            ));
+            return None;
+        }
+    }
+
+    for step in level.builders.iter() {
+        match meta_builder_by_name(&step.name, step.params.as_ref().unwrap_or(&no_params)) {
+            Ok(builder) => chain.with(builder),
+            Err(problem) => {
+                crate::diagnostic(format!(
+                    "WARNING - Level {} builder [{}]: {}",
+                    level.name, step.name, problem
+                ));
+                return None;
+            }
+        }
+    }
+
+    if let Some(theme) = &level.theme {
+        chain.set_theme(string_to_theme(theme));
+    }
+
+    Some(chain)
+}
+
+pub fn get_level_builder(
+    raws: &RawMaster,
+    new_depth: i32,
+    width: i32,
+    height: i32,
+) -> Option<BuilderChain> {
+    if raws.level_index.contains_key(&new_depth) {
+        let level = &raws.raws.levels[raws.level_index[&new_depth]];
+        return build_level_chain(level, new_depth, width, height);
+    }
+    None
+}
+
+pub fn get_named_level_builder(
+    raws: &RawMaster,
+    name: &str,
+    new_depth: i32,
+    width: i32,
+    height: i32,
+) -> Option<BuilderChain> {
+    raws.raws
+        .levels
+        .iter()
+        .find(|level| level.name == name)
+        .and_then(|level| build_level_chain(level, new_depth, width, height))
+}
+
+pub fn get_level_names(raws: &RawMaster) -> Vec<String> {
+    raws.raws.levels.iter().map(|l| l.name.clone()).collect()
+}
@@ -5,5 +5,22 @@ use std::collections::HashMap;
 pub struct Spell {
     pub name : String,
     pub mana_cost : i32,
-    pub effects : HashMap<String, String>
+    pub effects : HashMap<String, String>,
+    /// Shown in the spellbook
+    pub description : Option<String>,
+    /// Which tiles the spell hits; without one it hits the target, or its area of effect
+    pub shape : Option<SpellShape>,
+    /// Turns to wait before casting it again
+    pub cooldown : Option<i32>,
+    pub school : Option<String>,
+    /// The Magic skill needed to cast it
+    pub min_magic : Option<i32>
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SpellShape {
+    /// bolt, cone, line, burst or chain
+    pub kind : String,
+    /// Length for cones and lines, radius for bursts, jumps for chains
+    pub size : Option<i32>
 }
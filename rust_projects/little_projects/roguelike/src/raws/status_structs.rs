@@ -0,0 +1,39 @@
+use super::ItemAttributeBonus;
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+pub struct StatusEffectDef {
+    pub name: String,
+    /// Turns the effect lasts for
+    pub duration: i32,
+    /// What happens when it lands on someone who already has it: "refresh" (the default) resets
+    /// the duration, "stack" also adds another stack, "ignore" leaves the existing one alone
+    pub stacking: Option<String>,
+    pub max_stacks: Option<i32>,
+    /// Color the effect is listed in on the HUD
+    pub color: Option<String>,
+    /// The afflicted lose their turns
+    pub skip_turn: Option<bool>,
+    /// Monsters forget what they were doing and run
+    pub flee: Option<bool>,
+    /// The afflicted can see no further than this
+    pub vision_range: Option<i32>,
+    /// Per stack; negative values hasten
+    pub initiative_penalty: Option<f32>,
+    /// Per stack
+    pub attributes: Option<ItemAttributeBonus>,
+    /// Happens every turn, per stack
+    pub on_tick: Option<StatusTrigger>,
+    /// Happens once, when the effect runs out
+    pub on_expire: Option<StatusTrigger>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct StatusTrigger {
+    pub damage: Option<i32>,
+    /// "physical" if missing
+    pub damage_type: Option<String>,
+    pub healing: Option<i32>,
+    /// Another status effect to apply; only on expiry
+    pub status: Option<String>,
+}
@@ -0,0 +1,633 @@
+use super::{
+    damage_type_by_name, is_dice_string, parse_mitigation, target_shape, theme_by_name, RawMaster,
+    WeaponRider, DEFAULT_BACKGROUND, DEFAULT_BEHAVIOR,
+};
+use crate::map_builders::{initial_builder_by_name, meta_builder_by_name};
+use std::collections::HashMap;
+use std::fmt;
+
+/// A problem found in the raws, with the JSON path of the offending value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawError {
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for RawError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+const INT_EFFECTS: &[&str] = &[
+    "provides_healing",
+    "provides_mana",
+    "ranged",
+    "damage",
+    "area_of_effect",
+];
+const FLAG_EFFECTS: &[&str] = &[
+    "magic_mapping",
+    "town_portal",
+    "food",
+    "single_activation",
+    "remove_curse",
+    "identify",
+    "target_self",
+];
+const SLOTS: &[&str] = &["Shield", "Head", "Torso", "Legs", "Feet", "Hands", "Melee"];
+const SKILLS: &[&str] = &["Melee", "Defense", "Magic", "Stealth"];
+const MOVEMENTS: &[&str] = &["static", "random", "random_waypoint"];
+const REACTIONS: &[&str] = &["ignore", "flee", "attack"];
+const STACKING: &[&str] = &["refresh", "stack", "ignore"];
+const ACTIONS: &[&str] = &[
+    "melee",
+    "cast",
+    "shoot",
+    "flee",
+    "approach",
+    "surround",
+    "investigate",
+    "keep_distance",
+    "chase",
+    "guard",
+    "heal_ally",
+    "pick_up",
+    "call_for_help",
+    "wander",
+    "wait",
+];
+
+struct Validator<'a> {
+    raws: &'a RawMaster,
+    errors: Vec<RawError>,
+}
+
+impl<'a> Validator<'a> {
+    fn error(&mut self, path: String, message: String) {
+        self.errors.push(RawError { path, message });
+    }
+
+    /// Where the i'th entry of a section came from, as "file:$.section[index]".
+    fn entry_path(&self, section: &str, i: usize) -> String {
+        match self
+            .raws
+            .entry_paths
+            .get(section)
+            .and_then(|paths| paths.get(i))
+        {
+            Some(path) => path.clone(),
+            None => format!("$.{}[{}]", section, i),
+        }
+    }
+
+    fn check_dice(&mut self, path: String, dice: &str) {
+        if !is_dice_string(dice) {
+            self.error(path, format!("[{}] is not a dice string", dice));
+        }
+    }
+
+    fn check_item(&mut self, path: String, name: &str) {
+        if !self.raws.item_index.contains_key(name) {
+            self.error(path, format!("unknown item [{}]", name));
+        }
+    }
+
+    fn check_spell(&mut self, path: String, name: &str) {
+        if !self.raws.spell_index.contains_key(name) {
+            self.error(path, format!("unknown spell [{}]", name));
+        }
+    }
+
+    fn check_status(&mut self, path: String, name: &str) {
+        if !self.raws.status_index.contains_key(name) {
+            self.error(path, format!("unknown status effect [{}]", name));
+        }
+    }
+
+    fn check_resistances(
+        &mut self,
+        path: &str,
+        resistances: &Option<HashMap<String, i32>>,
+        immunities: &Option<Vec<String>>,
+    ) {
+        for name in resistances.iter().flat_map(|r| r.keys()) {
+            self.check_status(format!("{}.status_resistances.{}", path, name), name);
+        }
+        for (i, name) in immunities.iter().flatten().enumerate() {
+            self.check_status(format!("{}.status_immunities[{}]", path, i), name);
+        }
+    }
+
+    fn check_damage_type(&mut self, path: String, name: &str) {
+        if damage_type_by_name(name).is_none() {
+            self.error(path, format!("unknown damage type [{}]", name));
+        }
+    }
+
+    fn check_damage_resistances(
+        &mut self,
+        path: &str,
+        resistances: &Option<HashMap<String, String>>,
+    ) {
+        for (name, value) in resistances.iter().flatten() {
+            let path = format!("{}.damage_resistances.{}", path, name);
+            self.check_damage_type(path.clone(), name);
+            if parse_mitigation(value).is_none() {
+                self.error(
+                    path,
+                    format!("[{}] is neither a percentage nor a number", value),
+                );
+            }
+        }
+    }
+
+    fn check_rider(&mut self, path: &str, rider: &WeaponRider) {
+        self.check_damage_type(format!("{}.rider.damage_type", path), &rider.damage_type);
+        self.check_dice(format!("{}.rider.damage", path), &rider.damage);
+    }
+
+    fn check_color(&mut self, path: String, color: &str) {
+        if rltk::RGB::from_hex(color).is_err() {
+            self.error(path, format!("[{}] is not a hex color", color));
+        }
+    }
+
+    fn check_effects(&mut self, path: &str, effects: &HashMap<String, String>) {
+        for (name, value) in effects.iter() {
+            let path = format!("{}.{}", path, name);
+            if INT_EFFECTS.contains(&name.as_str()) {
+                if value.parse::<i32>().is_err() {
+                    self.error(path, format!("[{}] is not a whole number", value));
+                }
+            } else if name == "status" {
+                for status in value.split(',') {
+                    self.check_status(path.clone(), status.trim());
+                }
+            } else if name == "damage_type" {
+                self.check_damage_type(path.clone(), value);
+                if !effects.contains_key("damage") {
+                    self.error(path, "a damage type needs damage".to_string());
+                }
+            } else if name == "teach_spell" {
+                self.check_spell(path, value);
+            } else if name == "particle" || name == "particle_line" {
+                let tokens: Vec<_> = value.split(';').collect();
+                if tokens.len() != 3 || tokens[0].is_empty() || tokens[2].parse::<f32>().is_err() {
+                    self.error(path, format!("[{}] is not glyph;color;lifetime", value));
+                } else {
+                    self.check_color(path, tokens[1]);
+                }
+            } else if !FLAG_EFFECTS.contains(&name.as_str()) {
+                self.error(path, format!("unknown effect [{}]", name));
+            }
+        }
+    }
+
+    fn validate_items(&mut self) {
+        let raws = self.raws;
+        for (i, item) in raws.raws.items.iter().take(raws.raw_item_count).enumerate() {
+            let path = self.entry_path("items", i);
+            if let Some(consumable) = &item.consumable {
+                self.check_effects(&format!("{}.consumable.effects", path), &consumable.effects);
+            }
+            if let Some(weapon) = &item.weapon {
+                self.check_dice(format!("{}.weapon.base_damage", path), &weapon.base_damage);
+                if weapon.range != "melee" && weapon.range.parse::<i32>().is_err() {
+                    self.error(
+                        format!("{}.weapon.range", path),
+                        format!("[{}] is neither melee nor a number", weapon.range),
+                    );
+                }
+                if let Some(proc_effects) = &weapon.proc_effects {
+                    self.check_effects(&format!("{}.weapon.proc_effects", path), proc_effects);
+                }
+                if let Some(damage_type) = &weapon.damage_type {
+                    self.check_damage_type(format!("{}.weapon.damage_type", path), damage_type);
+                }
+                if let Some(rider) = &weapon.rider {
+                    self.check_rider(&format!("{}.weapon", path), rider);
+                }
+            }
+            if let Some(wearable) = &item.wearable {
+                if !SLOTS.contains(&wearable.slot.as_str()) {
+                    self.error(
+                        format!("{}.wearable.slot", path),
+                        format!("unknown equipment slot [{}]", wearable.slot),
+                    );
+                }
+                self.check_damage_resistances(
+                    &format!("{}.wearable", path),
+                    &wearable.damage_resistances,
+                );
+            }
+            self.check_resistances(&path, &item.status_resistances, &item.status_immunities);
+            if let Some(template) = &item.template_magic {
+                if template.bonus_min > template.bonus_max {
+                    self.error(
+                        format!("{}.template_magic", path),
+                        format!("bonus_min is above bonus_max for {}", item.name),
+                    );
+                }
+            }
+        }
+    }
+
+    fn validate_mobs(&mut self) {
+        let raws = self.raws;
+        for (i, mob) in raws.raws.mobs.iter().enumerate() {
+            let path = self.entry_path("mobs", i);
+            if !MOVEMENTS.contains(&mob.movement.as_str()) {
+                self.error(
+                    format!("{}.movement", path),
+                    format!("unknown movement [{}]", mob.movement),
+                );
+            }
+            self.check_resistances(&path, &mob.status_resistances, &mob.status_immunities);
+            self.check_damage_resistances(&path, &mob.damage_resistances);
+            if let Some(behavior) = &mob.behavior {
+                if !raws.behavior_index.contains_key(behavior) {
+                    self.error(
+                        format!("{}.behavior", path),
+                        format!("unknown behavior [{}]", behavior),
+                    );
+                }
+            }
+            if let Some(skills) = &mob.skills {
+                for skill in skills.keys() {
+                    if !SKILLS.contains(&skill.as_str()) {
+                        self.error(
+                            format!("{}.skills.{}", path, skill),
+                            format!("unknown skill [{}]", skill),
+                        );
+                    }
+                }
+            }
+            if let Some(equipped) = &mob.equipped {
+                for (j, item) in equipped.iter().enumerate() {
+                    self.check_item(format!("{}.equipped[{}]", path, j), item);
+                }
+            }
+            if let Some(natural) = &mob.natural {
+                if let Some(attacks) = &natural.attacks {
+                    for (j, attack) in attacks.iter().enumerate() {
+                        self.check_dice(
+                            format!("{}.natural.attacks[{}].damage", path, j),
+                            &attack.damage,
+                        );
+                        if let Some(damage_type) = &attack.damage_type {
+                            self.check_damage_type(
+                                format!("{}.natural.attacks[{}].damage_type", path, j),
+                                damage_type,
+                            );
+                        }
+                    }
+                }
+            }
+            if let Some(loot_table) = &mob.loot_table {
+                if !raws.loot_index.contains_key(loot_table) {
+                    self.error(
+                        format!("{}.loot_table", path),
+                        format!("unknown loot table [{}]", loot_table),
+                    );
+                }
+            }
+            if let Some(light) = &mob.light {
+                self.check_color(format!("{}.light.color", path), &light.color);
+            }
+            if let Some(faction) = &mob.faction {
+                if !raws.faction_index.contains_key(faction) {
+                    self.error(
+                        format!("{}.faction", path),
+                        format!("unknown faction [{}]", faction),
+                    );
+                }
+            }
+            if let Some(gold) = &mob.gold {
+                self.check_dice(format!("{}.gold", path), gold);
+            }
+            for (list, abilities) in [("abilities", &mob.abilities), ("on_death", &mob.on_death)] {
+                if let Some(abilities) = abilities {
+                    for (j, ability) in abilities.iter().enumerate() {
+                        self.check_spell(format!("{}.{}[{}].spell", path, list, j), &ability.spell);
+                    }
+                }
+            }
+        }
+    }
+
+    fn validate_props(&mut self) {
+        let raws = self.raws;
+        for (i, prop) in raws.raws.props.iter().enumerate() {
+            let path = self.entry_path("props", i);
+            if let Some(trigger) = &prop.entry_trigger {
+                self.check_effects(&format!("{}.entry_trigger.effects", path), &trigger.effects);
+            }
+            if let Some(light) = &prop.light {
+                self.check_color(format!("{}.light.color", path), &light.color);
+            }
+        }
+    }
+
+    fn validate_spawn_table(&mut self) {
+        let raws = self.raws;
+        for (i, spawn) in raws
+            .raws
+            .spawn_table
+            .iter()
+            .take(raws.raw_spawn_count)
+            .enumerate()
+        {
+            let path = self.entry_path("spawn_table", i);
+            if !raws.item_index.contains_key(&spawn.name)
+                && !raws.mob_index.contains_key(&spawn.name)
+                && !raws.prop_index.contains_key(&spawn.name)
+            {
+                self.error(
+                    format!("{}.name", path),
+                    format!("unknown entity [{}]", spawn.name),
+                );
+            }
+            if spawn.min_depth > spawn.max_depth {
+                self.error(
+                    path,
+                    format!(
+                        "min_depth {} is above max_depth {} for {}",
+                        spawn.min_depth, spawn.max_depth, spawn.name
+                    ),
+                );
+            }
+        }
+    }
+
+    fn validate_loot_tables(&mut self) {
+        let raws = self.raws;
+        for (i, table) in raws.raws.loot_tables.iter().enumerate() {
+            for (j, drop) in table.drops.iter().enumerate() {
+                self.check_item(
+                    format!("{}.drops[{}].name", self.entry_path("loot_tables", i), j),
+                    &drop.name,
+                );
+            }
+        }
+    }
+
+    fn validate_factions(&mut self) {
+        let raws = self.raws;
+        for (i, faction) in raws.raws.faction_table.iter().enumerate() {
+            for (other, reaction) in faction.responses.iter() {
+                let path = format!(
+                    "{}.responses.{}",
+                    self.entry_path("faction_table", i),
+                    other
+                );
+                if other != "Default" && !raws.faction_index.contains_key(other) {
+                    self.error(path.clone(), format!("unknown faction [{}]", other));
+                }
+                if !REACTIONS.contains(&reaction.as_str()) {
+                    self.error(path, format!("unknown reaction [{}]", reaction));
+                }
+            }
+        }
+    }
+
+    fn validate_spells(&mut self) {
+        let raws = self.raws;
+        for (i, spell) in raws.raws.spells.iter().enumerate() {
+            let spell_path = self.entry_path("spells", i);
+            self.check_effects(&format!("{}.effects", spell_path), &spell.effects);
+            if let Some(shape) = &spell.shape {
+                let path = format!("{}.shape", spell_path);
+                if target_shape(shape).is_none() {
+                    self.error(
+                        format!("{}.kind", path),
+                        format!("unknown shape [{}]", shape.kind),
+                    );
+                } else if shape.kind != "burst" && !spell.effects.contains_key("ranged") {
+                    self.error(path.clone(), format!("a {} needs a range", shape.kind));
+                }
+                if shape.size.is_some_and(|size| size < 1) {
+                    self.error(format!("{}.size", path), "must be positive".to_string());
+                }
+            }
+            if spell.cooldown.is_some_and(|turns| turns < 0) {
+                self.error(
+                    format!("{}.cooldown", spell_path),
+                    "can't be negative".to_string(),
+                );
+            }
+        }
+        for (i, weapon_trait) in raws.raws.weapon_traits.iter().enumerate() {
+            let path = self.entry_path("weapon_traits", i);
+            self.check_effects(&format!("{}.effects", path), &weapon_trait.effects);
+            if let Some(rider) = &weapon_trait.rider {
+                self.check_rider(&path, rider);
+            }
+        }
+    }
+
+    fn validate_behaviors(&mut self) {
+        let raws = self.raws;
+        if !raws.behavior_index.contains_key(DEFAULT_BEHAVIOR) {
+            self.error(
+                "$.behaviors".to_string(),
+                format!("there is no [{}] behavior", DEFAULT_BEHAVIOR),
+            );
+        }
+        for (i, behavior) in raws.raws.behaviors.iter().enumerate() {
+            for (j, action) in behavior.actions.iter().enumerate() {
+                let path = format!("{}.actions[{}]", self.entry_path("behaviors", i), j);
+                if !ACTIONS.contains(&action.action.as_str()) {
+                    self.error(
+                        format!("{}.action", path),
+                        format!("unknown action [{}]", action.action),
+                    );
+                }
+                if action.action == "heal_ally" && action.spell.is_none() {
+                    self.error(path.clone(), "heal_ally needs a spell".to_string());
+                }
+                if let Some(spell) = &action.spell {
+                    self.check_spell(format!("{}.spell", path), spell);
+                }
+                if let Some(reactions) = &action.reactions {
+                    for (k, reaction) in reactions.iter().enumerate() {
+                        if !REACTIONS.contains(&reaction.as_str()) {
+                            self.error(
+                                format!("{}.reactions[{}]", path, k),
+                                format!("unknown reaction [{}]", reaction),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn validate_status_effects(&mut self) {
+        let raws = self.raws;
+        for (i, status) in raws.raws.status_effects.iter().enumerate() {
+            let path = self.entry_path("status_effects", i);
+            if status.duration < 1 {
+                self.error(
+                    format!("{}.duration", path),
+                    format!("{} must last at least a turn", status.name),
+                );
+            }
+            if let Some(stacking) = &status.stacking {
+                if !STACKING.contains(&stacking.as_str()) {
+                    self.error(
+                        format!("{}.stacking", path),
+                        format!("unknown stacking rule [{}]", stacking),
+                    );
+                }
+            }
+            if let Some(color) = &status.color {
+                self.check_color(format!("{}.color", path), color);
+            }
+            if status.on_tick.as_ref().is_some_and(|t| t.status.is_some()) {
+                self.error(
+                    format!("{}.on_tick.status", path),
+                    "statuses can only be applied on expiry".to_string(),
+                );
+            }
+            for (trigger, when) in [
+                (&status.on_tick, "on_tick"),
+                (&status.on_expire, "on_expire"),
+            ] {
+                if let Some(damage_type) = trigger.as_ref().and_then(|t| t.damage_type.as_ref()) {
+                    self.check_damage_type(format!("{}.{}.damage_type", path, when), damage_type);
+                }
+            }
+            if let Some(next) = status.on_expire.as_ref().and_then(|e| e.status.as_ref()) {
+                self.check_status(format!("{}.on_expire.status", path), next);
+            }
+        }
+    }
+
+    fn validate_backgrounds(&mut self) {
+        let raws = self.raws;
+        if !raws.background_index.contains_key(DEFAULT_BACKGROUND) {
+            self.error(
+                "$.backgrounds".to_string(),
+                format!("there is no [{}] background", DEFAULT_BACKGROUND),
+            );
+        }
+        for (i, background) in raws.raws.backgrounds.iter().enumerate() {
+            let path = self.entry_path("backgrounds", i);
+            for skill in background.skills.iter().flat_map(|s| s.keys()) {
+                if !SKILLS.contains(&skill.as_str()) {
+                    self.error(
+                        format!("{}.skills.{}", path, skill),
+                        format!("unknown skill [{}]", skill),
+                    );
+                }
+            }
+            for (j, spell) in background.spells.iter().flatten().enumerate() {
+                self.check_spell(format!("{}.spells[{}]", path, j), spell);
+            }
+            for (j, item) in background.equipped.iter().flatten().enumerate() {
+                let item_path = format!("{}.equipped[{}]", path, j);
+                match raws.item_index.get(item) {
+                    None => self.check_item(item_path, item),
+                    Some(idx) => {
+                        let template = &raws.raws.items[*idx];
+                        if template.weapon.is_none() && template.wearable.is_none() {
+                            self.error(item_path, format!("[{}] can't be equipped", item));
+                        }
+                    }
+                }
+            }
+            for (j, item) in background.carried.iter().flatten().enumerate() {
+                self.check_item(format!("{}.carried[{}]", path, j), item);
+            }
+        }
+    }
+
+    fn validate_perks(&mut self) {
+        let raws = self.raws;
+        for (i, perk) in raws.raws.perks.iter().enumerate() {
+            let path = self.entry_path("perks", i);
+            for (j, required) in perk.requires.iter().flatten().enumerate() {
+                if !raws.perk_index.contains_key(required) {
+                    self.error(
+                        format!("{}.requires[{}]", path, j),
+                        format!("unknown perk [{}]", required),
+                    );
+                }
+            }
+            if let Some(attack) = &perk.natural_attack {
+                self.check_dice(format!("{}.natural_attack.damage", path), &attack.damage);
+                if let Some(damage_type) = &attack.damage_type {
+                    self.check_damage_type(
+                        format!("{}.natural_attack.damage_type", path),
+                        damage_type,
+                    );
+                }
+            }
+            if perk.crit_chance.is_some_and(|c| !(0..=100).contains(&c)) {
+                self.error(
+                    format!("{}.crit_chance", path),
+                    "must be a percentage".to_string(),
+                );
+            }
+            for skill in perk.skills.iter().flat_map(|s| s.keys()) {
+                if !SKILLS.contains(&skill.as_str()) {
+                    self.error(
+                        format!("{}.skills.{}", path, skill),
+                        format!("unknown skill [{}]", skill),
+                    );
+                }
+            }
+        }
+    }
+
+    fn validate_levels(&mut self) {
+        let raws = self.raws;
+        let no_params = Vec::new();
+        for (i, level) in raws.raws.levels.iter().enumerate() {
+            let path = self.entry_path("levels", i);
+            if let Some(theme) = &level.theme {
+                if theme_by_name(theme).is_none() {
+                    self.error(
+                        format!("{}.theme", path),
+                        format!("unknown theme [{}]", theme),
+                    );
+                }
+            }
+            let starter = &level.starter;
+            let params = starter.params.as_ref().unwrap_or(&no_params);
+            if let Err(problem) = initial_builder_by_name(&starter.name, params) {
+                self.error(format!("{}.starter", path), problem);
+            }
+            for (j, step) in level.builders.iter().enumerate() {
+                let params = step.params.as_ref().unwrap_or(&no_params);
+                if let Err(problem) = meta_builder_by_name(&step.name, params) {
+                    self.error(format!("{}.builders[{}]", path, j), problem);
+                }
+            }
+        }
+    }
+}
+
+impl RawMaster {
+    /// Cross-checks every name reference, dice string and depth range in the loaded raws.
+    pub fn validate(&self) -> Vec<RawError> {
+        let mut validator = Validator {
+            raws: self,
+            errors: Vec::new(),
+        };
+        validator.validate_items();
+        validator.validate_mobs();
+        validator.validate_props();
+        validator.validate_spawn_table();
+        validator.validate_loot_tables();
+        validator.validate_factions();
+        validator.validate_spells();
+        validator.validate_behaviors();
+        validator.validate_status_effects();
+        validator.validate_backgrounds();
+        validator.validate_perks();
+        validator.validate_levels();
+        validator.errors
+    }
+}
@@ -1,8 +1,12 @@
 use serde::{Deserialize};
 use std::collections::HashMap;
+use super::WeaponRider;
 
 #[derive(Deserialize, Debug)]
 pub struct WeaponTrait {
     pub name : String,
-    pub effects : HashMap<String, String>
+    /// Proc effects; traits with none don't proc
+    #[serde(default)]
+    pub effects : HashMap<String, String>,
+    pub rider : Option<WeaponRider>
 }
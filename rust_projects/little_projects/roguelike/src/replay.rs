@@ -0,0 +1,351 @@
+//! Recording and playback of the player's input. A run is fully decided by its seed and what the
+//! player pressed, so a replay is just the seed and the character the player created, followed by
+//! one input frame per line.
+
+use super::input::{
+    key_from_name, key_name, right_click, set_right_click, Action, ActionGroup, KeyBindings,
+    KeyCombo, BINDINGS,
+};
+use super::{gamelog, Character, RunState};
+use rltk::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Every new run is recorded into this directory.
+pub const REPLAY_DIR: &str = "./replays";
+const REPLAY_VERSION: u32 = 1;
+/// How long each recorded input stays on screen at normal playback speed.
+const PLAYBACK_DELAY_MS: f32 = 150.0;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct ReplayHeader {
+    version: u32,
+    seed: u64,
+    /// Missing from replays recorded before characters could be created
+    #[serde(default)]
+    character: Option<Character>,
+    /// Missing from replays recorded before keys could be remapped, which used the defaults
+    #[serde(default)]
+    bindings: Option<KeyBindings>,
+}
+
+/// What the player did on one frame: a key press and/or a click, with the mouse position in
+/// console cells.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct InputFrame {
+    pub key: Option<String>,
+    pub shift: bool,
+    pub control: bool,
+    pub mouse: (i32, i32),
+    pub click: bool,
+    /// Missing from replays recorded before right clicks did anything
+    #[serde(default)]
+    pub right_click: bool,
+}
+
+impl InputFrame {
+    /// The input on this frame, if the player pressed anything the game understands.
+    fn capture(ctx: &Rltk) -> Option<InputFrame> {
+        let key = ctx.key.and_then(key_name);
+        if key.is_none() && !ctx.left_click && !right_click() {
+            return None;
+        }
+        Some(InputFrame {
+            key: key.map(|name| name.to_string()),
+            shift: ctx.shift,
+            control: ctx.control,
+            mouse: ctx.mouse_pos(),
+            click: ctx.left_click,
+            right_click: right_click(),
+        })
+    }
+
+    fn apply(&self, ctx: &mut Rltk) {
+        ctx.key = self.key.as_deref().and_then(key_from_name);
+        ctx.shift = self.shift;
+        ctx.control = self.control;
+        ctx.left_click = self.click;
+        set_right_click(self.right_click);
+    }
+}
+
+fn clear_input(ctx: &mut Rltk) {
+    ctx.key = None;
+    ctx.shift = false;
+    ctx.control = false;
+    ctx.left_click = false;
+    set_right_click(false);
+}
+
+/// The file the run with this save slot is recorded into.
+pub fn replay_path(slot: &str) -> PathBuf {
+    Path::new(REPLAY_DIR).join(format!("{}.replay", slot))
+}
+
+/// A recorded run, ready to play back.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replay {
+    pub seed: u64,
+    pub character: Option<Character>,
+    /// The key bindings the run was played with
+    pub bindings: KeyBindings,
+    pub frames: Vec<InputFrame>,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> Result<Replay, String> {
+        let data = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let mut lines = data.lines();
+        let header: ReplayHeader = serde_json::from_str(lines.next().unwrap_or(""))
+            .map_err(|e| format!("unreadable header: {}", e))?;
+        if header.version > REPLAY_VERSION {
+            return Err(format!(
+                "recorded by a newer version of the game (replay version {})",
+                header.version
+            ));
+        }
+
+        let lines: Vec<&str> = lines.collect();
+        let mut frames = Vec::new();
+        for (i, line) in lines.iter().enumerate() {
+            match serde_json::from_str(line) {
+                Ok(frame) => frames.push(frame),
+                // A crash can cut the last line short; everything before it is still good
+                Err(_) if i == lines.len() - 1 => {}
+                Err(e) => return Err(format!("line {}: {}", i + 2, e)),
+            }
+        }
+        Ok(Replay {
+            seed: header.seed,
+            character: header.character,
+            bindings: header.bindings.unwrap_or_default().with_defaults(),
+            frames,
+        })
+    }
+}
+
+/// Appends input frames to a replay file as they happen, so a crash still leaves a usable replay.
+pub struct Recorder {
+    file: File,
+}
+
+impl Recorder {
+    fn write_line<T: Serialize>(&mut self, value: &T) -> Result<(), String> {
+        let line = serde_json::to_string(value).map_err(|e| e.to_string())?;
+        writeln!(self.file, "{}", line).map_err(|e| e.to_string())
+    }
+}
+
+/// Where the player's input on input-taking frames comes from, and where it goes.
+pub struct Playback {
+    replay: Replay,
+    position: usize,
+    paused: bool,
+    step: bool,
+    fast: bool,
+    timer: f32,
+    mouse: (i32, i32),
+    /// The watcher's own bindings, used for the playback controls and put back afterwards
+    own_bindings: KeyBindings,
+}
+
+impl Playback {
+    /// Handles the playback controls, then replaces the real input with the next recorded frame
+    /// when it's due. Returns false once the replay is over or the player takes over.
+    fn feed(&mut self, ctx: &mut Rltk) -> bool {
+        let control = KeyCombo::pressed(ctx)
+            .and_then(|pressed| self.own_bindings.action(ActionGroup::Replay, pressed));
+        clear_input(ctx);
+        match control {
+            Some(Action::ReplayPause) => self.paused = !self.paused,
+            Some(Action::ReplayStep) => self.step = true,
+            Some(Action::ReplayFast) => self.fast = !self.fast,
+            Some(Action::ReplayTakeOver) => return false,
+            _ => {}
+        }
+        if self.position >= self.replay.frames.len() {
+            return false;
+        }
+
+        self.timer += ctx.frame_time_ms;
+        let due = if self.paused {
+            self.step
+        } else {
+            self.fast || self.timer >= PLAYBACK_DELAY_MS
+        };
+        if due {
+            let frame = &self.replay.frames[self.position];
+            frame.apply(ctx);
+            self.mouse = frame.mouse;
+            self.position += 1;
+            self.step = false;
+            self.timer = 0.0;
+        }
+        true
+    }
+}
+
+pub enum ReplayMode {
+    Off,
+    Recording(Recorder),
+    Playing(Playback),
+}
+
+impl ReplayMode {
+    /// Starts recording a run with this seed and character, replacing any earlier recording at
+    /// the path. Recording is skipped (with a warning) if the file can't be written.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn record(path: &Path, seed: u64, character: &Character) -> ReplayMode {
+        let recorder = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| File::create(path))
+            .map_err(|e| e.to_string())
+            .and_then(|file| {
+                let mut recorder = Recorder { file };
+                recorder.write_line(&ReplayHeader {
+                    version: REPLAY_VERSION,
+                    seed,
+                    character: Some(character.clone()),
+                    bindings: Some(BINDINGS.lock().unwrap().clone()),
+                })?;
+                Ok(recorder)
+            });
+        match recorder {
+            Ok(recorder) => ReplayMode::Recording(recorder),
+            Err(e) => {
+                crate::diagnostic(format!(
+                    "WARNING - unable to record to {}: {}",
+                    path.display(),
+                    e
+                ));
+                ReplayMode::Off
+            }
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn record(_path: &Path, _seed: u64, _character: &Character) -> ReplayMode {
+        ReplayMode::Off
+    }
+
+    /// Starts playing the replay back, with the keys it was recorded with.
+    pub fn play(replay: Replay) -> ReplayMode {
+        let own_bindings =
+            std::mem::replace(&mut *BINDINGS.lock().unwrap(), replay.bindings.clone());
+        ReplayMode::Playing(Playback {
+            replay,
+            position: 0,
+            paused: false,
+            step: false,
+            fast: false,
+            timer: 0.0,
+            mouse: (0, 0),
+            own_bindings,
+        })
+    }
+
+    pub fn is_playing(&self) -> bool {
+        matches!(self, ReplayMode::Playing(_))
+    }
+
+    /// Records this frame's input, or swaps in the recorded input during playback. Only called
+    /// in states where the player acts, since everything else follows from those.
+    pub fn process_input(&mut self, ctx: &mut Rltk) {
+        let keep_going = match self {
+            ReplayMode::Off => true,
+            ReplayMode::Recording(recorder) => match InputFrame::capture(ctx) {
+                None => true,
+                Some(frame) => match recorder.write_line(&frame) {
+                    Ok(()) => true,
+                    Err(e) => {
+                        crate::diagnostic(format!("WARNING - replay recording stopped: {}", e));
+                        false
+                    }
+                },
+            },
+            ReplayMode::Playing(playback) => {
+                let playing = playback.feed(ctx);
+                if !playing {
+                    *BINDINGS.lock().unwrap() = playback.own_bindings.clone();
+                    gamelog::Logger::new()
+                        .color(rltk::CYAN)
+                        .append("The replay is over. You have control.")
+                        .log();
+                }
+                playing
+            }
+        };
+        if !keep_going {
+            *self = ReplayMode::Off;
+        }
+    }
+
+    /// Where the mouse is for the game's purposes: the recorded position during playback.
+    pub fn mouse_pos(&self, ctx: &Rltk) -> (i32, i32) {
+        match self {
+            ReplayMode::Playing(playback) => playback.mouse,
+            _ => ctx.mouse_pos(),
+        }
+    }
+
+    /// Shows the playback position and controls along the top of the map.
+    pub fn draw_status(&self) {
+        if let ReplayMode::Playing(playback) = self {
+            let mut draw_batch = DrawBatch::new();
+            let speed = if playback.paused {
+                "paused"
+            } else if playback.fast {
+                "fast"
+            } else {
+                "playing"
+            };
+            draw_batch.print_color(
+                Point::new(1, 0),
+                format!(
+                    "Replay {}/{} {}",
+                    playback.position,
+                    playback.replay.frames.len(),
+                    speed
+                ),
+                ColorPair::new(RGB::named(rltk::CYAN), RGB::named(rltk::BLACK)),
+            );
+            draw_batch.print_color(
+                Point::new(1, 45),
+                format!(
+                    "{} pause {} step {} fast {} take over",
+                    playback.own_bindings.describe(Action::ReplayPause),
+                    playback.own_bindings.describe(Action::ReplayStep),
+                    playback.own_bindings.describe(Action::ReplayFast),
+                    playback.own_bindings.describe(Action::ReplayTakeOver)
+                ),
+                ColorPair::new(RGB::named(rltk::GRAY), RGB::named(rltk::BLACK)),
+            );
+            let _ = draw_batch.submit(5500);
+        }
+    }
+}
+
+/// True for the states that wait on the player; only their input is recorded and replayed.
+pub fn takes_input(state: RunState) -> bool {
+    matches!(
+        state,
+        RunState::AwaitingInput
+            | RunState::ShowInventory
+            | RunState::ShowSpellbook
+            | RunState::ShowDropItem
+            | RunState::ShowRemoveItem
+            | RunState::ShowRemoveCurse
+            | RunState::ShowIdentify
+            | RunState::ShowPerks { .. }
+            | RunState::ShowLog { .. }
+            | RunState::ShowTargeting { .. }
+            | RunState::ShowVendor { .. }
+            | RunState::ShowContextMenu { .. }
+            | RunState::ShowCheatMenu
+            | RunState::SaveGame
+            | RunState::GameOver
+    )
+}
@@ -9,6 +9,20 @@ pub fn reseed(seed: u64) {
     *RNG.lock().unwrap() = RandomNumberGenerator::seeded(seed);
 }
 
+/// Picks a fresh run seed from the clock, without disturbing the global generator.
+pub fn new_seed() -> u64 {
+    RandomNumberGenerator::new().next_u64()
+}
+
+/// Derives a stable sub-seed for one dungeon depth from the run seed (splitmix64 finalizer).
+/// Depth 0 is reserved for run-wide data such as the obfuscated item names.
+pub fn depth_seed(seed: u64, depth: i32) -> u64 {
+    let mut z = seed ^ (depth as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
 pub fn roll_dice(n: i32, die_type: i32) -> i32 {
     RNG.lock().unwrap().roll_dice(n, die_type)
 }
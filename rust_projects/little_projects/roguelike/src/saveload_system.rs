@@ -1,38 +1,226 @@
 use super::components::*;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use specs::error::NoError;
 use specs::prelude::*;
 use specs::saveload::{
     DeserializeComponents, MarkedBuilder, SerializeComponents, SimpleMarker, SimpleMarkerAllocator,
 };
 use std::fs;
-use std::fs::File;
-use std::path::Path;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+mod migrations;
+
+/// Bump this, and add a migration, whenever the saved components or their fields change.
+pub const SAVE_FORMAT_VERSION: u32 = 3;
+const SAVE_DIR: &str = "./saves";
+// Where saves went before there were slots
+const LEGACY_SAVE: &str = "./savegame.json";
+const BACKUP_SUFFIX: &str = ".bak";
+
+/// The first line of every save file, readable without loading the rest.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SaveHeader {
+    pub format_version: u32,
+    pub game_version: String,
+    pub seed: u64,
+    pub depth: i32,
+    pub timestamp: u64,
+}
+
+/// How the world is encoded after the header line. Loading works out the format by itself.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum SaveFormat {
+    Json,
+    Binary,
+    CompressedBinary,
+}
+
+// Binary bodies start with one of these, so they can't be mistaken for JSON
+const BINARY_MAGIC: &[u8] = b"RLMP";
+const COMPRESSED_MAGIC: &[u8] = b"RLMZ";
+
+impl SaveFormat {
+    pub const ALL: [SaveFormat; 3] = [
+        SaveFormat::Json,
+        SaveFormat::Binary,
+        SaveFormat::CompressedBinary,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SaveFormat::Json => "JSON",
+            SaveFormat::Binary => "Binary",
+            SaveFormat::CompressedBinary => "Compressed binary",
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            SaveFormat::Json => "json",
+            SaveFormat::Binary | SaveFormat::CompressedBinary => "sav",
+        }
+    }
+}
+
+pub struct SaveSlot {
+    pub name: String,
+    pub path: PathBuf,
+    pub header: Result<SaveHeader, String>,
+}
 
 macro_rules! serialize_individually {
-    ($ecs:expr, $ser:expr, $data:expr, $( $type:ty),*) => {
+    ($ecs:expr, $world:expr, $data:expr, $( $type:ty),*) => {
         $(
-        SerializeComponents::<NoError, SimpleMarker<SerializeMe>>::serialize(
-            &( $ecs.read_storage::<$type>(), ),
-            &$data.0,
-            &$data.1,
-            &mut $ser,
-        )
-        .unwrap();
+        $world.insert(
+            stringify!($type).to_string(),
+            SerializeComponents::<NoError, SimpleMarker<SerializeMe>>::serialize(
+                &( $ecs.read_storage::<$type>(), ),
+                &$data.0,
+                &$data.1,
+                serde_json::value::Serializer,
+            )
+            .unwrap(),
+        );
         )*
     };
 }
 
+/// A slot name for a new run. Runs can share a seed, so the name also carries the time the run
+/// started, bumped along if two runs start in the same millisecond.
+pub fn new_slot_name(seed: u64) -> String {
+    static LAST_START: AtomicU64 = AtomicU64::new(0);
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    let mut last = LAST_START.load(Ordering::Relaxed);
+    let started = loop {
+        let started = u64::max(now, last + 1);
+        match LAST_START.compare_exchange(last, started, Ordering::Relaxed, Ordering::Relaxed) {
+            Ok(_) => break started,
+            Err(current) => last = current,
+        }
+    };
+    format!("run-{}-{}", seed, started)
+}
+
+/// The slot the current run saves into.
+pub fn run_slot(ecs: &World) -> String {
+    let dm = ecs.fetch::<super::map::MasterDungeonMap>();
+    if dm.slot.is_empty() {
+        format!("run-{}", dm.seed)
+    } else {
+        dm.slot.clone()
+    }
+}
+
+/// Where a slot is saved in this format.
+pub fn slot_path(slot: &str, format: SaveFormat) -> PathBuf {
+    Path::new(SAVE_DIR).join(format!("{}.{}", slot, format.extension()))
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// The previous save in a slot is kept here, in case the newest one can't be read.
+fn backup_path(path: &Path) -> PathBuf {
+    with_suffix(path, BACKUP_SUFFIX)
+}
+
+fn timestamp() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn encode_world(world: &Map<String, Value>, format: SaveFormat) -> Result<Vec<u8>, String> {
+    match format {
+        SaveFormat::Json => {
+            serde_json::to_vec(world).map_err(|e| format!("Unable to serialize save: {}", e))
+        }
+        SaveFormat::Binary => {
+            let mut body = BINARY_MAGIC.to_vec();
+            rmp_serde::encode::write(&mut body, world)
+                .map_err(|e| format!("Unable to serialize save: {}", e))?;
+            Ok(body)
+        }
+        SaveFormat::CompressedBinary => {
+            let mut encoder =
+                DeflateEncoder::new(COMPRESSED_MAGIC.to_vec(), Compression::default());
+            rmp_serde::encode::write(&mut encoder, world)
+                .map_err(|e| format!("Unable to serialize save: {}", e))?;
+            encoder
+                .finish()
+                .map_err(|e| format!("Unable to compress save: {}", e))
+        }
+    }
+}
+
+fn decode_world(body: &[u8]) -> Result<Map<String, Value>, String> {
+    let world = if let Some(packed) = body.strip_prefix(BINARY_MAGIC) {
+        rmp_serde::from_slice(packed).map_err(|e| format!("Unreadable save: {}", e))?
+    } else if let Some(deflated) = body.strip_prefix(COMPRESSED_MAGIC) {
+        rmp_serde::from_read(DeflateDecoder::new(deflated))
+            .map_err(|e| format!("Unreadable save: {}", e))?
+    } else {
+        serde_json::from_slice(body).map_err(|e| format!("Unreadable save: {}", e))?
+    };
+    match world {
+        Value::Object(world) => Ok(world),
+        _ => Err("Save contents are not an object".to_string()),
+    }
+}
+
 #[cfg(target_arch = "wasm32")]
-pub fn save_game(_ecs: &mut World) {}
+pub fn save_game(_ecs: &mut World, _slot: &str, _format: SaveFormat) -> Result<(), String> {
+    Ok(())
+}
 
+/// Saves into a slot, replacing whatever the slot held before in any format.
 #[cfg(not(target_arch = "wasm32"))]
-pub fn save_game(ecs: &mut World) {
+pub fn save_game(ecs: &mut World, slot: &str, format: SaveFormat) -> Result<(), String> {
+    fs::create_dir_all(SAVE_DIR).map_err(|e| format!("Unable to create {}: {}", SAVE_DIR, e))?;
+    let path = slot_path(slot, format);
+    write_save(ecs, &path, format)?;
+    for other in SaveFormat::ALL.iter() {
+        let other_path = slot_path(slot, *other);
+        if other_path != path {
+            delete_save(&other_path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn write_save(_ecs: &mut World, _path: &Path, _format: SaveFormat) -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write_save(ecs: &mut World, path: &Path, format: SaveFormat) -> Result<(), String> {
     // Create helper
     let mapcopy = ecs.get_mut::<super::map::Map>().unwrap().clone();
     let dungeon_master = ecs
         .get_mut::<super::map::MasterDungeonMap>()
         .unwrap()
         .clone();
+    let header = SaveHeader {
+        format_version: SAVE_FORMAT_VERSION,
+        game_version: env!("CARGO_PKG_VERSION").to_string(),
+        seed: dungeon_master.seed,
+        depth: mapcopy.depth,
+        timestamp: timestamp(),
+    };
     let savehelper = ecs
         .create_entity()
         .with(SerializationHelper { map: mapcopy })
@@ -49,17 +237,16 @@ pub fn save_game(ecs: &mut World) {
         .build();
 
     // Actually serialize
+    let mut world = Map::new();
     {
         let data = (
             ecs.entities(),
             ecs.read_storage::<SimpleMarker<SerializeMe>>(),
         );
 
-        let writer = File::create("./savegame.json").unwrap();
-        let mut serializer = serde_json::Serializer::new(writer);
         serialize_individually!(
             ecs,
-            serializer,
+            world,
             data,
             Position,
             Renderable,
@@ -107,8 +294,10 @@ pub fn save_game(ecs: &mut World) {
             Initiative,
             MyTurn,
             Faction,
-            WantsToApproach,
-            WantsToFlee,
+            AiBehavior,
+            GroupLeader,
+            Routed,
+            Alertness,
             MoveMode,
             Chasing,
             EquipmentChanged,
@@ -130,45 +319,161 @@ pub fn save_game(ecs: &mut World) {
             Duration,
             KnownSpells,
             SpellTemplate,
+            SpellShape,
             WantsToCastSpell,
             TeachesSpell,
             ProvidesMana,
             Slow,
             DamageOverTime,
+            HealOverTime,
+            Blinded,
+            StatusStacks,
+            AppliesStatus,
+            StatusResistances,
+            DamageResistances,
+            Character,
+            SkillProgress,
+            Perks,
             SpecialAbilities,
             TileSize,
             OnDeath,
             AlwaysTargetsSelf,
             Target,
-            WantsToShoot
+            WantsToShoot,
+            WinsGame
         );
     }
 
     // Clean up
     ecs.delete_entity(savehelper).expect("Crash on cleanup");
     ecs.delete_entity(savehelper2).expect("Crash on cleanup");
+
+    let mut contents = serde_json::to_vec(&header)
+        .map_err(|e| format!("Unable to serialize save header: {}", e))?;
+    contents.push(b'\n');
+    contents.append(&mut encode_world(&world, format)?);
+
+    // Write everything to a temporary file first, so a crash can't leave a half-written save.
+    // The previous save becomes the backup.
+    let temp = with_suffix(path, ".tmp");
+    let written = fs::File::create(&temp).and_then(|mut file| {
+        file.write_all(&contents)?;
+        file.sync_all()
+    });
+    if let Err(e) = written {
+        let _ = fs::remove_file(&temp);
+        return Err(format!("Unable to write {}: {}", temp.display(), e));
+    }
+    if path.exists() {
+        fs::rename(path, backup_path(path))
+            .map_err(|e| format!("Unable to back up {}: {}", path.display(), e))?;
+    }
+    fs::rename(&temp, path).map_err(|e| format!("Unable to write {}: {}", path.display(), e))
+}
+
+fn read_header(path: &Path) -> Result<SaveHeader, String> {
+    let file = fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut first_line = String::new();
+    BufReader::new(file)
+        .read_line(&mut first_line)
+        .map_err(|e| e.to_string())?;
+    if first_line.starts_with('[') {
+        return Ok(migrations::LEGACY_HEADER);
+    }
+    serde_json::from_str(&first_line).map_err(|e| format!("Unreadable save header: {}", e))
+}
+
+/// The main file of every slot on disk, including slots where only the backup survived.
+fn save_paths() -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = match fs::read_dir(SAVE_DIR) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .map(|path| match path.to_str().and_then(|p| p.strip_suffix(BACKUP_SUFFIX)) {
+                Some(primary) => PathBuf::from(primary),
+                None => path,
+            })
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|ext| SaveFormat::ALL.iter().any(|f| ext == f.extension()))
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    paths.sort();
+    paths.dedup();
+    if Path::new(LEGACY_SAVE).exists() {
+        paths.push(PathBuf::from(LEGACY_SAVE));
+    }
+    paths
+}
+
+/// Every save slot on disk, newest first. A slot whose save can't be read shows its backup.
+pub fn list_saves() -> Vec<SaveSlot> {
+    let mut slots: Vec<SaveSlot> = save_paths()
+        .into_iter()
+        .map(|path| SaveSlot {
+            name: path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            header: read_header(&path)
+                .or_else(|e| read_header(&backup_path(&path)).map_err(|_| e)),
+            path,
+        })
+        .collect();
+    slots.sort_by_key(|slot| {
+        std::cmp::Reverse(slot.header.as_ref().map(|h| h.timestamp).unwrap_or(0))
+    });
+    slots
 }
 
 pub fn does_save_exist() -> bool {
-    Path::new("./savegame.json").exists()
+    list_saves().iter().any(|slot| slot.header.is_ok())
 }
 
 macro_rules! deserialize_individually {
-    ($ecs:expr, $de:expr, $data:expr, $( $type:ty),*) => {
+    ($ecs:expr, $world:expr, $data:expr, $( $type:ty),*) => {
         $(
         DeserializeComponents::<NoError, _>::deserialize(
             &mut ( &mut $ecs.write_storage::<$type>(), ),
             &$data.0, // entities
             &mut $data.1, // marker
             &mut $data.2, // allocater
-            &mut $de,
+            // Components added since the save was written simply have no entries
+            $world.get(stringify!($type)).unwrap_or(&Value::Array(Vec::new())),
         )
-        .unwrap();
+        .map_err(|e| format!("Unable to load {}: {}", stringify!($type), e))?;
         )*
     };
 }
 
-pub fn load_game(ecs: &mut World) {
+/// Reads a save file and upgrades it to the current format.
+fn read_save(path: &Path) -> Result<(SaveHeader, Map<String, Value>), String> {
+    let data = fs::read(path).map_err(|e| format!("Unable to read save: {}", e))?;
+    let (mut header, mut world) = if data.starts_with(b"[") {
+        let data = std::str::from_utf8(&data).map_err(|e| format!("Unreadable save: {}", e))?;
+        migrations::read_legacy(data)?
+    } else {
+        let split = data
+            .iter()
+            .position(|b| *b == b'\n')
+            .ok_or("Save file is truncated")?;
+        let header: SaveHeader = serde_json::from_slice(&data[..split])
+            .map_err(|e| format!("Unreadable save header: {}", e))?;
+        (header, decode_world(&data[split + 1..])?)
+    };
+    migrations::migrate(&mut header, &mut world)?;
+    Ok((header, world))
+}
+
+/// Replaces the world with a saved one, falling back to the backup if the save can't be read. On
+/// an error the world may already be partly replaced, so it's only fit to be thrown away.
+pub fn load_game(ecs: &mut World, path: &Path) -> Result<(), String> {
+    // Read and migrate everything first, so a save that can't be read leaves the world alone.
+    // Anything wrong with the components themselves only shows once the old world is gone.
+    let (_header, world) =
+        read_save(path).or_else(|e| read_save(&backup_path(path)).map_err(|_| e))?;
+
     {
         // Delete everything
         let mut to_delete = Vec::new();
@@ -180,9 +485,6 @@ pub fn load_game(ecs: &mut World) {
         }
     }
 
-    let data = fs::read_to_string("./savegame.json").unwrap();
-    let mut de = serde_json::Deserializer::from_str(&data);
-
     {
         let mut d = (
             &mut ecs.entities(),
@@ -192,7 +494,7 @@ pub fn load_game(ecs: &mut World) {
 
         deserialize_individually!(
             ecs,
-            de,
+            world,
             d,
             Position,
             Renderable,
@@ -240,8 +542,10 @@ pub fn load_game(ecs: &mut World) {
             Initiative,
             MyTurn,
             Faction,
-            WantsToApproach,
-            WantsToFlee,
+            AiBehavior,
+            GroupLeader,
+            Routed,
+            Alertness,
             MoveMode,
             Chasing,
             EquipmentChanged,
@@ -263,17 +567,28 @@ pub fn load_game(ecs: &mut World) {
             Duration,
             KnownSpells,
             SpellTemplate,
+            SpellShape,
             WantsToCastSpell,
             TeachesSpell,
             ProvidesMana,
             Slow,
             DamageOverTime,
+            HealOverTime,
+            Blinded,
+            StatusStacks,
+            AppliesStatus,
+            StatusResistances,
+            DamageResistances,
+            Character,
+            SkillProgress,
+            Perks,
             SpecialAbilities,
             TileSize,
             OnDeath,
             AlwaysTargetsSelf,
             Target,
-            WantsToShoot
+            WantsToShoot,
+            WinsGame
         );
     }
 
@@ -305,14 +620,31 @@ pub fn load_game(ecs: &mut World) {
             *player_resource = e;
         }
     }
-    ecs.delete_entity(deleteme.unwrap())
+    ecs.delete_entity(deleteme.ok_or("Save has no map")?)
         .expect("Unable to delete helper");
-    ecs.delete_entity(deleteme2.unwrap())
+    ecs.delete_entity(deleteme2.ok_or("Save has no dungeon")?)
         .expect("Unable to delete helper");
+    Ok(())
+}
+
+/// Deletes a save and its backup.
+pub fn delete_save(path: &Path) {
+    for path in [path.to_path_buf(), backup_path(path)] {
+        if path.exists() {
+            if let Err(e) = std::fs::remove_file(&path) {
+                crate::diagnostic(format!(
+                    "WARNING - unable to delete {}: {}",
+                    path.display(),
+                    e
+                ));
+            }
+        }
+    }
 }
 
-pub fn delete_save() {
-    if Path::new("./savegame.json").exists() {
-        std::fs::remove_file("./savegame.json").expect("Unable to delete file");
+/// Deletes a slot in every format, e.g. when its run ends.
+pub fn delete_slot(slot: &str) {
+    for format in SaveFormat::ALL.iter() {
+        delete_save(&slot_path(slot, *format));
     }
 }
@@ -0,0 +1,251 @@
+//! Upgrades saves written by older versions of the game, one format version at a time.
+
+use super::{SaveHeader, SAVE_FORMAT_VERSION};
+use serde_json::{json, Map, Value};
+use std::collections::HashSet;
+
+/// Saves from before format version 2 have no header line.
+pub const LEGACY_HEADER: SaveHeader = SaveHeader {
+    format_version: 1,
+    game_version: String::new(),
+    seed: 0,
+    depth: 0,
+    timestamp: 0,
+};
+
+/// The order components were written in by format version 1, which didn't name them.
+const V1_COMPONENTS: &[&str] = &[
+    "Position",
+    "Renderable",
+    "Player",
+    "Viewshed",
+    "Name",
+    "BlocksTile",
+    "WantsToMelee",
+    "Item",
+    "Consumable",
+    "Ranged",
+    "InflictsDamage",
+    "AreaOfEffect",
+    "Confusion",
+    "ProvidesHealing",
+    "InBackpack",
+    "WantsToPickupItem",
+    "WantsToUseItem",
+    "WantsToDropItem",
+    "SerializationHelper",
+    "Equippable",
+    "Equipped",
+    "Weapon",
+    "Wearable",
+    "WantsToRemoveItem",
+    "ParticleLifetime",
+    "HungerClock",
+    "ProvidesFood",
+    "MagicMapper",
+    "Hidden",
+    "EntryTrigger",
+    "EntityMoved",
+    "SingleActivation",
+    "BlocksVisibility",
+    "Door",
+    "Quips",
+    "Attributes",
+    "Skills",
+    "Pools",
+    "NaturalAttackDefense",
+    "LootTable",
+    "OtherLevelPosition",
+    "DMSerializationHelper",
+    "LightSource",
+    "Initiative",
+    "MyTurn",
+    "Faction",
+    "WantsToApproach",
+    "WantsToFlee",
+    "MoveMode",
+    "Chasing",
+    "EquipmentChanged",
+    "Vendor",
+    "TownPortal",
+    "TeleportTo",
+    "ApplyMove",
+    "ApplyTeleport",
+    "MagicItem",
+    "ObfuscatedName",
+    "IdentifiedItem",
+    "SpawnParticleBurst",
+    "SpawnParticleLine",
+    "CursedItem",
+    "ProvidesRemoveCurse",
+    "ProvidesIdentification",
+    "AttributeBonus",
+    "StatusEffect",
+    "Duration",
+    "KnownSpells",
+    "SpellTemplate",
+    "WantsToCastSpell",
+    "TeachesSpell",
+    "ProvidesMana",
+    "Slow",
+    "DamageOverTime",
+    "SpecialAbilities",
+    "TileSize",
+    "OnDeath",
+    "AlwaysTargetsSelf",
+    "Target",
+    "WantsToShoot",
+];
+
+type Migration = fn(&mut SaveHeader, &mut Map<String, Value>) -> Result<(), String>;
+
+/// `MIGRATIONS[n]` upgrades a save from format version n + 1 to n + 2.
+const MIGRATIONS: &[Migration] = &[v1_to_v2, v2_to_v3];
+
+/// Reads a version 1 save: one unnamed array of entities per component, back to back.
+pub fn read_legacy(data: &str) -> Result<(SaveHeader, Map<String, Value>), String> {
+    let mut world = Map::new();
+    let stream = serde_json::Deserializer::from_str(data).into_iter::<Value>();
+    for (name, storage) in V1_COMPONENTS.iter().zip(stream) {
+        let storage = storage.map_err(|e| format!("Unreadable save: {}", e))?;
+        world.insert(name.to_string(), storage);
+    }
+    Ok((LEGACY_HEADER, world))
+}
+
+pub fn migrate(header: &mut SaveHeader, world: &mut Map<String, Value>) -> Result<(), String> {
+    if header.format_version == 0 || header.format_version > SAVE_FORMAT_VERSION {
+        return Err(format!(
+            "Save format {} is not supported by this version of the game",
+            header.format_version
+        ));
+    }
+    while header.format_version < SAVE_FORMAT_VERSION {
+        MIGRATIONS[header.format_version as usize - 1](header, world)?;
+        header.format_version += 1;
+    }
+    Ok(())
+}
+
+/// The single component of the first entity stored for a component type.
+fn first_component<'a>(world: &'a mut Map<String, Value>, name: &str) -> Option<&'a mut Value> {
+    world
+        .get_mut(name)?
+        .get_mut(0)?
+        .get_mut("components")?
+        .get_mut(0)
+}
+
+fn default_field(object: &mut Value, field: &str, value: Value) {
+    if let Some(object) = object.as_object_mut() {
+        object.entry(field).or_insert(value);
+    }
+}
+
+/// Version 2 added map themes and run seeds, and moved the seed and depth into the header.
+fn v1_to_v2(header: &mut SaveHeader, world: &mut Map<String, Value>) -> Result<(), String> {
+    if let Some(helper) = first_component(world, "SerializationHelper") {
+        default_field(&mut helper["map"], "theme", Value::from("Default"));
+        header.depth = helper["map"]["depth"].as_i64().unwrap_or(0) as i32;
+    }
+    if let Some(helper) = first_component(world, "DMSerializationHelper") {
+        let dungeon = &mut helper["map"];
+        default_field(dungeon, "seed", Value::from(0));
+        if let Some(maps) = dungeon["maps"].as_object_mut() {
+            for map in maps.values_mut() {
+                default_field(map, "theme", Value::from("Default"));
+            }
+        }
+        header.seed = dungeon["seed"].as_u64().unwrap_or(0);
+    }
+    Ok(())
+}
+
+/// The markers of every entity that has a component of this type.
+fn markers_with(world: &Map<String, Value>, name: &str) -> HashSet<String> {
+    world
+        .get(name)
+        .and_then(Value::as_array)
+        .map(|entries| {
+            entries
+                .iter()
+                .filter(|entry| !entry["components"][0].is_null())
+                .map(|entry| entry["marker"].to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Takes every component of this type off the entities that pass the filter, returning each
+/// with its entity's marker.
+fn take_components(
+    world: &mut Map<String, Value>,
+    name: &str,
+    filter: impl Fn(&str) -> bool,
+) -> Vec<(Value, Value)> {
+    let mut taken = Vec::new();
+    if let Some(entries) = world.get_mut(name).and_then(Value::as_array_mut) {
+        entries.retain(|entry| {
+            let component = &entry["components"][0];
+            if component.is_null() || !filter(&entry["marker"].to_string()) {
+                return true;
+            }
+            taken.push((entry["marker"].clone(), component.clone()));
+            false
+        });
+    }
+    taken
+}
+
+/// Version 3 moved status effects into the raws. Items, spells and traps that caused one carry
+/// the effect's name instead of their own copy of it; the effects already on people are kept.
+fn v2_to_v3(_header: &mut SaveHeader, world: &mut Map<String, Value>) -> Result<(), String> {
+    let statuses = markers_with(world, "StatusEffect");
+    let consumables = markers_with(world, "Consumable");
+    let causes = |marker: &str| !statuses.contains(marker);
+
+    let mut applies: Vec<(Value, &str)> = Vec::new();
+    for (marker, _) in take_components(world, "Confusion", causes) {
+        applies.push((marker, "Confused"));
+    }
+    for (marker, slow) in take_components(world, "Slow", causes) {
+        let penalty = slow["initiative_penalty"].as_f64().unwrap_or(0.0);
+        let status = if penalty < 0.0 {
+            "Hasted"
+        } else if penalty >= 10.0 {
+            "Webbed"
+        } else {
+            "Slowed"
+        };
+        applies.push((marker, status));
+    }
+    for (marker, dot) in take_components(world, "DamageOverTime", causes) {
+        let damage = dot["damage"].as_i64().unwrap_or(0);
+        applies.push((marker, if damage >= 4 { "Envenomed" } else { "Poisoned" }));
+    }
+    for (marker, _) in take_components(world, "AttributeBonus", |marker| {
+        causes(marker) && consumables.contains(marker)
+    }) {
+        applies.push((marker, "Strengthened"));
+    }
+    // Only confusion gave its causes a duration
+    take_components(world, "Duration", causes);
+
+    let mut grouped: Vec<(Value, Vec<&str>)> = Vec::new();
+    for (marker, status) in applies {
+        match grouped.iter_mut().find(|(m, _)| *m == marker) {
+            Some((_, names)) => names.push(status),
+            None => grouped.push((marker, vec![status])),
+        }
+    }
+    if let Some(entries) = world
+        .entry("AppliesStatus")
+        .or_insert_with(|| Value::Array(Vec::new()))
+        .as_array_mut()
+    {
+        for (marker, names) in grouped {
+            entries.push(json!({ "marker": marker, "components": [{ "statuses": names }] }));
+        }
+    }
+    Ok(())
+}
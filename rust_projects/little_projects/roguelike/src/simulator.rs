@@ -0,0 +1,373 @@
+//! Headless fights between a player loadout and mobs from the raws, for balancing. A fight is a
+//! series of rounds in which the player, then the mob if it still stands, makes one attack
+//! through the real combat systems and effects queue. Status effects land, but durations only
+//! run down during a real turn, so nothing ticks.
+
+use crate::components::*;
+use crate::raws::{
+    get_spawn_table_for_depth, spawn_named_entity, spawn_named_mob, SpawnType, RAWS,
+};
+use crate::systems::particle_system::ParticleBuilder;
+use crate::{attr_bonus, mana_at_level, player_hp_at_level};
+use serde::Serialize;
+use specs::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+
+/// Where the two sides stand; any tile will do, since nothing moves.
+const PLAYER_SPOT: (i32, i32) = (1, 1);
+const OPPONENT_SPOT: (i32, i32) = (2, 1);
+
+/// The player's side of a fight.
+#[derive(Clone)]
+pub struct Loadout {
+    pub level: i32,
+    pub might: i32,
+    pub fitness: i32,
+    pub quickness: i32,
+    pub intelligence: i32,
+    /// Item names from the raws
+    pub equipped: Vec<String>,
+}
+
+impl Default for Loadout {
+    /// A new character in their starting gear.
+    fn default() -> Self {
+        Loadout {
+            level: 1,
+            might: 11,
+            fitness: 11,
+            quickness: 11,
+            intelligence: 11,
+            equipped: [
+                "Rusty Longsword",
+                "Stained Tunic",
+                "Torn Trousers",
+                "Old Boots",
+            ]
+            .iter()
+            .map(|name| name.to_string())
+            .collect(),
+        }
+    }
+}
+
+pub struct Fight {
+    pub opponent: String,
+    pub won: bool,
+    pub rounds: i32,
+    /// Damage done by each round in which the player hurt the opponent
+    pub hits: Vec<i32>,
+    pub damage_taken: i32,
+}
+
+/// Minimum, mean and spread of a set of numbers.
+#[derive(Serialize, Default, Clone, PartialEq, Debug)]
+pub struct Stats {
+    pub count: usize,
+    pub mean: f32,
+    pub min: i32,
+    pub median: i32,
+    pub p90: i32,
+    pub max: i32,
+}
+
+impl Stats {
+    pub fn of(values: &[i32]) -> Stats {
+        if values.is_empty() {
+            return Stats::default();
+        }
+        let mut sorted = values.to_vec();
+        sorted.sort_unstable();
+        let at = |fraction: f32| sorted[((sorted.len() - 1) as f32 * fraction).round() as usize];
+        Stats {
+            count: sorted.len(),
+            mean: sorted.iter().sum::<i32>() as f32 / sorted.len() as f32,
+            min: sorted[0],
+            median: at(0.5),
+            p90: at(0.9),
+            max: sorted[sorted.len() - 1],
+        }
+    }
+}
+
+/// How a batch of fights against one opponent went.
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct Report {
+    pub depth: Option<i32>,
+    pub opponent: String,
+    pub fights: usize,
+    pub wins: usize,
+    pub win_rate: f32,
+    /// Rounds taken to win, over the fights that were won
+    pub turns_to_kill: Stats,
+    pub damage_per_hit: Stats,
+    /// Damage the player took in each fight
+    pub damage_taken: Stats,
+}
+
+impl Report {
+    pub fn new(depth: Option<i32>, opponent: &str, fights: &[&Fight]) -> Report {
+        let wins: Vec<i32> = fights.iter().filter(|f| f.won).map(|f| f.rounds).collect();
+        let hits: Vec<i32> = fights.iter().flat_map(|f| f.hits.iter().copied()).collect();
+        let taken: Vec<i32> = fights.iter().map(|f| f.damage_taken).collect();
+        Report {
+            depth,
+            opponent: opponent.to_string(),
+            fights: fights.len(),
+            wins: wins.len(),
+            win_rate: if fights.is_empty() {
+                0.0
+            } else {
+                wins.len() as f32 / fights.len() as f32
+            },
+            turns_to_kill: Stats::of(&wins),
+            damage_per_hit: Stats::of(&hits),
+            damage_taken: Stats::of(&taken),
+        }
+    }
+}
+
+fn spawn_player_side(ecs: &mut World, loadout: &Loadout) -> Result<Entity, String> {
+    let attribute = |base: i32| Attribute {
+        base,
+        modifiers: 0,
+        bonus: attr_bonus(base),
+    };
+    let mut skills = HashMap::new();
+    for skill in [Skill::Melee, Skill::Defense, Skill::Magic, Skill::Stealth] {
+        skills.insert(skill, 1);
+    }
+    let hp = player_hp_at_level(loadout.fitness, loadout.level);
+    let mana = mana_at_level(loadout.intelligence, loadout.level);
+    let player = ecs
+        .create_entity()
+        .with(Position {
+            x: PLAYER_SPOT.0,
+            y: PLAYER_SPOT.1,
+        })
+        .with(Name {
+            name: "Player".to_string(),
+        })
+        .with(Attributes {
+            might: attribute(loadout.might),
+            fitness: attribute(loadout.fitness),
+            quickness: attribute(loadout.quickness),
+            intelligence: attribute(loadout.intelligence),
+        })
+        .with(Skills { skills })
+        .with(Pools {
+            hit_points: Pool {
+                current: hp,
+                max: hp,
+            },
+            mana: Pool {
+                current: mana,
+                max: mana,
+            },
+            xp: 0,
+            level: loadout.level,
+            total_weight: 0.0,
+            total_initiative_penalty: 0.0,
+            gold: 0.0,
+            god_mode: false,
+        })
+        .with(EquipmentChanged {})
+        .build();
+    for item in loadout.equipped.iter() {
+        if spawn_named_entity(
+            &RAWS.lock().unwrap(),
+            ecs,
+            item,
+            SpawnType::Equipped { by: player },
+        )
+        .is_none()
+        {
+            remove_combatants(ecs, &[player]);
+            return Err(format!("unknown item [{}]", item));
+        }
+    }
+    Ok(player)
+}
+
+fn spawn_opponent(ecs: &mut World, name: &str) -> Result<Entity, String> {
+    let opponent = spawn_named_mob(
+        &RAWS.lock().unwrap(),
+        ecs,
+        name,
+        SpawnType::AtPosition {
+            x: OPPONENT_SPOT.0,
+            y: OPPONENT_SPOT.1,
+        },
+    )
+    .ok_or(format!("unknown mob [{}]", name))?;
+    // No sneak attacks; the fight starts with both sides ready
+    if let Some(alert) = ecs.write_storage::<Alertness>().get_mut(opponent) {
+        alert.state = AlertState::Hunting;
+    }
+    Ok(opponent)
+}
+
+/// Shoots if wielding something with a range, otherwise swings.
+fn queue_attack(ecs: &mut World, attacker: Entity, target: Entity) {
+    let ranged = (
+        &ecs.read_storage::<Equipped>(),
+        &ecs.read_storage::<Weapon>(),
+    )
+        .join()
+        .any(|(equipped, weapon)| {
+            equipped.owner == attacker
+                && equipped.slot == EquipmentSlot::Melee
+                && weapon.range.is_some()
+        });
+    if ranged {
+        ecs.write_storage::<WantsToShoot>()
+            .insert(attacker, WantsToShoot { target })
+            .expect("Insert failed");
+    } else {
+        ecs.write_storage::<WantsToMelee>()
+            .insert(attacker, WantsToMelee { target })
+            .expect("Insert failed");
+    }
+}
+
+fn hit_points(ecs: &World, entity: Entity) -> i32 {
+    ecs.read_storage::<Pools>()
+        .get(entity)
+        .map_or(0, |pools| pools.hit_points.current)
+}
+
+/// Deletes the fighters, with their gear, their status effects and the fight's leftovers.
+fn remove_combatants(ecs: &mut World, fighters: &[Entity]) {
+    let mut doomed: Vec<Entity> = fighters.to_vec();
+    for (entity, equipped) in (&ecs.entities(), &ecs.read_storage::<Equipped>()).join() {
+        if fighters.contains(&equipped.owner) {
+            doomed.push(entity);
+        }
+    }
+    for (entity, status) in (&ecs.entities(), &ecs.read_storage::<StatusEffect>()).join() {
+        if fighters.contains(&status.target) {
+            doomed.push(entity);
+        }
+    }
+    for (entity, _) in (&ecs.entities(), &ecs.read_storage::<ParticleLifetime>()).join() {
+        doomed.push(entity);
+    }
+    for entity in doomed {
+        ecs.delete_entity(entity).expect("Delete failed");
+    }
+    ecs.maintain();
+    *ecs.write_resource::<ParticleBuilder>() = ParticleBuilder::new();
+    crate::gamelog::clear_log();
+}
+
+/// Fights the named mob until one side falls or the rounds run out, which counts as a loss.
+pub fn fight(
+    ecs: &mut World,
+    loadout: &Loadout,
+    opponent_name: &str,
+    max_rounds: i32,
+) -> Result<Fight, String> {
+    let player = spawn_player_side(ecs, loadout)?;
+    let opponent = match spawn_opponent(ecs, opponent_name) {
+        Ok(opponent) => opponent,
+        Err(e) => {
+            remove_combatants(ecs, &[player]);
+            return Err(e);
+        }
+    };
+    // Let worn items settle into the attributes before anyone swings
+    crate::systems::resolve_attacks(ecs);
+
+    let starting_hp = hit_points(ecs, player);
+    let mut result = Fight {
+        opponent: opponent_name.to_string(),
+        won: false,
+        rounds: 0,
+        hits: Vec::new(),
+        damage_taken: 0,
+    };
+    while result.rounds < max_rounds {
+        result.rounds += 1;
+        let opponent_hp = hit_points(ecs, opponent);
+        queue_attack(ecs, player, opponent);
+        crate::systems::resolve_attacks(ecs);
+        crate::effects::run_effects_queue(ecs);
+        if hit_points(ecs, opponent) > 0 {
+            queue_attack(ecs, opponent, player);
+            crate::systems::resolve_attacks(ecs);
+            crate::effects::run_effects_queue(ecs);
+        }
+
+        let dealt = opponent_hp - hit_points(ecs, opponent);
+        if dealt > 0 {
+            result.hits.push(dealt);
+        }
+        let player_down = hit_points(ecs, player) < 1;
+        if player_down || hit_points(ecs, opponent) < 1 {
+            result.won = !player_down;
+            break;
+        }
+    }
+    result.damage_taken = starting_hp - hit_points(ecs, player);
+
+    remove_combatants(ecs, &[player, opponent]);
+    Ok(result)
+}
+
+/// Fights the named mob over and over.
+pub fn simulate_mob(
+    ecs: &mut World,
+    loadout: &Loadout,
+    opponent: &str,
+    fights: usize,
+    max_rounds: i32,
+) -> Result<Report, String> {
+    let mut results = Vec::with_capacity(fights);
+    for _ in 0..fights {
+        results.push(fight(ecs, loadout, opponent, max_rounds)?);
+    }
+    Ok(Report::new(
+        None,
+        opponent,
+        &results.iter().collect::<Vec<_>>(),
+    ))
+}
+
+/// Fights mobs rolled from a depth's spawn table. Returns a report per mob met, in name order,
+/// followed by one for the whole depth.
+pub fn simulate_depth(
+    ecs: &mut World,
+    loadout: &Loadout,
+    depth: i32,
+    fights: usize,
+    max_rounds: i32,
+) -> Result<Vec<Report>, String> {
+    let table = get_spawn_table_for_depth(&RAWS.lock().unwrap(), depth);
+    let mut results = Vec::with_capacity(fights);
+    for _ in 0..fights {
+        // The table sometimes rolls nothing even when it has mobs, so give it a few tries
+        let opponent = (0..100)
+            .map(|_| table.roll_mob())
+            .find(|name| name != "None")
+            .ok_or(format!("no mobs spawn at depth {}", depth))?;
+        results.push(fight(ecs, loadout, &opponent, max_rounds)?);
+    }
+
+    let mut by_opponent: BTreeMap<&str, Vec<&Fight>> = BTreeMap::new();
+    for result in results.iter() {
+        by_opponent
+            .entry(result.opponent.as_str())
+            .or_default()
+            .push(result);
+    }
+    let mut reports: Vec<Report> = by_opponent
+        .iter()
+        .map(|(opponent, fights)| Report::new(Some(depth), opponent, fights))
+        .collect();
+    reports.push(Report::new(
+        Some(depth),
+        "(all)",
+        &results.iter().collect::<Vec<_>>(),
+    ));
+    Ok(reports)
+}
@@ -1,9 +1,8 @@
 use super::{
-    random_table::MasterTable, raws::*, Attribute, AttributeBonus, Attributes, Duration,
-    EntryTrigger, EquipmentChanged, Faction, HungerClock, HungerState, Initiative, KnownSpells,
-    LightSource, Map, MasterDungeonMap, Name, OtherLevelPosition, Player, Pool, Pools, Position,
-    Rect, Renderable, SerializeMe, SingleActivation, Skill, Skills, StatusEffect, TeleportTo,
-    TileType, Viewshed,
+    random_table::MasterTable, raws::*, Attribute, Attributes, Character, EntryTrigger,
+    EquipmentChanged, Faction, HungerClock, HungerState, Initiative, KnownSpells, LightSource, Map,
+    MasterDungeonMap, Name, OtherLevelPosition, Perks, Player, Pool, Pools, Position, Rect, Renderable,
+    SerializeMe, SingleActivation, SkillProgress, Skills, TeleportTo, TileType, Viewshed,
 };
 use crate::{attr_bonus, mana_at_level, player_hp_at_level};
 use rltk::RGB;
@@ -15,13 +14,6 @@ use std::collections::HashMap;
 pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
     spawn_all_spells(ecs);
 
-    let mut skills = Skills {
-        skills: HashMap::new(),
-    };
-    skills.skills.insert(Skill::Melee, 1);
-    skills.skills.insert(Skill::Defense, 1);
-    skills.skills.insert(Skill::Magic, 1);
-
     let player = ecs
         .create_entity()
         .with(Position {
@@ -69,7 +61,11 @@ pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
                 bonus: attr_bonus(11),
             },
         })
-        .with(skills)
+        .with(Skills {
+            skills: HashMap::new(),
+        })
+        .with(SkillProgress::default())
+        .with(Perks::default())
         .with(Pools {
             hit_points: Pool {
                 current: player_hp_at_level(11, 1),
@@ -99,59 +95,11 @@ pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 
-    // Starting equipment
-    spawn_named_entity(
-        &RAWS.lock().unwrap(),
-        ecs,
-        "Rusty Longsword",
-        SpawnType::Equipped { by: player },
-    );
-    spawn_named_entity(
-        &RAWS.lock().unwrap(),
-        ecs,
-        "Dried Sausage",
-        SpawnType::Carried { by: player },
-    );
-    spawn_named_entity(
-        &RAWS.lock().unwrap(),
-        ecs,
-        "Beer",
-        SpawnType::Carried { by: player },
-    );
-    spawn_named_entity(
-        &RAWS.lock().unwrap(),
-        ecs,
-        "Stained Tunic",
-        SpawnType::Equipped { by: player },
-    );
-    spawn_named_entity(
-        &RAWS.lock().unwrap(),
-        ecs,
-        "Torn Trousers",
-        SpawnType::Equipped { by: player },
-    );
-    spawn_named_entity(
-        &RAWS.lock().unwrap(),
-        ecs,
-        "Old Boots",
-        SpawnType::Equipped { by: player },
-    );
+    // Starting background and kit
+    crate::character::apply(ecs, player, &Character::default());
 
     // Starting hangover
-    ecs.create_entity()
-        .with(StatusEffect { target: player })
-        .with(Duration { turns: 10 })
-        .with(Name {
-            name: "Hangover".to_string(),
-        })
-        .with(AttributeBonus {
-            might: Some(-1),
-            fitness: None,
-            quickness: Some(-1),
-            intelligence: Some(-1),
-        })
-        .marked::<SimpleMarker<SerializeMe>>()
-        .build();
+    crate::effects::add_status(ecs, player, "Hangover");
 
     player
 }
@@ -188,7 +136,7 @@ pub fn spawn_region(
     spawn_list: &mut Vec<(usize, String)>,
 ) {
     let spawn_table = room_table(map_depth);
-    let mut spawn_points: HashMap<usize, String> = HashMap::new();
+    let mut spawn_points: Vec<(usize, String)> = Vec::new();
     let mut areas: Vec<usize> = Vec::from(area);
 
     // Scope to keep the borrow checker happy
@@ -209,15 +157,13 @@ pub fn spawn_region(
             };
 
             let map_idx = areas[array_index];
-            spawn_points.insert(map_idx, spawn_table.roll());
+            spawn_points.push((map_idx, spawn_table.roll()));
             areas.remove(array_index);
         }
     }
 
     // Actually spawn the monsters
-    for spawn in spawn_points.iter() {
-        spawn_list.push((*spawn.0, spawn.1.to_string()));
-    }
+    spawn_list.append(&mut spawn_points);
 }
 
 /// Spawns a named entity (name in tuple.1) at the location in (tuple.0)
@@ -239,7 +185,7 @@ pub fn spawn_entity(ecs: &mut World, spawn: &(&usize, &String)) {
     }
 
     if spawn.1 != "None" {
-        rltk::console::log(format!(
+        crate::diagnostic(format!(
             "WARNING: We don't know how to spawn [{}]!",
             spawn.1
         ));
@@ -1,5 +1,6 @@
 mod dispatcher;
 pub use dispatcher::UnifiedDispatcher;
+use specs::prelude::*;
 
 // System imports
 mod map_indexing_system;
@@ -28,3 +29,15 @@ use lighting_system::LightingSystem;
 pub fn build() -> Box<dyn UnifiedDispatcher + 'static> {
     dispatcher::new()
 }
+
+/// Settles equipment changes, then everyone's pending melee and ranged attacks, without the rest
+/// of a turn. The combat simulator fights with this.
+pub fn resolve_attacks(ecs: &mut World) {
+    let mut encumbrance = EncumbranceSystem {};
+    encumbrance.run_now(ecs);
+    let mut melee = MeleeCombatSystem {};
+    melee.run_now(ecs);
+    let mut ranged = RangedCombatSystem {};
+    ranged.run_now(ecs);
+    ecs.maintain();
+}
@@ -1,20 +1,15 @@
 mod initiative_system;
 mod turn_status;
 mod quipping;
-mod adjacent_ai_system;
-mod visible_ai_system;
-mod approach_ai_system;
-mod flee_ai_system;
-mod default_move_system;
-mod chase_ai_system;
+mod actions;
+mod awareness_system;
+mod behavior_ai_system;
+mod faction_alert_system;
 mod encumbrance_system;
 pub use initiative_system::InitiativeSystem;
 pub use turn_status::TurnStatusSystem;
 pub use quipping::QuipSystem;
-pub use adjacent_ai_system::AdjacentAI;
-pub use visible_ai_system::VisibleAI;
-pub use approach_ai_system::ApproachAI;
-pub use flee_ai_system::FleeAI;
-pub use default_move_system::DefaultMoveAI;
-pub use chase_ai_system::ChaseAI;
+pub use awareness_system::AwarenessSystem;
+pub use behavior_ai_system::BehaviorAI;
+pub use faction_alert_system::FactionAlertSystem;
 pub use encumbrance_system::EncumbranceSystem;
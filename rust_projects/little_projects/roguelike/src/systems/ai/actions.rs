@@ -0,0 +1,697 @@
+//! The things a mob can do with its turn. Behaviors in the raws choose between them, and each one
+//! returns true if it used up the turn.
+
+use crate::{
+    map::tile_walkable,
+    raws::{faction_reaction, RawMaster, Reaction},
+    rect::Rect,
+    Alertness, ApplyMove, Chasing, Equipped, Faction, Item, Map, MoveMode, Movement, Name, Pools,
+    Position, Ranged, SpecialAbilities, SpellTemplate, TileSize, Viewshed, WantsToCastSpell,
+    WantsToMelee, WantsToPickupItem, WantsToShoot, Weapon,
+};
+use rltk::{DistanceAlg, Point};
+use specs::prelude::*;
+
+/// Allies below this fraction of their hit points are worth healing.
+const WOUNDED: f32 = 0.75;
+/// How far a chase goes before the mob gives up.
+const CHASE_RANGE: i32 = 15;
+
+/// Someone a mob can see, and what it thinks of them.
+pub struct Sighting {
+    pub idx: usize,
+    pub entity: Entity,
+    pub reaction: Reaction,
+    pub ally: bool,
+}
+
+/// A mob taking its turn.
+pub struct Actor<'a> {
+    pub entity: Entity,
+    pub pos: Point,
+    pub idx: usize,
+    pub view: &'a [Point],
+    /// Everyone with a faction in view, nearest first
+    pub seen: Vec<Sighting>,
+}
+
+impl<'a> Actor<'a> {
+    pub fn new(
+        entity: Entity,
+        pos: &Position,
+        viewshed: Option<&'a Viewshed>,
+        map: &Map,
+        factions: &ReadStorage<Faction>,
+        raws: &RawMaster,
+    ) -> Actor<'a> {
+        let view: &[Point] = viewshed.map_or(&[], |vs| &vs.visible_tiles);
+        let idx = map.xy_idx(pos.x, pos.y);
+        let mut seen = Vec::new();
+        if let Some(my_faction) = factions.get(entity) {
+            for tile in view.iter() {
+                let tile_idx = map.xy_idx(tile.x, tile.y);
+                if tile_idx == idx {
+                    continue;
+                }
+                crate::spatial::for_each_tile_content(tile_idx, |other| {
+                    if let Some(faction) = factions.get(other) {
+                        seen.push(Sighting {
+                            idx: tile_idx,
+                            entity: other,
+                            reaction: faction_reaction(&my_faction.name, &faction.name, raws),
+                            ally: faction.name == my_faction.name,
+                        });
+                    }
+                });
+            }
+        }
+        let here = Point::new(pos.x, pos.y);
+        seen.sort_by(|a, b| {
+            distance(here, tile_point(map, a.idx))
+                .total_cmp(&distance(here, tile_point(map, b.idx)))
+        });
+        Actor {
+            entity,
+            pos: here,
+            idx,
+            view,
+            seen,
+        }
+    }
+
+    /// The tiles and entities this mob reacts to in one of these ways, nearest first.
+    pub fn targets(&self, reactions: &[Reaction]) -> Vec<(usize, Entity)> {
+        self.seen
+            .iter()
+            .filter(|s| reactions.contains(&s.reaction))
+            .map(|s| (s.idx, s.entity))
+            .collect()
+    }
+
+    /// Forgets the enemies in view, for a mob that hasn't noticed them yet.
+    pub fn overlook_enemies(&mut self) {
+        self.seen.retain(|s| s.reaction == Reaction::Ignore);
+    }
+}
+
+fn tile_point(map: &Map, idx: usize) -> Point {
+    Point::new(idx as i32 % map.width, idx as i32 / map.width)
+}
+
+fn distance(a: Point, b: Point) -> f32 {
+    DistanceAlg::Pythagoras.distance2d(a, b)
+}
+
+/// Takes one step along the shortest path to the tile, if there is one.
+fn step_toward(
+    actor: &Actor,
+    map: &Map,
+    dest_idx: usize,
+    apply_move: &mut WriteStorage<ApplyMove>,
+) -> bool {
+    let path = rltk::a_star_search(actor.idx, dest_idx, map);
+    if path.success && path.steps.len() > 1 {
+        apply_move
+            .insert(
+                actor.entity,
+                ApplyMove {
+                    dest_idx: path.steps[1],
+                },
+            )
+            .expect("Unable to insert");
+        return true;
+    }
+    false
+}
+
+/// Attacks anyone next to the mob, all the way around it for big mobs.
+pub fn melee(
+    actor: &Actor,
+    reactions: &[Reaction],
+    map: &Map,
+    size: Option<&TileSize>,
+    factions: &ReadStorage<Faction>,
+    raws: &RawMaster,
+    want_melee: &mut WriteStorage<WantsToMelee>,
+) -> bool {
+    let my_faction = match factions.get(actor.entity) {
+        Some(faction) => &faction.name,
+        None => return false,
+    };
+    let (w, h) = size.map_or((1, 1), |size| (size.x, size.y));
+    let body = Rect::new(actor.pos.x, actor.pos.y, w, h).get_all_tiles();
+    let mut around: Vec<(i32, i32)> = Rect::new(actor.pos.x - 1, actor.pos.y - 1, w + 2, h + 2)
+        .get_all_tiles()
+        .into_iter()
+        .filter(|t| !body.contains(t))
+        .filter(|t| t.0 >= 0 && t.0 < map.width && t.1 >= 0 && t.1 < map.height)
+        .collect();
+    // Tiles come out of a set; sort them so the same fight always goes the same way
+    around.sort();
+
+    let mut target = None;
+    for (x, y) in around.iter() {
+        crate::spatial::for_each_tile_content(map.xy_idx(*x, *y), |other| {
+            if let Some(faction) = factions.get(other) {
+                if target.is_none()
+                    && reactions.contains(&faction_reaction(my_faction, &faction.name, raws))
+                {
+                    target = Some(other);
+                }
+            }
+        });
+    }
+    if let Some(target) = target {
+        want_melee
+            .insert(actor.entity, WantsToMelee { target })
+            .expect("Error inserting melee");
+        return true;
+    }
+    false
+}
+
+/// Uses one of the mob's special abilities (or only the named one) on a target in range.
+pub fn cast(
+    actor: &Actor,
+    targets: &[(usize, Entity)],
+    map: &Map,
+    only: Option<&str>,
+    abilities: Option<&SpecialAbilities>,
+    find_spell: &dyn Fn(&str) -> Option<Entity>,
+    casting: &mut WriteStorage<WantsToCastSpell>,
+) -> bool {
+    let abilities = match abilities {
+        Some(abilities) => abilities,
+        None => return false,
+    };
+    for (idx, _) in targets.iter() {
+        let target = tile_point(map, *idx);
+        let range = distance(actor.pos, target);
+        for ability in abilities.abilities.iter() {
+            if only.is_some_and(|spell| spell != ability.spell) {
+                continue;
+            }
+            if range >= ability.min_range
+                && range <= ability.range
+                && crate::rng::roll_dice(1, 100) <= (ability.chance * 100.0) as i32
+            {
+                if let Some(spell) = find_spell(&ability.spell) {
+                    casting
+                        .insert(
+                            actor.entity,
+                            WantsToCastSpell {
+                                spell,
+                                target: Some(target),
+                            },
+                        )
+                        .expect("Unable to insert");
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
+/// Fires an equipped ranged weapon at the nearest target it can reach.
+pub fn shoot(
+    actor: &Actor,
+    targets: &[(usize, Entity)],
+    map: &Map,
+    weapons: &ReadStorage<Weapon>,
+    equipped: &ReadStorage<Equipped>,
+    wants_shoot: &mut WriteStorage<WantsToShoot>,
+) -> bool {
+    let reach = (weapons, equipped)
+        .join()
+        .filter(|(_, equip)| equip.owner == actor.entity)
+        .filter_map(|(weapon, _)| weapon.range)
+        .max();
+    if let Some(reach) = reach {
+        for (idx, target) in targets.iter() {
+            if reach >= distance(actor.pos, tile_point(map, *idx)) as i32 {
+                wants_shoot
+                    .insert(actor.entity, WantsToShoot { target: *target })
+                    .expect("Insert fail");
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// Runs away from everything in the list. Standing still when cornered still takes the turn.
+pub fn flee(
+    actor: &Actor,
+    targets: &[(usize, Entity)],
+    map: &mut Map,
+    apply_move: &mut WriteStorage<ApplyMove>,
+) -> bool {
+    if targets.is_empty() {
+        return false;
+    }
+    let indices: Vec<usize> = targets.iter().map(|(idx, _)| *idx).collect();
+    map.populate_blocked();
+    let flee_map = rltk::DijkstraMap::new(
+        map.width as usize,
+        map.height as usize,
+        &indices,
+        &*map,
+        100.0,
+    );
+    if let Some(flee_target) = rltk::DijkstraMap::find_highest_exit(&flee_map, actor.idx, &*map) {
+        if !crate::spatial::is_blocked(flee_target) {
+            apply_move
+                .insert(
+                    actor.entity,
+                    ApplyMove {
+                        dest_idx: flee_target,
+                    },
+                )
+                .expect("Unable to insert");
+        }
+    }
+    true
+}
+
+/// Heads for the nearest target within range, and keeps after it on later turns.
+pub fn approach(
+    actor: &Actor,
+    targets: &[(usize, Entity)],
+    map: &Map,
+    range: Option<i32>,
+    chasing: &mut WriteStorage<Chasing>,
+    apply_move: &mut WriteStorage<ApplyMove>,
+) -> bool {
+    let nearest = targets.iter().find(|(idx, _)| {
+        range.is_none_or(|range| distance(actor.pos, tile_point(map, *idx)) <= range as f32)
+    });
+    if let Some((idx, target)) = nearest {
+        if step_toward(actor, map, *idx, apply_move) {
+            chasing
+                .insert(actor.entity, Chasing { target: *target })
+                .expect("Unable to insert");
+            return true;
+        }
+    }
+    false
+}
+
+/// Moves to a free tile beside the target (the one it is chasing, or the nearest in view),
+/// preferring tiles away from allies so a group spreads out around it rather than queueing.
+pub fn surround(
+    actor: &Actor,
+    targets: &[(usize, Entity)],
+    map: &Map,
+    range: Option<i32>,
+    chasing: &WriteStorage<Chasing>,
+    positions: &ReadStorage<Position>,
+    apply_move: &mut WriteStorage<ApplyMove>,
+) -> bool {
+    let quarry = match chasing.get(actor.entity) {
+        Some(chase) => positions
+            .get(chase.target)
+            .map(|pos| Point::new(pos.x, pos.y)),
+        None => targets.first().map(|(idx, _)| tile_point(map, *idx)),
+    };
+    let quarry = match quarry {
+        Some(quarry) => quarry,
+        None => return false,
+    };
+    let how_far = distance(actor.pos, quarry);
+    if how_far < 1.5 || how_far > range.unwrap_or(CHASE_RANGE) as f32 {
+        return false;
+    }
+
+    let allies: Vec<Point> = actor
+        .seen
+        .iter()
+        .filter(|s| s.ally)
+        .map(|s| tile_point(map, s.idx))
+        .collect();
+    let mut best: Option<(usize, f32, usize)> = None;
+    for dy in -1..=1 {
+        for dx in -1..=1 {
+            let spot = Point::new(quarry.x + dx, quarry.y + dy);
+            if (dx == 0 && dy == 0)
+                || spot.x < 1
+                || spot.x > map.width - 2
+                || spot.y < 1
+                || spot.y > map.height - 2
+            {
+                continue;
+            }
+            let idx = map.xy_idx(spot.x, spot.y);
+            if !tile_walkable(map.tiles[idx]) || crate::spatial::is_blocked(idx) {
+                continue;
+            }
+            let crowding = allies
+                .iter()
+                .filter(|ally| distance(**ally, spot) < 1.5)
+                .count();
+            let cost = distance(actor.pos, spot);
+            if best.is_none_or(|(c, d, _)| crowding < c || (crowding == c && cost < d)) {
+                best = Some((crowding, cost, idx));
+            }
+        }
+    }
+    match best {
+        Some((_, _, idx)) => step_toward(actor, map, idx, apply_move),
+        None => false,
+    }
+}
+
+/// Backs off from any target closer than the range, so archers keep shooting rather than trade
+/// blows. Fails when there is nowhere further away to go.
+pub fn keep_distance(
+    actor: &Actor,
+    targets: &[(usize, Entity)],
+    map: &Map,
+    range: Option<i32>,
+    apply_move: &mut WriteStorage<ApplyMove>,
+) -> bool {
+    let range = range.unwrap_or(3) as f32;
+    let close: Vec<Point> = targets
+        .iter()
+        .map(|(idx, _)| tile_point(map, *idx))
+        .filter(|pos| distance(actor.pos, *pos) < range)
+        .collect();
+    if close.is_empty() {
+        return false;
+    }
+    let room = |spot: Point| {
+        close
+            .iter()
+            .map(|pos| distance(spot, *pos))
+            .fold(f32::MAX, f32::min)
+    };
+
+    let mut best: Option<(f32, usize)> = None;
+    for dy in -1..=1 {
+        for dx in -1..=1 {
+            let spot = Point::new(actor.pos.x + dx, actor.pos.y + dy);
+            if (dx == 0 && dy == 0)
+                || spot.x < 1
+                || spot.x > map.width - 2
+                || spot.y < 1
+                || spot.y > map.height - 2
+            {
+                continue;
+            }
+            let idx = map.xy_idx(spot.x, spot.y);
+            if tile_walkable(map.tiles[idx])
+                && !crate::spatial::is_blocked(idx)
+                && best.is_none_or(|(r, _)| room(spot) > r)
+            {
+                best = Some((room(spot), idx));
+            }
+        }
+    }
+    match best {
+        Some((further, dest_idx)) if further > room(actor.pos) => {
+            apply_move
+                .insert(actor.entity, ApplyMove { dest_idx })
+                .expect("Unable to insert");
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Goes to look at where the mob last heard or glimpsed something, forgetting it on arrival.
+pub fn investigate(
+    actor: &Actor,
+    alertness: Option<&mut Alertness>,
+    map: &Map,
+    apply_move: &mut WriteStorage<ApplyMove>,
+) -> bool {
+    let alert = match alertness {
+        Some(alert) => alert,
+        None => return false,
+    };
+    let dest_idx = match alert.heard {
+        Some(idx) => idx,
+        None => return false,
+    };
+    if distance(actor.pos, tile_point(map, dest_idx)) < 1.5
+        || !step_toward(actor, map, dest_idx, apply_move)
+    {
+        alert.heard = None;
+        return false;
+    }
+    true
+}
+
+/// Follows whoever the mob is chasing, even out of sight, until the trail gets too long.
+pub fn chase(
+    actor: &Actor,
+    map: &Map,
+    range: Option<i32>,
+    size: Option<&TileSize>,
+    positions: &ReadStorage<Position>,
+    chasing: &mut WriteStorage<Chasing>,
+    apply_move: &mut WriteStorage<ApplyMove>,
+) -> bool {
+    let target_pos = match chasing.get(actor.entity).map(|c| positions.get(c.target)) {
+        None => return false,
+        Some(None) => {
+            chasing.remove(actor.entity);
+            return false;
+        }
+        Some(Some(pos)) => map.xy_idx(pos.x, pos.y),
+    };
+
+    let path = if let Some(size) = size {
+        let mut map_copy = map.clone();
+        map_copy.populate_blocked_multi(size.x, size.y);
+        rltk::a_star_search(actor.idx, target_pos, &map_copy)
+    } else {
+        rltk::a_star_search(actor.idx, target_pos, map)
+    };
+    let range = range.unwrap_or(CHASE_RANGE) as usize;
+    if path.success && path.steps.len() > 1 && path.steps.len() < range {
+        apply_move
+            .insert(
+                actor.entity,
+                ApplyMove {
+                    dest_idx: path.steps[1],
+                },
+            )
+            .expect("Unable to insert");
+        return true;
+    }
+    chasing.remove(actor.entity);
+    false
+}
+
+/// Walks back home once the mob has strayed further than the range.
+pub fn guard(
+    actor: &Actor,
+    home: Point,
+    range: Option<i32>,
+    map: &Map,
+    apply_move: &mut WriteStorage<ApplyMove>,
+) -> bool {
+    if distance(actor.pos, home) <= range.unwrap_or(0) as f32 {
+        return false;
+    }
+    step_toward(actor, map, map.xy_idx(home.x, home.y), apply_move)
+}
+
+/// Casts a healing spell on the most badly hurt ally in view, if the mob has the mana for it.
+pub fn heal_ally(
+    actor: &Actor,
+    map: &Map,
+    spell: Option<Entity>,
+    pools: &ReadStorage<Pools>,
+    spells: &ReadStorage<SpellTemplate>,
+    ranges: &ReadStorage<Ranged>,
+    casting: &mut WriteStorage<WantsToCastSpell>,
+) -> bool {
+    let spell = match spell {
+        Some(spell) => spell,
+        None => return false,
+    };
+    let mana = pools.get(actor.entity).map_or(0, |p| p.mana.current);
+    if spells.get(spell).is_none_or(|s| s.mana_cost > mana) {
+        return false;
+    }
+
+    let mut patient: Option<(f32, usize)> = None;
+    for sighting in actor.seen.iter().filter(|s| s.ally) {
+        let in_range = ranges
+            .get(spell)
+            .is_none_or(|r| distance(actor.pos, tile_point(map, sighting.idx)) <= r.range as f32);
+        if let Some(pool) = pools.get(sighting.entity) {
+            let health = pool.hit_points.current as f32 / pool.hit_points.max.max(1) as f32;
+            if in_range && health < WOUNDED && patient.is_none_or(|(worst, _)| health < worst) {
+                patient = Some((health, sighting.idx));
+            }
+        }
+    }
+    if let Some((_, idx)) = patient {
+        casting
+            .insert(
+                actor.entity,
+                WantsToCastSpell {
+                    spell,
+                    target: Some(tile_point(map, idx)),
+                },
+            )
+            .expect("Unable to insert");
+        return true;
+    }
+    false
+}
+
+/// Picks up an item underfoot, or walks to the nearest one in view and within range.
+pub fn pick_up(
+    actor: &Actor,
+    map: &Map,
+    range: Option<i32>,
+    items: &ReadStorage<Item>,
+    positions: &ReadStorage<Position>,
+    wants_pickup: &mut WriteStorage<WantsToPickupItem>,
+    apply_move: &mut WriteStorage<ApplyMove>,
+) -> bool {
+    let mut underfoot = None;
+    crate::spatial::for_each_tile_content(actor.idx, |entity| {
+        if underfoot.is_none() && items.get(entity).is_some() {
+            underfoot = Some(entity);
+        }
+    });
+    if let Some(item) = underfoot {
+        wants_pickup
+            .insert(
+                actor.entity,
+                WantsToPickupItem {
+                    collected_by: actor.entity,
+                    item,
+                },
+            )
+            .expect("Unable to insert want to pickup");
+        return true;
+    }
+
+    let mut nearest: Option<(f32, usize)> = None;
+    for (_item, pos) in (items, positions).join() {
+        let spot = Point::new(pos.x, pos.y);
+        let how_far = distance(actor.pos, spot);
+        if actor.view.contains(&spot)
+            && range.is_none_or(|range| how_far <= range as f32)
+            && nearest.is_none_or(|(best, _)| how_far < best)
+        {
+            nearest = Some((how_far, map.xy_idx(pos.x, pos.y)));
+        }
+    }
+    match nearest {
+        Some((_, idx)) => step_toward(actor, map, idx, apply_move),
+        None => false,
+    }
+}
+
+/// Sets every ally in view that isn't already busy chasing on the mob's target.
+pub fn call_for_help(
+    actor: &Actor,
+    targets: &[(usize, Entity)],
+    map: &Map,
+    names: &ReadStorage<Name>,
+    chasing: &mut WriteStorage<Chasing>,
+) -> bool {
+    let target = match targets.first() {
+        Some((_, target)) => *target,
+        None => return false,
+    };
+    let recruits: Vec<Entity> = actor
+        .seen
+        .iter()
+        .filter(|s| s.ally && chasing.get(s.entity).is_none())
+        .map(|s| s.entity)
+        .collect();
+    if recruits.is_empty() {
+        return false;
+    }
+    for recruit in recruits.iter().chain(std::iter::once(&actor.entity)) {
+        chasing
+            .insert(*recruit, Chasing { target })
+            .expect("Unable to insert");
+    }
+    if map.visible_tiles[actor.idx] {
+        if let Some(name) = names.get(actor.entity) {
+            crate::gamelog::Logger::new()
+                .category(crate::gamelog::LogCategory::Dialogue)
+                .npc_name(&name.name)
+                .append("calls for help!")
+                .log();
+        }
+    }
+    true
+}
+
+/// Moves about according to the mob's movement mode from the raws.
+pub fn wander(
+    actor: &Actor,
+    mode: Option<&mut MoveMode>,
+    map: &Map,
+    apply_move: &mut WriteStorage<ApplyMove>,
+) -> bool {
+    let mode = match mode {
+        Some(mode) => mode,
+        None => return false,
+    };
+    match &mut mode.mode {
+        Movement::Static => {}
+
+        Movement::Random => {
+            let mut x = actor.pos.x;
+            let mut y = actor.pos.y;
+            let move_roll = crate::rng::roll_dice(1, 5);
+            match move_roll {
+                1 => x -= 1,
+                2 => x += 1,
+                3 => y -= 1,
+                4 => y += 1,
+                _ => {}
+            }
+
+            if x > 0 && x < map.width - 1 && y > 0 && y < map.height - 1 {
+                let dest_idx = map.xy_idx(x, y);
+                if !crate::spatial::is_blocked(dest_idx) {
+                    apply_move
+                        .insert(actor.entity, ApplyMove { dest_idx })
+                        .expect("Unable to insert");
+                }
+            }
+        }
+
+        Movement::RandomWaypoint { path } => {
+            if let Some(path) = path {
+                // We have a target - go there
+                if path.len() > 1 {
+                    if !crate::spatial::is_blocked(path[1]) {
+                        apply_move
+                            .insert(actor.entity, ApplyMove { dest_idx: path[1] })
+                            .expect("Unable to insert");
+                        path.remove(0); // Remove the first step in the path
+                    }
+                    // Otherwise we wait a turn to see if the path clears up
+                } else {
+                    mode.mode = Movement::RandomWaypoint { path: None };
+                }
+            } else {
+                let target_x = crate::rng::roll_dice(1, map.width - 2);
+                let target_y = crate::rng::roll_dice(1, map.height - 2);
+                let idx = map.xy_idx(target_x, target_y);
+                if tile_walkable(map.tiles[idx]) {
+                    let path = rltk::a_star_search(actor.idx, idx, map);
+                    if path.success && path.steps.len() > 1 {
+                        mode.mode = Movement::RandomWaypoint {
+                            path: Some(path.steps),
+                        };
+                    }
+                }
+            }
+        }
+    }
+    true
+}
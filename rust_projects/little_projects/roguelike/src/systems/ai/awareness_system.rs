@@ -0,0 +1,116 @@
+use super::actions::Actor;
+use crate::effects::{HUNTING_TURNS, SUSPICIOUS_TURNS};
+use crate::raws::{Reaction, RAWS};
+use crate::{
+    skill_bonus, AlertState, Alertness, Faction, Map, MyTurn, Name, Position, Skill, Skills,
+    Viewshed,
+};
+use specs::prelude::*;
+
+/// Mobs that aren't hunting yet try to spot the enemies in view, and slowly calm down when
+/// there are none.
+pub struct AwarenessSystem {}
+
+/// Rolls for a mob to notice someone this far away, against the target's stealth.
+fn noticed(alert: &Alertness, distance: f32, stealth: i32) -> bool {
+    if distance < 1.5 {
+        return true;
+    }
+    let wary = if alert.state == AlertState::Suspicious {
+        5
+    } else {
+        0
+    };
+    crate::rng::roll_dice(1, 20) + wary + alert.hearing > 10 + stealth + distance as i32 / 2
+}
+
+impl<'a> System<'a> for AwarenessSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadStorage<'a, MyTurn>,
+        ReadStorage<'a, Faction>,
+        ReadStorage<'a, Position>,
+        ReadExpect<'a, Map>,
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        ReadStorage<'a, Viewshed>,
+        WriteStorage<'a, Alertness>,
+        ReadStorage<'a, Skills>,
+        ReadStorage<'a, Name>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            turns,
+            factions,
+            positions,
+            map,
+            entities,
+            player,
+            viewsheds,
+            mut alertness,
+            skills,
+            names,
+        ) = data;
+
+        let raws = RAWS.lock().unwrap();
+        for (entity, _turn, pos, alert) in (&entities, &turns, &positions, &mut alertness).join() {
+            let actor = Actor::new(entity, pos, viewsheds.get(entity), &map, &factions, &raws);
+            let threats = actor.targets(&[Reaction::Attack, Reaction::Flee]);
+            let nearest = match threats.first() {
+                Some((idx, _)) => *idx,
+                None => {
+                    alert.turns -= 1;
+                    if alert.turns < 1 {
+                        match alert.state {
+                            AlertState::Hunting => {
+                                alert.state = AlertState::Suspicious;
+                                alert.turns = SUSPICIOUS_TURNS;
+                            }
+                            _ => {
+                                alert.state = AlertState::Unaware;
+                                alert.heard = None;
+                            }
+                        }
+                    }
+                    continue;
+                }
+            };
+
+            if alert.state != AlertState::Hunting {
+                let spotted = threats.iter().find(|(idx, target)| {
+                    let distance = rltk::DistanceAlg::Pythagoras.distance2d(
+                        actor.pos,
+                        rltk::Point::new(*idx as i32 % map.width, *idx as i32 / map.width),
+                    );
+                    let stealth = skills
+                        .get(*target)
+                        .map_or(0, |s| skill_bonus(Skill::Stealth, s));
+                    noticed(alert, distance, stealth)
+                });
+                match spotted {
+                    Some((_, target)) => {
+                        alert.state = AlertState::Hunting;
+                        if *target == *player && map.visible_tiles[actor.idx] {
+                            if let Some(name) = names.get(entity) {
+                                crate::gamelog::Logger::new()
+                                    .category(crate::gamelog::LogCategory::Combat)
+                                    .npc_name(&name.name)
+                                    .append("notices you!")
+                                    .log();
+                            }
+                        }
+                    }
+                    None => alert.state = AlertState::Suspicious,
+                }
+            }
+            // Even a glimpse is worth looking into
+            alert.heard = Some(nearest);
+            alert.turns = if alert.state == AlertState::Hunting {
+                HUNTING_TURNS
+            } else {
+                SUSPICIOUS_TURNS
+            };
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use super::actions::{self, Actor};
+use crate::raws::{get_behavior, BehaviorAction, Reaction, DEFAULT_BEHAVIOR, RAWS};
+use crate::{
+    AiBehavior, AlertState, Alertness, ApplyMove, Chasing, Equipped, Faction, Item, Map, MoveMode,
+    MyTurn, Name, Pools, Position, Ranged, Routed, SpecialAbilities, SpellTemplate, TileSize,
+    Viewshed, WantsToCastSpell, WantsToMelee, WantsToPickupItem, WantsToShoot, Weapon,
+};
+use specs::prelude::*;
+
+/// Decides what every mob does with its turn, using the behavior named in its raws.
+pub struct BehaviorAI {}
+
+/// The faction reactions an action responds to when the raws don't say.
+fn reactions(option: &BehaviorAction) -> Vec<Reaction> {
+    match &option.reactions {
+        Some(names) => names
+            .iter()
+            .map(|name| match name.as_str() {
+                "ignore" => Reaction::Ignore,
+                "flee" => Reaction::Flee,
+                _ => Reaction::Attack,
+            })
+            .collect(),
+        None if option.action == "flee" => vec![Reaction::Flee],
+        None => vec![Reaction::Attack],
+    }
+}
+
+/// True if the action's conditions hold for a mob with this much of its health left.
+fn considered(option: &BehaviorAction, health: f32) -> bool {
+    option.hp_below.is_none_or(|hp| health < hp)
+        && option.hp_above.is_none_or(|hp| health > hp)
+        && option
+            .chance
+            .is_none_or(|chance| crate::rng::roll_dice(1, 100) <= (chance * 100.0) as i32)
+}
+
+impl<'a> System<'a> for BehaviorAI {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        WriteStorage<'a, MyTurn>,
+        ReadStorage<'a, Faction>,
+        ReadStorage<'a, Position>,
+        WriteExpect<'a, Map>,
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        ReadStorage<'a, Viewshed>,
+        WriteStorage<'a, Chasing>,
+        ReadStorage<'a, SpecialAbilities>,
+        WriteStorage<'a, WantsToCastSpell>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, SpellTemplate>,
+        WriteStorage<'a, WantsToShoot>,
+        ReadStorage<'a, Weapon>,
+        ReadStorage<'a, Equipped>,
+        WriteStorage<'a, WantsToMelee>,
+        WriteStorage<'a, ApplyMove>,
+        WriteStorage<'a, MoveMode>,
+        ReadStorage<'a, TileSize>,
+        ReadStorage<'a, Pools>,
+        ReadStorage<'a, AiBehavior>,
+        ReadStorage<'a, Item>,
+        WriteStorage<'a, WantsToPickupItem>,
+        ReadStorage<'a, Ranged>,
+        WriteStorage<'a, Routed>,
+        WriteStorage<'a, Alertness>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            mut turns,
+            factions,
+            positions,
+            mut map,
+            entities,
+            player,
+            viewsheds,
+            mut chasing,
+            abilities,
+            mut casting,
+            names,
+            spells,
+            mut wants_shoot,
+            weapons,
+            equipped,
+            mut want_melee,
+            mut apply_move,
+            mut move_mode,
+            sizes,
+            pools,
+            behaviors,
+            items,
+            mut wants_pickup,
+            ranges,
+            mut routed,
+            mut alertness,
+        ) = data;
+
+        let raws = RAWS.lock().unwrap();
+        let find_spell =
+            |name: &str| crate::raws::find_spell_entity_by_name(name, &names, &spells, &entities);
+
+        let movers: Vec<Entity> = (&entities, &turns, &positions)
+            .join()
+            .map(|(entity, _turn, _pos)| entity)
+            .filter(|entity| *entity != *player)
+            .collect();
+        let mut turn_done: Vec<Entity> = Vec::new();
+        for entity in movers {
+            let behavior = behaviors.get(entity);
+            let behavior = match get_behavior(
+                &raws,
+                behavior.map_or(DEFAULT_BEHAVIOR, |b| b.name.as_str()),
+            ) {
+                Some(behavior) => behavior,
+                None => continue,
+            };
+            let pos = positions.get(entity).unwrap();
+            let mut actor = Actor::new(entity, pos, viewsheds.get(entity), &map, &factions, &raws);
+            // A routed mob forgets its behavior and runs from anyone who means it harm
+            if let Some(rout) = routed.get_mut(entity) {
+                rout.turns -= 1;
+                let threats = actor.targets(&[Reaction::Attack, Reaction::Flee]);
+                actions::flee(&actor, &threats, &mut map, &mut apply_move);
+                turn_done.push(entity);
+                continue;
+            }
+            if alertness
+                .get(entity)
+                .is_some_and(|a| a.state != AlertState::Hunting)
+            {
+                actor.overlook_enemies();
+            }
+
+            let health = pools.get(entity).map_or(1.0, |p| {
+                p.hit_points.current as f32 / p.hit_points.max.max(1) as f32
+            });
+
+            // Highest score first; the sort is stable, so ties go to the first listed
+            let mut options: Vec<&BehaviorAction> = behavior.actions.iter().collect();
+            options.sort_by(|a, b| b.score.total_cmp(&a.score));
+            for option in options {
+                if !considered(option, health) {
+                    continue;
+                }
+                let reactions = reactions(option);
+                let targets = actor.targets(&reactions);
+                let done = match option.action.as_str() {
+                    "melee" => actions::melee(
+                        &actor,
+                        &reactions,
+                        &map,
+                        sizes.get(entity),
+                        &factions,
+                        &raws,
+                        &mut want_melee,
+                    ),
+                    "cast" => actions::cast(
+                        &actor,
+                        &targets,
+                        &map,
+                        option.spell.as_deref(),
+                        abilities.get(entity),
+                        &find_spell,
+                        &mut casting,
+                    ),
+                    "shoot" => actions::shoot(
+                        &actor,
+                        &targets,
+                        &map,
+                        &weapons,
+                        &equipped,
+                        &mut wants_shoot,
+                    ),
+                    "flee" => actions::flee(&actor, &targets, &mut map, &mut apply_move),
+                    "approach" => actions::approach(
+                        &actor,
+                        &targets,
+                        &map,
+                        option.range,
+                        &mut chasing,
+                        &mut apply_move,
+                    ),
+                    "surround" => actions::surround(
+                        &actor,
+                        &targets,
+                        &map,
+                        option.range,
+                        &chasing,
+                        &positions,
+                        &mut apply_move,
+                    ),
+                    "keep_distance" => actions::keep_distance(
+                        &actor,
+                        &targets,
+                        &map,
+                        option.range,
+                        &mut apply_move,
+                    ),
+                    "chase" => actions::chase(
+                        &actor,
+                        &map,
+                        option.range,
+                        sizes.get(entity),
+                        &positions,
+                        &mut chasing,
+                        &mut apply_move,
+                    ),
+                    "guard" => {
+                        let home = behavior_home(behaviors.get(entity), pos);
+                        actions::guard(&actor, home, option.range, &map, &mut apply_move)
+                    }
+                    "heal_ally" => actions::heal_ally(
+                        &actor,
+                        &map,
+                        option.spell.as_deref().and_then(find_spell),
+                        &pools,
+                        &spells,
+                        &ranges,
+                        &mut casting,
+                    ),
+                    "pick_up" => actions::pick_up(
+                        &actor,
+                        &map,
+                        option.range,
+                        &items,
+                        &positions,
+                        &mut wants_pickup,
+                        &mut apply_move,
+                    ),
+                    "call_for_help" => {
+                        actions::call_for_help(&actor, &targets, &map, &names, &mut chasing)
+                    }
+                    "investigate" => actions::investigate(
+                        &actor,
+                        alertness.get_mut(entity),
+                        &map,
+                        &mut apply_move,
+                    ),
+                    "wander" => {
+                        actions::wander(&actor, move_mode.get_mut(entity), &map, &mut apply_move)
+                    }
+                    "wait" => true,
+                    _ => false,
+                };
+                if done {
+                    turn_done.push(entity);
+                    break;
+                }
+            }
+        }
+
+        // Remove turn marker for those that are done
+        for done in turn_done.iter() {
+            turns.remove(*done);
+        }
+
+        let rallied: Vec<Entity> = (&entities, &routed)
+            .join()
+            .filter(|(_, rout)| rout.turns < 1)
+            .map(|(entity, _)| entity)
+            .collect();
+        for entity in rallied {
+            routed.remove(entity);
+        }
+    }
+}
+
+/// Where a guard returns to: where it was spawned, or where it stands if that isn't known.
+fn behavior_home(behavior: Option<&AiBehavior>, pos: &Position) -> rltk::Point {
+    match behavior {
+        Some(b) => rltk::Point::new(b.home_x, b.home_y),
+        None => rltk::Point::new(pos.x, pos.y),
+    }
+}
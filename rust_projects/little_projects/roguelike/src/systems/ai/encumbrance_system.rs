@@ -1,6 +1,7 @@
 use crate::{
-    gamesystem::attr_bonus, AttributeBonus, Attributes, EquipmentChanged, Equipped, InBackpack,
-    Item, Pools, Slow, StatusEffect,
+    gamesystem::{attr_bonus, carry_capacity_lbs},
+    AttributeBonus, Attributes, EquipmentChanged, Equipped, InBackpack, Item, Perks, Pools, Slow,
+    StatusEffect,
 };
 use specs::prelude::*;
 use std::collections::HashMap;
@@ -21,6 +22,7 @@ impl<'a> System<'a> for EncumbranceSystem {
         ReadStorage<'a, AttributeBonus>,
         ReadStorage<'a, StatusEffect>,
         ReadStorage<'a, Slow>,
+        ReadStorage<'a, Perks>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -36,6 +38,7 @@ impl<'a> System<'a> for EncumbranceSystem {
             attrbonus,
             statuses,
             slowed,
+            perks,
         ) = data;
 
         if equip_dirty.is_empty() {
@@ -131,8 +134,7 @@ impl<'a> System<'a> for EncumbranceSystem {
                     attr.intelligence.bonus =
                         attr_bonus(attr.intelligence.base + attr.intelligence.modifiers);
 
-                    let carry_capacity_lbs = (attr.might.base + attr.might.modifiers) * 15;
-                    if pool.total_weight as i32 > carry_capacity_lbs {
+                    if pool.total_weight as i32 > carry_capacity_lbs(attr, perks.get(*entity)) {
                         // Overburdened
                         pool.total_initiative_penalty += 4.0;
                         if *entity == *player {
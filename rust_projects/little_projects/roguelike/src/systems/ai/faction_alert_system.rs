@@ -0,0 +1,99 @@
+use super::actions::Actor;
+use crate::effects::HUNTING_TURNS;
+use crate::raws::{behavior_has_action, faction_tactics, Reaction, RAWS};
+use crate::{
+    AiBehavior, AlertState, Alertness, Chasing, Faction, Map, MyTurn, Name, Position, Viewshed,
+};
+use specs::prelude::*;
+
+/// Mobs that spot an enemy on their turn shout, setting every ally of their faction within the
+/// faction's alert radius on it. Allies that chase give chase; the rest go and look where it was
+/// seen. Shouting is free; the mob still gets to act.
+pub struct FactionAlertSystem {}
+
+impl<'a> System<'a> for FactionAlertSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadStorage<'a, MyTurn>,
+        ReadStorage<'a, Faction>,
+        ReadStorage<'a, Position>,
+        ReadExpect<'a, Map>,
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        ReadStorage<'a, Viewshed>,
+        WriteStorage<'a, Chasing>,
+        ReadStorage<'a, Name>,
+        WriteStorage<'a, Alertness>,
+        ReadStorage<'a, AiBehavior>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            turns,
+            factions,
+            positions,
+            map,
+            entities,
+            player,
+            viewsheds,
+            mut chasing,
+            names,
+            mut alertness,
+            behaviors,
+        ) = data;
+
+        let raws = RAWS.lock().unwrap();
+        for (entity, _turn, faction, pos) in (&entities, &turns, &factions, &positions).join() {
+            if entity == *player {
+                continue;
+            }
+            let (radius, _) = faction_tactics(&raws, &faction.name);
+            let hunting = alertness
+                .get(entity)
+                .is_none_or(|a| a.state == AlertState::Hunting);
+            if radius < 1 || !hunting {
+                continue;
+            }
+            let actor = Actor::new(entity, pos, viewsheds.get(entity), &map, &factions, &raws);
+            let (target_idx, target) = match actor.targets(&[Reaction::Attack]).first() {
+                Some(sighting) => *sighting,
+                None => continue,
+            };
+
+            let here = rltk::Point::new(pos.x, pos.y);
+            let mut alerted = 0;
+            for (ally, ally_faction, ally_pos) in (&entities, &factions, &positions).join() {
+                if ally != entity
+                    && ally_faction.name == faction.name
+                    && chasing.get(ally).is_none()
+                    && rltk::DistanceAlg::Pythagoras
+                        .distance2d(here, rltk::Point::new(ally_pos.x, ally_pos.y))
+                        <= radius as f32
+                {
+                    let behavior = behaviors.get(ally).map(|b| b.name.as_str());
+                    if behavior_has_action(&raws, behavior, "chase") {
+                        chasing
+                            .insert(ally, Chasing { target })
+                            .expect("Unable to insert");
+                    }
+                    if let Some(alert) = alertness.get_mut(ally) {
+                        alert.state = AlertState::Hunting;
+                        alert.heard = Some(target_idx);
+                        alert.turns = HUNTING_TURNS;
+                    }
+                    alerted += 1;
+                }
+            }
+
+            if alerted > 0 && map.visible_tiles[actor.idx] {
+                if let Some(name) = names.get(entity) {
+                    crate::gamelog::Logger::new()
+                        .category(crate::gamelog::LogCategory::Dialogue)
+                        .npc_name(&name.name)
+                        .append("shouts a warning!")
+                        .log();
+                }
+            }
+        }
+    }
+}
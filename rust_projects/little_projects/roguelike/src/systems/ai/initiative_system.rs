@@ -1,6 +1,6 @@
 use crate::{
-    Attributes, DamageOverTime, Duration, EquipmentChanged, Initiative, MyTurn, Pools, Position,
-    RunState, StatusEffect,
+    Attributes, DamageOverTime, Duration, EquipmentChanged, HealOverTime, Initiative, KnownSpells,
+    MyTurn, Name, OtherLevelPosition, Pools, Position, RunState, StatusEffect, Viewshed,
 };
 use specs::prelude::*;
 
@@ -22,6 +22,11 @@ impl<'a> System<'a> for InitiativeSystem {
         WriteStorage<'a, EquipmentChanged>,
         ReadStorage<'a, StatusEffect>,
         ReadStorage<'a, DamageOverTime>,
+        ReadStorage<'a, HealOverTime>,
+        ReadStorage<'a, Name>,
+        WriteStorage<'a, Viewshed>,
+        WriteStorage<'a, KnownSpells>,
+        ReadStorage<'a, OtherLevelPosition>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -39,6 +44,11 @@ impl<'a> System<'a> for InitiativeSystem {
             mut dirty,
             statuses,
             dots,
+            hots,
+            names,
+            mut viewsheds,
+            mut known_spells,
+            frozen,
         ) = data;
 
         if *runstate != RunState::Ticking {
@@ -93,13 +103,31 @@ impl<'a> System<'a> for InitiativeSystem {
         // Handle durations
         if *runstate == RunState::AwaitingInput {
             use crate::effects::*;
+            for known in (&mut known_spells).join() {
+                for spell in known.spells.iter_mut() {
+                    spell.cooldown = i32::max(0, spell.cooldown - 1);
+                }
+            }
+            // Those on other levels catch up when the player changes level
             for (effect_entity, duration, status) in (&entities, &mut durations, &statuses).join() {
-                if entities.is_alive(status.target) {
+                if entities.is_alive(status.target) && frozen.get(status.target).is_none() {
                     duration.turns -= 1;
                     if let Some(dot) = dots.get(effect_entity) {
                         add_effect(
                             None,
-                            EffectType::Damage { amount: dot.damage },
+                            EffectType::Damage {
+                                amount: dot.damage,
+                                damage_type: dot.damage_type,
+                            },
+                            Targets::Single {
+                                target: status.target,
+                            },
+                        );
+                    }
+                    if let Some(hot) = hots.get(effect_entity) {
+                        add_effect(
+                            None,
+                            EffectType::Healing { amount: hot.amount },
                             Targets::Single {
                                 target: status.target,
                             },
@@ -109,6 +137,12 @@ impl<'a> System<'a> for InitiativeSystem {
                         dirty
                             .insert(status.target, EquipmentChanged {})
                             .expect("Unable to insert");
+                        if let Some(viewshed) = viewsheds.get_mut(status.target) {
+                            viewshed.dirty = true;
+                        }
+                        if let Some(name) = names.get(effect_entity) {
+                            status_expired(&name.name, status.target);
+                        }
                         entities.delete(effect_entity).expect("Unable to delete");
                     }
                 }
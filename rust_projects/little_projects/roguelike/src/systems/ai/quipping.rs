@@ -28,6 +28,7 @@ impl<'a> System<'a> for QuipSystem {
                 };
 
                 crate::gamelog::Logger::new()
+                    .category(crate::gamelog::LogCategory::Dialogue)
                     .npc_name(&name.name)
                     .append("says")
                     .item_name(&quip.available[quip_index])
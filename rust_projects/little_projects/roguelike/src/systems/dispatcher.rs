@@ -1,17 +1,9 @@
-#[cfg(target_arch = "wasm32")]
+// Systems run one after another in a fixed order on every platform, so a seed and the player's
+// input always play out the same way (see replay.rs)
 #[macro_use]
 mod single_thread;
-
-#[cfg(not(target_arch = "wasm32"))]
-#[macro_use]
-mod multi_thread;
-
-#[cfg(target_arch = "wasm32")]
 pub use single_thread::*;
 
-#[cfg(not(target_arch = "wasm32"))]
-pub use multi_thread::*;
-
 use super::*;
 use specs::prelude::World;
 
@@ -26,12 +18,9 @@ construct_dispatcher!(
     (InitiativeSystem, "initiative", &[]),
     (TurnStatusSystem, "turnstatus", &[]),
     (QuipSystem, "quips", &[]),
-    (AdjacentAI, "adjacent", &[]),
-    (VisibleAI, "visible", &[]),
-    (ApproachAI, "approach", &[]),
-    (FleeAI, "flee", &[]),
-    (ChaseAI, "chase", &[]),
-    (DefaultMoveAI, "default_move", &[]),
+    (AwarenessSystem, "awareness", &[]),
+    (FactionAlertSystem, "faction_alert", &[]),
+    (BehaviorAI, "behavior_ai", &[]),
     (MovementSystem, "movement", &[]),
     (TriggerSystem, "triggers", &[]),
     (MeleeCombatSystem, "melee", &[]),
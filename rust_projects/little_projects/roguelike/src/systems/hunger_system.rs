@@ -1,4 +1,4 @@
-use crate::{effects::*, HungerClock, HungerState, MyTurn};
+use crate::{effects::*, DamageType, HungerClock, HungerState, MyTurn};
 use specs::prelude::*;
 
 pub struct HungerSystem {}
@@ -24,6 +24,7 @@ impl<'a> System<'a> for HungerSystem {
                         clock.duration = 200;
                         if entity == *player_entity {
                             crate::gamelog::Logger::new()
+                                .category(crate::gamelog::LogCategory::System)
                                 .color(rltk::ORANGE)
                                 .append("You are no longer well fed")
                                 .log();
@@ -34,6 +35,7 @@ impl<'a> System<'a> for HungerSystem {
                         clock.duration = 200;
                         if entity == *player_entity {
                             crate::gamelog::Logger::new()
+                                .category(crate::gamelog::LogCategory::System)
                                 .color(rltk::ORANGE)
                                 .append("You are hungry")
                                 .log();
@@ -44,6 +46,7 @@ impl<'a> System<'a> for HungerSystem {
                         clock.duration = 200;
                         if entity == *player_entity {
                             crate::gamelog::Logger::new()
+                                .category(crate::gamelog::LogCategory::System)
                                 .color(rltk::RED)
                                 .append("You are starving!")
                                 .log();
@@ -53,13 +56,17 @@ impl<'a> System<'a> for HungerSystem {
                         // Inflict damage from hunger
                         if entity == *player_entity {
                             crate::gamelog::Logger::new()
+                                .category(crate::gamelog::LogCategory::Combat)
                                 .color(rltk::RED)
                                 .append("Your hunger pangs are getting painful! You suffer 1 hp damage.")
                                 .log();
                         }
                         add_effect(
                             None,
-                            EffectType::Damage { amount: 1 },
+                            EffectType::Damage {
+                                amount: 1,
+                                damage_type: DamageType::Physical,
+                            },
                             Targets::Single { target: entity },
                         );
                     }
@@ -2,6 +2,7 @@ use crate::{
     gamelog, AreaOfEffect, CursedItem, EquipmentChanged, Equippable, Equipped, IdentifiedItem,
     InBackpack, Item, MagicItem, Map, MasterDungeonMap, Name, ObfuscatedName, Position,
     WantsToCastSpell, WantsToDropItem, WantsToPickupItem, WantsToRemoveItem, WantsToUseItem,
+    WinsGame,
 };
 
 mod collection_system;
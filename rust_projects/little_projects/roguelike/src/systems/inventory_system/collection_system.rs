@@ -1,7 +1,8 @@
 use super::{
     EquipmentChanged, InBackpack, MagicItem, MasterDungeonMap, Name, ObfuscatedName, Position,
-    WantsToPickupItem,
+    WantsToPickupItem, WinsGame,
 };
+use crate::morgue::RunEnd;
 use specs::prelude::*;
 
 pub struct ItemCollectionSystem {}
@@ -18,6 +19,8 @@ impl<'a> System<'a> for ItemCollectionSystem {
         ReadStorage<'a, MagicItem>,
         ReadStorage<'a, ObfuscatedName>,
         ReadExpect<'a, MasterDungeonMap>,
+        ReadStorage<'a, WinsGame>,
+        WriteExpect<'a, RunEnd>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -31,6 +34,8 @@ impl<'a> System<'a> for ItemCollectionSystem {
             magic_items,
             obfuscated_names,
             dm,
+            wins_game,
+            mut run_end,
         ) = data;
 
         for pickup in wants_pickup.join() {
@@ -49,6 +54,7 @@ impl<'a> System<'a> for ItemCollectionSystem {
 
             if pickup.collected_by == *player_entity {
                 crate::gamelog::Logger::new()
+                    .category(crate::gamelog::LogCategory::Loot)
                     .append("You pick up the")
                     .item_name(super::obfuscate_name(
                         pickup.item,
@@ -58,6 +64,9 @@ impl<'a> System<'a> for ItemCollectionSystem {
                         &dm,
                     ))
                     .log();
+                if wins_game.get(pickup.item).is_some() {
+                    *run_end = RunEnd::Won;
+                }
             }
         }
 
@@ -50,6 +50,7 @@ impl<'a> System<'a> for ItemEquipOnUse {
                         if cursed.get(item_entity).is_some() {
                             // Cursed item unequipping
                             crate::gamelog::Logger::new()
+                                .category(crate::gamelog::LogCategory::Loot)
                                 .append("You cannot unequip")
                                 .item_name(&name.name)
                                 .append("- it is cursed!")
@@ -60,6 +61,7 @@ impl<'a> System<'a> for ItemEquipOnUse {
                             if target == *player_entity {
                                 // Unequipped item
                                 crate::gamelog::Logger::new()
+                                    .category(crate::gamelog::LogCategory::Loot)
                                     .append("You unequip")
                                     .item_name(&name.name)
                                     .log();
@@ -102,6 +104,7 @@ impl<'a> System<'a> for ItemEquipOnUse {
                     if target == *player_entity {
                         // Wield
                         crate::gamelog::Logger::new()
+                            .category(crate::gamelog::LogCategory::Loot)
                             .append("You equip")
                             .item_name(&names.get(useitem.item).unwrap().name)
                             .log();
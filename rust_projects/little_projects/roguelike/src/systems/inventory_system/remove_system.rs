@@ -22,6 +22,7 @@ impl<'a> System<'a> for ItemRemoveSystem {
         for (entity, to_remove) in (&entities, &wants_remove).join() {
             if cursed.get(to_remove.item).is_some() {
                 crate::gamelog::Logger::new()
+                    .category(crate::gamelog::LogCategory::Loot)
                     .append("You cannot remove ")
                     .append(names.get(to_remove.item).unwrap().name.clone())
                     .append(", it is cursed")
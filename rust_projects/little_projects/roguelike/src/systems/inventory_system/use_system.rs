@@ -1,6 +1,7 @@
 use specs::prelude::*;
 use super::{Name, WantsToUseItem,Map, AreaOfEffect, EquipmentChanged, IdentifiedItem, WantsToCastSpell};
 use crate::effects::*;
+use crate::Skill;
 
 pub struct ItemUseSystem {}
 
@@ -96,6 +97,8 @@ impl<'a> System<'a> for SpellUseSystem {
                 }
             );
 
+            // Casting is practice for magic
+            add_effect(None, EffectType::Practice{ skill: Skill::Magic }, Targets::Single{ target: entity });
         }
 
         wants_use.clear();
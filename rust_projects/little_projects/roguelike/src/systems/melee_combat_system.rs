@@ -1,9 +1,13 @@
 use crate::{
-    effects::*, skill_bonus, Attributes, EquipmentSlot, Equipped, HungerClock, HungerState, Name,
-    NaturalAttackDefense, Pools, Skill, Skills, WantsToMelee, Weapon, WeaponAttribute, Wearable,
+    effects::*, mitigate_damage, skill_bonus, AlertState, Alertness, Attributes, DamageResistances,
+    DamageType, EquipmentSlot, Equipped, HungerClock, HungerState, Name, NaturalAttackDefense,
+    Perks, Pools, Skill, Skills, WantsToMelee, Weapon, WeaponAttribute, Wearable,
 };
 use specs::prelude::*;
 
+/// Hit bonus for attacking someone who hasn't noticed the attacker.
+const SNEAK_HIT_BONUS: i32 = 4;
+
 pub struct MeleeCombatSystem {}
 
 impl<'a> System<'a> for MeleeCombatSystem {
@@ -20,6 +24,9 @@ impl<'a> System<'a> for MeleeCombatSystem {
         ReadStorage<'a, Weapon>,
         ReadStorage<'a, Wearable>,
         ReadStorage<'a, NaturalAttackDefense>,
+        ReadStorage<'a, Alertness>,
+        ReadStorage<'a, DamageResistances>,
+        ReadStorage<'a, Perks>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -35,6 +42,9 @@ impl<'a> System<'a> for MeleeCombatSystem {
             meleeweapons,
             wearables,
             natural,
+            alertness,
+            damage_resistances,
+            perks,
         ) = data;
 
         for (entity, wants_melee, name, attacker_attributes, attacker_skills, attacker_pools) in (
@@ -64,6 +74,8 @@ impl<'a> System<'a> for MeleeCombatSystem {
                     damage_bonus: 0,
                     proc_chance: None,
                     proc_target: None,
+                    damage_type: DamageType::Physical,
+                    rider: None,
                 };
 
                 if let Some(nat) = natural.get(entity) {
@@ -77,6 +89,7 @@ impl<'a> System<'a> for MeleeCombatSystem {
                         weapon_info.damage_n_dice = nat.attacks[attack_index].damage_n_dice;
                         weapon_info.damage_die_type = nat.attacks[attack_index].damage_die_type;
                         weapon_info.damage_bonus = nat.attacks[attack_index].damage_bonus;
+                        weapon_info.damage_type = nat.attacks[attack_index].damage_type;
                     }
                 }
 
@@ -90,145 +103,276 @@ impl<'a> System<'a> for MeleeCombatSystem {
                     }
                 }
 
-                let natural_roll = crate::rng::roll_dice(1, 20);
-                let attribute_hit_bonus = if weapon_info.attribute == WeaponAttribute::Might {
-                    attacker_attributes.might.bonus
-                } else {
-                    attacker_attributes.quickness.bonus
-                };
-                let skill_hit_bonus = skill_bonus(Skill::Melee, &*attacker_skills);
-                let weapon_hit_bonus = weapon_info.hit_bonus;
-                let mut status_hit_bonus = 0;
-                if let Some(hc) = hunger_clock.get(entity) {
-                    // Well-Fed grants +1
-                    if hc.state == HungerState::WellFed {
-                        status_hit_bonus += 1;
+                // Perks add attacks of their own, made after the main one
+                let mut attacks = vec![(weapon_info, weapon_entity)];
+                let mut crit_chance = 0;
+                if let Some(perks) = perks.get(entity) {
+                    crit_chance = perks.crit_chance;
+                    for attack in perks.extra_attacks.iter() {
+                        attacks.push((
+                            Weapon {
+                                range: None,
+                                attribute: WeaponAttribute::Might,
+                                hit_bonus: attack.hit_bonus,
+                                damage_n_dice: attack.damage_n_dice,
+                                damage_die_type: attack.damage_die_type,
+                                damage_bonus: attack.damage_bonus,
+                                proc_chance: None,
+                                proc_target: None,
+                                damage_type: attack.damage_type,
+                                rider: None,
+                            },
+                            None,
+                        ));
                     }
                 }
-                let modified_hit_roll = natural_roll
-                    + attribute_hit_bonus
-                    + skill_hit_bonus
-                    + weapon_hit_bonus
-                    + status_hit_bonus;
-                //println!("Natural roll: {}", natural_roll);
-                //println!("Modified hit roll: {}", modified_hit_roll);
-
-                let mut armor_item_bonus_f = 0.0;
-                for (wielded, armor) in (&equipped_items, &wearables).join() {
-                    if wielded.owner == wants_melee.target {
-                        armor_item_bonus_f += armor.armor_class;
+
+                for (weapon_info, weapon_entity) in attacks {
+                    let natural_roll = crate::rng::roll_dice(1, 20);
+                    let attribute_hit_bonus = if weapon_info.attribute == WeaponAttribute::Might {
+                        attacker_attributes.might.bonus
+                    } else {
+                        attacker_attributes.quickness.bonus
+                    };
+                    let skill_hit_bonus = skill_bonus(Skill::Melee, &*attacker_skills);
+                    let weapon_hit_bonus = weapon_info.hit_bonus;
+                    let sneak_attack = alertness
+                        .get(wants_melee.target)
+                        .is_some_and(|a| a.state != AlertState::Hunting);
+                    let mut status_hit_bonus = 0;
+                    if sneak_attack {
+                        status_hit_bonus += SNEAK_HIT_BONUS;
                     }
-                }
-                let base_armor_class = match natural.get(wants_melee.target) {
-                    None => 10,
-                    Some(nat) => nat.armor_class.unwrap_or(10),
-                };
-                let armor_quickness_bonus = target_attributes.quickness.bonus;
-                let armor_skill_bonus = skill_bonus(Skill::Defense, &*target_skills);
-                let armor_item_bonus = armor_item_bonus_f as i32;
-                let armor_class =
-                    base_armor_class + armor_quickness_bonus + armor_skill_bonus + armor_item_bonus;
-
-                //println!("Armor class: {}", armor_class);
-                if natural_roll != 1 && (natural_roll == 20 || modified_hit_roll > armor_class) {
-                    // Target hit! Until we support weapons, we're going with 1d4
-                    let base_damage = crate::rng::roll_dice(
-                        weapon_info.damage_n_dice,
-                        weapon_info.damage_die_type,
-                    );
-                    let attr_damage_bonus = attacker_attributes.might.bonus;
-                    let skill_damage_bonus = skill_bonus(Skill::Melee, &*attacker_skills);
-                    let weapon_damage_bonus = weapon_info.damage_bonus;
+                    if let Some(hc) = hunger_clock.get(entity) {
+                        // Well-Fed grants +1
+                        if hc.state == HungerState::WellFed {
+                            status_hit_bonus += 1;
+                        }
+                    }
+                    let modified_hit_roll = natural_roll
+                        + attribute_hit_bonus
+                        + skill_hit_bonus
+                        + weapon_hit_bonus
+                        + status_hit_bonus;
+                    //println!("Natural roll: {}", natural_roll);
+                    //println!("Modified hit roll: {}", modified_hit_roll);
 
-                    let damage = i32::max(
-                        0,
-                        base_damage + attr_damage_bonus + skill_damage_bonus + weapon_damage_bonus,
-                    );
+                    let mut armor_item_bonus_f = 0.0;
+                    for (wielded, armor) in (&equipped_items, &wearables).join() {
+                        if wielded.owner == wants_melee.target {
+                            armor_item_bonus_f += armor.armor_class;
+                        }
+                    }
+                    let base_armor_class = match natural.get(wants_melee.target) {
+                        None => 10,
+                        Some(nat) => nat.armor_class.unwrap_or(10),
+                    };
+                    let armor_quickness_bonus = target_attributes.quickness.bonus;
+                    let armor_skill_bonus = skill_bonus(Skill::Defense, &*target_skills);
+                    let armor_item_bonus = armor_item_bonus_f as i32;
+                    let armor_class = base_armor_class
+                        + armor_quickness_bonus
+                        + armor_skill_bonus
+                        + armor_item_bonus;
 
-                    /*println!("Damage: {} + {}attr + {}skill + {}weapon = {}",
-                        base_damage, attr_damage_bonus, skill_damage_bonus,
-                        weapon_damage_bonus, damage
-                    );*/
-                    add_effect(
-                        Some(entity),
-                        EffectType::Damage { amount: damage },
-                        Targets::Single {
-                            target: wants_melee.target,
-                        },
-                    );
+                    //println!("Armor class: {}", armor_class);
+                    if natural_roll != 1 && (natural_roll == 20 || modified_hit_roll > armor_class)
+                    {
+                        // Target hit! Until we support weapons, we're going with 1d4
+                        let base_damage = crate::rng::roll_dice(
+                            weapon_info.damage_n_dice,
+                            weapon_info.damage_die_type,
+                        );
+                        let attr_damage_bonus = attacker_attributes.might.bonus;
+                        let skill_damage_bonus = skill_bonus(Skill::Melee, &*attacker_skills);
+                        let weapon_damage_bonus = weapon_info.damage_bonus;
+                        // Unaware targets take double the dice, plus the attacker's stealth
+                        let sneak_damage_bonus = if sneak_attack {
+                            base_damage + skill_bonus(Skill::Stealth, attacker_skills)
+                        } else {
+                            0
+                        };
+                        // Critical hits roll the dice again
+                        let critical =
+                            crit_chance > 0 && crate::rng::roll_dice(1, 100) <= crit_chance;
+                        let crit_damage_bonus = if critical {
+                            crate::rng::roll_dice(
+                                weapon_info.damage_n_dice,
+                                weapon_info.damage_die_type,
+                            )
+                        } else {
+                            0
+                        };
+
+                        let damage = i32::max(
+                            0,
+                            base_damage
+                                + attr_damage_bonus
+                                + skill_damage_bonus
+                                + weapon_damage_bonus
+                                + sneak_damage_bonus
+                                + crit_damage_bonus,
+                        );
+
+                        /*println!("Damage: {} + {}attr + {}skill + {}weapon = {}",
+                            base_damage, attr_damage_bonus, skill_damage_bonus,
+                            weapon_damage_bonus, damage
+                        );*/
+                        add_effect(
+                            Some(entity),
+                            EffectType::Damage {
+                                amount: damage,
+                                damage_type: weapon_info.damage_type,
+                            },
+                            Targets::Single {
+                                target: wants_melee.target,
+                            },
+                        );
+                        let (dealt, resisted) = mitigate_damage(
+                            damage,
+                            weapon_info.damage_type,
+                            wants_melee.target,
+                            &damage_resistances,
+                            &equipped_items,
+                        );
 
-                    crate::gamelog::Logger::new()
-                        .npc_name(&name.name)
-                        .append("hits")
-                        .npc_name(&target_name.name)
-                        .append("for")
-                        .damage(damage)
-                        .append("hp.")
-                        .log();
-
-                    // Proc effects
-                    if let Some(chance) = &weapon_info.proc_chance {
-                        let roll = crate::rng::roll_dice(1, 100);
-                        //println!("Roll {}, Chance {}", roll, chance);
-                        if roll <= (chance * 100.0) as i32 {
-                            //println!("Proc!");
-                            let effect_target = if weapon_info.proc_target.unwrap() == "Self" {
-                                Targets::Single { target: entity }
+                        crate::gamelog::Logger::new()
+                            .category(crate::gamelog::LogCategory::Combat)
+                            .npc_name(&name.name)
+                            .append(if sneak_attack {
+                                "sneak attacks"
+                            } else if critical {
+                                "critically hits"
                             } else {
-                                Targets::Single {
-                                    target: wants_melee.target,
-                                }
-                            };
+                                "hits"
+                            })
+                            .npc_name(&target_name.name)
+                            .append("for")
+                            .typed_damage(dealt, weapon_info.damage_type, resisted)
+                            .log();
+
+                        if let Some(rider) = &weapon_info.rider {
+                            let rider_damage = i32::max(
+                                0,
+                                crate::rng::roll_dice(rider.n_dice, rider.die_type) + rider.bonus,
+                            );
                             add_effect(
                                 Some(entity),
-                                EffectType::ItemUse {
-                                    item: weapon_entity.unwrap(),
+                                EffectType::Damage {
+                                    amount: rider_damage,
+                                    damage_type: rider.damage_type,
                                 },
-                                effect_target,
-                            )
+                                Targets::Single {
+                                    target: wants_melee.target,
+                                },
+                            );
+                            let (dealt, resisted) = mitigate_damage(
+                                rider_damage,
+                                rider.damage_type,
+                                wants_melee.target,
+                                &damage_resistances,
+                                &equipped_items,
+                            );
+                            crate::gamelog::Logger::new()
+                                .category(crate::gamelog::LogCategory::Combat)
+                                .npc_name(&target_name.name)
+                                .append("also takes")
+                                .typed_damage(dealt, rider.damage_type, resisted)
+                                .log();
                         }
+
+                        add_effect(
+                            None,
+                            EffectType::Practice {
+                                skill: Skill::Melee,
+                            },
+                            Targets::Single { target: entity },
+                        );
+
+                        // Proc effects
+                        if let Some(chance) = &weapon_info.proc_chance {
+                            let roll = crate::rng::roll_dice(1, 100);
+                            //println!("Roll {}, Chance {}", roll, chance);
+                            if roll <= (chance * 100.0) as i32 {
+                                //println!("Proc!");
+                                let effect_target = if weapon_info.proc_target.unwrap() == "Self" {
+                                    Targets::Single { target: entity }
+                                } else {
+                                    Targets::Single {
+                                        target: wants_melee.target,
+                                    }
+                                };
+                                add_effect(
+                                    Some(entity),
+                                    EffectType::ItemUse {
+                                        item: weapon_entity.unwrap(),
+                                    },
+                                    effect_target,
+                                )
+                            }
+                        }
+                    } else if natural_roll == 1 {
+                        // Natural 1 miss
+                        crate::gamelog::Logger::new()
+                            .category(crate::gamelog::LogCategory::Combat)
+                            .npc_name(&name.name)
+                            .append("considers attacking")
+                            .npc_name(&target_name.name)
+                            .append("but misjudges the timing!")
+                            .log();
+                        add_effect(
+                            None,
+                            EffectType::Particle {
+                                glyph: rltk::to_cp437('‼'),
+                                fg: rltk::RGB::named(rltk::BLUE),
+                                bg: rltk::RGB::named(rltk::BLACK),
+                                lifespan: 200.0,
+                            },
+                            Targets::Single {
+                                target: wants_melee.target,
+                            },
+                        );
+                    } else {
+                        // Miss
+                        crate::gamelog::Logger::new()
+                            .category(crate::gamelog::LogCategory::Combat)
+                            .npc_name(&name.name)
+                            .append("attacks")
+                            .npc_name(&target_name.name)
+                            .append("but can't connect.")
+                            .log();
+                        add_effect(
+                            None,
+                            EffectType::Particle {
+                                glyph: rltk::to_cp437('‼'),
+                                fg: rltk::RGB::named(rltk::CYAN),
+                                bg: rltk::RGB::named(rltk::BLACK),
+                                lifespan: 200.0,
+                            },
+                            Targets::Single {
+                                target: wants_melee.target,
+                            },
+                        );
                     }
-                } else if natural_roll == 1 {
-                    // Natural 1 miss
-                    crate::gamelog::Logger::new()
-                        .npc_name(&name.name)
-                        .append("considers attacking")
-                        .npc_name(&target_name.name)
-                        .append("but misjudges the timing!")
-                        .log();
-                    add_effect(
-                        None,
-                        EffectType::Particle {
-                            glyph: rltk::to_cp437('‼'),
-                            fg: rltk::RGB::named(rltk::BLUE),
-                            bg: rltk::RGB::named(rltk::BLACK),
-                            lifespan: 200.0,
-                        },
-                        Targets::Single {
-                            target: wants_melee.target,
-                        },
-                    );
-                } else {
-                    // Miss
-                    crate::gamelog::Logger::new()
-                        .npc_name(&name.name)
-                        .append("attacks")
-                        .npc_name(&target_name.name)
-                        .append("but can't connect.")
-                        .log();
+                    // Being attacked is practice for defending
                     add_effect(
                         None,
-                        EffectType::Particle {
-                            glyph: rltk::to_cp437('‼'),
-                            fg: rltk::RGB::named(rltk::CYAN),
-                            bg: rltk::RGB::named(rltk::BLACK),
-                            lifespan: 200.0,
+                        EffectType::Practice {
+                            skill: Skill::Defense,
                         },
                         Targets::Single {
                             target: wants_melee.target,
                         },
                     );
                 }
+
+                add_effect(
+                    Some(entity),
+                    EffectType::Noise {
+                        volume: MELEE_NOISE,
+                    },
+                    Targets::Single { target: entity },
+                );
             }
         }
 
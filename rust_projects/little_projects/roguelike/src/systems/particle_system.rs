@@ -2,6 +2,8 @@ use crate::{Map, ParticleLifetime, Position, Renderable, Rltk};
 use rltk::RGB;
 use specs::prelude::*;
 
+/// Animates particles and hides the ones that have run out of time. They aren't deleted until
+/// `cull_particles`, since freeing entity ids on a timer would make replays drift.
 pub fn update_particles(ecs: &mut World, ctx: &Rltk) {
     let mut dead_particles: Vec<Entity> = Vec::new();
     {
@@ -30,8 +32,21 @@ pub fn update_particles(ecs: &mut World, ctx: &Rltk) {
             }
         }
     }
+    let mut renderables = ecs.write_storage::<Renderable>();
     for dead in dead_particles.iter() {
-        ecs.delete_entity(*dead).expect("Particle will not die");
+        renderables.remove(*dead);
+    }
+}
+
+/// Deletes every particle. Called at the start of each turn, so entity ids are freed at the same
+/// point whatever the frame rate.
+pub fn cull_particles(ecs: &mut World) {
+    let particles: Vec<Entity> = (&ecs.entities(), &ecs.read_storage::<ParticleLifetime>())
+        .join()
+        .map(|(entity, _)| entity)
+        .collect();
+    for particle in particles.iter() {
+        ecs.delete_entity(*particle).expect("Particle will not die");
     }
 }
 
@@ -1,7 +1,7 @@
 use crate::{
-    effects::*, skill_bonus, Attributes, EquipmentSlot, Equipped, HungerClock, HungerState, Map,
-    Name, NaturalAttackDefense, Pools, Position, Skill, Skills, WantsToShoot, Weapon,
-    WeaponAttribute, Wearable,
+    effects::*, mitigate_damage, skill_bonus, Attributes, DamageResistances, DamageType,
+    EquipmentSlot, Equipped, HungerClock, HungerState, Map, Name, NaturalAttackDefense, Perks,
+    Pools, Position, Skill, Skills, WantsToShoot, Weapon, WeaponAttribute, Wearable,
 };
 use rltk::{to_cp437, Point, RGB};
 use specs::prelude::*;
@@ -24,6 +24,8 @@ impl<'a> System<'a> for RangedCombatSystem {
         ReadStorage<'a, NaturalAttackDefense>,
         ReadStorage<'a, Position>,
         ReadExpect<'a, Map>,
+        ReadStorage<'a, DamageResistances>,
+        ReadStorage<'a, Perks>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -41,6 +43,8 @@ impl<'a> System<'a> for RangedCombatSystem {
             natural,
             positions,
             map,
+            damage_resistances,
+            perks,
         ) = data;
 
         for (entity, wants_shoot, name, attacker_attributes, attacker_skills, attacker_pools) in (
@@ -81,6 +85,13 @@ impl<'a> System<'a> for RangedCombatSystem {
                         tile_idx: map.xy_idx(apos.x, apos.y) as i32,
                     },
                 );
+                add_effect(
+                    Some(entity),
+                    EffectType::Noise { volume: SHOT_NOISE },
+                    Targets::Tile {
+                        tile_idx: map.xy_idx(apos.x, apos.y) as i32,
+                    },
+                );
 
                 // Define the basic unarmed attack - overridden by wielding check below if a weapon is equipped
                 let mut weapon_info = Weapon {
@@ -92,6 +103,8 @@ impl<'a> System<'a> for RangedCombatSystem {
                     damage_bonus: 0,
                     proc_chance: None,
                     proc_target: None,
+                    damage_type: DamageType::Physical,
+                    rider: None,
                 };
 
                 if let Some(nat) = natural.get(entity) {
@@ -105,6 +118,7 @@ impl<'a> System<'a> for RangedCombatSystem {
                         weapon_info.damage_n_dice = nat.attacks[attack_index].damage_n_dice;
                         weapon_info.damage_die_type = nat.attacks[attack_index].damage_die_type;
                         weapon_info.damage_bonus = nat.attacks[attack_index].damage_bonus;
+                        weapon_info.damage_type = nat.attacks[attack_index].damage_type;
                     }
                 }
 
@@ -165,10 +179,25 @@ impl<'a> System<'a> for RangedCombatSystem {
                     let attr_damage_bonus = attacker_attributes.might.bonus;
                     let skill_damage_bonus = skill_bonus(Skill::Melee, &*attacker_skills);
                     let weapon_damage_bonus = weapon_info.damage_bonus;
+                    // Critical hits roll the dice again
+                    let crit_chance = perks.get(entity).map_or(0, |p| p.crit_chance);
+                    let critical = crit_chance > 0 && crate::rng::roll_dice(1, 100) <= crit_chance;
+                    let crit_damage_bonus = if critical {
+                        crate::rng::roll_dice(
+                            weapon_info.damage_n_dice,
+                            weapon_info.damage_die_type,
+                        )
+                    } else {
+                        0
+                    };
 
                     let damage = i32::max(
                         0,
-                        base_damage + attr_damage_bonus + skill_damage_bonus + weapon_damage_bonus,
+                        base_damage
+                            + attr_damage_bonus
+                            + skill_damage_bonus
+                            + weapon_damage_bonus
+                            + crit_damage_bonus,
                     );
 
                     /*println!("Damage: {} + {}attr + {}skill + {}weapon = {}",
@@ -177,21 +206,69 @@ impl<'a> System<'a> for RangedCombatSystem {
                     );*/
                     add_effect(
                         Some(entity),
-                        EffectType::Damage { amount: damage },
+                        EffectType::Damage {
+                            amount: damage,
+                            damage_type: weapon_info.damage_type,
+                        },
                         Targets::Single {
                             target: wants_shoot.target,
                         },
                     );
+                    let (dealt, resisted) = mitigate_damage(
+                        damage,
+                        weapon_info.damage_type,
+                        wants_shoot.target,
+                        &damage_resistances,
+                        &equipped_items,
+                    );
 
                     crate::gamelog::Logger::new()
+                        .category(crate::gamelog::LogCategory::Combat)
                         .npc_name(&name.name)
-                        .append("hits")
+                        .append(if critical { "critically hits" } else { "hits" })
                         .npc_name(&target_name.name)
                         .append("for")
-                        .damage(damage)
-                        .append("hp.")
+                        .typed_damage(dealt, weapon_info.damage_type, resisted)
                         .log();
 
+                    if let Some(rider) = &weapon_info.rider {
+                        let rider_damage = i32::max(
+                            0,
+                            crate::rng::roll_dice(rider.n_dice, rider.die_type) + rider.bonus,
+                        );
+                        add_effect(
+                            Some(entity),
+                            EffectType::Damage {
+                                amount: rider_damage,
+                                damage_type: rider.damage_type,
+                            },
+                            Targets::Single {
+                                target: wants_shoot.target,
+                            },
+                        );
+                        let (dealt, resisted) = mitigate_damage(
+                            rider_damage,
+                            rider.damage_type,
+                            wants_shoot.target,
+                            &damage_resistances,
+                            &equipped_items,
+                        );
+                        crate::gamelog::Logger::new()
+                            .category(crate::gamelog::LogCategory::Combat)
+                            .npc_name(&target_name.name)
+                            .append("also takes")
+                            .typed_damage(dealt, rider.damage_type, resisted)
+                            .log();
+                    }
+
+                    add_effect(
+                        None,
+                        EffectType::Practice {
+                            skill: Skill::Melee,
+                        },
+                        Targets::Single { target: entity },
+                    );
+
                     // Proc effects
                     if let Some(chance) = &weapon_info.proc_chance {
                         let roll = crate::rng::roll_dice(1, 100);
@@ -217,6 +294,7 @@ impl<'a> System<'a> for RangedCombatSystem {
                 } else if natural_roll == 1 {
                     // Natural 1 miss
                     crate::gamelog::Logger::new()
+                        .category(crate::gamelog::LogCategory::Combat)
                         .npc_name(&name.name)
                         .append("considers attacking")
                         .npc_name(&target_name.name)
@@ -237,6 +315,7 @@ impl<'a> System<'a> for RangedCombatSystem {
                 } else {
                     // Miss
                     crate::gamelog::Logger::new()
+                        .category(crate::gamelog::LogCategory::Combat)
                         .npc_name(&name.name)
                         .append("attacks")
                         .npc_name(&target_name.name)
@@ -255,6 +334,17 @@ impl<'a> System<'a> for RangedCombatSystem {
                         },
                     );
                 }
+
+                // Being shot at is practice for defending
+                add_effect(
+                    None,
+                    EffectType::Practice {
+                        skill: Skill::Defense,
+                    },
+                    Targets::Single {
+                        target: wants_shoot.target,
+                    },
+                );
             }
         }
 
@@ -33,6 +33,7 @@ impl<'a> System<'a> for TriggerSystem {
                             let name = names.get(entity_id);
                             if let Some(name) = name {
                                 crate::gamelog::Logger::new()
+                                    .category(crate::gamelog::LogCategory::Combat)
                                     .color(rltk::RED)
                                     .append(&name.name)
                                     .color(rltk::WHITE)
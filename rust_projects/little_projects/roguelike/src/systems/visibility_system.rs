@@ -1,6 +1,9 @@
-use crate::{BlocksVisibility, Hidden, Map, Name, Player, Position, Viewshed};
+use crate::{
+    Blinded, BlocksVisibility, Hidden, Map, Name, Player, Position, StatusEffect, Viewshed,
+};
 use rltk::{field_of_view, Point};
 use specs::prelude::*;
+use std::collections::HashMap;
 
 pub struct VisibilitySystem {}
 
@@ -15,11 +18,23 @@ impl<'a> System<'a> for VisibilitySystem {
         WriteStorage<'a, Hidden>,
         ReadStorage<'a, Name>,
         ReadStorage<'a, BlocksVisibility>,
+        ReadStorage<'a, StatusEffect>,
+        ReadStorage<'a, Blinded>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut map, entities, mut viewshed, pos, player, mut hidden, names, blocks_visibility) =
-            data;
+        let (
+            mut map,
+            entities,
+            mut viewshed,
+            pos,
+            player,
+            mut hidden,
+            names,
+            blocks_visibility,
+            statuses,
+            blinded,
+        ) = data;
 
         map.view_blocked.clear();
         for (block_pos, _block) in (&pos, &blocks_visibility).join() {
@@ -27,11 +42,22 @@ impl<'a> System<'a> for VisibilitySystem {
             map.view_blocked.insert(idx);
         }
 
+        // Blindness cuts everyone's sight down to the worst of their afflictions
+        let mut sight_limits: HashMap<Entity, i32> = HashMap::new();
+        for (status, blind) in (&statuses, &blinded).join() {
+            let limit = sight_limits
+                .entry(status.target)
+                .or_insert(blind.vision_range);
+            *limit = i32::min(*limit, blind.vision_range);
+        }
+
         for (ent, viewshed, pos) in (&entities, &mut viewshed, &pos).join() {
             if viewshed.dirty {
                 viewshed.dirty = false;
-                viewshed.visible_tiles =
-                    field_of_view(Point::new(pos.x, pos.y), viewshed.range, &*map);
+                let range = sight_limits
+                    .get(&ent)
+                    .map_or(viewshed.range, |limit| i32::min(*limit, viewshed.range));
+                viewshed.visible_tiles = field_of_view(Point::new(pos.x, pos.y), range, &*map);
                 viewshed
                     .visible_tiles
                     .retain(|p| p.x >= 0 && p.x < map.width && p.y >= 0 && p.y < map.height);
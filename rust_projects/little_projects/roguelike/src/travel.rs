@@ -0,0 +1,384 @@
+//! Walking somewhere over many turns from a single command: exploring, heading for the stairs,
+//! going to a clicked tile or fetching an item. Every step is an ordinary move that takes a turn,
+//! and the journey is cut short as soon as anything worth the player's attention happens.
+
+use crate::components::*;
+use crate::map::{tile_cost, tile_walkable, Map, TileType};
+use crate::player::is_hostile;
+use crate::{gamelog, RunState};
+use rltk::{BaseMap, DijkstraMap, Point, SmallVec};
+use specs::prelude::*;
+use std::collections::HashSet;
+
+/// How far the travel maps look, in movement cost.
+const MAX_TRAVEL_DISTANCE: f32 = 4096.0;
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Destination {
+    /// The nearest revealed tile next to one that hasn't been seen yet
+    Unexplored,
+    /// The nearest known way down
+    DownStairs,
+    Tile {
+        x: i32,
+        y: i32,
+    },
+    /// An item lying somewhere, picked up on arrival
+    Item(Entity),
+}
+
+/// What things were like when the journey started or last took a step, to tell when to stop.
+struct Journey {
+    destination: Destination,
+    depth: i32,
+    hit_points: i32,
+    hunger: Option<HungerState>,
+    items_in_view: HashSet<Entity>,
+}
+
+/// The journey the player is on, if any.
+#[derive(Default)]
+pub struct Travel {
+    journey: Option<Journey>,
+}
+
+pub fn is_travelling(ecs: &World) -> bool {
+    ecs.fetch::<Travel>().journey.is_some()
+}
+
+pub fn stop(ecs: &mut World) {
+    ecs.fetch_mut::<Travel>().journey = None;
+}
+
+/// The map as travel sees it: only revealed tiles, and none that are better walked around.
+struct TravelMap<'a> {
+    map: &'a Map,
+    avoid: &'a HashSet<usize>,
+}
+
+impl BaseMap for TravelMap<'_> {
+    fn is_opaque(&self, idx: usize) -> bool {
+        self.map.is_opaque(idx)
+    }
+
+    // Only the ground counts: doors open and friendly folk swap places when walked into
+    fn get_available_exits(&self, idx: usize) -> SmallVec<[(usize, f32); 10]> {
+        let cost = tile_cost(self.map.tiles[idx]);
+        let x = idx as i32 % self.map.width;
+        neighbours(self.map, idx)
+            .into_iter()
+            .filter(|exit| {
+                self.map.revealed_tiles[*exit]
+                    && tile_walkable(self.map.tiles[*exit])
+                    && !self.avoid.contains(exit)
+            })
+            .map(|exit| {
+                if exit as i32 % self.map.width == x
+                    || exit / self.map.width as usize == idx / self.map.width as usize
+                {
+                    (exit, cost)
+                } else {
+                    (exit, cost * 1.5)
+                }
+            })
+            .collect()
+    }
+
+    fn get_pathing_distance(&self, idx1: usize, idx2: usize) -> f32 {
+        self.map.get_pathing_distance(idx1, idx2)
+    }
+}
+
+/// Tiles that would end the journey somewhere else or hurt: stairs, and traps the player knows
+/// about.
+fn tiles_to_avoid(ecs: &World, map: &Map) -> HashSet<usize> {
+    let mut avoid: HashSet<usize> = map
+        .tiles
+        .iter()
+        .enumerate()
+        .filter(|(_, tile)| matches!(tile, TileType::DownStairs | TileType::UpStairs))
+        .map(|(idx, _)| idx)
+        .collect();
+    let hidden = ecs.read_storage::<Hidden>();
+    for (entity, _trap, pos) in (
+        &ecs.entities(),
+        &ecs.read_storage::<EntryTrigger>(),
+        &ecs.read_storage::<Position>(),
+    )
+        .join()
+    {
+        let idx = map.xy_idx(pos.x, pos.y);
+        if hidden.get(entity).is_none() && map.revealed_tiles[idx] {
+            avoid.insert(idx);
+        }
+    }
+    avoid
+}
+
+fn neighbours(map: &Map, idx: usize) -> Vec<usize> {
+    let x = idx as i32 % map.width;
+    let y = idx as i32 / map.width;
+    let mut result = Vec::new();
+    for (dx, dy) in [
+        (0, -1),
+        (0, 1),
+        (-1, 0),
+        (1, 0),
+        (-1, -1),
+        (1, -1),
+        (-1, 1),
+        (1, 1),
+    ] {
+        let (nx, ny) = (x + dx, y + dy);
+        if nx >= 0 && nx < map.width && ny >= 0 && ny < map.height {
+            result.push(map.xy_idx(nx, ny));
+        }
+    }
+    result
+}
+
+/// The tiles the journey is heading for. Empty if there's nowhere to go.
+fn targets(ecs: &World, map: &Map, destination: Destination, avoid: &HashSet<usize>) -> Vec<usize> {
+    match destination {
+        Destination::Unexplored => (0..map.tiles.len())
+            .filter(|idx| {
+                map.revealed_tiles[*idx]
+                    && tile_walkable(map.tiles[*idx])
+                    && !avoid.contains(idx)
+                    && neighbours(map, *idx)
+                        .iter()
+                        .any(|n| !map.revealed_tiles[*n])
+            })
+            .collect(),
+        Destination::DownStairs => (0..map.tiles.len())
+            .filter(|idx| map.revealed_tiles[*idx] && map.tiles[*idx] == TileType::DownStairs)
+            .collect(),
+        Destination::Tile { x, y } => {
+            if x < 0 || x >= map.width || y < 0 || y >= map.height {
+                return Vec::new();
+            }
+            let idx = map.xy_idx(x, y);
+            if map.revealed_tiles[idx] && tile_walkable(map.tiles[idx]) {
+                vec![idx]
+            } else {
+                Vec::new()
+            }
+        }
+        // Gone if someone else got to it first
+        Destination::Item(item) => match ecs.read_storage::<Position>().get(item) {
+            Some(pos) => targets(ecs, map, Destination::Tile { x: pos.x, y: pos.y }, avoid),
+            None => Vec::new(),
+        },
+    }
+}
+
+/// The first hostile the player can see, and every item lying in view.
+fn in_view(ecs: &World) -> (Option<Entity>, HashSet<Entity>) {
+    let player = *ecs.fetch::<Entity>();
+    let map = ecs.fetch::<Map>();
+    let viewsheds = ecs.read_storage::<Viewshed>();
+    let items = ecs.read_storage::<Item>();
+    let hidden = ecs.read_storage::<Hidden>();
+    let mut first_hostile = None;
+    let mut seen_items = HashSet::new();
+    if let Some(viewshed) = viewsheds.get(player) {
+        for tile in viewshed.visible_tiles.iter() {
+            let idx = map.xy_idx(tile.x, tile.y);
+            crate::spatial::for_each_tile_content(idx, |entity| {
+                if entity == player || hidden.get(entity).is_some() {
+                    return;
+                }
+                if items.get(entity).is_some() {
+                    seen_items.insert(entity);
+                } else if first_hostile.is_none() && is_hostile(ecs, entity) {
+                    first_hostile = Some(entity);
+                }
+            });
+        }
+    }
+    (first_hostile, seen_items)
+}
+
+fn name_of(ecs: &World, entity: Entity) -> String {
+    ecs.read_storage::<Name>()
+        .get(entity)
+        .map_or("Something".to_string(), |name| name.name.clone())
+}
+
+fn player_condition(ecs: &World) -> (i32, Option<HungerState>) {
+    let player = *ecs.fetch::<Entity>();
+    let hit_points = ecs
+        .read_storage::<Pools>()
+        .get(player)
+        .map_or(0, |pools| pools.hit_points.current);
+    let hunger = ecs
+        .read_storage::<HungerClock>()
+        .get(player)
+        .map(|clock| clock.state);
+    (hit_points, hunger)
+}
+
+/// Sets off for the destination, taking the first step straight away. Nothing happens if an
+/// enemy is in view or there's nowhere to go.
+pub fn start(ecs: &mut World, destination: Destination) -> RunState {
+    let (hostile, items_in_view) = in_view(ecs);
+    if let Some(hostile) = hostile {
+        gamelog::Logger::new()
+            .append("You can't travel with")
+            .npc_name(name_of(ecs, hostile))
+            .append("in view.")
+            .log();
+        return RunState::AwaitingInput;
+    }
+    let (hit_points, hunger) = player_condition(ecs);
+    let depth = ecs.fetch::<Map>().depth;
+    ecs.fetch_mut::<Travel>().journey = Some(Journey {
+        destination,
+        depth,
+        hit_points,
+        hunger,
+        items_in_view,
+    });
+    step(ecs)
+}
+
+/// Why the journey has to end before the next step, if it does.
+fn interruption(ecs: &World, journey: &Journey) -> Option<gamelog::Logger> {
+    let (hostile, items) = in_view(ecs);
+    if let Some(hostile) = hostile {
+        return Some(
+            gamelog::Logger::new()
+                .append("You stop as")
+                .npc_name(name_of(ecs, hostile))
+                .append("comes into view."),
+        );
+    }
+    if let Some(item) = items
+        .iter()
+        .find(|item| !journey.items_in_view.contains(item))
+    {
+        return Some(
+            gamelog::Logger::new()
+                .category(gamelog::LogCategory::Loot)
+                .append("You stop to look at the")
+                .item_name(crate::gui::get_item_display_name(ecs, *item))
+                .append("."),
+        );
+    }
+    let (hit_points, hunger) = player_condition(ecs);
+    if hit_points < journey.hit_points {
+        return Some(
+            gamelog::Logger::new()
+                .color(rltk::RED)
+                .append("You stop, hurt."),
+        );
+    }
+    if hunger != journey.hunger {
+        return Some(
+            gamelog::Logger::new()
+                .color(rltk::ORANGE)
+                .append("You stop as your hunger changes."),
+        );
+    }
+    None
+}
+
+/// Takes the next step of the journey, or ends it. Returns the state to carry on in: `Ticking`
+/// after a step, so the rest of the world gets its turn.
+pub fn step(ecs: &mut World) -> RunState {
+    let journey = match ecs.fetch_mut::<Travel>().journey.take() {
+        Some(journey) => journey,
+        None => return RunState::AwaitingInput,
+    };
+    if ecs.fetch::<Map>().depth != journey.depth {
+        return RunState::AwaitingInput;
+    }
+    if let Some(message) = interruption(ecs, &journey) {
+        message.log();
+        return RunState::AwaitingInput;
+    }
+
+    let next = {
+        let map = ecs.fetch::<Map>();
+        let player_pos = *ecs.fetch::<Point>();
+        let player_idx = map.xy_idx(player_pos.x, player_pos.y);
+        let avoid = tiles_to_avoid(ecs, &map);
+        let mut targets = targets(ecs, &map, journey.destination, &avoid);
+        if journey.destination == Destination::Unexplored {
+            // Standing here didn't show what's next to it, so there's nothing more to see here
+            targets.retain(|idx| *idx != player_idx);
+        }
+        if targets.contains(&player_idx) {
+            if let Destination::Item(item) = journey.destination {
+                crate::player::pick_up(ecs, item);
+                return RunState::Ticking;
+            }
+            return RunState::AwaitingInput;
+        }
+        if targets.is_empty() {
+            let message = match journey.destination {
+                Destination::Unexplored => "There's nothing left to explore here.",
+                Destination::DownStairs => "You don't know of a way down yet.",
+                Destination::Tile { .. } => "You can't go there.",
+                Destination::Item(_) => "You can't get to that any more.",
+            };
+            gamelog::Logger::new().append(message).log();
+            return RunState::AwaitingInput;
+        }
+        // Heading for stairs or a clicked tile means walking onto it, whatever it is
+        let avoid: HashSet<usize> = avoid
+            .difference(&targets.iter().copied().collect())
+            .copied()
+            .collect();
+        let travel_map = TravelMap {
+            map: &map,
+            avoid: &avoid,
+        };
+        let dijkstra = DijkstraMap::new(
+            map.width,
+            map.height,
+            &targets,
+            &travel_map,
+            MAX_TRAVEL_DISTANCE,
+        );
+        // The targets themselves are left unmarked by the map, so they're worth nothing
+        let distance = |idx: usize| {
+            if targets.contains(&idx) {
+                0.0
+            } else {
+                dijkstra.map[idx]
+            }
+        };
+        neighbours(&map, player_idx)
+            .into_iter()
+            .filter(|idx| distance(*idx) < f32::MAX)
+            .min_by(|a, b| distance(*a).partial_cmp(&distance(*b)).unwrap())
+            .map(|idx| {
+                (
+                    idx as i32 % map.width - player_pos.x,
+                    idx as i32 / map.width - player_pos.y,
+                )
+            })
+    };
+    let (dx, dy) = match next {
+        Some(delta) => delta,
+        None => {
+            gamelog::Logger::new()
+                .append("You can't find a way there.")
+                .log();
+            return RunState::AwaitingInput;
+        }
+    };
+
+    let result = crate::player::try_move_player(dx, dy, ecs);
+    // Anything but an ordinary turn, like taking the stairs or meeting a vendor, ends the journey
+    if result == RunState::Ticking {
+        let (hit_points, hunger) = player_condition(ecs);
+        ecs.fetch_mut::<Travel>().journey = Some(Journey {
+            hit_points,
+            hunger,
+            ..journey
+        });
+    }
+    result
+}
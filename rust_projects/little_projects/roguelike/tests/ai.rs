@@ -0,0 +1,230 @@
+//! Mobs must act out the behaviors the raws give them.
+
+mod common;
+
+use common::{clean_up, headless_context, lock_world, new_game, wait_a_turn};
+use roguelike::effects::{add_effect, run_effects_queue, EffectType, Targets};
+use roguelike::raws::{spawn_named_entity, SpawnType, RAWS};
+use roguelike::{
+    tile_walkable, AlertState, Alertness, Chasing, Faction, Map, Name, Pools, Position, Routed,
+    State,
+};
+use specs::prelude::*;
+
+const SEED: u64 = 4242;
+
+fn position_of(gs: &State, entity: Entity) -> Position {
+    gs.ecs
+        .read_storage::<Position>()
+        .get(entity)
+        .unwrap()
+        .clone()
+}
+
+fn player_pos(gs: &State) -> Position {
+    position_of(gs, *gs.ecs.fetch::<Entity>())
+}
+
+fn distance(a: &Position, b: &Position) -> f32 {
+    rltk::DistanceAlg::Pythagoras.distance2d(rltk::Point::new(a.x, a.y), rltk::Point::new(b.x, b.y))
+}
+
+/// Spawns a mob on an open tile in the player's sight, at about this distance from them.
+fn spawn_near_player(gs: &mut State, name: &str, range: f32) -> Entity {
+    let player = player_pos(gs);
+    let spot = {
+        let map = gs.ecs.fetch::<Map>();
+        let mut open: Vec<Position> = (0..map.tiles.len())
+            .filter(|idx| map.visible_tiles[*idx] && tile_walkable(map.tiles[*idx]))
+            .filter(|idx| !roguelike::spatial::is_blocked(*idx))
+            .map(|idx| Position {
+                x: idx as i32 % map.width,
+                y: idx as i32 / map.width,
+            })
+            .collect();
+        open.sort_by(|a, b| {
+            (distance(a, &player) - range)
+                .abs()
+                .total_cmp(&(distance(b, &player) - range).abs())
+        });
+        open[0].clone()
+    };
+    spawn_named_entity(
+        &RAWS.lock().unwrap(),
+        &mut gs.ecs,
+        name,
+        SpawnType::AtPosition {
+            x: spot.x,
+            y: spot.y,
+        },
+    )
+    .unwrap()
+}
+
+#[test]
+fn pack_animals_close_in() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let mut gs = new_game(&mut ctx, SEED, "ai");
+    let wolf = spawn_near_player(&mut gs, "Mangy Wolf", 5.0);
+    assert_eq!(
+        gs.ecs.read_storage::<Name>().get(wolf).unwrap().name,
+        "Mangy Wolf"
+    );
+
+    let start = distance(&position_of(&gs, wolf), &player_pos(&gs));
+    for _ in 0..10 {
+        wait_a_turn(&mut gs, &mut ctx);
+    }
+    let end = distance(&position_of(&gs, wolf), &player_pos(&gs));
+    clean_up(&gs, "ai");
+    assert!(end < start, "the wolf went from {} to {} away", start, end);
+}
+
+#[test]
+fn default_mobs_close_in() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let mut gs = new_game(&mut ctx, SEED, "ai");
+    let rat = spawn_near_player(&mut gs, "Rat", 5.0);
+    // Past the roll to notice the player
+    if let Some(alert) = gs.ecs.write_storage::<Alertness>().get_mut(rat) {
+        alert.state = AlertState::Hunting;
+    }
+
+    let start = distance(&position_of(&gs, rat), &player_pos(&gs));
+    for _ in 0..5 {
+        wait_a_turn(&mut gs, &mut ctx);
+    }
+    let end = distance(&position_of(&gs, rat), &player_pos(&gs));
+    let player = *gs.ecs.fetch::<Entity>();
+    let chasing = gs
+        .ecs
+        .read_storage::<Chasing>()
+        .get(rat)
+        .is_some_and(|c| c.target == player);
+    clean_up(&gs, "ai");
+    assert!(end < start, "the rat went from {} to {} away", start, end);
+    assert!(chasing, "the rat isn't chasing the player");
+}
+
+#[test]
+fn cowards_run_when_hurt() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let mut gs = new_game(&mut ctx, SEED, "ai");
+    let bandit = spawn_near_player(&mut gs, "Bandit", 1.0);
+    wait_a_turn(&mut gs, &mut ctx);
+    if let Some(pools) = gs.ecs.write_storage::<Pools>().get_mut(bandit) {
+        pools.hit_points.current = 1;
+    }
+
+    for _ in 0..5 {
+        wait_a_turn(&mut gs, &mut ctx);
+    }
+    let end = distance(&position_of(&gs, bandit), &player_pos(&gs));
+    clean_up(&gs, "ai");
+    assert!(end > 1.5, "the bandit stayed next to the player");
+}
+
+/// Removes everyone but the player, so the player is the only one worth chasing.
+fn clear_the_town(gs: &mut State) {
+    let player = *gs.ecs.fetch::<Entity>();
+    let others: Vec<Entity> = (&gs.ecs.entities(), &gs.ecs.read_storage::<Faction>())
+        .join()
+        .map(|(entity, _)| entity)
+        .filter(|entity| *entity != player)
+        .collect();
+    gs.ecs.delete_entities(&others).unwrap();
+    gs.ecs.maintain();
+}
+
+#[test]
+fn spotting_an_enemy_alerts_the_faction() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let mut gs = new_game(&mut ctx, SEED, "ai");
+    clear_the_town(&mut gs);
+    let archer = spawn_near_player(&mut gs, "Bandit Archer", 3.0);
+    let bandit = spawn_near_player(&mut gs, "Bandit", 6.0);
+    for _ in 0..3 {
+        wait_a_turn(&mut gs, &mut ctx);
+    }
+
+    let player = *gs.ecs.fetch::<Entity>();
+    let chasing = gs.ecs.read_storage::<Chasing>();
+    let hunted: Vec<bool> = [archer, bandit]
+        .iter()
+        .map(|mob| chasing.get(*mob).is_some_and(|c| c.target == player))
+        .collect();
+    drop(chasing);
+    let bandit_state = alert_state(&gs, bandit);
+    clean_up(&gs, "ai");
+    // Bandits don't chase, so the warning only puts them on their guard
+    assert_eq!(hunted, vec![true, false]);
+    assert_eq!(bandit_state, AlertState::Hunting);
+}
+
+#[test]
+fn followers_rout_when_their_leader_falls() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let mut gs = new_game(&mut ctx, SEED, "ai");
+    let leader = spawn_near_player(&mut gs, "Orc Leader", 4.0);
+    let follower = spawn_near_player(&mut gs, "Orc", 3.0);
+    if let Some(pools) = gs.ecs.write_storage::<Pools>().get_mut(leader) {
+        pools.hit_points.current = 0;
+    }
+    wait_a_turn(&mut gs, &mut ctx);
+
+    let routed = gs.ecs.read_storage::<Routed>().get(follower).is_some();
+    clean_up(&gs, "ai");
+    assert!(routed, "the orc stood its ground");
+}
+
+fn alert_state(gs: &State, entity: Entity) -> AlertState {
+    gs.ecs
+        .read_storage::<Alertness>()
+        .get(entity)
+        .unwrap()
+        .state
+}
+
+#[test]
+fn noise_carries_but_not_through_walls() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let mut gs = new_game(&mut ctx, SEED, "ai");
+    let walled_in = spawn_near_player(&mut gs, "Bandit", 5.0);
+    {
+        let pos = position_of(&gs, walled_in);
+        let mut map = gs.ecs.fetch_mut::<Map>();
+        for (dx, dy) in [
+            (-1, -1),
+            (0, -1),
+            (1, -1),
+            (-1, 0),
+            (1, 0),
+            (-1, 1),
+            (0, 1),
+            (1, 1),
+        ] {
+            let idx = map.xy_idx(pos.x + dx, pos.y + dy);
+            map.tiles[idx] = roguelike::TileType::Wall;
+        }
+    }
+    let listener = spawn_near_player(&mut gs, "Bandit", 2.0);
+
+    let player = player_pos(&gs);
+    let tile_idx = gs.ecs.fetch::<Map>().xy_idx(player.x, player.y) as i32;
+    add_effect(
+        None,
+        EffectType::Noise { volume: 10 },
+        Targets::Tile { tile_idx },
+    );
+    run_effects_queue(&mut gs.ecs);
+
+    let heard = (alert_state(&gs, listener), alert_state(&gs, walled_in));
+    clean_up(&gs, "ai");
+    assert_eq!(heard, (AlertState::Suspicious, AlertState::Unaware));
+}
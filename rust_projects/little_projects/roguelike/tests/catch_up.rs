@@ -0,0 +1,198 @@
+//! Levels the player isn't on must catch up on the turns they missed when the player comes back.
+
+mod common;
+
+use common::{
+    clean_up, headless_context, lock_world, new_game, runstate, tick, turns, wait_a_turn,
+};
+use rltk::{Rltk, VirtualKeyCode};
+use roguelike::effects::add_status;
+use roguelike::replay::takes_input;
+use roguelike::{
+    catch_up_frozen_levels, Duration, Map, MasterDungeonMap, Pools, RunState, State, StatusEffect,
+    Vendor,
+};
+use specs::prelude::*;
+
+const SEED: u64 = 4242;
+
+/// Takes the stairs (or pretends to), and waits for the new level to be ready.
+fn change_level(gs: &mut State, ctx: &mut Rltk, direction: RunState) {
+    *gs.ecs.write_resource::<RunState>() = direction;
+    for _ in 0..1000 {
+        tick(gs, ctx, None);
+        if takes_input(runstate(gs)) {
+            return;
+        }
+    }
+    panic!("the level never finished loading");
+}
+
+#[test]
+fn town_recovers_while_the_player_is_away() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let mut gs = new_game(&mut ctx, SEED, "catch-up");
+    let player = *gs.ecs.fetch::<Entity>();
+    gs.ecs
+        .write_storage::<Pools>()
+        .get_mut(player)
+        .unwrap()
+        .god_mode = true;
+
+    // Rough up a vendor and buy out one of their wares
+    let (vendor, ware) = {
+        let entities = gs.ecs.entities();
+        let mut vendors = gs.ecs.write_storage::<Vendor>();
+        let (vendor, stock) = (&entities, &mut vendors).join().next().unwrap();
+        let ware = stock.stock.keys().min().unwrap().clone();
+        stock.stock.insert(ware.clone(), 0);
+        (vendor, ware)
+    };
+    gs.ecs
+        .write_storage::<Pools>()
+        .get_mut(vendor)
+        .unwrap()
+        .hit_points
+        .current = 1;
+
+    change_level(&mut gs, &mut ctx, RunState::NextLevel);
+    assert_eq!(gs.ecs.fetch::<Map>().depth, 2);
+    for _ in 0..100 {
+        wait_a_turn(&mut gs, &mut ctx);
+    }
+    change_level(&mut gs, &mut ctx, RunState::PreviousLevel);
+
+    let depth = gs.ecs.fetch::<Map>().depth;
+    let hit_points = gs
+        .ecs
+        .read_storage::<Pools>()
+        .get(vendor)
+        .unwrap()
+        .hit_points
+        .current;
+    let in_stock = gs.ecs.read_storage::<Vendor>().get(vendor).unwrap().stock[&ware];
+    clean_up(&gs, "catch-up");
+    assert_eq!(depth, 1);
+    assert!(hit_points > 1, "the vendor never healed");
+    assert!(in_stock > 0, "the vendor never restocked {}", ware);
+}
+
+/// Sends the player down to the first dungeon level, out of harm's way, leaving the town frozen
+/// with one of its vendors at full health.
+fn leave_town(gs: &mut State, ctx: &mut Rltk) -> Entity {
+    let player = *gs.ecs.fetch::<Entity>();
+    gs.ecs
+        .write_storage::<Pools>()
+        .get_mut(player)
+        .unwrap()
+        .god_mode = true;
+    let vendor = {
+        let entities = gs.ecs.entities();
+        let vendors = gs.ecs.read_storage::<Vendor>();
+        (&entities, &vendors).join().next().unwrap().0
+    };
+    change_level(gs, ctx, RunState::NextLevel);
+    let mut pools = gs.ecs.write_storage::<Pools>();
+    let pool = pools.get_mut(vendor).unwrap();
+    pool.hit_points.current = pool.hit_points.max;
+    vendor
+}
+
+fn hit_points(gs: &State, entity: Entity) -> i32 {
+    gs.ecs
+        .read_storage::<Pools>()
+        .get(entity)
+        .unwrap()
+        .hit_points
+        .current
+}
+
+#[test]
+fn short_trips_add_up() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let mut gs = new_game(&mut ctx, SEED, "catch-up");
+    let vendor = leave_town(&mut gs, &mut ctx);
+    gs.ecs
+        .write_storage::<Pools>()
+        .get_mut(vendor)
+        .unwrap()
+        .hit_points
+        .current = 1;
+
+    // Forty turns in all, none of the catch-ups long enough for a hit point on its own
+    for _ in 0..10 {
+        gs.ecs.write_resource::<MasterDungeonMap>().turns += 4;
+        catch_up_frozen_levels(&mut gs.ecs);
+    }
+
+    let healed = hit_points(&gs, vendor);
+    clean_up(&gs, "catch-up");
+    assert_eq!(healed, 5);
+}
+
+/// Turns left on whatever status effect the entity is under.
+fn status_turns(gs: &State, entity: Entity) -> Option<i32> {
+    let statuses = gs.ecs.read_storage::<StatusEffect>();
+    let durations = gs.ecs.read_storage::<Duration>();
+    (&statuses, &durations)
+        .join()
+        .find(|(status, _)| status.target == entity)
+        .map(|(_, duration)| duration.turns)
+}
+
+#[test]
+fn status_effects_run_their_course_on_frozen_levels() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let mut gs = new_game(&mut ctx, SEED, "catch-up");
+    let vendor = leave_town(&mut gs, &mut ctx);
+    let healthy = hit_points(&gs, vendor);
+    add_status(&mut gs.ecs, vendor, "Poisoned");
+    let before = status_turns(&gs, vendor);
+
+    for _ in 0..6 {
+        wait_a_turn(&mut gs, &mut ctx);
+    }
+    let while_away = status_turns(&gs, vendor);
+    change_level(&mut gs, &mut ctx, RunState::PreviousLevel);
+    let after = status_turns(&gs, vendor);
+    let hurt = hit_points(&gs, vendor);
+    clean_up(&gs, "catch-up");
+
+    // Nothing ticks while the town is frozen, then five turns of poison all at once. That is ten
+    // hit points, though it won't kill anyone there, and one may have come back since.
+    assert_eq!(before, Some(5));
+    assert_eq!(while_away, before);
+    assert_eq!(after, None);
+    let poisoned = i32::max(1, healthy - 10);
+    assert!(
+        hurt <= poisoned + 1,
+        "the vendor is on {} of {}",
+        hurt,
+        healthy
+    );
+}
+
+#[test]
+fn looking_through_menus_takes_no_time() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let mut gs = new_game(&mut ctx, SEED, "catch-up");
+    let start = turns(&gs);
+
+    for _ in 0..3 {
+        tick(&mut gs, &mut ctx, Some(VirtualKeyCode::I));
+        assert!(matches!(runstate(&gs), RunState::ShowInventory));
+        tick(&mut gs, &mut ctx, Some(VirtualKeyCode::Escape));
+        assert!(matches!(runstate(&gs), RunState::AwaitingInput));
+    }
+    let after_menus = turns(&gs);
+    wait_a_turn(&mut gs, &mut ctx);
+    let after_waiting = turns(&gs);
+    clean_up(&gs, "catch-up");
+
+    assert_eq!(after_menus, start);
+    assert_eq!(after_waiting, start + 1);
+}
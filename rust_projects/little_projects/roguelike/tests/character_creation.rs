@@ -0,0 +1,208 @@
+//! New characters come from a background in the raws, their attributes and a name, and keep
+//! them through replays.
+
+mod common;
+
+use common::{clean_up, headless_context, lock_world, player, replay_path, runstate, settle, tick};
+use rltk::VirtualKeyCode;
+use roguelike::replay::Replay;
+use roguelike::{
+    Attributes, Character, Equipped, InBackpack, KnownSpells, Name, Pools, RunState, Skill, Skills,
+    State,
+};
+use specs::prelude::*;
+
+const SEED: u64 = 4242;
+
+/// Names of the items the player wears or carries, sorted.
+fn kit(gs: &State) -> (Vec<String>, Vec<String>) {
+    let player = player(gs);
+    let names = gs.ecs.read_storage::<Name>();
+    let mut equipped: Vec<String> = (&names, &gs.ecs.read_storage::<Equipped>())
+        .join()
+        .filter(|(_, e)| e.owner == player)
+        .map(|(n, _)| n.name.clone())
+        .collect();
+    let mut carried: Vec<String> = (&names, &gs.ecs.read_storage::<InBackpack>())
+        .join()
+        .filter(|(_, b)| b.owner == player)
+        .map(|(n, _)| n.name.clone())
+        .collect();
+    equipped.sort();
+    carried.sort();
+    (equipped, carried)
+}
+
+fn hedge_mage() -> Character {
+    Character {
+        name: "Morwen".to_string(),
+        background: "Hedge Mage".to_string(),
+        might: 8,
+        fitness: 10,
+        quickness: 11,
+        intelligence: 15,
+    }
+}
+
+#[test]
+fn point_buy_keeps_within_its_limits() {
+    let mut character = Character::default();
+    assert_eq!(character.points_left(), 0);
+    assert!(!character.buy(0, 1), "bought a point with none left");
+
+    assert!(character.buy(0, -1));
+    assert_eq!(character.points_left(), 1);
+    assert!(character.buy(3, 1));
+    assert_eq!(character.attributes(), [10, 11, 11, 12]);
+
+    for _ in 0..10 {
+        character.buy(1, -1);
+        character.buy(3, 1);
+    }
+    assert_eq!(character.fitness, 8);
+    assert_eq!(character.intelligence, 15);
+    assert_eq!(character.points_left(), 0);
+}
+
+#[test]
+fn backgrounds_set_skills_spells_and_kit() {
+    let _guard = lock_world();
+    let mut gs = State::new();
+    gs.record_run(SEED, &replay_path("character-creation"));
+    let (equipped, carried) = kit(&gs);
+    assert_eq!(
+        equipped,
+        [
+            "Old Boots",
+            "Rusty Longsword",
+            "Stained Tunic",
+            "Torn Trousers"
+        ]
+    );
+    assert_eq!(carried, ["Beer", "Dried Sausage"]);
+
+    gs.record_run_as(SEED, &replay_path("character-creation"), &hedge_mage());
+    let player = player(&gs);
+    assert_eq!(
+        gs.ecs.read_storage::<Name>().get(player).unwrap().name,
+        "Morwen"
+    );
+    let attributes = gs.ecs.read_storage::<Attributes>();
+    let attributes = attributes.get(player).unwrap();
+    assert_eq!(attributes.might.base, 8);
+    assert_eq!(attributes.intelligence.base, 15);
+    let skills = gs.ecs.read_storage::<Skills>();
+    assert_eq!(skills.get(player).unwrap().skills[&Skill::Magic], 3);
+    assert_eq!(skills.get(player).unwrap().skills[&Skill::Melee], 1);
+    let spells: Vec<String> = gs
+        .ecs
+        .read_storage::<KnownSpells>()
+        .get(player)
+        .unwrap()
+        .spells
+        .iter()
+        .map(|s| s.display_name.clone())
+        .collect();
+    assert_eq!(spells, ["Zap", "Mend"]);
+    let pools = gs.ecs.read_storage::<Pools>();
+    let pools = pools.get(player).unwrap();
+    assert_eq!(
+        pools.mana.max,
+        roguelike::mana_at_level(15, 1),
+        "mana didn't follow intelligence"
+    );
+    assert_eq!(
+        gs.ecs.read_storage::<Character>().get(player),
+        Some(&hedge_mage())
+    );
+    let (equipped, carried) = kit(&gs);
+    assert_eq!(
+        equipped,
+        ["Cloth Pants", "Cloth Tunic", "Dagger", "Slippers"]
+    );
+    assert_eq!(carried, ["Mana Potion"]);
+
+    clean_up(&gs, "character-creation");
+}
+
+#[test]
+fn replays_remember_the_character() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let path = replay_path("character-creation");
+    let mut gs = State::new();
+    gs.record_run_as(SEED, &path, &hedge_mage());
+    settle(&mut gs, &mut ctx);
+    let recorded = kit(&gs);
+    let slot = roguelike::saveload_system::run_slot(&gs.ecs);
+
+    let replay = Replay::load(&path).unwrap();
+    assert_eq!(replay.character, Some(hedge_mage()));
+    let mut gs = State::new();
+    gs.play_replay(replay);
+    settle(&mut gs, &mut ctx);
+    assert_eq!(kit(&gs), recorded);
+    assert_eq!(
+        gs.ecs.read_storage::<Character>().get(player(&gs)),
+        Some(&hedge_mage())
+    );
+
+    let _ = std::fs::remove_file(&path);
+    roguelike::saveload_system::delete_slot(&slot);
+}
+
+#[test]
+fn the_creation_screen_builds_the_character() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let mut gs = State::new();
+    for _ in 0..1000 {
+        if matches!(runstate(&gs), RunState::MainMenu { .. }) {
+            break;
+        }
+        tick(&mut gs, &mut ctx, None);
+    }
+
+    // Begin New Game, then a Sellsword who traded some might for fitness, called Bo
+    let keys = [
+        VirtualKeyCode::Return,
+        VirtualKeyCode::Down,
+        VirtualKeyCode::Return,
+        VirtualKeyCode::Left,
+        VirtualKeyCode::Down,
+        VirtualKeyCode::Right,
+        VirtualKeyCode::Return,
+        VirtualKeyCode::B,
+        VirtualKeyCode::O,
+        VirtualKeyCode::Return,
+    ];
+    for key in keys {
+        tick(&mut gs, &mut ctx, Some(key));
+    }
+    settle(&mut gs, &mut ctx);
+
+    let character = gs
+        .ecs
+        .read_storage::<Character>()
+        .get(player(&gs))
+        .cloned()
+        .unwrap();
+    assert_eq!(
+        character,
+        Character {
+            name: "Bo".to_string(),
+            background: "Sellsword".to_string(),
+            might: 10,
+            fitness: 12,
+            quickness: 11,
+            intelligence: 11,
+        }
+    );
+    assert!(kit(&gs).0.contains(&"Shortsword".to_string()));
+    let slot = roguelike::saveload_system::run_slot(&gs.ecs);
+    let path = roguelike::replay::replay_path(&slot);
+    assert_eq!(Replay::load(&path).unwrap().character, Some(character));
+
+    let _ = std::fs::remove_file(&path);
+    roguelike::saveload_system::delete_slot(&slot);
+}
@@ -0,0 +1,133 @@
+//! Helpers shared by the tests that play the game without a window.
+
+// Each test file only uses some of these
+#![allow(dead_code)]
+
+use rltk::{GameState, Rltk, VirtualKeyCode};
+use roguelike::gamelog::log_lines;
+use roguelike::replay::takes_input;
+use roguelike::{MasterDungeonMap, RunState, State};
+use specs::prelude::*;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard, Once};
+
+// The RNG, effect queue, log, spatial index and key bindings are global, so only one world at a
+// time
+static WORLD_LOCK: Mutex<()> = Mutex::new(());
+static CONSOLES: Once = Once::new();
+
+/// Holds the game's globals for the rest of a test. A test that panicked while holding them
+/// doesn't stop the others.
+pub fn lock_world() -> MutexGuard<'static, ()> {
+    WORLD_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// A context with the game's two consoles and no window behind it.
+pub fn headless_context() -> Rltk {
+    let mut ctx = Rltk {
+        width_pixels: 640,
+        height_pixels: 480,
+        original_height_pixels: 480,
+        original_width_pixels: 640,
+        fps: 60.0,
+        frame_time_ms: 1000.0,
+        active_console: 0,
+        key: None,
+        mouse_pos: (0, 0),
+        left_click: false,
+        shift: false,
+        control: false,
+        alt: false,
+        web_button: None,
+        quitting: false,
+        post_scanlines: false,
+        post_screenburn: false,
+        screen_burn_color: rltk::RGB::named(rltk::BLACK),
+        mouse_visible: true,
+    };
+    CONSOLES.call_once(|| {
+        ctx.register_console(rltk::SimpleConsole::init(80, 60), 0);
+        ctx.register_console(rltk::SparseConsole::init(80, 30), 0);
+        // Mouse positions are scaled by the window's size, which nothing else sets without one
+        rltk::BACKEND
+            .lock()
+            .screen_scaler
+            .change_physical_size(640, 480, 1.0);
+    });
+    ctx
+}
+
+/// A file in the temp directory, kept apart from other test processes.
+pub fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("roguelike-test-{}-{}", std::process::id(), name))
+}
+
+/// Where `new_game` records the run called `name`.
+pub fn replay_path(name: &str) -> PathBuf {
+    temp_path(&format!("{}.replay", name))
+}
+
+pub fn runstate(gs: &State) -> RunState {
+    *gs.ecs.fetch::<RunState>()
+}
+
+/// One frame with this key pressed. Like a real window, the key only lasts for the frame.
+pub fn tick(gs: &mut State, ctx: &mut Rltk, key: Option<VirtualKeyCode>) {
+    ctx.key = key;
+    gs.tick(ctx);
+    ctx.key = None;
+}
+
+/// Ticks until the game wants input again.
+pub fn settle(gs: &mut State, ctx: &mut Rltk) {
+    for _ in 0..5000 {
+        if takes_input(runstate(gs)) {
+            return;
+        }
+        tick(gs, ctx, None);
+    }
+    panic!("the game never asked for input");
+}
+
+/// The player waits a turn, and everyone else gets theirs.
+pub fn wait_a_turn(gs: &mut State, ctx: &mut Rltk) {
+    tick(gs, ctx, Some(VirtualKeyCode::Numpad5));
+    settle(gs, ctx);
+}
+
+/// Where `new_game` keeps the morgue files and high scores of the run called `name`.
+pub fn records_path(name: &str) -> PathBuf {
+    temp_path(&format!("{}-records", name))
+}
+
+/// A new run from the seed, recorded under `name` and settled on its first turn.
+pub fn new_game(ctx: &mut Rltk, seed: u64, name: &str) -> State {
+    let mut gs = State::new();
+    gs.keep_records_in(&records_path(name));
+    gs.record_run(seed, &replay_path(name));
+    settle(&mut gs, ctx);
+    gs
+}
+
+/// Removes the replay, records and autosave a test game leaves behind.
+pub fn clean_up(gs: &State, name: &str) {
+    let _ = std::fs::remove_file(replay_path(name));
+    let _ = std::fs::remove_dir_all(records_path(name));
+    roguelike::saveload_system::delete_slot(&roguelike::saveload_system::run_slot(&gs.ecs));
+}
+
+pub fn player(gs: &State) -> Entity {
+    *gs.ecs.fetch::<Entity>()
+}
+
+/// Turns the player has taken this run.
+pub fn turns(gs: &State) -> i32 {
+    gs.ecs.fetch::<MasterDungeonMap>().turns
+}
+
+pub fn last_message() -> String {
+    log_lines(None)
+        .last()
+        .map(|line| line.text())
+        .unwrap_or_default()
+}
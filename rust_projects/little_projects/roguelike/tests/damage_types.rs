@@ -0,0 +1,107 @@
+//! Damage must be typed, and resistances and weaknesses must change how much of it lands.
+
+mod common;
+
+use common::{clean_up, headless_context, lock_world, new_game};
+use roguelike::effects::{add_effect, run_effects_queue, EffectType, Targets};
+use roguelike::raws::{spawn_named_entity, SpawnType, RAWS};
+use roguelike::{DamageType, Pools, State, Weapon};
+use specs::prelude::*;
+
+const SEED: u64 = 4242;
+
+/// Spawns anything named in the raws.
+fn spawn(gs: &mut State, name: &str, pos: SpawnType) -> Entity {
+    spawn_named_entity(&RAWS.lock().unwrap(), &mut gs.ecs, name, pos).unwrap()
+}
+
+/// Gives the target plenty of hit points, so nothing dies mid-test.
+fn toughen(gs: &mut State, target: Entity) {
+    let mut pools = gs.ecs.write_storage::<Pools>();
+    let pools = pools.get_mut(target).unwrap();
+    pools.hit_points.max = 1000;
+    pools.hit_points.current = 1000;
+}
+
+/// Hit points the target loses to this much damage of this type.
+fn damage_taken(gs: &mut State, target: Entity, amount: i32, damage_type: DamageType) -> i32 {
+    let hp = |gs: &State| {
+        gs.ecs
+            .read_storage::<Pools>()
+            .get(target)
+            .unwrap()
+            .hit_points
+            .current
+    };
+    let before = hp(gs);
+    add_effect(
+        None,
+        EffectType::Damage {
+            amount,
+            damage_type,
+        },
+        Targets::Single { target },
+    );
+    run_effects_queue(&mut gs.ecs);
+    before - hp(gs)
+}
+
+#[test]
+fn resistances_and_weaknesses_change_damage() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let mut gs = new_game(&mut ctx, SEED, "damage-types");
+    let cube = spawn(
+        &mut gs,
+        "Gelatinous Cube",
+        SpawnType::AtPosition { x: 1, y: 1 },
+    );
+    let golem = spawn(&mut gs, "Rock Golem", SpawnType::AtPosition { x: 2, y: 1 });
+    toughen(&mut gs, cube);
+    toughen(&mut gs, golem);
+
+    let acid = damage_taken(&mut gs, cube, 10, DamageType::Acid);
+    let fire = damage_taken(&mut gs, cube, 10, DamageType::Fire);
+    let cold = damage_taken(&mut gs, cube, 10, DamageType::Cold);
+    let physical = damage_taken(&mut gs, golem, 10, DamageType::Physical);
+    clean_up(&gs, "damage-types");
+    assert_eq!((acid, fire, cold, physical), (0, 15, 10, 8));
+}
+
+#[test]
+fn worn_armor_adds_its_resistances() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let mut gs = new_game(&mut ctx, SEED, "damage-types");
+    let bandit = spawn(&mut gs, "Bandit", SpawnType::AtPosition { x: 1, y: 1 });
+    toughen(&mut gs, bandit);
+    let bare = damage_taken(&mut gs, bandit, 20, DamageType::Fire);
+    spawn(
+        &mut gs,
+        "Dwarf-Steel Shirt",
+        SpawnType::Equipped { by: bandit },
+    );
+    spawn(&mut gs, "Tower Shield", SpawnType::Equipped { by: bandit });
+    let armored = damage_taken(&mut gs, bandit, 20, DamageType::Fire);
+    clean_up(&gs, "damage-types");
+    // A quarter off from the shirt, then two more from the shield
+    assert_eq!((bare, armored), (20, 13));
+}
+
+#[test]
+fn weapon_traits_add_elemental_riders() {
+    let _guard = lock_world();
+    let mut gs = State::new();
+    let sword = spawn(
+        &mut gs,
+        "Flaming Longsword +1",
+        SpawnType::AtPosition { x: 1, y: 1 },
+    );
+    let weapons = gs.ecs.read_storage::<Weapon>();
+    let weapon = weapons.get(sword).unwrap();
+    let rider = weapon.rider.as_ref().expect("the trait added no rider");
+    assert_eq!(weapon.damage_type, DamageType::Physical);
+    assert_eq!(rider.damage_type, DamageType::Fire);
+    assert_eq!((rider.n_dice, rider.die_type), (1, 4));
+    assert!(weapon.proc_chance.is_none());
+}
@@ -0,0 +1,203 @@
+//! Keys turn into actions through the bindings, which can be remapped and saved, and replays
+//! keep the bindings they were recorded with.
+
+mod common;
+
+use common::{
+    clean_up, headless_context, lock_world, new_game, player, replay_path, runstate, settle, tick,
+    turns,
+};
+use rltk::VirtualKeyCode;
+use roguelike::input::{Action, ActionGroup, KeyBindings, KeyCombo, BINDINGS};
+use roguelike::replay::Replay;
+use roguelike::{Pools, RunState, State};
+use specs::prelude::*;
+
+const SEED: u64 = 5151;
+
+fn key(key: VirtualKeyCode) -> KeyCombo {
+    KeyCombo::new(key)
+}
+
+#[test]
+fn the_defaults_keep_the_classic_keys() {
+    let bindings = KeyBindings::default();
+    let game = |combo| bindings.action(ActionGroup::Game, combo);
+
+    assert_eq!(game(key(VirtualKeyCode::Up)), Some(Action::MoveN));
+    assert_eq!(game(key(VirtualKeyCode::Y)), Some(Action::MoveNW));
+    assert_eq!(game(key(VirtualKeyCode::G)), Some(Action::PickUp));
+    assert_eq!(
+        bindings.action(ActionGroup::Menu, key(VirtualKeyCode::Up)),
+        Some(Action::MenuUp)
+    );
+    assert_eq!(
+        game(KeyCombo::shift(VirtualKeyCode::Key3)),
+        Some(Action::Hotkey3)
+    );
+    assert_eq!(
+        game(KeyCombo::control(VirtualKeyCode::Key3)),
+        Some(Action::Cast3)
+    );
+    assert_eq!(game(key(VirtualKeyCode::Key3)), None);
+    assert_eq!(
+        bindings.action(ActionGroup::SaveMenu, key(VirtualKeyCode::J)),
+        Some(Action::SaveAsJson)
+    );
+    assert_eq!(
+        bindings.action(ActionGroup::CheatMenu, key(VirtualKeyCode::R)),
+        Some(Action::CheatReveal)
+    );
+    // Holding a modifier doesn't stop a plain binding from working
+    assert_eq!(
+        game(KeyCombo::shift(VirtualKeyCode::Up)),
+        Some(Action::MoveN)
+    );
+}
+
+#[test]
+fn bindings_round_trip_through_toml() {
+    let mut bindings = KeyBindings::default();
+    bindings.bind(Action::Fire, KeyCombo::control(VirtualKeyCode::T));
+    let loaded = KeyBindings::from_toml(&bindings.to_toml()).unwrap();
+    assert_eq!(loaded, bindings);
+}
+
+#[test]
+fn a_config_file_only_changes_the_actions_it_names() {
+    let loaded = KeyBindings::from_toml(
+        "[bindings]\nMoveN = [\"Up\", \"Numpad8\", \"Z\"]\nHotkey1 = [\"Ctrl+Shift+A\"]\n",
+    )
+    .unwrap();
+    let defaults = KeyBindings::default();
+
+    assert_eq!(
+        loaded.action(ActionGroup::Game, key(VirtualKeyCode::Z)),
+        Some(Action::MoveN)
+    );
+    assert_eq!(
+        loaded.keys(Action::Hotkey1),
+        &[KeyCombo {
+            key: VirtualKeyCode::A,
+            shift: true,
+            control: true,
+        }]
+    );
+    assert_eq!(loaded.keys(Action::PickUp), defaults.keys(Action::PickUp));
+
+    assert!(KeyBindings::from_toml("[bindings]\nJump = [\"J\"]\n").is_err());
+    assert!(KeyBindings::from_toml("[bindings]\nFire = [\"Hyper+F\"]\n").is_err());
+    assert!(KeyBindings::from_toml("[bindings]\nFire = [\"NoSuchKey\"]\n").is_err());
+}
+
+#[test]
+fn binding_a_key_takes_it_from_the_same_group_only() {
+    let mut bindings = KeyBindings::default();
+    // Moving west with K takes it away from moving north
+    bindings.bind(Action::MoveW, key(VirtualKeyCode::K));
+    assert!(!bindings
+        .keys(Action::MoveN)
+        .contains(&key(VirtualKeyCode::K)));
+    assert_eq!(
+        bindings.action(ActionGroup::Game, key(VirtualKeyCode::K)),
+        Some(Action::MoveW)
+    );
+
+    bindings.bind(Action::MoveN, key(VirtualKeyCode::Up));
+    bindings.bind(Action::Wait, key(VirtualKeyCode::Up));
+    assert_eq!(
+        bindings.action(ActionGroup::Menu, key(VirtualKeyCode::Up)),
+        Some(Action::MenuUp)
+    );
+
+    bindings.clear(Action::MoveN);
+    assert_eq!(bindings.describe(Action::MoveN), "unbound");
+    bindings.reset(Action::MoveN);
+    assert_eq!(
+        bindings.keys(Action::MoveN),
+        KeyBindings::default().keys(Action::MoveN)
+    );
+    assert!(!bindings
+        .keys(Action::Wait)
+        .contains(&key(VirtualKeyCode::Up)));
+}
+
+#[test]
+fn remapped_keys_drive_the_game_and_replays_remember_them() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    {
+        let mut bindings = BINDINGS.lock().unwrap();
+        *bindings = KeyBindings::default();
+        bindings.clear(Action::Wait);
+        bindings.bind(Action::Wait, key(VirtualKeyCode::Q));
+    }
+
+    let mut gs = new_game(&mut ctx, SEED, "bindings");
+    let start = turns(&gs);
+    tick(&mut gs, &mut ctx, Some(VirtualKeyCode::Numpad5));
+    settle(&mut gs, &mut ctx);
+    assert_eq!(turns(&gs), start);
+    for _ in 0..3 {
+        tick(&mut gs, &mut ctx, Some(VirtualKeyCode::Q));
+        settle(&mut gs, &mut ctx);
+    }
+    let recorded = turns(&gs);
+    assert_eq!(recorded, start + 3);
+
+    // Watch it with the default keys in place; the recorded ones are used until it's over
+    *BINDINGS.lock().unwrap() = KeyBindings::default();
+    let mut replayed = State::new();
+    replayed.play_replay(Replay::load(&replay_path("bindings")).unwrap());
+    for _ in 0..1000 {
+        if !replayed.is_replaying() {
+            break;
+        }
+        tick(&mut replayed, &mut ctx, None);
+    }
+    assert!(!replayed.is_replaying(), "the replay never finished");
+    assert_eq!(turns(&replayed), recorded);
+    assert_eq!(*BINDINGS.lock().unwrap(), KeyBindings::default());
+
+    clean_up(&gs, "bindings");
+}
+
+fn hit_points(gs: &State) -> i32 {
+    gs.ecs
+        .read_storage::<Pools>()
+        .get(player(gs))
+        .unwrap()
+        .hit_points
+        .current
+}
+
+#[test]
+fn the_cheat_menu_follows_the_bindings() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    {
+        let mut bindings = BINDINGS.lock().unwrap();
+        *bindings = KeyBindings::default();
+        bindings.clear(Action::CheatHeal);
+        bindings.bind(Action::CheatHeal, key(VirtualKeyCode::Y));
+    }
+    let mut gs = new_game(&mut ctx, SEED, "cheats");
+    gs.ecs
+        .write_storage::<Pools>()
+        .get_mut(player(&gs))
+        .unwrap()
+        .hit_points
+        .current = 1;
+
+    tick(&mut gs, &mut ctx, Some(VirtualKeyCode::Backslash));
+    assert!(matches!(runstate(&gs), RunState::ShowCheatMenu));
+    // H no longer heals, so the menu stays open
+    tick(&mut gs, &mut ctx, Some(VirtualKeyCode::H));
+    assert!(matches!(runstate(&gs), RunState::ShowCheatMenu));
+    assert_eq!(hit_points(&gs), 1);
+    tick(&mut gs, &mut ctx, Some(VirtualKeyCode::Y));
+    let healed = hit_points(&gs);
+    *BINDINGS.lock().unwrap() = KeyBindings::default();
+    clean_up(&gs, "cheats");
+    assert!(healed > 1, "the rebound key didn't heal");
+}
@@ -0,0 +1,171 @@
+//! Validation harness for the map generators. Every chain reachable from `level_builder` and
+//! `random_builder` is built across many seeds and checked for playability.
+
+use roguelike::map_builders::{level_builder, random_builder, BuilderChain};
+use roguelike::{tile_ascii, tile_walkable, Map, TileType};
+use std::sync::{Mutex, Once};
+
+const WIDTH: i32 = 80;
+const HEIGHT: i32 = 50;
+const SEEDS_PER_LEVEL: u64 = 20;
+const RANDOM_SEEDS: u64 = 500;
+const LAST_DEPTH: i32 = 12;
+
+// The RNG and spatial index are process-wide, so map generation can't run on parallel threads
+static GENERATOR_LOCK: Mutex<()> = Mutex::new(());
+static LOAD_RAWS: Once = Once::new();
+
+fn dump_map(map: &Map) -> String {
+    let mut result = String::new();
+    for row in map.tiles.chunks(map.width as usize) {
+        result.extend(row.iter().map(|tt| tile_ascii(*tt)));
+        result.push('\n');
+    }
+    result
+}
+
+fn build(seed: u64, depth: i32, builder: fn(i32, i32, i32) -> BuilderChain) -> BuilderChain {
+    roguelike::rng::reseed(roguelike::rng::depth_seed(seed, depth));
+    let mut chain = builder(depth, WIDTH, HEIGHT);
+    chain.build_map();
+    chain.place_up_stairs();
+    chain
+}
+
+/// Returns a description of the first problem found with the map, if any.
+fn validate(chain: &BuilderChain, solid_border: bool) -> Option<String> {
+    let build_data = &chain.build_data;
+    let map = &build_data.map;
+
+    let start = match &build_data.starting_position {
+        None => return Some("no starting position".to_string()),
+        Some(pos) => pos,
+    };
+    if start.x < 1 || start.x > map.width - 2 || start.y < 1 || start.y > map.height - 2 {
+        return Some(format!("starting position {},{} is out of bounds", start.x, start.y));
+    }
+    let start_idx = map.xy_idx(start.x, start.y);
+    if !tile_walkable(map.tiles[start_idx]) {
+        return Some(format!("starting position {},{} is not walkable", start.x, start.y));
+    }
+
+    if solid_border {
+        for (idx, tt) in map.tiles.iter().enumerate() {
+            let (x, y) = (idx as i32 % map.width, idx as i32 / map.width);
+            let on_border = x == 0 || y == 0 || x == map.width - 1 || y == map.height - 1;
+            if on_border && tile_walkable(*tt) {
+                return Some(format!("border tile {},{} is walkable", x, y));
+            }
+        }
+    }
+
+    // "None" is what an empty spawn table roll produces; the spawner skips it
+    for (idx, name) in build_data.spawn_list.iter().filter(|(_, name)| name != "None") {
+        if !tile_walkable(map.tiles[*idx]) {
+            let (x, y) = (*idx as i32 % map.width, *idx as i32 / map.width);
+            return Some(format!("{} spawned on a non-walkable tile at {},{}", name, x, y));
+        }
+    }
+
+    let mut pathing = map.clone();
+    roguelike::spatial::set_size(pathing.tiles.len());
+    pathing.populate_blocked();
+    let dijkstra = rltk::DijkstraMap::new(
+        map.width,
+        map.height,
+        &[start_idx],
+        &pathing,
+        (map.width * map.height) as f32,
+    );
+
+    let mut has_down_stairs = false;
+    for (idx, tt) in map.tiles.iter().enumerate() {
+        if *tt == TileType::DownStairs || *tt == TileType::UpStairs {
+            has_down_stairs |= *tt == TileType::DownStairs;
+            if dijkstra.map[idx] == f32::MAX {
+                let (x, y) = (idx as i32 % map.width, idx as i32 / map.width);
+                return Some(format!("stairs at {},{} can't be reached from the start", x, y));
+            }
+        }
+    }
+    if !has_down_stairs && map.depth < LAST_DEPTH {
+        return Some("no down stairs".to_string());
+    }
+
+    None
+}
+
+fn check(seed: u64, depth: i32, builder: fn(i32, i32, i32) -> BuilderChain, solid_border: bool) {
+    let chain = build(seed, depth, builder);
+    if let Some(problem) = validate(&chain, solid_border) {
+        panic!(
+            "{} (depth {}, seed {}): {}\n{}",
+            chain.build_data.map.name,
+            depth,
+            seed,
+            problem,
+            dump_map(&chain.build_data.map)
+        );
+    }
+}
+
+fn setup() -> std::sync::MutexGuard<'static, ()> {
+    let guard = GENERATOR_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    LOAD_RAWS.call_once(roguelike::raws::load_raws);
+    guard
+}
+
+#[test]
+fn level_builder_maps_are_playable() {
+    let _guard = setup();
+    for depth in 1..=LAST_DEPTH {
+        // The town is open countryside out to the map edge (see `TownBuilder`)
+        let solid_border = depth != 1;
+        for seed in 0..SEEDS_PER_LEVEL {
+            check(seed, depth, level_builder, solid_border);
+        }
+    }
+}
+
+#[test]
+fn random_builder_maps_are_playable() {
+    let _guard = setup();
+    for seed in 0..RANDOM_SEEDS {
+        check(seed, LAST_DEPTH + 1, random_builder, true);
+    }
+}
+
+#[test]
+fn same_seed_builds_same_map() {
+    let _guard = setup();
+    for depth in 1..=LAST_DEPTH + 1 {
+        let first = build(1234, depth, level_builder);
+        let second = build(1234, depth, level_builder);
+        assert!(
+            first.build_data.map.tiles == second.build_data.map.tiles,
+            "depth {} produced different tiles for the same seed",
+            depth
+        );
+        assert_eq!(first.build_data.spawn_list, second.build_data.spawn_list);
+    }
+}
+
+#[test]
+fn mapgen_json_output_parses() {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_mapgen"))
+        .args(["--format", "json", "--seed", "42", "--depth", "3", "--history"])
+        .output()
+        .expect("Unable to run mapgen");
+    assert!(output.status.success());
+    // Warnings and debugging notes go to stderr, so stdout is only the report
+    let report: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("mapgen stdout is not JSON");
+    assert_eq!(report["seed"], 42);
+    assert_eq!(report["depth"], 3);
+    let map = &report["map"];
+    assert_eq!(
+        map["tiles"].as_array().unwrap().len() as i64,
+        map["width"].as_i64().unwrap() * map["height"].as_i64().unwrap()
+    );
+    assert!(report["starting_position"].is_array());
+}
@@ -0,0 +1,172 @@
+//! The message log keeps everything, files it by category and turn, folds repeats together and
+//! can be written out to a file.
+
+mod common;
+
+use common::{clean_up, headless_context, lock_world, new_game, player, runstate, temp_path, tick};
+use rltk::VirtualKeyCode;
+use roguelike::effects::{add_effect, run_effects_queue, EffectType, Targets};
+use roguelike::gamelog::{
+    clear_log, export_log, log_lines, recent_lines, record_event, LogCategory, LogFragment, Logger,
+};
+use roguelike::{practice_to_advance, RunState, Skill, Skills};
+use specs::prelude::*;
+
+const SEED: u64 = 6161;
+
+fn texts(filter: Option<LogCategory>) -> Vec<String> {
+    log_lines(filter).iter().map(|line| line.text()).collect()
+}
+
+fn recent_texts(count: usize) -> Vec<String> {
+    recent_lines(count).iter().map(|line| line.text()).collect()
+}
+
+#[test]
+fn entries_are_filed_by_category_and_turn() {
+    let _guard = lock_world();
+    clear_log();
+    record_event("Turn", 7);
+    let turn = roguelike::gamelog::get_event_count("Turn");
+    Logger::new()
+        .category(LogCategory::Combat)
+        .npc_name("Bandit")
+        .append("hits you.")
+        .log();
+    Logger::new()
+        .category(LogCategory::Loot)
+        .append("You pick up the")
+        .item_name("Dagger")
+        .log();
+    Logger::new().append("You change level.").log();
+
+    assert_eq!(texts(Some(LogCategory::Combat)), vec!["Bandit hits you."]);
+    assert_eq!(
+        texts(Some(LogCategory::Loot)),
+        vec!["You pick up the Dagger"]
+    );
+    assert_eq!(texts(Some(LogCategory::System)), vec!["You change level."]);
+    assert!(texts(Some(LogCategory::Dialogue)).is_empty());
+    assert_eq!(texts(None).len(), 3);
+    assert!(log_lines(None).iter().all(|line| line.turn == turn));
+}
+
+#[test]
+fn repeated_messages_are_collapsed() {
+    let _guard = lock_world();
+    clear_log();
+    for _ in 0..3 {
+        Logger::new().append("You are hungry").log();
+    }
+    Logger::new().append("You are starving!").log();
+    Logger::new().append("You are hungry").log();
+
+    assert_eq!(
+        texts(None),
+        vec!["You are hungry x3", "You are starving!", "You are hungry"]
+    );
+}
+
+#[test]
+fn a_rising_skill_is_filed_under_combat() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let mut gs = new_game(&mut ctx, SEED, "log");
+    let target = player(&gs);
+    let level = gs
+        .ecs
+        .read_storage::<Skills>()
+        .get(target)
+        .unwrap()
+        .skills
+        .get(&Skill::Melee)
+        .copied()
+        .unwrap_or(0);
+    clear_log();
+    for _ in 0..practice_to_advance(level) {
+        add_effect(
+            None,
+            EffectType::Practice {
+                skill: Skill::Melee,
+            },
+            Targets::Single { target },
+        );
+    }
+    run_effects_queue(&mut gs.ecs);
+
+    let rises = texts(Some(LogCategory::Combat));
+    clean_up(&gs, "log");
+    assert_eq!(rises.len(), 1);
+    assert!(rises[0].contains("skill rises"), "{:?}", rises);
+}
+
+#[test]
+fn the_end_of_the_log_collapses_like_the_rest() {
+    let _guard = lock_world();
+    clear_log();
+    for message in ["A", "B", "B", "C", "C", "C", "A", "A"] {
+        Logger::new().append(message).log();
+    }
+
+    assert_eq!(recent_texts(2), vec!["C x3", "A x2"]);
+    assert_eq!(recent_texts(10), texts(None));
+}
+
+#[test]
+fn the_log_exports_to_a_text_file() {
+    let _guard = lock_world();
+    clear_log();
+    Logger::new()
+        .category(LogCategory::Dialogue)
+        .npc_name("Barkeep")
+        .append("says")
+        .append("\"Welcome!\"")
+        .log();
+    Logger::new().append("You feel stronger!").log();
+    Logger::new().append("You feel stronger!").log();
+
+    let path = temp_path("logs/exported.txt");
+    export_log(&path).unwrap();
+    let text = std::fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with("[Dialogue] Barkeep says \"Welcome!\""));
+    assert!(lines[1].ends_with("[System] You feel stronger! x2"));
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test]
+fn logs_from_older_saves_still_load() {
+    let fragment: LogFragment =
+        serde_json::from_str(r#"{"color":{"r":1.0,"g":1.0,"b":1.0},"text":"Hello"}"#).unwrap();
+    assert_eq!(fragment.category, LogCategory::System);
+    assert_eq!(fragment.turn, 0);
+}
+
+#[test]
+fn the_log_screen_opens_and_closes() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let mut gs = new_game(&mut ctx, SEED, "log");
+
+    tick(&mut gs, &mut ctx, Some(VirtualKeyCode::M));
+    assert!(matches!(
+        runstate(&gs),
+        RunState::ShowLog {
+            offset: 0,
+            filter: None
+        }
+    ));
+    tick(&mut gs, &mut ctx, Some(VirtualKeyCode::Space));
+    assert!(matches!(
+        runstate(&gs),
+        RunState::ShowLog {
+            filter: Some(LogCategory::Combat),
+            ..
+        }
+    ));
+    tick(&mut gs, &mut ctx, Some(VirtualKeyCode::Escape));
+    assert!(matches!(runstate(&gs), RunState::AwaitingInput));
+
+    clean_up(&gs, "log");
+}
@@ -0,0 +1,166 @@
+//! A run that ends, by dying or by picking up the amulet, leaves a morgue file behind and goes on
+//! the high-score table.
+
+mod common;
+
+use common::{headless_context, lock_world, player, runstate, settle, temp_path, tick};
+use rltk::{Rltk, VirtualKeyCode};
+use roguelike::effects::{add_effect, EffectType, Targets};
+use roguelike::morgue::{
+    HighScore, HighScores, RunEnd, HIGH_SCORES_FILE, MAX_HIGH_SCORES, MORGUE_DIR,
+};
+use roguelike::raws::{spawn_named_entity, SpawnType, RAWS};
+use roguelike::{DamageType, Position, RunState, State};
+use specs::prelude::*;
+use std::path::PathBuf;
+
+const SEED: u64 = 7171;
+
+/// A recorded run, settled on its first turn, keeping its records in `dir`.
+fn start_run(ctx: &mut Rltk, dir: &PathBuf) -> State {
+    std::fs::create_dir_all(dir).unwrap();
+    let mut gs = State::new();
+    gs.keep_records_in(dir);
+    gs.record_run(SEED, &dir.join("run.replay"));
+    settle(&mut gs, ctx);
+    gs
+}
+
+fn finish(gs: &State, dir: &PathBuf) {
+    let _ = std::fs::remove_dir_all(dir);
+    roguelike::saveload_system::delete_slot(&roguelike::saveload_system::run_slot(&gs.ecs));
+}
+
+fn entry(name: &str, score: i32) -> HighScore {
+    HighScore {
+        name: name.to_string(),
+        background: "Brawler".to_string(),
+        level: 1,
+        score,
+        depth: 1,
+        turns: 10,
+        outcome: "killed by a Rat".to_string(),
+        seed: 1,
+    }
+}
+
+#[test]
+fn the_high_score_table_keeps_the_best_runs_in_order() {
+    let mut table = HighScores::default();
+    assert_eq!(table.insert(entry("First", 100)), Some(1));
+    assert_eq!(table.insert(entry("Better", 300)), Some(1));
+    assert_eq!(table.insert(entry("Tied", 100)), Some(3));
+    let names: Vec<&str> = table.entries.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, vec!["Better", "First", "Tied"]);
+
+    for i in 0..MAX_HIGH_SCORES {
+        table.insert(entry("Filler", 1000 + i as i32));
+    }
+    assert_eq!(table.entries.len(), MAX_HIGH_SCORES);
+    assert_eq!(table.insert(entry("Too low", 5)), None);
+    assert!(table.entries.iter().all(|e| e.name != "Too low"));
+
+    let path = temp_path("scores/highscores.json");
+    table.save(&path).unwrap();
+    assert_eq!(HighScores::load(&path), table);
+    assert_eq!(
+        HighScores::load(&temp_path("no-such-file.json")),
+        HighScores::default()
+    );
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test]
+fn dying_writes_a_morgue_file_and_a_high_score() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let dir = temp_path("death");
+    let mut gs = start_run(&mut ctx, &dir);
+
+    let player = player(&gs);
+    add_effect(
+        None,
+        EffectType::Damage {
+            amount: 10000,
+            damage_type: DamageType::Fire,
+        },
+        Targets::Single { target: player },
+    );
+    tick(&mut gs, &mut ctx, Some(VirtualKeyCode::Numpad5));
+    settle(&mut gs, &mut ctx);
+    assert!(matches!(runstate(&gs), RunState::GameOver));
+    assert_eq!(
+        *gs.ecs.fetch::<RunEnd>(),
+        RunEnd::Died {
+            cause: "succumbed to fire damage".to_string()
+        }
+    );
+
+    // The records are written when the game over screen first shows
+    tick(&mut gs, &mut ctx, None);
+    let morgue = std::fs::read_to_string(dir.join(MORGUE_DIR).join(format!(
+        "{}.txt",
+        roguelike::saveload_system::run_slot(&gs.ecs)
+    )))
+    .unwrap();
+    for section in [
+        "Succumbed to fire damage on level 1",
+        "\nAttributes\n",
+        "\nSkills\n",
+        "\nEquipment\n",
+        "\nInventory\n",
+        "\nSpells\n",
+        "\nEvents\n",
+        "\nLast messages\n",
+        "Turn",
+        "@",
+    ] {
+        assert!(
+            morgue.contains(section),
+            "no {:?} in the morgue file",
+            section
+        );
+    }
+
+    let table = HighScores::load(&dir.join(HIGH_SCORES_FILE));
+    assert_eq!(table.entries.len(), 1);
+    assert_eq!(table.entries[0].outcome, "succumbed to fire damage");
+    assert_eq!(table.entries[0].seed, SEED);
+
+    finish(&gs, &dir);
+}
+
+#[test]
+fn picking_up_the_amulet_wins_the_game() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let dir = temp_path("victory");
+    let mut gs = start_run(&mut ctx, &dir);
+
+    let player = player(&gs);
+    let pos = gs
+        .ecs
+        .read_storage::<Position>()
+        .get(player)
+        .unwrap()
+        .clone();
+    spawn_named_entity(
+        &RAWS.lock().unwrap(),
+        &mut gs.ecs,
+        "Amulet of Yala",
+        SpawnType::AtPosition { x: pos.x, y: pos.y },
+    )
+    .unwrap();
+    tick(&mut gs, &mut ctx, Some(VirtualKeyCode::G));
+    settle(&mut gs, &mut ctx);
+    assert!(matches!(runstate(&gs), RunState::GameOver));
+    assert_eq!(*gs.ecs.fetch::<RunEnd>(), RunEnd::Won);
+
+    tick(&mut gs, &mut ctx, None);
+    let table = HighScores::load(&dir.join(HIGH_SCORES_FILE));
+    assert_eq!(table.entries.len(), 1);
+    assert_eq!(table.entries[0].outcome, "escaped with the Amulet of Yala");
+    assert!(table.entries[0].score >= 5000);
+
+    finish(&gs, &dir);
+}
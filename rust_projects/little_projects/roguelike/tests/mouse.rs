@@ -0,0 +1,329 @@
+//! The game can be played with the mouse: clicking moves and attacks, right-clicking opens a menu
+//! of what can be done with a tile, and items and spells can be clicked where they're listed.
+
+mod common;
+
+use common::{
+    clean_up, headless_context, last_message, lock_world, new_game, player, replay_path, runstate,
+    settle, tick, turns, wait_a_turn,
+};
+use rltk::{GameState, Point, Rltk, VirtualKeyCode};
+use roguelike::input::set_right_click;
+use roguelike::raws::{spawn_named_entity, SpawnType, RAWS};
+use roguelike::replay::Replay;
+use roguelike::{tile_walkable, Consumable, Equipped, InBackpack, Map, Name, RunState, State};
+use specs::prelude::*;
+
+const SEED: u64 = 9191;
+
+/// Puts the mouse over a console cell; the window is 8 pixels to a cell.
+fn hover(ctx: &mut Rltk, cell: Point) {
+    ctx.mouse_pos = (cell.x * 8 + 4, cell.y * 8 + 4);
+}
+
+fn left_click(gs: &mut State, ctx: &mut Rltk, cell: Point) {
+    hover(ctx, cell);
+    ctx.left_click = true;
+    gs.tick(ctx);
+    ctx.left_click = false;
+}
+
+fn right_click(gs: &mut State, ctx: &mut Rltk, cell: Point) {
+    hover(ctx, cell);
+    set_right_click(true);
+    gs.tick(ctx);
+}
+
+fn player_pos(gs: &State) -> Point {
+    *gs.ecs.fetch::<Point>()
+}
+
+/// Where a map tile is drawn; the camera keeps the player at (25, 23).
+fn screen_cell(gs: &State, tile: Point) -> Point {
+    let pos = player_pos(gs);
+    Point::new(tile.x - pos.x + 25, tile.y - pos.y + 23)
+}
+
+/// A walkable, empty tile next to the player.
+fn free_neighbour(gs: &State) -> Point {
+    let map = gs.ecs.fetch::<Map>();
+    let pos = player_pos(gs);
+    [
+        (1, 0),
+        (-1, 0),
+        (0, 1),
+        (0, -1),
+        (1, 1),
+        (-1, -1),
+        (1, -1),
+        (-1, 1),
+    ]
+    .iter()
+    .map(|(dx, dy)| Point::new(pos.x + dx, pos.y + dy))
+    .find(|tile| {
+        let idx = map.xy_idx(tile.x, tile.y);
+        tile_walkable(map.tiles[idx]) && !roguelike::spatial::is_blocked(idx)
+    })
+    .unwrap()
+}
+
+fn spawn(gs: &mut State, name: &str, spawn_type: SpawnType) -> Entity {
+    spawn_named_entity(&RAWS.lock().unwrap(), &mut gs.ecs, name, spawn_type).unwrap()
+}
+
+/// Spawns a bandit next to the player and lets a turn pass so everyone can see it.
+fn bandit_next_door(gs: &mut State, ctx: &mut Rltk) -> Point {
+    let spot = free_neighbour(gs);
+    spawn(
+        gs,
+        "Bandit",
+        SpawnType::AtPosition {
+            x: spot.x,
+            y: spot.y,
+        },
+    );
+    wait_a_turn(gs, ctx);
+    spot
+}
+
+#[test]
+fn clicking_next_to_the_player_attacks_and_right_clicking_offers_actions() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let mut gs = new_game(&mut ctx, SEED, "melee");
+    let bandit = bandit_next_door(&mut gs, &mut ctx);
+    let start = player_pos(&gs);
+
+    let cell = screen_cell(&gs, bandit);
+    left_click(&mut gs, &mut ctx, cell);
+    assert!(matches!(runstate(&gs), RunState::Ticking));
+    settle(&mut gs, &mut ctx);
+    assert_eq!(player_pos(&gs), start);
+
+    // Examine is always first, and attacking comes with a bandit in reach
+    let cell = screen_cell(&gs, bandit);
+    right_click(&mut gs, &mut ctx, cell);
+    assert!(
+        runstate(&gs)
+            == RunState::ShowContextMenu {
+                x: bandit.x,
+                y: bandit.y
+            }
+    );
+    tick(&mut gs, &mut ctx, Some(VirtualKeyCode::A));
+    assert!(matches!(runstate(&gs), RunState::AwaitingInput));
+    assert!(
+        last_message().starts_with("You see Bandit on"),
+        "{}",
+        last_message()
+    );
+
+    let cell = screen_cell(&gs, bandit);
+    right_click(&mut gs, &mut ctx, cell);
+    tick(&mut gs, &mut ctx, Some(VirtualKeyCode::Escape));
+    assert!(matches!(runstate(&gs), RunState::AwaitingInput));
+
+    let before = turns(&gs);
+    let cell = screen_cell(&gs, bandit);
+    right_click(&mut gs, &mut ctx, cell);
+    tick(&mut gs, &mut ctx, Some(VirtualKeyCode::B));
+    assert!(matches!(runstate(&gs), RunState::Ticking));
+    settle(&mut gs, &mut ctx);
+    assert!(turns(&gs) > before);
+    assert_eq!(player_pos(&gs), start);
+
+    clean_up(&gs, "melee");
+}
+
+#[test]
+fn picking_up_and_using_items_by_clicking() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let mut gs = new_game(&mut ctx, SEED, "items");
+    let player = player(&gs);
+    let pos = player_pos(&gs);
+
+    // The player's own tile offers to pick up what's lying there
+    let dagger = spawn(
+        &mut gs,
+        "Dagger",
+        SpawnType::AtPosition { x: pos.x, y: pos.y },
+    );
+    wait_a_turn(&mut gs, &mut ctx);
+    let cell = screen_cell(&gs, pos);
+    right_click(&mut gs, &mut ctx, cell);
+    assert!(matches!(runstate(&gs), RunState::ShowContextMenu { .. }));
+    // The second row of the menu, drawn beside the tile
+    left_click(&mut gs, &mut ctx, Point::new(28, 25));
+    settle(&mut gs, &mut ctx);
+    assert_eq!(
+        gs.ecs
+            .read_storage::<InBackpack>()
+            .get(dagger)
+            .map(|b| b.owner),
+        Some(player)
+    );
+
+    // Every consumable is on the side panel under the equipment, in backpack order
+    let potion = spawn(&mut gs, "Health Potion", SpawnType::Carried { by: player });
+    let (equipped, consumables) = {
+        let equipped = gs
+            .ecs
+            .read_storage::<Equipped>()
+            .join()
+            .filter(|e| e.owner == player)
+            .count() as i32;
+        let entities = gs.ecs.entities();
+        let backpack = gs.ecs.read_storage::<InBackpack>();
+        let consumables: Vec<Entity> = (&entities, &backpack, &gs.ecs.read_storage::<Consumable>())
+            .join()
+            .filter(|(_, b, _)| b.owner == player)
+            .map(|(e, _, _)| e)
+            .collect();
+        (equipped, consumables)
+    };
+    let slot = consumables.iter().position(|e| *e == potion).unwrap() as i32;
+    left_click(&mut gs, &mut ctx, Point::new(55, 14 + equipped + slot));
+    assert!(matches!(runstate(&gs), RunState::Ticking));
+    settle(&mut gs, &mut ctx);
+    assert!(gs.ecs.read_storage::<Name>().get(potion).is_none());
+
+    // The inventory lists the backpack from row 25 - count / 2
+    tick(&mut gs, &mut ctx, Some(VirtualKeyCode::I));
+    assert!(matches!(runstate(&gs), RunState::ShowInventory));
+    let listed: Vec<Entity> = (&gs.ecs.entities(), &gs.ecs.read_storage::<InBackpack>())
+        .join()
+        .filter(|(_, b)| b.owner == player)
+        .map(|(e, _)| e)
+        .collect();
+    let row = listed.iter().position(|e| *e == dagger).unwrap() as i32;
+    let first_row = 25 - listed.len() as i32 / 2;
+    left_click(&mut gs, &mut ctx, Point::new(20, first_row + row));
+    settle(&mut gs, &mut ctx);
+    assert!(gs.ecs.read_storage::<Equipped>().get(dagger).is_some());
+
+    clean_up(&gs, "items");
+}
+
+#[test]
+fn items_further_off_are_fetched_from_the_context_menu() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let mut gs = new_game(&mut ctx, SEED, "fetch");
+    let player = player(&gs);
+
+    let spot = {
+        let map = gs.ecs.fetch::<Map>();
+        let pos = player_pos(&gs);
+        (-5..=5)
+            .flat_map(|dy| (-5..=5).map(move |dx| Point::new(pos.x + dx, pos.y + dy)))
+            .find(|tile| {
+                let idx = map.xy_idx(tile.x, tile.y);
+                map.visible_tiles[idx]
+                    && tile_walkable(map.tiles[idx])
+                    && !roguelike::spatial::is_blocked(idx)
+                    && rltk::DistanceAlg::Chebyshev.distance2d(*tile, pos) == 3.0
+            })
+            .unwrap()
+    };
+    let dagger = spawn(
+        &mut gs,
+        "Dagger",
+        SpawnType::AtPosition {
+            x: spot.x,
+            y: spot.y,
+        },
+    );
+    wait_a_turn(&mut gs, &mut ctx);
+
+    // Examine, then Pick up, then Walk here
+    let cell = screen_cell(&gs, spot);
+    right_click(&mut gs, &mut ctx, cell);
+    tick(&mut gs, &mut ctx, Some(VirtualKeyCode::B));
+    settle(&mut gs, &mut ctx);
+    assert_eq!(player_pos(&gs), spot, "{}", last_message());
+    assert_eq!(
+        gs.ecs
+            .read_storage::<InBackpack>()
+            .get(dagger)
+            .map(|b| b.owner),
+        Some(player)
+    );
+
+    clean_up(&gs, "fetch");
+}
+
+#[test]
+fn vendors_can_be_talked_to_as_well_as_traded_with() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let mut gs = new_game(&mut ctx, SEED, "talk");
+    let spot = free_neighbour(&gs);
+    spawn(
+        &mut gs,
+        "Barkeep",
+        SpawnType::AtPosition {
+            x: spot.x,
+            y: spot.y,
+        },
+    );
+    wait_a_turn(&mut gs, &mut ctx);
+
+    // Examine, then Talk, then Trade
+    let cell = screen_cell(&gs, spot);
+    right_click(&mut gs, &mut ctx, cell);
+    tick(&mut gs, &mut ctx, Some(VirtualKeyCode::B));
+    assert!(matches!(runstate(&gs), RunState::AwaitingInput));
+    assert_eq!(last_message(), "Barkeep says Looking for food?");
+
+    let cell = screen_cell(&gs, spot);
+    right_click(&mut gs, &mut ctx, cell);
+    tick(&mut gs, &mut ctx, Some(VirtualKeyCode::C));
+    assert!(matches!(runstate(&gs), RunState::ShowVendor { .. }));
+
+    clean_up(&gs, "talk");
+}
+
+#[test]
+fn walking_from_the_context_menu_replays_the_same() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let mut gs = new_game(&mut ctx, SEED, "walk");
+
+    let destination = {
+        let map = gs.ecs.fetch::<Map>();
+        let pos = player_pos(&gs);
+        (-10..=10)
+            .flat_map(|dy| (-10..=10).map(move |dx| Point::new(pos.x + dx, pos.y + dy)))
+            .find(|tile| {
+                let idx = map.xy_idx(tile.x, tile.y);
+                map.visible_tiles[idx]
+                    && tile_walkable(map.tiles[idx])
+                    && !roguelike::spatial::is_blocked(idx)
+                    && rltk::DistanceAlg::Chebyshev.distance2d(*tile, pos) >= 4.0
+            })
+            .unwrap()
+    };
+    let cell = screen_cell(&gs, destination);
+    right_click(&mut gs, &mut ctx, cell);
+    assert!(matches!(runstate(&gs), RunState::ShowContextMenu { .. }));
+    // Nobody stands there, so it's Examine then Walk here
+    tick(&mut gs, &mut ctx, Some(VirtualKeyCode::B));
+    settle(&mut gs, &mut ctx);
+    assert_eq!(player_pos(&gs), destination, "{}", last_message());
+    let recorded_turns = turns(&gs);
+
+    let mut replayed = State::new();
+    replayed.play_replay(Replay::load(&replay_path("walk")).unwrap());
+    for _ in 0..5000 {
+        if !replayed.is_replaying() {
+            break;
+        }
+        tick(&mut replayed, &mut ctx, None);
+    }
+    settle(&mut replayed, &mut ctx);
+    assert_eq!(turns(&replayed), recorded_turns);
+    assert_eq!(player_pos(&replayed), destination);
+
+    clean_up(&gs, "walk");
+}
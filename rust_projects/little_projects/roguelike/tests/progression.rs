@@ -0,0 +1,124 @@
+//! Skills improve with use, and each level gained buys a perk from the raws.
+
+mod common;
+
+use common::{clean_up, headless_context, lock_world, new_game, player, runstate, tick};
+use rltk::VirtualKeyCode;
+use roguelike::effects::{add_effect, run_effects_queue, EffectType, Targets};
+use roguelike::progression::{perk_choices, take_perk};
+use roguelike::{
+    carry_capacity_lbs, practice_to_advance, spell_slots, Attributes, Perks, Pools, RunState,
+    Skill, Skills, State,
+};
+use specs::prelude::*;
+
+const SEED: u64 = 4242;
+
+fn skill_level(gs: &State, skill: Skill) -> i32 {
+    let skills = gs.ecs.read_storage::<Skills>();
+    let skills = skills.get(player(gs)).unwrap();
+    skills.skills.get(&skill).copied().unwrap_or(0)
+}
+
+fn perks(gs: &State) -> Perks {
+    gs.ecs.read_storage::<Perks>().get(player(gs)).unwrap().clone()
+}
+
+#[test]
+fn practice_raises_a_skill() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let mut gs = new_game(&mut ctx, SEED, "progression");
+    let target = player(&gs);
+    let before = skill_level(&gs, Skill::Defense);
+
+    for _ in 0..practice_to_advance(before) - 1 {
+        add_effect(
+            None,
+            EffectType::Practice {
+                skill: Skill::Defense,
+            },
+            Targets::Single { target },
+        );
+    }
+    run_effects_queue(&mut gs.ecs);
+    assert_eq!(skill_level(&gs, Skill::Defense), before);
+
+    add_effect(
+        None,
+        EffectType::Practice {
+            skill: Skill::Defense,
+        },
+        Targets::Single { target },
+    );
+    run_effects_queue(&mut gs.ecs);
+    assert_eq!(skill_level(&gs, Skill::Defense), before + 1);
+    clean_up(&gs, "progression");
+}
+
+#[test]
+fn perks_need_their_level_and_prerequisites() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let mut gs = new_game(&mut ctx, SEED, "progression");
+    let player = player(&gs);
+
+    let choices = perk_choices(&gs.ecs, player);
+    assert!(choices.contains(&"Keen Eye".to_string()));
+    assert!(!choices.contains(&"Deadly Precision".to_string()));
+
+    assert!(take_perk(&mut gs.ecs, player, "Keen Eye"));
+    gs.ecs.write_storage::<Pools>().get_mut(player).unwrap().level = 4;
+    let choices = perk_choices(&gs.ecs, player);
+    assert!(!choices.contains(&"Keen Eye".to_string()));
+    assert!(choices.contains(&"Deadly Precision".to_string()));
+
+    assert!(take_perk(&mut gs.ecs, player, "Deadly Precision"));
+    assert_eq!(perks(&gs).crit_chance, 20);
+    clean_up(&gs, "progression");
+}
+
+#[test]
+fn perks_raise_carry_weight_spell_slots_and_skills() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let mut gs = new_game(&mut ctx, SEED, "progression");
+    let player = player(&gs);
+    let attributes = gs.ecs.read_storage::<Attributes>().get(player).unwrap().clone();
+    let capacity = carry_capacity_lbs(&attributes, Some(&perks(&gs)));
+    let slots = spell_slots(Some(&perks(&gs)));
+    let magic = skill_level(&gs, Skill::Magic);
+
+    for perk in ["Pack Mule", "Arcane Memory", "Scholar", "Brawler"] {
+        assert!(take_perk(&mut gs.ecs, player, perk));
+    }
+
+    let perks = perks(&gs);
+    assert_eq!(carry_capacity_lbs(&attributes, Some(&perks)), capacity + 50);
+    assert_eq!(spell_slots(Some(&perks)), slots + 2);
+    assert_eq!(skill_level(&gs, Skill::Magic), magic + 1);
+    assert_eq!(perks.extra_attacks.len(), 1);
+    assert_eq!(perks.extra_attacks[0].name, "Headbutt");
+    clean_up(&gs, "progression");
+}
+
+#[test]
+fn the_perk_menu_takes_the_highlighted_perk() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let mut gs = new_game(&mut ctx, SEED, "progression");
+    let player = player(&gs);
+    let choices = perk_choices(&gs.ecs, player);
+    gs.ecs.write_storage::<Perks>().get_mut(player).unwrap().pending = 1;
+    *gs.ecs.write_resource::<RunState>() = RunState::ShowPerks { selection: 0 };
+
+    tick(&mut gs, &mut ctx, None);
+    tick(&mut gs, &mut ctx, Some(VirtualKeyCode::Down));
+    tick(&mut gs, &mut ctx, Some(VirtualKeyCode::Return));
+
+    let perks = perks(&gs);
+    assert_eq!(perks.taken, vec![choices[1].clone()]);
+    assert_eq!(perks.pending, 0);
+    assert!(!matches!(runstate(&gs), RunState::ShowPerks { .. }));
+    clean_up(&gs, "progression");
+}
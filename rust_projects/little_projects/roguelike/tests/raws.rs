@@ -0,0 +1,356 @@
+//! Mods must merge over the base raws by name, and problems in either must be reported against
+//! the file and entry they are in.
+
+use roguelike::raws::{get_spell, merge_raws, RawError, RawFiles, RawMaster};
+
+/// The base game's raw files, as they are on disk.
+fn base_raws() -> RawFiles {
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("raws");
+    let mut paths: Vec<_> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    paths.sort();
+    paths
+        .iter()
+        .map(|path| {
+            let name = path.file_name().unwrap().to_string_lossy();
+            (
+                format!("raws/{}", name),
+                std::fs::read_to_string(path).unwrap(),
+            )
+        })
+        .collect()
+}
+
+/// A mod made of the given (file name, contents) pairs.
+fn a_mod(name: &str, files: &[(&str, &str)]) -> RawFiles {
+    files
+        .iter()
+        .map(|(file, json)| (format!("mods/{}/{}", name, file), json.to_string()))
+        .collect()
+}
+
+/// Merges the layers, which must load.
+fn merged(layers: &[RawFiles]) -> (RawMaster, Vec<(String, String)>) {
+    let (master, errors) = merge_raws(layers);
+    let master = master.unwrap_or_else(|| panic!("the raws didn't load: {:?}", errors));
+    let errors = errors
+        .into_iter()
+        .map(|RawError { path, message }| (path, message))
+        .collect();
+    (master, errors)
+}
+
+fn errors(layers: &[RawFiles]) -> Vec<(String, String)> {
+    merged(layers).1
+}
+
+/// The problems reported in the mods, leaving out any the base game has of its own.
+fn mod_errors(layers: &[RawFiles]) -> Vec<(String, String)> {
+    errors(layers)
+        .into_iter()
+        .filter(|(path, _)| path.starts_with("mods/"))
+        .collect()
+}
+
+fn expected(errors: &[(&str, &str)]) -> Vec<(String, String)> {
+    errors
+        .iter()
+        .map(|(path, message)| (path.to_string(), message.to_string()))
+        .collect()
+}
+
+#[test]
+fn broken_references_name_the_mod_file() {
+    let broken = a_mod(
+        "broken",
+        &[
+            (
+                "mobs.json",
+                r##"{ "mobs": [ {
+                    "name": "Broken Rat",
+                    "blocks_tile": true,
+                    "vision_range": 8,
+                    "movement": "static",
+                    "attributes": {},
+                    "equipped": [ "Dagger", "Cardboard Sword" ],
+                    "natural": { "armor_class": 11, "attacks": [
+                        { "name": "bite", "hit_bonus": 0, "damage": "one bite" }
+                    ] },
+                    "loot_table": "Nothing At All",
+                    "faction": "Nobody",
+                    "gold": "lots",
+                    "abilities": [
+                        { "spell": "Fizzle", "chance": 1.0, "range": 6.0, "min_range": 0.0 }
+                    ]
+                } ] }"##,
+            ),
+            (
+                "spawn_table.json",
+                r##"{ "spawn_table": [
+                    { "name": "Broken Rat", "weight": 1, "min_depth": 5, "max_depth": 3 }
+                ] }"##,
+            ),
+        ],
+    );
+
+    let mob = "mods/broken/mobs.json:$.mobs[0]";
+    let spawn = "mods/broken/spawn_table.json:$.spawn_table[0]";
+    assert_eq!(
+        mod_errors(&[base_raws(), broken]),
+        expected(&[
+            (
+                &format!("{}.equipped[1]", mob),
+                "unknown item [Cardboard Sword]"
+            ),
+            (
+                &format!("{}.natural.attacks[0].damage", mob),
+                "[one bite] is not a dice string"
+            ),
+            (
+                &format!("{}.loot_table", mob),
+                "unknown loot table [Nothing At All]"
+            ),
+            (&format!("{}.faction", mob), "unknown faction [Nobody]"),
+            (&format!("{}.gold", mob), "[lots] is not a dice string"),
+            (
+                &format!("{}.abilities[0].spell", mob),
+                "unknown spell [Fizzle]"
+            ),
+            (spawn, "min_depth 5 is above max_depth 3 for Broken Rat"),
+        ])
+    );
+}
+
+#[test]
+fn broken_base_entries_name_their_file() {
+    let mut base = base_raws();
+    let (_, factions) = base
+        .iter_mut()
+        .find(|(source, _)| source == "raws/factions.json")
+        .unwrap();
+    *factions = factions.replacen(r#""Player""#, r#""Nobody""#, 1);
+
+    let path = errors(&[base])
+        .into_iter()
+        .find(|(_, message)| message == "unknown faction [Player]")
+        .map(|(path, _)| path)
+        .expect("the missing faction wasn't reported");
+    assert!(
+        path.starts_with("raws/factions.json:$.faction_table["),
+        "reported at {}",
+        path
+    );
+}
+
+#[test]
+fn mods_override_the_base_by_name() {
+    let stronger = a_mod(
+        "stronger",
+        &[(
+            "spells.json",
+            r#"{ "spells": [ { "name": "Zap", "mana_cost": 3, "effects": { "damage": "9" } } ] }"#,
+        )],
+    );
+
+    let (master, errors) = merged(&[base_raws(), stronger]);
+    assert!(
+        !errors.iter().any(|(path, _)| path.starts_with("mods/")),
+        "{:?}",
+        errors
+    );
+    let zap = get_spell(&master, "Zap").unwrap();
+    assert_eq!(zap.mana_cost, 3);
+    assert_eq!(zap.description, None);
+}
+
+#[test]
+fn mods_remove_entries_by_name() {
+    let purge = a_mod(
+        "purge",
+        &[(
+            "spells.json",
+            r#"{ "remove": { "spells": [ "Frost Nova", "Fireball" ] } }"#,
+        )],
+    );
+
+    let layers = [base_raws(), purge];
+    assert!(get_spell(&merged(&layers).0, "Frost Nova").is_none());
+    assert_eq!(
+        mod_errors(&layers),
+        expected(&[(
+            "mods/purge/spells.json:$.remove.spells[1]",
+            "can't remove unknown entry [Fireball]"
+        )])
+    );
+}
+
+#[test]
+fn patches_change_only_the_fields_they_name() {
+    let tweaks = a_mod(
+        "tweaks",
+        &[(
+            "spells.json",
+            r#"{ "patch": { "spells": [
+                { "name": "Force Bolt", "mana_cost": 1, "cooldown": null },
+                { "name": "Fireball", "mana_cost": 1 }
+            ] } }"#,
+        )],
+    );
+
+    let layers = [base_raws(), tweaks];
+    let (master, _) = merged(&layers);
+    let force_bolt = get_spell(&master, "Force Bolt").unwrap();
+    assert_eq!(force_bolt.mana_cost, 1);
+    assert_eq!(force_bolt.cooldown, None, "a null should remove the field");
+    assert!(force_bolt.shape.is_some(), "other fields should be kept");
+    assert_eq!(
+        mod_errors(&layers),
+        expected(&[(
+            "mods/tweaks/spells.json:$.patch.spells[1]",
+            "can't patch unknown entry [Fireball]"
+        )])
+    );
+}
+
+#[test]
+fn patching_a_spawn_band_moves_it_to_its_new_depths() {
+    // The base game has one band of rats, at depth 2. Once it has been moved, a band at the new
+    // depths must replace it rather than sit alongside it.
+    let moved = a_mod(
+        "a",
+        &[(
+            "spawn_table.json",
+            r#"{ "patch": { "spawn_table": [ { "name": "Rat", "min_depth": 9, "max_depth": 3 } ] } }"#,
+        )],
+    );
+    let replaced = a_mod(
+        "b",
+        &[(
+            "spawn_table.json",
+            r#"{ "spawn_table": [ { "name": "Rat", "weight": 1, "min_depth": 9, "max_depth": 3 } ] }"#,
+        )],
+    );
+
+    let rat_errors: Vec<_> = errors(&[base_raws(), moved, replaced])
+        .into_iter()
+        .filter(|(_, message)| message.ends_with(" for Rat"))
+        .collect();
+    assert_eq!(
+        rat_errors,
+        expected(&[(
+            "mods/b/spawn_table.json:$.spawn_table[0]",
+            "min_depth 9 is above max_depth 3 for Rat"
+        )])
+    );
+}
+
+#[test]
+fn mods_may_not_override_each_other() {
+    let sparkle = |mana_cost: i32| {
+        format!(
+            r#"{{ "name": "Sparkle", "mana_cost": {}, "effects": {{ "damage": "1" }} }}"#,
+            mana_cost
+        )
+    };
+    let first = a_mod(
+        "a",
+        &[(
+            "spells.json",
+            &format!(r#"{{ "spells": [ {} ] }}"#, sparkle(1)),
+        )],
+    );
+    let second = a_mod(
+        "b",
+        &[(
+            "spells.json",
+            &format!(r#"{{ "spells": [ {} ] }}"#, sparkle(2)),
+        )],
+    );
+
+    let layers = [base_raws(), first, second];
+    assert_eq!(
+        get_spell(&merged(&layers).0, "Sparkle").unwrap().mana_cost,
+        2
+    );
+    assert_eq!(
+        mod_errors(&layers),
+        expected(&[(
+            "mods/b/spells.json:$.spells[0]",
+            "[Sparkle] is also defined in mods/a/spells.json; using this definition"
+        )])
+    );
+}
+
+#[test]
+fn names_must_be_unique_within_a_layer() {
+    let mut base = base_raws();
+    base.push((
+        "raws/zz_extra.json".to_string(),
+        r#"{ "spells": [ { "name": "Zap", "mana_cost": 3, "effects": {} } ] }"#.to_string(),
+    ));
+    let twice = a_mod(
+        "twice",
+        &[(
+            "spells.json",
+            r#"{ "spells": [
+                { "name": "Sparkle", "mana_cost": 1, "effects": {} },
+                { "name": "Sparkle", "mana_cost": 2, "effects": {} }
+            ] }"#,
+        )],
+    );
+
+    let duplicates: Vec<_> = errors(&[base, twice])
+        .into_iter()
+        .filter(|(_, message)| message.contains("is also defined in"))
+        .collect();
+    assert_eq!(
+        duplicates,
+        expected(&[
+            (
+                "raws/zz_extra.json:$.spells[0]",
+                "[Zap] is also defined in raws/spells.json; using this definition"
+            ),
+            (
+                "mods/twice/spells.json:$.spells[1]",
+                "[Sparkle] is also defined in mods/twice/spells.json; using this definition"
+            ),
+        ])
+    );
+}
+
+#[test]
+fn unknown_sections_are_reported() {
+    let typos = a_mod(
+        "typos",
+        &[(
+            "spells.json",
+            r#"{
+                "spellz": [],
+                "remove": { "spellz": [ "Zap" ] },
+                "patch": { "spellz": [ { "name": "Zap" } ] }
+            }"#,
+        )],
+    );
+
+    let mut errors = mod_errors(&[base_raws(), typos]);
+    errors.sort();
+    assert_eq!(
+        errors,
+        expected(&[
+            (
+                "mods/typos/spells.json:$.patch.spellz",
+                "unknown section [spellz]"
+            ),
+            (
+                "mods/typos/spells.json:$.remove.spellz",
+                "unknown section [spellz]"
+            ),
+            (
+                "mods/typos/spells.json:$.spellz",
+                "unknown section [spellz]"
+            ),
+        ])
+    );
+}
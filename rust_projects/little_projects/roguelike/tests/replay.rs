@@ -0,0 +1,86 @@
+//! A recorded run must play back to exactly the world it was recorded in.
+
+mod common;
+
+use common::{clean_up, headless_context, lock_world, new_game, replay_path, settle, tick};
+use rltk::VirtualKeyCode;
+use roguelike::replay::Replay;
+use roguelike::{Map, Name, Pools, Position, State};
+use specs::prelude::*;
+
+const SEED: u64 = 4242;
+const MOVES: usize = 400;
+
+/// Everything a replay could get wrong: who is where, the player's pools and the level.
+fn snapshot(gs: &State) -> Vec<String> {
+    let names = gs.ecs.read_storage::<Name>();
+    let positions = gs.ecs.read_storage::<Position>();
+    let pools = gs.ecs.read_storage::<Pools>();
+    let mut result: Vec<String> = (&names, &positions)
+        .join()
+        .map(|(name, pos)| format!("{} at {},{}", name.name, pos.x, pos.y))
+        .collect();
+    result.sort();
+    let player = pools.get(*gs.ecs.fetch::<Entity>()).unwrap();
+    result.push(format!(
+        "player hp {} xp {} gold {}",
+        player.hit_points.current, player.xp, player.gold
+    ));
+    result.push(format!("depth {}", gs.ecs.fetch::<Map>().depth));
+    result
+}
+
+#[test]
+fn replay_reproduces_the_recorded_run() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+
+    // Wander about, looking in menus and picking things up along the way
+    let keys = [
+        VirtualKeyCode::Numpad8,
+        VirtualKeyCode::Numpad6,
+        VirtualKeyCode::Numpad2,
+        VirtualKeyCode::Numpad4,
+        VirtualKeyCode::Numpad9,
+        VirtualKeyCode::Numpad3,
+        VirtualKeyCode::Numpad1,
+        VirtualKeyCode::Numpad7,
+        VirtualKeyCode::G,
+        VirtualKeyCode::Numpad5,
+        VirtualKeyCode::I,
+        VirtualKeyCode::Escape,
+    ];
+    let mut gs = new_game(&mut ctx, SEED, "run");
+    let start = snapshot(&gs);
+    let mut roll = SEED;
+    for _ in 0..MOVES {
+        roll = roll
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        tick(
+            &mut gs,
+            &mut ctx,
+            Some(keys[(roll >> 33) as usize % keys.len()]),
+        );
+        settle(&mut gs, &mut ctx);
+    }
+    let recorded = snapshot(&gs);
+    assert_ne!(recorded, start, "nothing happened in the recorded run");
+
+    let replay = Replay::load(&replay_path("run")).unwrap();
+    assert_eq!(replay.seed, SEED);
+    assert_eq!(replay.frames.len(), MOVES);
+
+    let mut replayed = State::new();
+    replayed.play_replay(replay);
+    for _ in 0..MOVES * 100 {
+        if !replayed.is_replaying() {
+            break;
+        }
+        tick(&mut replayed, &mut ctx, None);
+    }
+    assert!(!replayed.is_replaying(), "the replay never finished");
+    assert_eq!(snapshot(&replayed), recorded);
+
+    clean_up(&gs, "run");
+}
@@ -0,0 +1,218 @@
+//! Saves must restore exactly the world that was saved, in every format and after a crash.
+
+mod common;
+
+use common::{clean_up, headless_context, lock_world, new_game, temp_path, tick, wait_a_turn};
+use rltk::VirtualKeyCode;
+use roguelike::raws::{spawn_named_entity, SpawnType, RAWS};
+use roguelike::saveload_system::{
+    delete_slot, load_game, new_slot_name, run_slot, slot_path, write_save, SaveFormat,
+};
+use roguelike::{AppliesStatus, Duration, MasterDungeonMap, Name, State};
+use serde_json::{json, Value};
+use specs::prelude::*;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// The components in a JSON save as (entity marker, component) pairs, in marker order. Entities
+/// are stored in whatever order the ECS hands them out, and the helpers holding the maps get new
+/// markers on every save, so neither is part of the comparison.
+fn components_of(path: &Path) -> BTreeMap<String, Vec<(u64, String)>> {
+    let data = std::fs::read_to_string(path).unwrap();
+    let (_header, world) = data.split_once('\n').unwrap();
+    let world: BTreeMap<String, Vec<Value>> = serde_json::from_str(world).unwrap();
+    world
+        .into_iter()
+        .map(|(name, entries)| {
+            let is_helper = name.ends_with("SerializationHelper");
+            let mut components: Vec<(u64, String)> = entries
+                .iter()
+                .filter(|entry| !entry["components"][0].is_null())
+                .map(|entry| {
+                    let marker = if is_helper {
+                        0
+                    } else {
+                        entry["marker"][0].as_u64().unwrap()
+                    };
+                    (marker, entry["components"][0].to_string())
+                })
+                .collect();
+            components.sort();
+            (name, components)
+        })
+        .collect()
+}
+
+#[test]
+fn every_format_restores_the_same_world() {
+    let _guard = lock_world();
+    let mut gs = State::new();
+    let original = temp_path("original.json");
+    write_save(&mut gs.ecs, &original, SaveFormat::Json).unwrap();
+    let expected = components_of(&original);
+    let json_size = std::fs::metadata(&original).unwrap().len();
+
+    for format in SaveFormat::ALL.iter() {
+        let saved = temp_path(&format!("{:?}.sav", format));
+        write_save(&mut gs.ecs, &saved, *format).unwrap();
+        load_game(&mut gs.ecs, &saved).unwrap_or_else(|e| panic!("{:?}: {}", format, e));
+
+        // Saving the loaded world as JSON again must give back the original
+        let resaved = temp_path(&format!("{:?}-resaved.json", format));
+        write_save(&mut gs.ecs, &resaved, SaveFormat::Json).unwrap();
+        assert!(
+            components_of(&resaved) == expected,
+            "{:?} save didn't restore the same world",
+            format
+        );
+
+        if *format == SaveFormat::CompressedBinary {
+            let compressed_size = std::fs::metadata(&saved).unwrap().len();
+            assert!(compressed_size < json_size / 4);
+        }
+
+        std::fs::remove_file(saved).unwrap();
+        std::fs::remove_file(resaved).unwrap();
+    }
+    std::fs::remove_file(original).unwrap();
+}
+
+#[test]
+fn unreadable_save_falls_back_to_the_backup() {
+    let _guard = lock_world();
+    let mut gs = State::new();
+    let path = temp_path("slot.sav");
+    let backup = temp_path("slot.sav.bak");
+    write_save(&mut gs.ecs, &path, SaveFormat::CompressedBinary).unwrap();
+    assert!(!backup.exists());
+
+    // The second save moves the first one into the backup
+    write_save(&mut gs.ecs, &path, SaveFormat::CompressedBinary).unwrap();
+    assert!(backup.exists());
+
+    // As if the game crashed while writing
+    let data = std::fs::read(&path).unwrap();
+    std::fs::write(&path, &data[..data.len() / 2]).unwrap();
+    load_game(&mut gs.ecs, &path).unwrap();
+
+    std::fs::remove_file(&path).unwrap();
+    load_game(&mut gs.ecs, &path).unwrap();
+
+    std::fs::remove_file(&backup).unwrap();
+    assert!(load_game(&mut gs.ecs, &path).is_err());
+}
+
+#[test]
+fn failed_saves_are_reported() {
+    let _guard = lock_world();
+    let mut gs = State::new();
+    let path = temp_path("missing-directory").join("slot.sav");
+    assert!(write_save(&mut gs.ecs, &path, SaveFormat::CompressedBinary).is_err());
+    assert!(!path.exists());
+
+    // The world is left as it was, so saving somewhere else still works
+    let path = temp_path("after-failure.sav");
+    write_save(&mut gs.ecs, &path, SaveFormat::CompressedBinary).unwrap();
+    load_game(&mut gs.ecs, &path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn runs_with_the_same_seed_keep_their_own_slots() {
+    let _guard = lock_world();
+    let mut gs = State::new();
+    let seed = gs.ecs.fetch::<MasterDungeonMap>().seed;
+    let slot = run_slot(&gs.ecs);
+    assert_ne!(new_slot_name(seed), slot);
+
+    // Loading a save picks its slot back up, so the run goes on saving over itself
+    let path = temp_path("own-slot.sav");
+    write_save(&mut gs.ecs, &path, SaveFormat::CompressedBinary).unwrap();
+    gs.ecs.insert(MasterDungeonMap::new(seed));
+    assert_ne!(run_slot(&gs.ecs), slot);
+    load_game(&mut gs.ecs, &path).unwrap();
+    assert_eq!(run_slot(&gs.ecs), slot);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn old_status_causes_are_migrated() {
+    let _guard = lock_world();
+    let mut gs = State::new();
+    let scroll = spawn_named_entity(
+        &RAWS.lock().unwrap(),
+        &mut gs.ecs,
+        "Confusion Scroll",
+        SpawnType::AtPosition { x: 1, y: 1 },
+    )
+    .unwrap();
+    gs.ecs.write_storage::<AppliesStatus>().remove(scroll);
+    let path = temp_path("format-2.json");
+    write_save(&mut gs.ecs, &path, SaveFormat::Json).unwrap();
+
+    // Give the scroll its own confusion, the way format 2 stored it
+    let data = std::fs::read_to_string(&path).unwrap();
+    let (header, world) = data.split_once('\n').unwrap();
+    let mut header: Value = serde_json::from_str(header).unwrap();
+    let mut world: serde_json::Map<String, Value> = serde_json::from_str(world).unwrap();
+    header["format_version"] = Value::from(2);
+    let marker = world["Name"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|entry| entry["components"][0]["name"] == "Confusion Scroll")
+        .unwrap()["marker"]
+        .clone();
+    world["Confusion"]
+        .as_array_mut()
+        .unwrap()
+        .push(json!({ "marker": marker, "components": [{}] }));
+    world["Duration"]
+        .as_array_mut()
+        .unwrap()
+        .push(json!({ "marker": marker, "components": [{ "turns": 4 }] }));
+    std::fs::write(&path, format!("{}\n{}", header, Value::Object(world))).unwrap();
+
+    load_game(&mut gs.ecs, &path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let names = gs.ecs.read_storage::<Name>();
+    let (scroll, _) = (&gs.ecs.entities(), &names)
+        .join()
+        .find(|(_, name)| name.name == "Confusion Scroll")
+        .unwrap();
+    let statuses = gs
+        .ecs
+        .read_storage::<AppliesStatus>()
+        .get(scroll)
+        .map(|applies| applies.statuses.clone());
+    assert_eq!(statuses, Some(vec!["Confused".to_string()]));
+    assert!(gs.ecs.read_storage::<Duration>().get(scroll).is_none());
+}
+
+#[test]
+fn autosaves_come_every_two_hundred_turns() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let mut gs = new_game(&mut ctx, 4242, "autosave");
+    let slot = run_slot(&gs.ecs);
+    let path = slot_path(&slot, SaveFormat::CompressedBinary);
+    // Entering the level saved already
+    assert!(path.exists());
+    delete_slot(&slot);
+
+    // Looking through menus is no time at all
+    gs.ecs.write_resource::<MasterDungeonMap>().turns += 198;
+    for _ in 0..5 {
+        tick(&mut gs, &mut ctx, Some(VirtualKeyCode::I));
+        tick(&mut gs, &mut ctx, Some(VirtualKeyCode::Escape));
+    }
+    wait_a_turn(&mut gs, &mut ctx);
+    let early = path.exists();
+    wait_a_turn(&mut gs, &mut ctx);
+    let on_time = path.exists();
+    clean_up(&gs, "autosave");
+
+    assert!(!early, "saved after 199 turns");
+    assert!(on_time, "not saved after 200 turns");
+}
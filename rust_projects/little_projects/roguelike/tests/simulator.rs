@@ -0,0 +1,71 @@
+//! The combat simulator must fight through the real combat code and clean up after itself.
+
+use roguelike::raws::{spawn_type_by_name, SpawnTableType, RAWS};
+use roguelike::simulator::{fight, simulate_depth, simulate_mob, Loadout};
+use roguelike::State;
+use specs::prelude::*;
+use std::sync::Mutex;
+
+// The game keeps its RNG, log and spatial index in globals, so only one world at a time
+static WORLD_LOCK: Mutex<()> = Mutex::new(());
+
+fn veteran() -> Loadout {
+    Loadout {
+        level: 10,
+        might: 18,
+        equipped: vec!["War Axe".to_string(), "Breastplate".to_string()],
+        ..Loadout::default()
+    }
+}
+
+fn entity_count(gs: &State) -> usize {
+    gs.ecs.entities().join().count()
+}
+
+#[test]
+fn veterans_beat_rats() {
+    let _guard = WORLD_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut gs = State::new();
+    let report = simulate_mob(&mut gs.ecs, &veteran(), "Rat", 50, 100).unwrap();
+    assert_eq!((report.fights, report.wins), (50, 50));
+    assert!(report.damage_per_hit.min > 0);
+}
+
+#[test]
+fn fights_leave_nothing_behind() {
+    let _guard = WORLD_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut gs = State::new();
+    let before = entity_count(&gs);
+    fight(&mut gs.ecs, &Loadout::default(), "Bandit", 100).unwrap();
+    assert!(fight(&mut gs.ecs, &Loadout::default(), "Nobody", 100).is_err());
+    assert_eq!(entity_count(&gs), before);
+}
+
+#[test]
+fn the_same_seed_gives_the_same_report() {
+    let _guard = WORLD_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut gs = State::new();
+    roguelike::rng::reseed(7);
+    let first = simulate_mob(&mut gs.ecs, &Loadout::default(), "Bandit", 30, 100).unwrap();
+    roguelike::rng::reseed(7);
+    let second = simulate_mob(&mut gs.ecs, &Loadout::default(), "Bandit", 30, 100).unwrap();
+    assert_eq!(first, second);
+}
+
+#[test]
+fn depths_fight_what_spawns_there() {
+    let _guard = WORLD_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut gs = State::new();
+    let reports = simulate_depth(&mut gs.ecs, &veteran(), 3, 100, 100).unwrap();
+    let (all, by_mob) = reports.split_last().unwrap();
+    assert_eq!((all.opponent.as_str(), all.fights), ("(all)", 100));
+    assert_eq!(by_mob.iter().map(|r| r.fights).sum::<usize>(), 100);
+    let raws = RAWS.lock().unwrap();
+    for report in by_mob {
+        assert!(report.depth == Some(3));
+        assert!(matches!(
+            spawn_type_by_name(&raws, &report.opponent),
+            SpawnTableType::Mob
+        ));
+    }
+}
@@ -0,0 +1,224 @@
+//! Spells hit the tiles their shape covers, wait out their cooldowns, and need enough Magic to
+//! cast.
+
+mod common;
+
+use common::{
+    clean_up, headless_context, lock_world, new_game, runstate, settle, tick, wait_a_turn,
+};
+use rltk::{Point, VirtualKeyCode};
+use roguelike::effects::{add_effect, run_effects_queue, shape_tiles, EffectType, Targets};
+use roguelike::raws::{find_spell_entity, get_spell, spawn_named_entity, SpawnType, RAWS};
+use roguelike::{
+    KnownSpell, KnownSpells, Map, Pools, Position, RunState, Skill, Skills, State, TargetShape,
+    TileType,
+};
+use specs::prelude::*;
+
+const SEED: u64 = 4242;
+
+/// A world whose map is open floor inside a wall border.
+fn open_floor() -> State {
+    let mut gs = State::new();
+    let mut map = Map::new(1, 30, 30, "Open Floor");
+    for y in 1..29 {
+        for x in 1..29 {
+            let idx = map.xy_idx(x, y);
+            map.tiles[idx] = TileType::Floor;
+        }
+    }
+    roguelike::spatial::clear();
+    roguelike::spatial::populate_blocked_from_map(&map);
+    gs.ecs.insert(map);
+    gs
+}
+
+fn wall(gs: &mut State, x: i32, y: i32) {
+    let mut map = gs.ecs.fetch_mut::<Map>();
+    let idx = map.xy_idx(x, y);
+    map.tiles[idx] = TileType::Wall;
+}
+
+/// Spawns a bandit and puts it in the spatial index, as the map indexing system would.
+fn bandit(gs: &mut State, x: i32, y: i32) {
+    let bandit = spawn_named_entity(
+        &RAWS.lock().unwrap(),
+        &mut gs.ecs,
+        "Bandit",
+        SpawnType::AtPosition { x, y },
+    )
+    .unwrap();
+    let idx = gs.ecs.fetch::<Map>().xy_idx(x, y);
+    roguelike::spatial::index_entity(bandit, idx, true);
+}
+
+fn idx(gs: &State, x: i32, y: i32) -> i32 {
+    gs.ecs.fetch::<Map>().xy_idx(x, y) as i32
+}
+
+fn tiles(gs: &State, shape: TargetShape, origin: (i32, i32), target: (i32, i32)) -> Vec<i32> {
+    shape_tiles(
+        &gs.ecs,
+        shape,
+        Point::new(origin.0, origin.1),
+        Point::new(target.0, target.1),
+    )
+}
+
+#[test]
+fn bolts_stop_at_the_first_creature() {
+    let _guard = lock_world();
+    let mut gs = open_floor();
+    bandit(&mut gs, 10, 5);
+    bandit(&mut gs, 14, 5);
+
+    assert_eq!(
+        tiles(&gs, TargetShape::Bolt, (5, 5), (14, 5)),
+        vec![idx(&gs, 10, 5)]
+    );
+    assert_eq!(
+        tiles(&gs, TargetShape::Bolt, (5, 8), (9, 8)),
+        vec![idx(&gs, 9, 8)]
+    );
+}
+
+#[test]
+fn lines_pass_through_creatures_but_not_walls() {
+    let _guard = lock_world();
+    let mut gs = open_floor();
+    bandit(&mut gs, 7, 5);
+    wall(&mut gs, 12, 5);
+
+    let expected: Vec<i32> = (6..12).map(|x| idx(&gs, x, 5)).collect();
+    assert_eq!(
+        tiles(&gs, TargetShape::Line { length: 8 }, (5, 5), (6, 5)),
+        expected
+    );
+}
+
+#[test]
+fn cones_spread_towards_the_target() {
+    let _guard = lock_world();
+    let gs = open_floor();
+    let cone = tiles(&gs, TargetShape::Cone { length: 4 }, (10, 10), (14, 10));
+
+    assert!(cone.contains(&idx(&gs, 14, 10)));
+    assert!(cone.contains(&idx(&gs, 13, 11)));
+    assert!(!cone.contains(&idx(&gs, 10, 10)));
+    assert!(!cone.contains(&idx(&gs, 9, 10)));
+    assert!(!cone.contains(&idx(&gs, 10, 13)));
+    assert!(!cone.contains(&idx(&gs, 16, 10)));
+}
+
+#[test]
+fn bursts_surround_the_caster() {
+    let _guard = lock_world();
+    let gs = open_floor();
+    let burst = tiles(&gs, TargetShape::Burst { radius: 2 }, (10, 10), (10, 10));
+
+    assert!(!burst.contains(&idx(&gs, 10, 10)));
+    for (x, y) in [(8, 10), (12, 10), (10, 8), (10, 12), (11, 11)] {
+        assert!(burst.contains(&idx(&gs, x, y)));
+    }
+    assert!(!burst.contains(&idx(&gs, 13, 10)));
+}
+
+#[test]
+fn chains_jump_to_the_nearest_creature_in_reach() {
+    let _guard = lock_world();
+    let mut gs = open_floor();
+    bandit(&mut gs, 10, 10);
+    bandit(&mut gs, 13, 10);
+    bandit(&mut gs, 12, 12);
+    bandit(&mut gs, 20, 10);
+
+    assert_eq!(
+        tiles(&gs, TargetShape::Chain { jumps: 3 }, (5, 10), (10, 10)),
+        vec![idx(&gs, 10, 10), idx(&gs, 12, 12), idx(&gs, 13, 10)]
+    );
+}
+
+fn teach(gs: &mut State, spell: &str) {
+    let player = *gs.ecs.fetch::<Entity>();
+    let mana_cost = get_spell(&RAWS.lock().unwrap(), spell).unwrap().mana_cost;
+    gs.ecs
+        .write_storage::<KnownSpells>()
+        .insert(
+            player,
+            KnownSpells {
+                spells: vec![KnownSpell {
+                    display_name: spell.to_string(),
+                    mana_cost,
+                    cooldown: 0,
+                }],
+            },
+        )
+        .unwrap();
+    let mut pools = gs.ecs.write_storage::<Pools>();
+    let pools = pools.get_mut(player).unwrap();
+    pools.mana.max = 100;
+    pools.mana.current = 100;
+}
+
+fn cooldown(gs: &State) -> i32 {
+    let player = *gs.ecs.fetch::<Entity>();
+    gs.ecs.read_storage::<KnownSpells>().get(player).unwrap().spells[0].cooldown
+}
+
+#[test]
+fn casting_starts_a_cooldown_that_ticks_down() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let mut gs = new_game(&mut ctx, SEED, "spells");
+    teach(&mut gs, "Frost Nova");
+    let player = *gs.ecs.fetch::<Entity>();
+    let spell = find_spell_entity(&gs.ecs, "Frost Nova").unwrap();
+    let pos = gs.ecs.read_storage::<Position>().get(player).unwrap().clone();
+    let tile_idx = idx(&gs, pos.x, pos.y);
+
+    add_effect(
+        Some(player),
+        EffectType::SpellUse { spell },
+        Targets::Tile { tile_idx },
+    );
+    run_effects_queue(&mut gs.ecs);
+    assert_eq!(cooldown(&gs), 10);
+    let mana = gs.ecs.read_storage::<Pools>().get(player).unwrap().mana.current;
+    assert_eq!(mana, 96);
+
+    wait_a_turn(&mut gs, &mut ctx);
+    assert_eq!(cooldown(&gs), 9);
+    clean_up(&gs, "spells");
+}
+
+#[test]
+fn the_spellbook_needs_enough_magic_to_cast() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let mut gs = new_game(&mut ctx, SEED, "spells");
+    teach(&mut gs, "Chain Lightning");
+    let player = *gs.ecs.fetch::<Entity>();
+    gs.ecs
+        .write_storage::<Skills>()
+        .get_mut(player)
+        .unwrap()
+        .skills
+        .insert(Skill::Magic, 0);
+
+    tick(&mut gs, &mut ctx, Some(VirtualKeyCode::Z));
+    assert!(matches!(runstate(&gs), RunState::ShowSpellbook));
+    tick(&mut gs, &mut ctx, Some(VirtualKeyCode::A));
+    assert!(!matches!(runstate(&gs), RunState::ShowTargeting { .. }));
+
+    settle(&mut gs, &mut ctx);
+    gs.ecs
+        .write_storage::<Skills>()
+        .get_mut(player)
+        .unwrap()
+        .skills
+        .insert(Skill::Magic, 3);
+    tick(&mut gs, &mut ctx, Some(VirtualKeyCode::Z));
+    tick(&mut gs, &mut ctx, Some(VirtualKeyCode::A));
+    assert!(matches!(runstate(&gs), RunState::ShowTargeting { .. }));
+    clean_up(&gs, "spells");
+}
@@ -0,0 +1,133 @@
+//! Status effects must follow the stacking, resistance and expiry rules in their raws.
+
+mod common;
+
+use common::{clean_up, headless_context, lock_world, new_game, wait_a_turn};
+use roguelike::effects::{add_effect, run_effects_queue, EffectType, Targets};
+use roguelike::raws::{spawn_named_entity, SpawnType, RAWS};
+use roguelike::{DamageOverTime, Duration, Name, Pools, State, StatusEffect, StatusStacks};
+use specs::prelude::*;
+
+const SEED: u64 = 4242;
+
+/// Spawns anything named in the raws.
+fn spawn(gs: &mut State, name: &str, pos: SpawnType) -> Entity {
+    spawn_named_entity(&RAWS.lock().unwrap(), &mut gs.ecs, name, pos).unwrap()
+}
+
+fn afflict(gs: &mut State, target: Entity, status: &str) {
+    add_effect(
+        None,
+        EffectType::Status {
+            name: status.to_string(),
+        },
+        Targets::Single { target },
+    );
+    run_effects_queue(&mut gs.ecs);
+}
+
+/// The status effect entity with this name on the target, if they have it.
+fn status_on(gs: &State, target: Entity, status: &str) -> Option<Entity> {
+    (
+        &gs.ecs.entities(),
+        &gs.ecs.read_storage::<StatusEffect>(),
+        &gs.ecs.read_storage::<Name>(),
+    )
+        .join()
+        .find(|(_, effect, name)| effect.target == target && name.name == status)
+        .map(|(entity, _, _)| entity)
+}
+
+fn turns_left(gs: &State, status: Entity) -> i32 {
+    gs.ecs.read_storage::<Duration>().get(status).unwrap().turns
+}
+
+#[test]
+fn poison_stacks_up_to_its_limit() {
+    let _guard = lock_world();
+    let mut gs = State::new();
+    let bandit = spawn(&mut gs, "Bandit", SpawnType::AtPosition { x: 1, y: 1 });
+    for _ in 0..5 {
+        afflict(&mut gs, bandit, "Poisoned");
+    }
+
+    let poison = status_on(&gs, bandit, "Poisoned").unwrap();
+    let stacks = gs
+        .ecs
+        .read_storage::<StatusStacks>()
+        .get(poison)
+        .unwrap()
+        .stacks;
+    let damage = gs
+        .ecs
+        .read_storage::<DamageOverTime>()
+        .get(poison)
+        .unwrap()
+        .damage;
+    let copies = gs
+        .ecs
+        .read_storage::<StatusEffect>()
+        .join()
+        .filter(|effect| effect.target == bandit)
+        .count();
+    assert_eq!((stacks, damage, copies), (3, 6, 1));
+}
+
+#[test]
+fn resistances_shorten_effects_and_immunities_stop_them() {
+    let _guard = lock_world();
+    let mut gs = State::new();
+    let golem = spawn(&mut gs, "Rock Golem", SpawnType::AtPosition { x: 1, y: 1 });
+    let spider = spawn(
+        &mut gs,
+        "Large Spider",
+        SpawnType::AtPosition { x: 2, y: 1 },
+    );
+    let bandit = spawn(&mut gs, "Bandit", SpawnType::AtPosition { x: 3, y: 1 });
+    spawn(&mut gs, "Drow Chain", SpawnType::Equipped { by: bandit });
+    for target in [golem, spider, bandit] {
+        afflict(&mut gs, target, "Poisoned");
+    }
+
+    assert!(status_on(&gs, golem, "Poisoned").is_none());
+    let spider_poison = status_on(&gs, spider, "Poisoned").unwrap();
+    assert_eq!(turns_left(&gs, spider_poison), 2);
+    let bandit_poison = status_on(&gs, bandit, "Poisoned").unwrap();
+    assert_eq!(turns_left(&gs, bandit_poison), 2);
+}
+
+#[test]
+fn expiring_venom_leaves_poison_behind() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let mut gs = new_game(&mut ctx, SEED, "status-effects");
+    let player = *gs.ecs.fetch::<Entity>();
+    let hp = {
+        let mut pools = gs.ecs.write_storage::<Pools>();
+        let pools = pools.get_mut(player).unwrap();
+        pools.hit_points.max = 1000;
+        pools.hit_points.current = 1000;
+        pools.hit_points.current
+    };
+    afflict(&mut gs, player, "Envenomed");
+    assert!(status_on(&gs, player, "Envenomed").is_some());
+
+    for _ in 0..6 {
+        wait_a_turn(&mut gs, &mut ctx);
+    }
+
+    let venom = status_on(&gs, player, "Envenomed");
+    let poison = status_on(&gs, player, "Poisoned");
+    let hurt = gs
+        .ecs
+        .read_storage::<Pools>()
+        .get(player)
+        .unwrap()
+        .hit_points
+        .current
+        < hp;
+    clean_up(&gs, "status-effects");
+    assert!(venom.is_none(), "the venom never wore off");
+    assert!(poison.is_some(), "the venom left no poison behind");
+    assert!(hurt, "the venom did no damage");
+}
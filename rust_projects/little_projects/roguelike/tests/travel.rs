@@ -0,0 +1,159 @@
+//! Exploring and travelling take many turns from one command, stop as soon as something needs
+//! the player, and replay exactly like walking step by step.
+
+mod common;
+
+use common::{
+    clean_up, headless_context, last_message, lock_world, new_game, player, replay_path, runstate,
+    settle, tick, turns, wait_a_turn,
+};
+use rltk::VirtualKeyCode;
+use roguelike::effects::{add_effect, EffectType, Targets};
+use roguelike::raws::{spawn_named_entity, SpawnType, RAWS};
+use roguelike::replay::Replay;
+use roguelike::{tile_walkable, DamageType, HungerClock, Map, RunState, State};
+use specs::prelude::*;
+
+const SEED: u64 = 8181;
+
+fn revealed(gs: &State) -> usize {
+    gs.ecs
+        .fetch::<Map>()
+        .revealed_tiles
+        .iter()
+        .filter(|r| **r)
+        .count()
+}
+
+#[test]
+fn exploring_walks_many_turns_and_replays_the_same() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let mut gs = new_game(&mut ctx, SEED, "explore");
+    let (start_turns, start_revealed) = (turns(&gs), revealed(&gs));
+
+    tick(&mut gs, &mut ctx, Some(VirtualKeyCode::O));
+    settle(&mut gs, &mut ctx);
+    assert!(matches!(runstate(&gs), RunState::AwaitingInput));
+    assert!(turns(&gs) > start_turns + 1, "{}", last_message());
+    assert!(revealed(&gs) > start_revealed);
+    let (recorded_turns, recorded_pos) = (turns(&gs), *gs.ecs.fetch::<rltk::Point>());
+
+    let mut replayed = State::new();
+    replayed.play_replay(Replay::load(&replay_path("explore")).unwrap());
+    for _ in 0..5000 {
+        if !replayed.is_replaying() {
+            break;
+        }
+        tick(&mut replayed, &mut ctx, None);
+    }
+    settle(&mut replayed, &mut ctx);
+    assert_eq!(turns(&replayed), recorded_turns);
+    assert_eq!(*replayed.ecs.fetch::<rltk::Point>(), recorded_pos);
+
+    clean_up(&gs, "explore");
+}
+
+#[test]
+fn getting_hungry_or_hurt_stops_the_journey() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let mut gs = new_game(&mut ctx, SEED, "interrupt");
+    let player = player(&gs);
+
+    gs.ecs
+        .write_storage::<HungerClock>()
+        .get_mut(player)
+        .unwrap()
+        .duration = 2;
+    let start = turns(&gs);
+    tick(&mut gs, &mut ctx, Some(VirtualKeyCode::O));
+    settle(&mut gs, &mut ctx);
+    assert_eq!(last_message(), "You stop as your hunger changes.");
+    assert!(turns(&gs) <= start + 3);
+
+    tick(&mut gs, &mut ctx, Some(VirtualKeyCode::O));
+    assert!(matches!(runstate(&gs), RunState::Ticking));
+    add_effect(
+        None,
+        EffectType::Damage {
+            amount: 1,
+            damage_type: DamageType::Physical,
+        },
+        Targets::Single { target: player },
+    );
+    settle(&mut gs, &mut ctx);
+    assert_eq!(last_message(), "You stop, hurt.");
+
+    clean_up(&gs, "interrupt");
+}
+
+#[test]
+fn nobody_travels_with_an_enemy_in_view() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let mut gs = new_game(&mut ctx, SEED, "hostile");
+
+    let spot = {
+        let map = gs.ecs.fetch::<Map>();
+        let pos = *gs.ecs.fetch::<rltk::Point>();
+        [
+            (1, 0),
+            (-1, 0),
+            (0, 1),
+            (0, -1),
+            (1, 1),
+            (-1, -1),
+            (1, -1),
+            (-1, 1),
+        ]
+        .iter()
+        .map(|(dx, dy)| (pos.x + dx, pos.y + dy))
+        .find(|(x, y)| tile_walkable(map.tiles[map.xy_idx(*x, *y)]))
+        .unwrap()
+    };
+    spawn_named_entity(
+        &RAWS.lock().unwrap(),
+        &mut gs.ecs,
+        "Bandit",
+        SpawnType::AtPosition {
+            x: spot.0,
+            y: spot.1,
+        },
+    )
+    .unwrap();
+    // A turn passes so the bandit is on the map for everyone, the player included
+    wait_a_turn(&mut gs, &mut ctx);
+
+    let start = turns(&gs);
+    tick(&mut gs, &mut ctx, Some(VirtualKeyCode::O));
+    assert!(matches!(runstate(&gs), RunState::AwaitingInput));
+    assert_eq!(turns(&gs), start);
+    assert_eq!(last_message(), "You can't travel with Bandit in view.");
+
+    clean_up(&gs, "hostile");
+}
+
+#[test]
+fn travelling_to_known_stairs_takes_them() {
+    let _guard = lock_world();
+    let mut ctx = headless_context();
+    let mut gs = new_game(&mut ctx, SEED, "stairs");
+
+    tick(&mut gs, &mut ctx, Some(VirtualKeyCode::T));
+    assert_eq!(last_message(), "You don't know of a way down yet.");
+
+    for revealed in gs.ecs.fetch_mut::<Map>().revealed_tiles.iter_mut() {
+        *revealed = true;
+    }
+    for _ in 0..20 {
+        if gs.ecs.fetch::<Map>().depth > 1 {
+            break;
+        }
+        tick(&mut gs, &mut ctx, Some(VirtualKeyCode::T));
+        settle(&mut gs, &mut ctx);
+    }
+    assert_eq!(gs.ecs.fetch::<Map>().depth, 2, "{}", last_message());
+
+    clean_up(&gs, "stairs");
+}